@@ -2,6 +2,7 @@
 
 use crate::config::BaserowConfig;
 use crate::error::{LennardError, Result};
+use crate::instrumentation::{ObservedSend, CLIENT_BASEROW};
 use crate::types::LinkedInProfile;
 use reqwest::Client as HttpClient;
 use serde_json::{json, Value};
@@ -48,7 +49,7 @@ impl BaserowClient {
             .get(&url)
             .header("Authorization", format!("Token {}", self.config.api_key))
             .query(&[("filters", filter.to_string())])
-            .send()
+            .send_observed(CLIENT_BASEROW)
             .await?;
             
         if !response.status().is_success() {
@@ -75,7 +76,7 @@ impl BaserowClient {
             .get(&url)
             .header("Authorization", format!("Token {}", self.config.api_key))
             .query(&[("size", "100")])  // Limit to 100 for testing
-            .send()
+            .send_observed(CLIENT_BASEROW)
             .await?;
             
         if !response.status().is_success() {
@@ -2,6 +2,7 @@
 
 use crate::config::DossierConfig;
 use crate::error::{LennardError, Result};
+use crate::instrumentation::{observe_grpc, CLIENT_DOSSIER};
 use crate::types::MailingAddress;
 use crate::paths;
 use dossier_grpc_client::{
@@ -12,9 +13,10 @@ use tonic::transport::Channel;
 use std::fs;
 use std::path::PathBuf;
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 
 /// Result from dossier generation containing structured data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DossierResult {
     pub person_dossier_content: String,
     pub company_dossier_content: String,
@@ -36,6 +38,17 @@ impl DossierClient {
         }
     }
     
+    /// Check that the dossier service accepts connections
+    pub async fn health_check(&self) -> Result<bool> {
+        let endpoint = Channel::from_shared(self.grpc_url.clone())
+            .map_err(|e| LennardError::Config(format!("Invalid gRPC URL: {}", e)))?;
+        
+        match endpoint.connect().await {
+            Ok(_) => Ok(true),
+            Err(_) => Ok(false),  // Connection failed
+        }
+    }
+    
     /// Generate and upload dossiers for a LinkedIn profile (legacy method for compatibility)
     pub async fn generate_and_upload_dossiers(&self, linkedin_data: &serde_json::Value, contact_id: &str) -> Result<()> {
         // Use the new method and ignore the structured result for backward compatibility
@@ -79,9 +92,7 @@ impl DossierClient {
                             log::info!("Logged gRPC request to: {}", request_log_path.display());
                             
                             // Use GenerateBothDossiers - the service handles URL extraction internally
-                            let request = tonic::Request::new(request_msg);
-                            
-                            match grpc_client.generate_both_dossiers(request).await {
+                            match observe_grpc(CLIENT_DOSSIER, "GenerateBothDossiers", request_msg, |request| grpc_client.generate_both_dossiers(request)).await {
                                 Ok(response) => {
                                     let bundle = response.into_inner();
                                     
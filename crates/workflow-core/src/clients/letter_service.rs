@@ -2,16 +2,19 @@
 
 use crate::config::LetterServiceConfig;
 use crate::error::{LennardError, Result};
+use crate::instrumentation::{observe_grpc, CLIENT_LETTER_SERVICE};
 use crate::types::{LetterContent, LinkedInProfile, ZohoContact};
 use crate::clients::dossier::DossierResult;
 use letter_grpc_client::{
     LetterGenerationServiceClient,
+    LetterContent as GrpcLetterContent,
     GenerateLetterRequest,
     GenerateLetterWithApprovalRequest,
     RecipientInfo,
     DossierContent,
     ApprovalData,
     LetterHistoryEntry,
+    HealthRequest,
 };
 use tonic::transport::Channel;
 
@@ -29,6 +32,28 @@ impl LetterServiceClient {
         }
     }
     
+    /// Ask the letter service for its health via `GetHealth`
+    pub async fn health_check(&self) -> Result<bool> {
+        let channel = Channel::from_shared(self.grpc_url.clone())
+            .map_err(|e| LennardError::Config(format!("Invalid gRPC URL: {}", e)))?
+            .connect()
+            .await
+            .map_err(|e| LennardError::ServiceUnavailable(
+                format!("Failed to connect to letter service at {}: {}", self.grpc_url, e)
+            ))?;
+        
+        let response = LetterGenerationServiceClient::new(channel)
+            .get_health(HealthRequest {})
+            .await
+            .map_err(|e| LennardError::ServiceUnavailable(format!("Letter service health check failed: {}", e)))?
+            .into_inner();
+        
+        if !response.healthy {
+            log::warn!("Letter service reports status {} ({:?})", response.status, response.dependencies);
+        }
+        Ok(response.healthy)
+    }
+    
     /// Generate personalized letter content using gRPC service
     pub async fn generate_letter(
         &self,
@@ -93,8 +118,7 @@ impl LetterServiceClient {
         log::info!("Dossiers included - Person: {} chars, Company: {} chars",
                   dossier_result.person_dossier_content.len(),
                   dossier_result.company_dossier_content.len());
-        let response = grpc_client
-            .generate_letter(request)
+        let response = observe_grpc(CLIENT_LETTER_SERVICE, "GenerateLetter", request, |request| grpc_client.generate_letter(request))
             .await
             .map_err(|e| LennardError::ServiceUnavailable(
                 format!("Letter generation gRPC call failed: {}", e)
@@ -112,20 +136,15 @@ impl LetterServiceClient {
             .ok_or_else(|| LennardError::Processing("No letter content in response".to_string()))?;
             
         // Convert gRPC letter content to our internal type
-        Ok(LetterContent {
-            subject: letter_grpc.betreff,
-            greeting: letter_grpc.anrede,
-            body: letter_grpc.brieftext,
-            sender_name: letter_grpc.sender_name,
-            recipient_name: contact.full_name.clone(),
-            company_name: dossier_result.company_name.clone(),
-        })
+        Ok(letter_content(letter_grpc, &contact.full_name, &dossier_result.company_name))
     }
     
-    /// Generate improved letter based on feedback with full approval context
+    /// Generate improved letter based on feedback with full approval context and the dossiers
     pub async fn generate_improved_letter_with_approval(
         &self,
         approval_data: &crate::workflow::approval_types::ApprovalData,
+        person_dossier: &str,
+        company_dossier: &str,
         feedback: &str
     ) -> Result<LetterContent> {
         // Validate that we have required mailing address
@@ -177,7 +196,7 @@ impl LetterServiceClient {
         };
         
         // Build request using the approval-aware endpoint with full context
-        let request = tonic::Request::new(GenerateLetterWithApprovalRequest {
+        let request = GenerateLetterWithApprovalRequest {
             approval_data: Some(grpc_approval_data),
             recipient_info: Some(RecipientInfo {
                 first_name: "".to_string(), // Not needed when full_name is provided
@@ -194,10 +213,10 @@ impl LetterServiceClient {
             our_company_info: "HEIN+FRICKE GmbH & Co.KG - Führender IT-Dienstleister".to_string(),
             letter_type: "improvement".to_string(),
             dossier_content: Some(DossierContent {
-                person_dossier: approval_data.person_dossier.clone().unwrap_or_default(),
-                company_dossier: approval_data.company_dossier.clone().unwrap_or_default(),
+                person_dossier: person_dossier.to_string(),
+                company_dossier: company_dossier.to_string(),
             })
-        });
+        };
         
         log::info!("Sending improvement request to gRPC service with feedback and full context");
         log::info!("Improvement request details - Name: {}, Email: {:?}, Title: {:?}, Company: {}", 
@@ -206,12 +225,13 @@ impl LetterServiceClient {
                   approval_data.recipient_title,
                   approval_data.company_name);
         log::info!("Dossiers included - Person: {} chars, Company: {} chars",
-                  approval_data.person_dossier.as_ref().map(|d| d.len()).unwrap_or(0),
-                  approval_data.company_dossier.as_ref().map(|d| d.len()).unwrap_or(0));
+                  person_dossier.len(),
+                  company_dossier.len());
         
         // Call the gRPC service
-        let response = grpc_client
-            .generate_letter_with_approval(request)
+        let response = observe_grpc(CLIENT_LETTER_SERVICE, "GenerateLetterWithApproval", request, |request| {
+            grpc_client.generate_letter_with_approval(request)
+        })
             .await
             .map_err(|e| LennardError::ServiceUnavailable(
                 format!("Letter improvement service failed: {}", e)
@@ -230,21 +250,14 @@ impl LetterServiceClient {
             
         log::info!("Successfully received improved letter from gRPC service");
         
-        Ok(LetterContent {
-            subject: letter_grpc.betreff,
-            greeting: letter_grpc.anrede,
-            body: letter_grpc.brieftext,
-            sender_name: letter_grpc.sender_name,
-            recipient_name: approval_data.recipient_name.clone(),
-            company_name: approval_data.company_name.clone(),
-        })
+        Ok(letter_content(letter_grpc, &approval_data.recipient_name, &approval_data.company_name))
     }
 
     /// Regenerate letter with feedback (simpler version for PDF page limit scenarios)
     pub async fn regenerate_letter_with_feedback(
         &self,
         contact: &ZohoContact,
-        profile: &LinkedInProfile,
+        _profile: &LinkedInProfile,
         dossier_result: &DossierResult,
         letter: &LetterContent,
         feedback: &str,
@@ -255,6 +268,7 @@ impl LetterServiceClient {
 
         // Build temporary ApprovalData to leverage GenerateLetterWithApproval which properly handles feedback
         let temp_approval = crate::workflow::approval_types::ApprovalData {
+            schema_version: crate::workflow::schema::APPROVAL_SCHEMA_VERSION,
             approval_id: crate::workflow::approval_types::ApprovalId::new(),  // Generate random UUID for temporary approval
             task_id: crate::workflow::approval_types::TaskId::new("retry".to_string()),
             contact_id: crate::workflow::approval_types::ContactId::new(contact.id.clone()),
@@ -270,6 +284,8 @@ impl LetterServiceClient {
                     content: letter.clone(),
                     feedback: None,
                     created_at: Utc::now(),
+                    pdf: None,
+                    edited_by: None,
                 }
             ],
             requested_at: Utc::now(),
@@ -278,15 +294,60 @@ impl LetterServiceClient {
             telegram_chat_id: None,
             updated_at: Utc::now(),
             mailing_address: contact.mailing_address.clone(),
-            pdf_base64: None,
-            person_dossier: Some(dossier_result.person_dossier_content.clone()),
-            company_dossier: Some(dossier_result.company_dossier_content.clone()),
+            pdf: None,
+            person_dossier: None,
+            company_dossier: None,
             industry: None,
             website: None,
+            idempotency_key: None,
+            letterexpress_job_id: None,
+            sent_at: None,
+            awaiting_response_since: None,
+            reminders_sent: 0,
+            last_reminder_at: None,
+            approved_at: None,
         };
 
         // Use GenerateLetterWithApproval which properly handles feedback via approval context
         log::info!("Calling generate_improved_letter_with_approval with feedback: {}", feedback);
-        self.generate_improved_letter_with_approval(&temp_approval, feedback).await
+        self.generate_improved_letter_with_approval(
+            &temp_approval,
+            &dossier_result.person_dossier_content,
+            &dossier_result.company_dossier_content,
+            feedback
+        ).await
+    }
+}
+
+/// Convert a letter from the service, turning newlines it sends as literal `\n` into real ones
+fn letter_content(letter: GrpcLetterContent, recipient_name: &str, company_name: &str) -> LetterContent {
+    let unescape = |text: String| if text.contains("\\n") { text.replace("\\n", "\n") } else { text };
+    LetterContent {
+        subject: unescape(letter.betreff),
+        greeting: unescape(letter.anrede),
+        body: unescape(letter.brieftext),
+        sender_name: letter.sender_name,
+        recipient_name: recipient_name.to_string(),
+        company_name: company_name.to_string(),
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter_content_unescapes_newlines() {
+        let letter = letter_content(GrpcLetterContent {
+            betreff: "Subject".to_string(),
+            anrede: "Dear John,\\n".to_string(),
+            brieftext: "First\\n\\nSecond".to_string(),
+            sender_name: "Sender".to_string(),
+            ..Default::default()
+        }, "John Doe", "Test Company");
+
+        assert_eq!(letter.greeting, "Dear John,\n");
+        assert_eq!(letter.body, "First\n\nSecond");
+        assert_eq!(letter.recipient_name, "John Doe");
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::config::LetterExpressConfig;
 use crate::error::{LennardError, Result};
+use crate::instrumentation::{ObservedSend, CLIENT_LETTEREXPRESS};
 use crate::types::LetterExpressRequest;
 use reqwest::Client as HttpClient;
 use serde_json;
@@ -47,6 +48,7 @@ impl LetterExpressClient {
             "letter": {
                 "base64_file": pdf_base64,
                 "base64_file_checksum": checksum,  // MD5 checksum of base64 string
+                "notice": request.reference,  // Our idempotency key, shown in the job list
                 "specification": {
                     "color": if request.color == crate::types::PrintColor::Color { "4" } else { "1" },  // "1" for b/w, "4" for color
                     "mode": if request.mode == crate::types::PrintMode::Duplex { "duplex" } else { "simplex" },
@@ -59,14 +61,13 @@ impl LetterExpressClient {
             .post(&url)
             .header("Content-Type", "application/json")
             .json(&request_body)
-            .send()
+            .send_observed(CLIENT_LETTEREXPRESS)
             .await?;
             
-        if !response.status().is_success() {
+        let status = response.status();
+        if !status.is_success() {
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(LennardError::ServiceUnavailable(
-                format!("LetterExpress returned error: {}", error_text)
-            ));
+            return Err(Self::send_error(status, error_text));
         }
         
         let result: serde_json::Value = response.json().await?;
@@ -79,6 +80,68 @@ impl LetterExpressClient {
             .to_string())
     }
     
+    /// Error of a print job request LetterExpress did not accept
+    /// Client errors are definite rejections, anything else may have created the job anyway
+    fn send_error(status: reqwest::StatusCode, error_text: String) -> LennardError {
+        if status.is_client_error() && status != reqwest::StatusCode::REQUEST_TIMEOUT && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            LennardError::Validation(format!("LetterExpress rejected the letter ({}): {}", status, error_text))
+        } else {
+            LennardError::ServiceUnavailable(format!("LetterExpress returned error ({}): {}", status, error_text))
+        }
+    }
+    
+    /// Find the print job that was sent with `reference` as its notice
+    /// Returns the job ID, or None if LetterExpress has no such job
+    pub async fn find_job_by_reference(&self, reference: &str) -> Result<Option<String>> {
+        let url = format!("{}/printjobs", self.config.base_url);
+        
+        // Like every v3 endpoint, the job list takes the auth in the JSON body
+        let auth_body = serde_json::json!({
+            "auth": {
+                "username": self.config.username,
+                "apikey": self.config.api_key,
+                "mode": self.config.mode
+            }
+        });
+        
+        let response = self.http_client
+            .get(&url)
+            .header("Content-Type", "application/json")
+            .json(&auth_body)
+            .send_observed(CLIENT_LETTEREXPRESS)
+            .await?;
+            
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(LennardError::ServiceUnavailable(
+                format!("LetterExpress job list returned error: {}", error_text)
+            ));
+        }
+        
+        let result: serde_json::Value = response.json().await?;
+        Ok(Self::job_id_for_reference(&result, reference))
+    }
+    
+    /// Search a job list response - `data` is either a list of jobs or a map keyed by job ID
+    fn job_id_for_reference(response: &serde_json::Value, reference: &str) -> Option<String> {
+        let jobs: Vec<&serde_json::Value> = match &response["data"] {
+            serde_json::Value::Array(jobs) => jobs.iter().collect(),
+            serde_json::Value::Object(jobs) => jobs.values().collect(),
+            _ => Vec::new(),
+        };
+        
+        jobs.into_iter()
+            .find(|job| job["notice"].as_str() == Some(reference))
+            .and_then(|job| {
+                ["job_id", "id", "jid"].iter()
+                    .find_map(|key| match &job[*key] {
+                        serde_json::Value::String(id) => Some(id.clone()),
+                        serde_json::Value::Number(id) => Some(id.to_string()),
+                        _ => None,
+                    })
+            })
+    }
+    
     /// Test connection to LetterExpress service
     pub async fn test_connection(&self) -> Result<bool> {
         // Use /balance endpoint (v3 is already in base_url)
@@ -148,4 +211,36 @@ impl LetterExpressClient {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_id_for_reference() {
+        let list = serde_json::json!({
+            "status": 200,
+            "data": [
+                {"id": 4711, "notice": "lennard-a-1"},
+                {"id": "4712", "notice": "lennard-b-1"}
+            ]
+        });
+        assert_eq!(LetterExpressClient::job_id_for_reference(&list, "lennard-a-1"), Some("4711".to_string()));
+        assert_eq!(LetterExpressClient::job_id_for_reference(&list, "lennard-b-1"), Some("4712".to_string()));
+        assert_eq!(LetterExpressClient::job_id_for_reference(&list, "lennard-c-1"), None);
+
+        let map = serde_json::json!({"data": {"4713": {"jid": 4713, "notice": "lennard-d-1"}}});
+        assert_eq!(LetterExpressClient::job_id_for_reference(&map, "lennard-d-1"), Some("4713".to_string()));
+    }
+
+    #[test]
+    fn test_only_client_errors_are_rejections() {
+        let error = |status: u16| LetterExpressClient::send_error(reqwest::StatusCode::from_u16(status).unwrap(), String::new());
+        assert!(!error(400).is_ambiguous());
+        assert!(!error(422).is_ambiguous());
+        assert!(error(429).is_ambiguous());
+        assert!(error(408).is_ambiguous());
+        assert!(error(502).is_ambiguous());
+    }
+}
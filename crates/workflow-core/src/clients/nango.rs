@@ -7,6 +7,7 @@
 //! tokens are always fresh.
 
 use crate::error::{LennardError, Result};
+use crate::instrumentation::{ObservedSend, CLIENT_NANGO};
 use reqwest::Client as HttpClient;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -102,16 +103,22 @@ impl NangoClient {
             .get(&url)
             .bearer_auth(&self.secret_key)
             .query(&params)
-            .send()
+            .send_observed(CLIENT_NANGO)
             .await
             .map_err(LennardError::Http)?;
             
-        if !response.status().is_success() {
-            return Err(LennardError::Auth(format!(
+        let status = response.status();
+        if !status.is_success() {
+            let message = format!(
                 "Nango API request failed: {} - {}",
-                response.status(),
+                status,
                 response.text().await.unwrap_or_default()
-            )));
+            );
+            // Rate limits and server errors are transient, anything else is an auth problem
+            if status.as_u16() == 429 || status.is_server_error() {
+                return Err(LennardError::ServiceUnavailable(message));
+            }
+            return Err(LennardError::Auth(message));
         }
         
         let data: Value = response.json().await
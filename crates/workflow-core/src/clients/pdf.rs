@@ -2,6 +2,7 @@
 
 use crate::config::PDFServiceConfig;
 use crate::error::{LennardError, Result};
+use crate::instrumentation::{ObservedSend, CLIENT_PDF_SERVICE};
 use crate::types::PDFTemplateData;
 use crate::paths;
 use reqwest::{Client as HttpClient, multipart};
@@ -61,7 +62,7 @@ impl PDFService {
         let response = self.http_client
             .post(&url)
             .multipart(form)
-            .send()
+            .send_observed(CLIENT_PDF_SERVICE)
             .await?;
 
         if !response.status().is_success() {
@@ -148,7 +149,7 @@ impl PDFService {
         let response = self.http_client
             .post(&url)
             .multipart(form)
-            .send()
+            .send_observed(CLIENT_PDF_SERVICE)
             .await?;
             
         if !response.status().is_success() {
@@ -189,6 +190,7 @@ impl PDFService {
     }
 }
 
+#[cfg(test)]
 mod tests {
 
     #[test]
@@ -296,10 +298,11 @@ mod tests {
         assert!(json.contains("\"Anrede\":"));
         assert!(json.contains("\"Brieftext\":"));
         assert!(json.contains("\"Sender-Name\":"));
-        assert!(json.contains("\"Reciepient\":"));
-        assert!(json.contains("\"Street-1\":"));
+        assert!(json.contains("\"Company\":"));
+        assert!(json.contains("\"Recipient\":"));
+        assert!(json.contains("\"Street 1\":"));
         assert!(json.contains("\"City\":"));
-        assert!(json.contains("\"PLZ\":"));
+        assert!(json.contains("\"ZipCode\":"));
         assert!(json.contains("\"Country\":"));
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 //! Telegram client for sending approval notifications
 
 use crate::error::{LennardError, Result};
+use crate::instrumentation::{ObservedSend, CLIENT_TELEGRAM};
 use crate::config::TelegramConfig;
 use crate::workflow::approval_types::LetterContent;
 use crate::types::ZohoContact;
@@ -49,6 +50,24 @@ pub trait TelegramClientTrait: Send + Sync {
         approval_id: &str,
         pdf_data: Vec<u8>
     ) -> Result<()>;
+    
+    /// Remind the chat of an approval that is still waiting for a response, with action buttons
+    async fn send_approval_reminder(
+        &self,
+        approval_id: &str,
+        recipient_name: &str,
+        company_name: &str,
+        waiting_hours: i64
+    ) -> Result<()>;
+    
+    /// Tell the chat that an approval expired without a response
+    async fn send_approval_expired(
+        &self,
+        approval_id: &str,
+        recipient_name: &str,
+        company_name: &str,
+        waiting_hours: i64
+    ) -> Result<()>;
 }
 
 pub struct TelegramClient {
@@ -89,6 +108,26 @@ impl TelegramClient {
             .replace('<', "&lt;")
             .replace('>', "&gt;")
     }
+    
+    /// Send a sendMessage payload
+    async fn post_message(&self, payload: serde_json::Value) -> Result<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        
+        let response = self.http_client
+            .post(&url)
+            .json(&payload)
+            .send_observed(CLIENT_TELEGRAM)
+            .await?;
+            
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LennardError::ServiceUnavailable(
+                format!("Telegram API error: {}", error_text)
+            ));
+        }
+        
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -140,7 +179,7 @@ impl TelegramClientTrait for TelegramClient {
         let response = self.http_client
             .post(&url)
             .json(&payload)
-            .send()
+            .send_observed(CLIENT_TELEGRAM)
             .await?;
             
         if !response.status().is_success() {
@@ -194,7 +233,7 @@ impl TelegramClientTrait for TelegramClient {
         let response = self.http_client
             .post(&url)
             .json(&payload)
-            .send()
+            .send_observed(CLIENT_TELEGRAM)
             .await?;
             
         if !response.status().is_success() {
@@ -264,7 +303,7 @@ impl TelegramClientTrait for TelegramClient {
         let response = self.http_client
             .post(&url)
             .multipart(form)
-            .send()
+            .send_observed(CLIENT_TELEGRAM)
             .await?;
             
         if !response.status().is_success() {
@@ -277,6 +316,72 @@ impl TelegramClientTrait for TelegramClient {
         log::info!("Telegram approval message with PDF sent for approval_id: {}", approval_id);
         Ok(())
     }
+    
+    async fn send_approval_reminder(
+        &self,
+        approval_id: &str,
+        recipient_name: &str,
+        company_name: &str,
+        waiting_hours: i64
+    ) -> Result<()> {
+        let message = format!(
+            "⏳ <b>Erinnerung: Briefgenehmigung ausstehend</b>\n\n\
+            🔖 <b>Approval ID:</b> <code>{}</code>\n\n\
+            <b>Empfänger:</b> {}\n\
+            <b>Firma:</b> {}\n\
+            <b>Wartet seit:</b> {} Stunden\n\n\
+            Bitte genehmigen Sie den Brief oder lehnen Sie ihn ab.",
+            approval_id,
+            Self::escape_html(recipient_name),
+            Self::escape_html(company_name),
+            waiting_hours
+        );
+        
+        self.post_message(json!({
+            "chat_id": self.chat_id,
+            "text": message,
+            "parse_mode": "HTML",
+            "reply_markup": {
+                "inline_keyboard": [[
+                    {"text": "✅ Genehmigen", "callback_data": Self::generate_approve_callback(approval_id)},
+                    {"text": "❌ Ablehnen", "callback_data": Self::generate_reject_callback(approval_id)}
+                ]]
+            }
+        })).await?;
+        
+        log::info!("Telegram reminder sent for approval_id: {}", approval_id);
+        Ok(())
+    }
+    
+    async fn send_approval_expired(
+        &self,
+        approval_id: &str,
+        recipient_name: &str,
+        company_name: &str,
+        waiting_hours: i64
+    ) -> Result<()> {
+        let message = format!(
+            "⌛ <b>Briefgenehmigung abgelaufen</b>\n\n\
+            🔖 <b>Approval ID:</b> <code>{}</code>\n\n\
+            <b>Empfänger:</b> {}\n\
+            <b>Firma:</b> {}\n\n\
+            Keine Antwort nach {} Stunden. Die Task wurde in Zoho CRM zurückgesetzt \
+            und wird erneut bearbeitet.",
+            approval_id,
+            Self::escape_html(recipient_name),
+            Self::escape_html(company_name),
+            waiting_hours
+        );
+        
+        self.post_message(json!({
+            "chat_id": self.chat_id,
+            "text": message,
+            "parse_mode": "HTML"
+        })).await?;
+        
+        log::info!("Telegram expiry notification sent for approval_id: {}", approval_id);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -4,8 +4,9 @@
 //! The compiler will refuse to compile code that tries to call API methods on an
 //! unauthenticated client.
 
-use crate::config::{ZohoConfig, LennardConfig};
+use crate::config::{ZohoConfig, LennardConfig, TaskSelectionConfig, SubjectMatch};
 use crate::error::{LennardError, Result};
+use crate::instrumentation::{ObservedSend, CLIENT_ZOHO};
 use crate::types::{ZohoContact, MailingAddress};
 use crate::clients::NangoClient;
 use reqwest::Client as HttpClient;
@@ -136,7 +137,7 @@ pub fn add_working_days(start: chrono::DateTime<chrono::Utc>, days: i32) -> chro
     let mut days_added = 0;
 
     while days_added < days {
-        current = current + chrono::Duration::days(1);
+        current += chrono::Duration::days(1);
 
         // Check if it's a weekday (Monday = 1, Sunday = 7)
         let weekday = current.weekday().num_days_from_monday();
@@ -148,6 +149,63 @@ pub fn add_working_days(start: chrono::DateTime<chrono::Utc>, days: i32) -> chro
     current
 }
 
+/// Build the Search API criteria for a task selection, e.g.
+/// `((Subject:equals:Connect%20on%20LinkedIn)and(Status:equals:Not%20started))`
+///
+/// Values are escaped and URL-encoded here - the criteria goes into the URL as is
+pub fn task_search_criteria(selection: &TaskSelectionConfig, today: chrono::NaiveDate) -> String {
+    fn value(raw: &str) -> String {
+        // Parentheses and commas are part of the criteria syntax
+        raw.replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)")
+            .replace(',', "\\,")
+            .replace('%', "%25")
+            .replace('&', "%26")
+            .replace('#', "%23")
+            .replace('+', "%2B")
+            .replace(' ', "%20")
+    }
+    
+    // Any of the values matches
+    fn any_of(field: &str, values: &[String]) -> Option<String> {
+        let parts: Vec<String> = values.iter()
+            .map(|v| format!("({}:equals:{})", field, value(v)))
+            .collect();
+        match parts.len() {
+            0 => None,
+            1 => parts.into_iter().next(),
+            _ => Some(format!("({})", parts.join("or"))),
+        }
+    }
+    
+    let mut criteria_parts = Vec::new();
+    match &selection.subject {
+        Some(SubjectMatch::Equals(subject)) => criteria_parts.push(format!("(Subject:equals:{})", value(subject))),
+        Some(SubjectMatch::StartsWith(prefix)) => criteria_parts.push(format!("(Subject:starts_with:{})", value(prefix))),
+        None => {}
+    }
+    criteria_parts.extend(any_of("Status", &selection.statuses));
+    // Search API needs Owner.id for the owner
+    criteria_parts.extend(any_of("Owner.id", &selection.owners));
+    criteria_parts.extend(any_of("Tag", &selection.tags));
+    if let Some(window) = &selection.due_date {
+        if let Some(from) = window.from_days {
+            criteria_parts.push(format!("(Due_Date:greater_equal:{})", (today + chrono::Duration::days(from)).format("%Y-%m-%d")));
+        }
+        if let Some(to) = window.to_days {
+            criteria_parts.push(format!("(Due_Date:less_equal:{})", (today + chrono::Duration::days(to)).format("%Y-%m-%d")));
+        }
+    }
+    
+    // For multiple criteria, wrap the entire expression in parentheses
+    if criteria_parts.len() > 1 {
+        format!("({})", criteria_parts.join("and"))
+    } else {
+        criteria_parts.join("")
+    }
+}
+
 // Implementation for authenticated client (API methods only available here)
 impl ZohoClient<Authenticated> {
     /// Check if client is authenticated (always true for Authenticated state)
@@ -155,6 +213,11 @@ impl ZohoClient<Authenticated> {
         true  // Compile-time guarantee
     }
     
+    /// Test that Nango still hands out a token for the Zoho connection
+    pub async fn test_connection(&self) -> Result<bool> {
+        self.nango_client.test_connection(&self.connection_id, &self.integration_id).await
+    }
+    
     /// Get a fresh access token from Nango (uses smart caching)
     async fn get_fresh_token(&self) -> Result<String> {
         self.nango_client.get_fresh_token(
@@ -186,7 +249,7 @@ impl ZohoClient<Authenticated> {
         let response = self.http_client
             .get(&url)
             .bearer_auth(&access_token)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
             
         if !response.status().is_success() {
@@ -227,7 +290,7 @@ impl ZohoClient<Authenticated> {
             .put(&url)
             .bearer_auth(&access_token)
             .json(&update_data)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
             
         if !response.status().is_success() {
@@ -265,7 +328,7 @@ impl ZohoClient<Authenticated> {
             .post(&url)
             .bearer_auth(&access_token)
             .json(&note_data)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
 
         if !response.status().is_success() {
@@ -290,7 +353,7 @@ impl ZohoClient<Authenticated> {
         let response = self.http_client
             .get(&url)
             .bearer_auth(&access_token)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
             
         if response.status() == 404 {
@@ -332,7 +395,7 @@ impl ZohoClient<Authenticated> {
             .put(&url)
             .bearer_auth(&access_token)
             .json(&update_data)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
             
         if !response.status().is_success() {
@@ -364,7 +427,7 @@ impl ZohoClient<Authenticated> {
             .put(&url)
             .bearer_auth(&access_token)
             .json(&update_data)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
 
         if !response.status().is_success() {
@@ -378,6 +441,38 @@ impl ZohoClient<Authenticated> {
         Ok(())
     }
 
+    /// Reset task status to "Not started" so the task is picked up again (after a cancelled run or an expired approval)
+    pub async fn reset_task_to_not_started(&self, task_id: &str) -> Result<()> {
+        let url = format!("{}/crm/v2/Tasks/{}", self.base_url, task_id);
+
+        // Get fresh token from Nango
+        let access_token = self.get_fresh_token().await?;
+
+        // Build update payload - only update status
+        let update_data = serde_json::json!({
+            "data": [{
+                "Status": "Not started",
+            }]
+        });
+
+        let response = self.http_client
+            .put(&url)
+            .bearer_auth(&access_token)
+            .json(&update_data)
+            .send_observed(CLIENT_ZOHO)
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(LennardError::ServiceUnavailable(
+                format!("Failed to reset Zoho task status: {}", error_text)
+            ));
+        }
+
+        log::info!("Reset Zoho task {} to 'Not started'", task_id);
+        Ok(())
+    }
+
     /// Attach a file to a Zoho task
     /// API Documentation: https://www.zoho.com/crm/developer/docs/api/v2/upload-attachment.html
     pub async fn attach_file_to_task(&self, task_id: &str, file_data: Vec<u8>, filename: &str) -> Result<()> {
@@ -398,7 +493,7 @@ impl ZohoClient<Authenticated> {
             .post(&url)
             .bearer_auth(access_token)
             .multipart(form)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
             
         let status = response.status();
@@ -433,37 +528,15 @@ impl ZohoClient<Authenticated> {
         ))
     }
     
-    /// Get tasks with filters using Search API (only available for authenticated clients)
-    pub async fn get_tasks(&self, filters: &[(&str, &str)]) -> Result<Vec<TasksResponse>> {
+    /// Get the tasks matching a selection using Search API (only available for authenticated clients)
+    pub async fn get_tasks(&self, selection: &TaskSelectionConfig) -> Result<Vec<TasksResponse>> {
         // Use Search API which properly respects Owner filter
         let url = format!("{}/crm/v2/Tasks/search", self.base_url);
         
         // Get fresh token from Nango (automatically refreshes if needed)
         let access_token = self.get_fresh_token().await?;
         
-        // Build criteria string from filters
-        // Search API requires different syntax for Owner and different operators
-        let criteria_parts: Vec<String> = filters.iter()
-            .map(|(key, value)| {
-                // URL-encode the value by replacing spaces
-                let encoded_value = value.replace(" ", "%20");
-                match *key {
-                    // Search API doesn't support 'contains' for Subject, use 'equals'
-                    "Subject" => format!("(Subject:equals:{})", encoded_value),
-                    "Status" => format!("(Status:equals:{})", encoded_value),
-                    // For Owner, use Owner.id:equals syntax for Search API
-                    "Owner" => format!("(Owner.id:equals:{})", encoded_value),
-                    _ => format!("({}:equals:{})", key, encoded_value),
-                }
-            })
-            .collect();
-        
-        // For multiple criteria, wrap the entire expression in parentheses
-        let criteria = if criteria_parts.len() > 1 {
-            format!("({})", criteria_parts.join("and"))
-        } else {
-            criteria_parts.join("")
-        };
+        let criteria = task_search_criteria(selection, chrono::Utc::now().date_naive());
         
         log::debug!("Zoho Tasks API URL: {}", url);
         log::debug!("Criteria: {}", criteria);
@@ -475,7 +548,7 @@ impl ZohoClient<Authenticated> {
         let response = self.http_client
             .get(&full_url)
             .bearer_auth(&access_token)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
             
         let status = response.status();
@@ -524,7 +597,7 @@ impl ZohoClient<Authenticated> {
             .put(&url)
             .bearer_auth(&access_token)
             .json(updates)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
             
         if !response.status().is_success() {
@@ -557,7 +630,7 @@ impl ZohoClient<Authenticated> {
             request = request.json(body);
         }
         
-        let response = request.send().await?;
+        let response = request.send_observed(CLIENT_ZOHO).await?;
             
         if !response.status().is_success() {
             let status = response.status();
@@ -607,7 +680,7 @@ impl ZohoClient<Authenticated> {
             .post(&url)
             .bearer_auth(&access_token)
             .json(&task_data)
-            .send()
+            .send_observed(CLIENT_ZOHO)
             .await?;
 
         if !response.status().is_success() {
@@ -701,11 +774,36 @@ mod tests {
         let _unauthenticated_client = ZohoClient::new(config);
         
         // This should NOT compile if uncommented:
-        // _unauthenticated_client.get_tasks(&[]).await;  // Compile error!
+        // _unauthenticated_client.get_tasks(&selection).await;  // Compile error!
         // _unauthenticated_client.get_contact("123").await;  // Compile error!
         
         // After authentication, API methods become available:
         // let authenticated_client = _unauthenticated_client.authenticate().await?;
-        // let tasks = authenticated_client.get_tasks(&[]).await?;  // This compiles!
+        // let tasks = authenticated_client.get_tasks(&selection).await?;  // This compiles!
+    }
+    
+    #[test]
+    fn test_task_search_criteria() {
+        let today = chrono::NaiveDate::from_ymd_opt(2025, 1, 10).unwrap();
+        
+        // The default selection is the former hard-coded filter
+        assert_eq!(
+            task_search_criteria(&TaskSelectionConfig::default(), today),
+            "((Subject:equals:Connect%20on%20LinkedIn)and(Status:equals:Not%20started)and(Owner.id:equals:1294764000001730350))"
+        );
+        
+        let selection = TaskSelectionConfig {
+            subject: Some(SubjectMatch::StartsWith("Follow up (Q1)".to_string())),
+            owners: vec!["1".to_string(), "2".to_string()],
+            statuses: Vec::new(),
+            due_date: Some(crate::config::DueDateWindow { from_days: Some(-7), to_days: Some(0) }),
+            tags: vec!["VIP".to_string()],
+            sort: Default::default(),
+        };
+        assert_eq!(
+            task_search_criteria(&selection, today),
+            "((Subject:starts_with:Follow%20up%20\\(Q1\\))and((Owner.id:equals:1)or(Owner.id:equals:2))and(Tag:equals:VIP)\
+             and(Due_Date:greater_equal:2025-01-03)and(Due_Date:less_equal:2025-01-10))"
+        );
     }
 }
\ No newline at end of file
@@ -2,7 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 use crate::error::{LennardError, Result};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Raw configuration structure matching credentials.json exactly
 #[derive(Debug, Deserialize)]
@@ -25,6 +26,12 @@ struct RawConfig {
     
     #[serde(default)]
     pub letter_service: Option<LetterServiceConfig>,
+    
+    #[serde(default)]
+    pub workflow: WorkflowConfig,
+    
+    #[serde(default)]
+    pub schedules: Vec<ScheduleConfig>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,6 +55,11 @@ pub struct LennardConfig {
     pub pdf_service: PDFServiceConfig,
     pub dossier: DossierConfig,
     pub letter_service: LetterServiceConfig,
+    #[serde(default)]
+    pub workflow: WorkflowConfig,
+    /// Recurring workflow triggers
+    #[serde(default)]
+    pub schedules: Vec<ScheduleConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,7 +140,503 @@ pub struct LetterServiceConfig {
     pub grpc_port: u16,
 }
 
+/// Workflow processing settings (optional section in credentials.json)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowConfig {
+    /// Tasks of one trigger processed in parallel - 1 processes them one after another
+    #[serde(default = "default_max_concurrent_tasks")]
+    pub max_concurrent_tasks: usize,
+    
+    /// Concurrent calls per downstream service, shared by all running tasks
+    #[serde(default)]
+    pub service_limits: ServiceLimitsConfig,
+    
+    /// Retries of transient step failures
+    #[serde(default)]
+    pub retry: RetryConfig,
+    
+    /// Reminders and expiry of approvals waiting for a response
+    #[serde(default)]
+    pub approval_expiry: ApprovalExpiryConfig,
+    
+    /// Zoho tasks picked up by triggers that don't bring their own selection
+    #[serde(default)]
+    pub task_selection: TaskSelectionConfig,
+    
+    /// Where approvals are kept
+    #[serde(default)]
+    pub approval_store: ApprovalStoreConfig,
+    
+    /// How long finished approvals, PDFs, processed files and logs are kept
+    #[serde(default)]
+    pub retention: RetentionConfig,
+}
+
+impl Default for WorkflowConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_tasks: default_max_concurrent_tasks(),
+            service_limits: ServiceLimitsConfig::default(),
+            retry: RetryConfig::default(),
+            approval_expiry: ApprovalExpiryConfig::default(),
+            task_selection: TaskSelectionConfig::default(),
+            approval_store: ApprovalStoreConfig::default(),
+            retention: RetentionConfig::default(),
+        }
+    }
+}
+
+/// Retry policy for all workflow steps, with optional overrides per `WorkflowSteps` method
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetryConfig {
+    #[serde(default)]
+    pub default: RetryPolicyConfig,
+    
+    /// Overrides by method name, e.g. `generate_dossiers` - unset fields use `default`
+    #[serde(default)]
+    pub steps: HashMap<String, RetryPolicyOverride>,
+}
+
+impl RetryConfig {
+    /// Effective policy of a step method
+    pub fn policy_for(&self, method: &str) -> RetryPolicyConfig {
+        match self.steps.get(method) {
+            Some(step) => step.apply_to(&self.default),
+            None => self.default.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicyConfig {
+    /// Attempts including the first one - 1 disables retries
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+    
+    #[serde(default = "default_retry_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    
+    #[serde(default = "default_retry_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    
+    #[serde(default = "default_retry_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+    
+    /// Random deviation of each backoff, as a fraction of it (0.2 = ±20%)
+    #[serde(default = "default_retry_jitter")]
+    pub jitter: f64,
+    
+    /// Timeout of a single attempt - 0 disables it
+    #[serde(default = "default_retry_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for RetryPolicyConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            initial_backoff_ms: default_retry_initial_backoff_ms(),
+            max_backoff_ms: default_retry_max_backoff_ms(),
+            backoff_multiplier: default_retry_backoff_multiplier(),
+            jitter: default_retry_jitter(),
+            timeout_secs: default_retry_timeout_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetryPolicyOverride {
+    pub max_attempts: Option<u32>,
+    pub initial_backoff_ms: Option<u64>,
+    pub max_backoff_ms: Option<u64>,
+    pub backoff_multiplier: Option<f64>,
+    pub jitter: Option<f64>,
+    pub timeout_secs: Option<u64>,
+}
+
+impl RetryPolicyOverride {
+    fn apply_to(&self, base: &RetryPolicyConfig) -> RetryPolicyConfig {
+        RetryPolicyConfig {
+            max_attempts: self.max_attempts.unwrap_or(base.max_attempts),
+            initial_backoff_ms: self.initial_backoff_ms.unwrap_or(base.initial_backoff_ms),
+            max_backoff_ms: self.max_backoff_ms.unwrap_or(base.max_backoff_ms),
+            backoff_multiplier: self.backoff_multiplier.unwrap_or(base.backoff_multiplier),
+            jitter: self.jitter.unwrap_or(base.jitter),
+            timeout_secs: self.timeout_secs.unwrap_or(base.timeout_secs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceLimitsConfig {
+    #[serde(default = "default_service_limit")]
+    pub dossier: usize,
+    
+    #[serde(default = "default_service_limit")]
+    pub letter_service: usize,
+    
+    #[serde(default = "default_service_limit")]
+    pub pdf_service: usize,
+}
+
+impl Default for ServiceLimitsConfig {
+    fn default() -> Self {
+        Self {
+            dossier: default_service_limit(),
+            letter_service: default_service_limit(),
+            pdf_service: default_service_limit(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalExpiryConfig {
+    /// Hours after which the chat is reminded of an unanswered approval - one reminder per entry
+    #[serde(default = "default_approval_reminder_after_hours")]
+    pub reminder_after_hours: Vec<u64>,
+    
+    /// Hours after which an unanswered approval expires and its task is reset - 0 disables expiry
+    #[serde(default = "default_approval_expire_after_hours")]
+    pub expire_after_hours: u64,
+    
+    #[serde(default = "default_approval_check_interval_secs")]
+    pub check_interval_secs: u64,
+}
+
+impl Default for ApprovalExpiryConfig {
+    fn default() -> Self {
+        Self {
+            reminder_after_hours: default_approval_reminder_after_hours(),
+            expire_after_hours: default_approval_expire_after_hours(),
+            check_interval_secs: default_approval_check_interval_secs(),
+        }
+    }
+}
+
+/// Storage backend of the approval queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStoreBackend {
+    /// One JSON file per approval in a directory per state
+    #[default]
+    File,
+    /// Embedded SQLite database - existing files are imported with `--migrate-approvals`
+    Sqlite,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalStoreConfig {
+    #[serde(default)]
+    pub backend: ApprovalStoreBackend,
+    
+    /// Database file of the SQLite backend - `approvals.db` in the data directory if unset
+    #[serde(default)]
+    pub sqlite_path: Option<String>,
+}
+
+impl ApprovalStoreConfig {
+    /// Database file of the SQLite backend for a data directory
+    pub fn sqlite_path<P: AsRef<Path>>(&self, data_root: P) -> PathBuf {
+        match &self.sqlite_path {
+            Some(path) => PathBuf::from(path),
+            None => data_root.as_ref().join(crate::paths::APPROVALS_DB_FILE_NAME),
+        }
+    }
+}
+
+/// Days each artifact class is kept - 0 keeps it forever
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Sent, failed and expired approvals older than this move into a dated archive
+    #[serde(default = "default_archive_approvals_after_days")]
+    pub archive_approvals_after_days: u64,
+    
+    /// Approval archives older than this are deleted
+    #[serde(default = "default_archive_retention_days")]
+    pub archive_retention_days: u64,
+    
+    /// PDFs in `data/pdfs/` older than this are deleted
+    #[serde(default = "default_pdf_retention_days")]
+    pub pdf_retention_days: u64,
+    
+    /// Processed and failed triggers and processed outbox entries older than this are deleted
+    #[serde(default = "default_processed_retention_days")]
+    pub processed_retention_days: u64,
+    
+    /// Run records and dry-run output older than this are deleted
+    #[serde(default = "default_run_retention_days")]
+    pub run_retention_days: u64,
+    
+    /// Checkpoints of tasks that never finished are deleted after this - they hold PDFs and dossiers
+    #[serde(default = "default_checkpoint_retention_days")]
+    pub checkpoint_retention_days: u64,
+    
+    /// Dossier and LetterExpress logs older than this are deleted - the dossier logs hold LinkedIn profiles
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: u64,
+    
+    /// Hours between runs of the server's retention task - 0, the default, disables it
+    #[serde(default = "default_retention_interval_hours")]
+    pub interval_hours: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            archive_approvals_after_days: default_archive_approvals_after_days(),
+            archive_retention_days: default_archive_retention_days(),
+            pdf_retention_days: default_pdf_retention_days(),
+            processed_retention_days: default_processed_retention_days(),
+            run_retention_days: default_run_retention_days(),
+            checkpoint_retention_days: default_checkpoint_retention_days(),
+            log_retention_days: default_log_retention_days(),
+            interval_hours: default_retention_interval_hours(),
+        }
+    }
+}
+
+/// Which Zoho tasks a workflow run picks up - all set criteria must match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskSelectionConfig {
+    /// Unset matches any subject
+    #[serde(default = "default_task_subject")]
+    pub subject: Option<SubjectMatch>,
+    
+    /// Zoho user IDs - a task of any of them matches
+    #[serde(default = "default_task_owners")]
+    pub owners: Vec<String>,
+    
+    /// A task in any of these states matches, e.g. "Not started" (Zoho uses English status values)
+    #[serde(default = "default_task_statuses")]
+    pub statuses: Vec<String>,
+    
+    #[serde(default)]
+    pub due_date: Option<DueDateWindow>,
+    
+    /// A task with any of these tags matches
+    #[serde(default)]
+    pub tags: Vec<String>,
+    
+    #[serde(default)]
+    pub sort: TaskSortOrder,
+}
+
+impl Default for TaskSelectionConfig {
+    fn default() -> Self {
+        Self {
+            subject: default_task_subject(),
+            owners: default_task_owners(),
+            statuses: default_task_statuses(),
+            due_date: None,
+            tags: Vec::new(),
+            sort: TaskSortOrder::default(),
+        }
+    }
+}
+
+impl TaskSelectionConfig {
+    pub fn validate(&self) -> Result<()> {
+        let subject_empty = match &self.subject {
+            Some(SubjectMatch::Equals(value)) | Some(SubjectMatch::StartsWith(value)) => value.trim().is_empty(),
+            None => false,
+        };
+        let values = self.owners.iter().chain(&self.statuses).chain(&self.tags);
+        if subject_empty || values.clone().any(|value| value.trim().is_empty()) {
+            return Err(LennardError::Config("Task selection values must not be empty".to_string()));
+        }
+        
+        // The Zoho search API needs at least one criterion
+        if self.subject.is_none() && self.owners.is_empty() && self.statuses.is_empty() && self.tags.is_empty() && self.due_date.is_none() {
+            return Err(LennardError::Config("Task selection needs at least one criterion".to_string()));
+        }
+        
+        if let Some(DueDateWindow { from_days: Some(from), to_days: Some(to) }) = self.due_date {
+            if from > to {
+                return Err(LennardError::Config("Task due date window: from_days must not be after to_days".to_string()));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectMatch {
+    Equals(String),
+    StartsWith(String),
+}
+
+/// Due dates relative to today, e.g. `{"to_days": 0}` selects tasks due today or overdue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct DueDateWindow {
+    #[serde(default)]
+    pub from_days: Option<i64>,
+    #[serde(default)]
+    pub to_days: Option<i64>,
+}
+
+/// Order in which selected tasks are processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSortOrder {
+    #[default]
+    OldestFirst,
+    NewestFirst,
+    DueDateFirst,
+}
+
+/// Recurring workflow trigger, e.g. weekdays 09:00 Europe/Berlin with up to 5 tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    /// Unique name - part of the trigger IDs, so letters, digits, `-` and `_` only
+    pub name: String,
+    
+    /// Cron expression with seconds: "sec min hour day-of-month month day-of-week", e.g. "0 0 9 * * Mon-Fri"
+    pub cron: String,
+    
+    /// IANA time zone the cron expression is evaluated in
+    #[serde(default = "default_schedule_timezone")]
+    pub timezone: String,
+    
+    #[serde(default = "default_schedule_max_tasks")]
+    pub max_tasks: u32,
+    
+    #[serde(default)]
+    pub dry_run: bool,
+    
+    /// What happens to runs missed while the server was down
+    #[serde(default)]
+    pub missed_runs: MissedRunPolicy,
+    
+    #[serde(default = "default_schedule_enabled")]
+    pub enabled: bool,
+    
+    /// Overrides `workflow.task_selection` for this schedule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_selection: Option<TaskSelectionConfig>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// Missed runs are dropped
+    #[default]
+    Skip,
+    /// The latest missed run is triggered once after startup
+    CatchUp,
+}
+
+impl std::fmt::Display for MissedRunPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MissedRunPolicy::Skip => write!(f, "skip"),
+            MissedRunPolicy::CatchUp => write!(f, "catch_up"),
+        }
+    }
+}
+
 // Default functions
+fn default_max_concurrent_tasks() -> usize {
+    1
+}
+
+fn default_service_limit() -> usize {
+    2
+}
+
+fn default_retry_max_attempts() -> u32 {
+    3
+}
+
+fn default_retry_initial_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_retry_max_backoff_ms() -> u64 {
+    30_000
+}
+
+fn default_retry_backoff_multiplier() -> f64 {
+    2.0
+}
+
+fn default_retry_jitter() -> f64 {
+    0.2
+}
+
+fn default_retry_timeout_secs() -> u64 {
+    600
+}
+
+fn default_approval_reminder_after_hours() -> Vec<u64> {
+    vec![24, 72]
+}
+
+fn default_approval_expire_after_hours() -> u64 {
+    168
+}
+
+fn default_approval_check_interval_secs() -> u64 {
+    300
+}
+
+fn default_archive_approvals_after_days() -> u64 {
+    30
+}
+
+fn default_archive_retention_days() -> u64 {
+    365
+}
+
+fn default_pdf_retention_days() -> u64 {
+    180
+}
+
+fn default_processed_retention_days() -> u64 {
+    30
+}
+
+fn default_run_retention_days() -> u64 {
+    90
+}
+
+fn default_checkpoint_retention_days() -> u64 {
+    30
+}
+
+fn default_log_retention_days() -> u64 {
+    14
+}
+
+fn default_retention_interval_hours() -> u64 {
+    0
+}
+
+fn default_task_subject() -> Option<SubjectMatch> {
+    Some(SubjectMatch::Equals("Connect on LinkedIn".to_string()))
+}
+
+fn default_task_owners() -> Vec<String> {
+    vec!["1294764000001730350".to_string()]  // Lennard's Zoho user ID
+}
+
+fn default_task_statuses() -> Vec<String> {
+    vec!["Not started".to_string()]
+}
+
+fn default_schedule_timezone() -> String {
+    "Europe/Berlin".to_string()
+}
+
+fn default_schedule_max_tasks() -> u32 {
+    1
+}
+
+fn default_schedule_enabled() -> bool {
+    true
+}
+
 fn default_pdf_service() -> PDFServiceConfig {
     PDFServiceConfig {
         base_url: "http://localhost:8000".to_string()
@@ -209,6 +717,8 @@ impl LennardConfig {
             pdf_service: raw.pdf_service,
             dossier: raw.dossier,
             letter_service: raw.letter_service.unwrap_or_else(default_letter_service),
+            workflow: raw.workflow,
+            schedules: raw.schedules,
         }
     }
     
@@ -233,6 +743,55 @@ impl LennardConfig {
             return Err(LennardError::Config("Telegram bot token is required".to_string()));
         }
         
+        let limits = &self.workflow.service_limits;
+        if self.workflow.max_concurrent_tasks == 0 || limits.dossier == 0 || limits.letter_service == 0 || limits.pdf_service == 0 {
+            return Err(LennardError::Config("Workflow concurrency limits must be at least 1".to_string()));
+        }
+        
+        let retry = &self.workflow.retry;
+        for method in retry.steps.keys() {
+            if !crate::workflow::retry::RETRIED_METHODS.contains(&method.as_str()) {
+                return Err(LennardError::Config(format!(
+                    "Unknown workflow step '{}' in retry config, expected one of: {}",
+                    method,
+                    crate::workflow::retry::RETRIED_METHODS.join(", ")
+                )));
+            }
+        }
+        for method in crate::workflow::retry::RETRIED_METHODS {
+            let policy = retry.policy_for(method);
+            if policy.max_attempts == 0 || policy.backoff_multiplier < 1.0 || !(0.0..=1.0).contains(&policy.jitter) {
+                return Err(LennardError::Config(format!(
+                    "Invalid retry policy for {}: max_attempts must be at least 1, backoff_multiplier at least 1.0 and jitter between 0 and 1",
+                    method
+                )));
+            }
+        }
+        
+        let expiry = &self.workflow.approval_expiry;
+        if expiry.check_interval_secs == 0 {
+            return Err(LennardError::Config("Approval expiry check interval must be at least 1 second".to_string()));
+        }
+        if expiry.reminder_after_hours.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(LennardError::Config("Approval reminder hours must be in ascending order".to_string()));
+        }
+        if expiry.expire_after_hours > 0 && expiry.reminder_after_hours.iter().any(|hours| *hours >= expiry.expire_after_hours) {
+            return Err(LennardError::Config("Approval reminders must come before the expiry deadline".to_string()));
+        }
+        
+        self.workflow.task_selection.validate()?;
+        
+        let mut schedule_names = std::collections::HashSet::new();
+        for schedule in &self.schedules {
+            if !schedule_names.insert(schedule.name.as_str()) {
+                return Err(LennardError::Config(format!("Duplicate schedule name '{}'", schedule.name)));
+            }
+            crate::workflow::scheduler::Schedule::from_config(schedule)?;
+            if let Some(selection) = &schedule.task_selection {
+                selection.validate()?;
+            }
+        }
+        
         Ok(())
     }
 }
\ No newline at end of file
@@ -1,5 +1,40 @@
-/// Workflow configuration constants
+//! Workflow configuration constants
 
 /// Maximum retry attempts when PDF generation exceeds page limit
 /// Used for both initial letter generation and improvement workflows
 pub const PDF_PAGE_LIMIT_MAX_RETRIES: u32 = 5;
+
+/// Attempts per outbox side effect before it is given up and surfaced as failed
+pub const OUTBOX_MAX_ATTEMPTS: u32 = 10;
+
+/// Delay before the first outbox retry - doubled after every failed attempt
+pub const OUTBOX_INITIAL_BACKOFF_SECS: i64 = 60;
+
+/// Upper bound of the delay between outbox retries
+pub const OUTBOX_MAX_BACKOFF_SECS: i64 = 3600;
+
+/// Age below which unreferenced blobs survive garbage collection - the approval referencing
+/// a freshly written blob may not be saved yet
+pub const BLOB_GC_MIN_AGE_SECS: u64 = 3600;
+
+/// How late a scheduled run may still start - older missed runs follow the schedule's missed run policy
+pub const SCHEDULE_MISFIRE_GRACE_SECS: i64 = 300;
+
+/// How often the scheduler checks for due runs
+pub const SCHEDULE_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Events buffered per event bus subscriber - slower subscribers miss the oldest events
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// How long a claimed letter may take to show up in the LetterExpress job list - until then
+/// an approval left in Sending is not queued again when its job is missing
+pub const SEND_RECONCILE_GRACE_SECS: i64 = 900;
+
+/// Minimum time between two LetterExpress job list lookups for approvals left in Sending
+pub const SEND_RECONCILE_INTERVAL_SECS: u64 = 60;
+
+/// How long a single dependency health probe may take before it counts as unhealthy
+pub const HEALTH_PROBE_TIMEOUT_SECS: u64 = 5;
+
+/// How long a dependency health report is reused before the dependencies are probed again
+pub const HEALTH_REPORT_TTL_SECS: i64 = 10;
@@ -25,6 +25,9 @@ pub enum LennardError {
     
     #[error("IO error: {0}")]
     IoError(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
     
     #[error("Workflow error: {0}")]
     Workflow(String),
@@ -44,6 +47,15 @@ pub enum LennardError {
     #[error("Deserialization error: {0}")]
     Deserialization(String),
 
+    #[error("Integrity check failed: {0}")]
+    Integrity(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
     #[error("PDF page limit exceeded: generated {page_count} pages (limit: {limit})")]
     PageLimitExceeded {
         page_count: u32,
@@ -52,5 +64,34 @@ pub enum LennardError {
     },
 }
 
+impl LennardError {
+    /// Whether the failure is transient, so repeating the same call may succeed
+    ///
+    /// Unavailable services, timeouts, connection errors and HTTP 429/5xx are retryable.
+    /// Everything else (validation, missing data, page limits, client errors, ...) is permanent.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LennardError::ServiceUnavailable(_) | LennardError::Timeout(_) => true,
+            LennardError::Http(e) => {
+                if e.is_timeout() || e.is_connect() {
+                    return true;
+                }
+                e.status()
+                    .map(|status| status.as_u16() == 429 || status.is_server_error())
+                    .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the failed request may still have been carried out by the service
+    ///
+    /// Retryable failures (a 5xx may come after the work was done) and unreadable
+    /// success responses are ambiguous. Everything else definitely changed nothing.
+    pub fn is_ambiguous(&self) -> bool {
+        self.is_retryable() || matches!(self, LennardError::Http(_) | LennardError::Json(_))
+    }
+}
+
 /// Result type for Lennard operations
 pub type Result<T> = std::result::Result<T, LennardError>;
\ No newline at end of file
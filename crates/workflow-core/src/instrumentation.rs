@@ -0,0 +1,199 @@
+//! Prometheus instruments of the workflow server
+//!
+//! Instruments live in the default registry and are registered on first use. The server's
+//! `/metrics` endpoint renders them with [`gather`]. Client requests are also traced.
+
+use crate::error::{LennardError, Result};
+use crate::trace_context;
+use crate::workflow::approval_types::{ApprovalState, StateCountMap};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_counter_vec, register_gauge, register_gauge_vec, register_histogram_vec, register_int_counter,
+    CounterVec, Encoder, Gauge, GaugeVec, HistogramVec, IntCounter, TextEncoder,
+};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+/// Client label values, shared with the dependency names of the health checks
+pub const CLIENT_ZOHO: &str = "zoho";
+pub const CLIENT_BASEROW: &str = "baserow";
+pub const CLIENT_NANGO: &str = "nango";
+pub const CLIENT_LETTEREXPRESS: &str = "letterexpress";
+pub const CLIENT_TELEGRAM: &str = "telegram";
+pub const CLIENT_PDF_SERVICE: &str = "pdf_service";
+pub const CLIENT_DOSSIER: &str = "dossier";
+pub const CLIENT_LETTER_SERVICE: &str = "letter_service";
+
+/// Content type of [`gather`]'s output
+pub const CONTENT_TYPE: &str = prometheus::TEXT_FORMAT;
+
+/// Upper bounds of the duration buckets - downstream calls range from milliseconds to minutes
+const DURATION_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+static STEPS_TOTAL: Lazy<CounterVec> = Lazy::new(|| register_counter_vec!(
+    "lennard_workflow_steps_total",
+    "Workflow steps run, by step and outcome",
+    &["step", "outcome"]
+).expect("workflow step counter"));
+
+static STEP_DURATION: Lazy<HistogramVec> = Lazy::new(|| register_histogram_vec!(
+    "lennard_workflow_step_duration_seconds",
+    "Duration of workflow steps including retries",
+    &["step", "outcome"],
+    DURATION_BUCKETS.to_vec()
+).expect("workflow step histogram"));
+
+static CLIENT_REQUESTS_TOTAL: Lazy<CounterVec> = Lazy::new(|| register_counter_vec!(
+    "lennard_client_requests_total",
+    "Requests to external services, by client and outcome",
+    &["client", "outcome"]
+).expect("client request counter"));
+
+static CLIENT_REQUEST_DURATION: Lazy<HistogramVec> = Lazy::new(|| register_histogram_vec!(
+    "lennard_client_request_duration_seconds",
+    "Duration of requests to external services",
+    &["client", "outcome"],
+    DURATION_BUCKETS.to_vec()
+).expect("client request histogram"));
+
+static PDF_PAGE_LIMIT_RETRIES: Lazy<IntCounter> = Lazy::new(|| register_int_counter!(
+    "lennard_pdf_page_limit_retries_total",
+    "Letters regenerated because their PDF exceeded the page limit"
+).expect("page limit retry counter"));
+
+static APPROVAL_QUEUE_DEPTH: Lazy<GaugeVec> = Lazy::new(|| register_gauge_vec!(
+    "lennard_approval_queue_depth",
+    "Approvals in the queue, by state",
+    &["state"]
+).expect("approval queue gauge"));
+
+static LAST_LETTER_SENT: Lazy<Gauge> = Lazy::new(|| register_gauge!(
+    "lennard_last_letter_sent_timestamp_seconds",
+    "Unix time of the last letter accepted by LetterExpress"
+).expect("last letter sent gauge"));
+
+fn outcome(success: bool) -> &'static str {
+    if success { "success" } else { "failure" }
+}
+
+/// Record a finished workflow step
+pub fn observe_step(step: &str, duration: Duration, success: bool) {
+    let labels = [step, outcome(success)];
+    STEPS_TOTAL.with_label_values(&labels).inc();
+    STEP_DURATION.with_label_values(&labels).observe(duration.as_secs_f64());
+}
+
+/// Record a request to an external service
+pub fn observe_client_request(client: &str, duration: Duration, success: bool) {
+    let labels = [client, outcome(success)];
+    CLIENT_REQUESTS_TOTAL.with_label_values(&labels).inc();
+    CLIENT_REQUEST_DURATION.with_label_values(&labels).observe(duration.as_secs_f64());
+}
+
+/// Run a gRPC call or other request and record its duration and outcome
+pub async fn observe_client<T, E, F>(client: &str, request: F) -> std::result::Result<T, E>
+where
+    F: Future<Output = std::result::Result<T, E>>,
+{
+    let started = Instant::now();
+    let result = request.await;
+    observe_client_request(client, started.elapsed(), result.is_ok());
+    result
+}
+
+/// Call a gRPC method in a client span, passing the trace context along in the request metadata
+pub async fn observe_grpc<M, T, F, Fut>(client: &'static str, method: &str, message: M, call: F) -> std::result::Result<T, tonic::Status>
+where
+    F: FnOnce(tonic::Request<M>) -> Fut,
+    Fut: Future<Output = std::result::Result<T, tonic::Status>>,
+{
+    let span = trace_context::client_span(client, method);
+    let request = trace_context::traced_request(&span, message);
+    observe_client(client, call(request)).instrument(span).await
+}
+
+/// `send` for HTTP clients that records and traces the request - non-2xx responses count as failures
+pub trait ObservedSend {
+    fn send_observed(self, client: &'static str) -> impl Future<Output = reqwest::Result<reqwest::Response>> + Send;
+}
+
+impl ObservedSend for reqwest::RequestBuilder {
+    async fn send_observed(self, client: &'static str) -> reqwest::Result<reqwest::Response> {
+        let (http_client, request) = self.build_split();
+        let mut request = request?;
+        let span = trace_context::client_span(client, &format!("{} {}", request.method(), request.url().path()));
+        trace_context::inject_http(&span, request.headers_mut());
+
+        let started = Instant::now();
+        let result = http_client.execute(request).instrument(span).await;
+        let success = result.as_ref().is_ok_and(|response| response.status().is_success());
+        observe_client_request(client, started.elapsed(), success);
+        result
+    }
+}
+
+pub fn record_pdf_page_limit_retry() {
+    PDF_PAGE_LIMIT_RETRIES.inc();
+}
+
+/// Set the queue depth of every state with its own directory
+pub fn set_approval_queue_depth(counts: &StateCountMap) {
+    for state in ApprovalState::QUEUED {
+        APPROVAL_QUEUE_DEPTH.with_label_values(&[state.name()]).set(counts.get(state) as f64);
+    }
+}
+
+/// Record a letter send - earlier timestamps than the recorded one are ignored
+pub fn record_letter_sent(sent_at: DateTime<Utc>) {
+    let timestamp = sent_at.timestamp() as f64;
+    if timestamp > LAST_LETTER_SENT.get() {
+        LAST_LETTER_SENT.set(timestamp);
+    }
+}
+
+/// Render all instruments in the Prometheus text format
+pub fn gather() -> Result<String> {
+    // Touch the instruments so they are exported before their first observation
+    Lazy::force(&PDF_PAGE_LIMIT_RETRIES);
+    Lazy::force(&LAST_LETTER_SENT);
+
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .map_err(|e| LennardError::Serialization(format!("Failed to encode metrics: {}", e)))?;
+    String::from_utf8(buffer)
+        .map_err(|e| LennardError::Serialization(format!("Metrics are not valid UTF-8: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_instruments_are_exported() {
+        observe_step("generate_pdf", Duration::from_millis(120), true);
+        let failed: std::result::Result<(), &str> = observe_client(CLIENT_DOSSIER, async { Err("unavailable") }).await;
+        assert!(failed.is_err());
+        record_pdf_page_limit_retry();
+
+        let mut counts = StateCountMap::new();
+        counts.increment(ApprovalState::PendingApproval);
+        counts.increment(ApprovalState::PendingApproval);
+        set_approval_queue_depth(&counts);
+
+        let sent_at = Utc::now();
+        record_letter_sent(sent_at);
+        record_letter_sent(sent_at - chrono::Duration::hours(1));
+
+        let text = gather().unwrap();
+        assert!(text.contains(r#"lennard_workflow_steps_total{outcome="success",step="generate_pdf"}"#));
+        assert!(text.contains(r#"lennard_workflow_step_duration_seconds_bucket{outcome="success",step="generate_pdf",le="0.25"}"#));
+        assert!(text.contains(r#"lennard_client_requests_total{client="dossier",outcome="failure"}"#));
+        assert!(text.contains(r#"lennard_approval_queue_depth{state="pending_approval"} 2"#));
+        assert!(text.contains(r#"lennard_approval_queue_depth{state="expired"} 0"#));
+        assert!(text.contains("lennard_pdf_page_limit_retries_total"));
+        assert!(text.contains(&format!("lennard_last_letter_sent_timestamp_seconds {}", sent_at.timestamp())));
+    }
+}
@@ -11,6 +11,8 @@ pub mod types;
 pub mod error;
 pub mod paths;
 pub mod constants;
+pub mod instrumentation;
+pub mod trace_context;
 
 // Re-export main types for easy access
 pub use config::LennardConfig;
@@ -51,6 +53,17 @@ pub use workflow::{
     HealthCheckResult,
     StateCountMap,
     WorkflowTrigger,
+    // Persistent workflow runs
+    WorkflowRunStore,
+    WorkflowRun,
+    WorkflowStatus,
+    TaskState,
+    // Resumable task checkpoints
+    TaskCheckpointStore,
+    WorkflowStep,
+    // Cooperative cancellation
+    CancellationRegistry,
+    CancellationToken,
 };
 
 #[cfg(test)]
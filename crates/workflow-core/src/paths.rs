@@ -66,6 +66,13 @@ pub const TRIGGERS_DIR_NAME: &str = "triggers";
 pub const PROCESSED_DIR_NAME: &str = "processed";
 pub const FAILED_DIR_NAME: &str = "failed";
 pub const DATA_DIR_NAME: &str = "data";
+pub const RUNS_DIR_NAME: &str = "runs";
+pub const DRY_RUNS_DIR_NAME: &str = "dry_runs";
+pub const CHECKPOINTS_DIR_NAME: &str = "checkpoints";
+pub const OUTBOX_DIR_NAME: &str = "outbox";
+pub const SCHEDULES_DIR_NAME: &str = "schedules";
+pub const LOCKS_DIR_NAME: &str = "locks";
+pub const APPROVALS_DB_FILE_NAME: &str = "approvals.db";
 
 // Approval state directories
 pub const PENDING_APPROVAL_DIR_NAME: &str = "pending_approval";
@@ -73,12 +80,20 @@ pub const AWAITING_RESPONSE_DIR_NAME: &str = "awaiting_response";
 pub const APPROVED_DIR_NAME: &str = "approved";
 pub const NEEDS_IMPROVEMENT_DIR_NAME: &str = "needs_improvement";
 pub const FAILED_STATE_DIR_NAME: &str = "failed";
+pub const EXPIRED_DIR_NAME: &str = "expired";
 
 // Data subdirectories
 pub const DOSSIERS_DIR_NAME: &str = "dossiers";
 pub const LETTERS_DIR_NAME: &str = "letters";
 pub const ATTACHMENTS_DIR_NAME: &str = "attachments";
 pub const PDFS_DIR_NAME: &str = "pdfs";
+pub const BLOBS_DIR_NAME: &str = "blobs";
+pub const ARCHIVE_DIR_NAME: &str = "archive";
+
+// Log directory names
+pub const GRPC_LOGS_DIR_NAME: &str = "grpc";
+pub const DOSSIER_LOGS_DIR_NAME: &str = "dossier";
+pub const LETTEREXPRESS_LOGS_DIR_NAME: &str = "letterexpress";
 
 // App subdirectories
 pub const CONFIG_DIR_NAME: &str = "config";
@@ -101,6 +116,38 @@ pub fn triggers_failed_dir() -> PathBuf {
     triggers_dir().join(FAILED_DIR_NAME)
 }
 
+pub fn runs_dir() -> PathBuf {
+    workflow_data_root().join(RUNS_DIR_NAME)
+}
+
+pub fn dry_runs_dir() -> PathBuf {
+    workflow_data_root().join(DRY_RUNS_DIR_NAME)
+}
+
+pub fn checkpoints_dir() -> PathBuf {
+    workflow_data_root().join(CHECKPOINTS_DIR_NAME)
+}
+
+pub fn outbox_dir() -> PathBuf {
+    workflow_data_root().join(OUTBOX_DIR_NAME)
+}
+
+pub fn outbox_processed_dir() -> PathBuf {
+    outbox_dir().join(PROCESSED_DIR_NAME)
+}
+
+pub fn outbox_failed_dir() -> PathBuf {
+    outbox_dir().join(FAILED_DIR_NAME)
+}
+
+pub fn schedules_dir() -> PathBuf {
+    workflow_data_root().join(SCHEDULES_DIR_NAME)
+}
+
+pub fn approvals_db_path() -> PathBuf {
+    workflow_data_root().join(APPROVALS_DB_FILE_NAME)
+}
+
 pub fn data_dir() -> PathBuf {
     workflow_data_root().join(DATA_DIR_NAME)
 }
@@ -121,6 +168,14 @@ pub fn pdfs_dir() -> PathBuf {
     data_dir().join(PDFS_DIR_NAME)
 }
 
+pub fn blobs_dir() -> PathBuf {
+    data_dir().join(BLOBS_DIR_NAME)
+}
+
+pub fn archive_dir() -> PathBuf {
+    workflow_data_root().join(ARCHIVE_DIR_NAME)
+}
+
 pub fn approval_state_dir(state_name: &str) -> PathBuf {
     workflow_data_root().join(state_name)
 }
@@ -153,6 +208,10 @@ pub fn failed_state_dir() -> PathBuf {
     approval_state_dir(FAILED_STATE_DIR_NAME)
 }
 
+pub fn expired_dir() -> PathBuf {
+    approval_state_dir(EXPIRED_DIR_NAME)
+}
+
 pub fn app_root() -> PathBuf {
     PathBuf::from(APP_ROOT)
 }
@@ -175,15 +234,15 @@ pub fn logs_root() -> PathBuf {
 }
 
 pub fn grpc_logs_dir() -> PathBuf {
-    logs_root().join("grpc")
+    logs_root().join(GRPC_LOGS_DIR_NAME)
 }
 
 pub fn dossier_logs_dir() -> PathBuf {
-    grpc_logs_dir().join("dossier")
+    grpc_logs_dir().join(DOSSIER_LOGS_DIR_NAME)
 }
 
 pub fn letterexpress_logs_dir() -> PathBuf {
-    logs_root().join("letterexpress")
+    logs_root().join(LETTEREXPRESS_LOGS_DIR_NAME)
 }
 
 /// Get all directories that should be created for the workflow system
@@ -193,16 +252,26 @@ pub fn all_workflow_directories() -> Vec<PathBuf> {
         triggers_dir(),
         triggers_processed_dir(),
         triggers_failed_dir(),
+        runs_dir(),
+        dry_runs_dir(),
+        checkpoints_dir(),
+        outbox_dir(),
+        outbox_processed_dir(),
+        outbox_failed_dir(),
+        schedules_dir(),
         data_dir(),
         dossiers_dir(),
         letters_dir(),
         attachments_dir(),
         pdfs_dir(),
+        blobs_dir(),
+        archive_dir(),
         pending_approval_dir(),
         awaiting_response_dir(),
         approved_dir(),
         needs_improvement_dir(),
         failed_state_dir(),
+        expired_dir(),
         letterexpress_logs_dir(),
     ]
 }
@@ -360,20 +429,30 @@ mod tests {
         assert!(all_dirs.contains(&triggers_dir()));
         assert!(all_dirs.contains(&triggers_processed_dir()));
         assert!(all_dirs.contains(&triggers_failed_dir()));
+        assert!(all_dirs.contains(&runs_dir()));
+        assert!(all_dirs.contains(&dry_runs_dir()));
+        assert!(all_dirs.contains(&checkpoints_dir()));
+        assert!(all_dirs.contains(&outbox_dir()));
+        assert!(all_dirs.contains(&outbox_processed_dir()));
+        assert!(all_dirs.contains(&outbox_failed_dir()));
+        assert!(all_dirs.contains(&schedules_dir()));
         assert!(all_dirs.contains(&data_dir()));
         assert!(all_dirs.contains(&dossiers_dir()));
         assert!(all_dirs.contains(&letters_dir()));
         assert!(all_dirs.contains(&attachments_dir()));
         assert!(all_dirs.contains(&pdfs_dir()));
+        assert!(all_dirs.contains(&blobs_dir()));
+        assert!(all_dirs.contains(&archive_dir()));
         assert!(all_dirs.contains(&pending_approval_dir()));
         assert!(all_dirs.contains(&awaiting_response_dir()));
         assert!(all_dirs.contains(&approved_dir()));
         assert!(all_dirs.contains(&needs_improvement_dir()));
         assert!(all_dirs.contains(&failed_state_dir()));
+        assert!(all_dirs.contains(&expired_dir()));
         assert!(all_dirs.contains(&letterexpress_logs_dir()));
         
-        // Should have exactly 15 directories
-        assert_eq!(all_dirs.len(), 15);
+        // Should have exactly 25 directories
+        assert_eq!(all_dirs.len(), 25);
     }
 
     #[test]
@@ -386,6 +465,7 @@ mod tests {
             "approved",
             "needs_improvement",
             "failed",
+            "expired",
         ];
 
         for state_name in expected_state_dirs {
@@ -0,0 +1,347 @@
+//! Deep health checks of the downstream dependencies
+//!
+//! Every dependency is probed concurrently with a timeout. Reports are reused for a short
+//! time so frequent `Check` and `Watch` calls do not hammer the downstream services.
+
+use crate::clients::zoho::Authenticated;
+use crate::clients::{DossierClient, LetterExpressClient, LetterServiceClient, PDFService, ZohoClient};
+use crate::constants::{HEALTH_PROBE_TIMEOUT_SECS, HEALTH_REPORT_TTL_SECS};
+use crate::error::{LennardError, Result};
+use crate::instrumentation::{
+    CLIENT_DOSSIER, CLIENT_LETTEREXPRESS, CLIENT_LETTER_SERVICE, CLIENT_PDF_SERVICE, CLIENT_ZOHO,
+};
+use crate::workflow::approval_types::HealthStatus;
+use crate::workflow::ApprovalQueue;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// A dependency that can be probed for its health
+#[async_trait]
+pub trait HealthProbe: Send + Sync {
+    /// Dependency name, e.g. `pdf_service`
+    fn name(&self) -> &str;
+
+    /// Errors count as unhealthy and are kept as the dependency's last error
+    async fn probe(&self) -> Result<HealthStatus>;
+}
+
+/// Healthy when the probe succeeded, an error naming the dependency otherwise
+fn reachable(name: &str, ok: bool) -> Result<HealthStatus> {
+    if ok {
+        Ok(HealthStatus::Healthy)
+    } else {
+        Err(LennardError::ServiceUnavailable(format!("{} is not reachable", name)))
+    }
+}
+
+#[async_trait]
+impl HealthProbe for PDFService {
+    fn name(&self) -> &str {
+        CLIENT_PDF_SERVICE
+    }
+
+    async fn probe(&self) -> Result<HealthStatus> {
+        reachable(self.name(), self.health_check().await?)
+    }
+}
+
+#[async_trait]
+impl HealthProbe for LetterExpressClient {
+    fn name(&self) -> &str {
+        CLIENT_LETTEREXPRESS
+    }
+
+    async fn probe(&self) -> Result<HealthStatus> {
+        reachable(self.name(), self.test_connection().await?)
+    }
+}
+
+#[async_trait]
+impl HealthProbe for ZohoClient<Authenticated> {
+    fn name(&self) -> &str {
+        CLIENT_ZOHO
+    }
+
+    async fn probe(&self) -> Result<HealthStatus> {
+        reachable(self.name(), self.test_connection().await?)
+    }
+}
+
+#[async_trait]
+impl HealthProbe for LetterServiceClient {
+    fn name(&self) -> &str {
+        CLIENT_LETTER_SERVICE
+    }
+
+    async fn probe(&self) -> Result<HealthStatus> {
+        reachable(self.name(), self.health_check().await?)
+    }
+}
+
+#[async_trait]
+impl HealthProbe for DossierClient {
+    fn name(&self) -> &str {
+        CLIENT_DOSSIER
+    }
+
+    async fn probe(&self) -> Result<HealthStatus> {
+        reachable(self.name(), self.health_check().await?)
+    }
+}
+
+#[async_trait]
+impl HealthProbe for ApprovalQueue {
+    fn name(&self) -> &str {
+        "approval_queue"
+    }
+
+    async fn probe(&self) -> Result<HealthStatus> {
+        Ok(self.health_check()?.status)
+    }
+}
+
+/// Outcome of the latest probe of one dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyHealth {
+    pub name: String,
+    pub status: HealthStatus,
+    pub latency_ms: u64,
+    /// Error of the latest failed probe - kept after the dependency recovered
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Health of all dependencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Worst status of all dependencies
+    pub status: HealthStatus,
+    pub dependencies: Vec<DependencyHealth>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl HealthReport {
+    pub fn dependency(&self, name: &str) -> Option<&DependencyHealth> {
+        self.dependencies.iter().find(|dependency| dependency.name == name)
+    }
+}
+
+fn severity(status: HealthStatus) -> u8 {
+    match status {
+        HealthStatus::Healthy => 0,
+        HealthStatus::Degraded => 1,
+        HealthStatus::Unhealthy => 2,
+    }
+}
+
+/// Probes all registered dependencies and aggregates their health
+pub struct HealthChecker {
+    probes: Vec<Arc<dyn HealthProbe>>,
+    probe_timeout: Duration,
+    report_ttl: ChronoDuration,
+    last_report: Mutex<Option<HealthReport>>,
+}
+
+impl HealthChecker {
+    pub fn new() -> Self {
+        Self {
+            probes: Vec::new(),
+            probe_timeout: Duration::from_secs(HEALTH_PROBE_TIMEOUT_SECS),
+            report_ttl: ChronoDuration::seconds(HEALTH_REPORT_TTL_SECS),
+            last_report: Mutex::new(None),
+        }
+    }
+
+    pub fn with_probe(mut self, probe: Arc<dyn HealthProbe>) -> Self {
+        self.probes.push(probe);
+        self
+    }
+
+    pub fn with_probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// How long a report is reused - zero probes on every check
+    pub fn with_report_ttl(mut self, ttl: ChronoDuration) -> Self {
+        self.report_ttl = ttl;
+        self
+    }
+
+    /// Names of the probed dependencies
+    pub fn dependency_names(&self) -> Vec<&str> {
+        self.probes.iter().map(|probe| probe.name()).collect()
+    }
+
+    /// Current health, probing the dependencies unless the last report is recent enough
+    pub async fn check(&self) -> HealthReport {
+        // Held while probing so concurrent callers share one round of probes
+        let mut last_report = self.last_report.lock().await;
+        if let Some(report) = last_report.as_ref() {
+            if Utc::now() - report.checked_at < self.report_ttl {
+                return report.clone();
+            }
+        }
+
+        let dependencies = join_all(self.probes.iter().map(|probe| {
+            let previous = last_report.as_ref().and_then(|report| report.dependency(probe.name()));
+            self.probe_dependency(probe.as_ref(), previous)
+        })).await;
+
+        let status = dependencies.iter()
+            .map(|dependency| dependency.status)
+            .max_by_key(|status| severity(*status))
+            .unwrap_or(HealthStatus::Healthy);
+
+        let report = HealthReport {
+            status,
+            dependencies,
+            checked_at: Utc::now(),
+        };
+        *last_report = Some(report.clone());
+        report
+    }
+
+    async fn probe_dependency(&self, probe: &dyn HealthProbe, previous: Option<&DependencyHealth>) -> DependencyHealth {
+        let started = Instant::now();
+        let outcome = match tokio::time::timeout(self.probe_timeout, probe.probe()).await {
+            Ok(outcome) => outcome,
+            Err(_) => Err(LennardError::Timeout(format!(
+                "{} did not answer within {}ms", probe.name(), self.probe_timeout.as_millis()
+            ))),
+        };
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let checked_at = Utc::now();
+
+        match outcome {
+            Ok(status) => DependencyHealth {
+                name: probe.name().to_string(),
+                status,
+                latency_ms,
+                last_error: previous.and_then(|p| p.last_error.clone()),
+                last_error_at: previous.and_then(|p| p.last_error_at),
+                checked_at,
+            },
+            Err(e) => {
+                log::warn!("Health probe of {} failed: {}", probe.name(), e);
+                DependencyHealth {
+                    name: probe.name().to_string(),
+                    status: HealthStatus::Unhealthy,
+                    latency_ms,
+                    last_error: Some(e.to_string()),
+                    last_error_at: Some(checked_at),
+                    checked_at,
+                }
+            }
+        }
+    }
+}
+
+impl Default for HealthChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    struct MockProbe {
+        name: &'static str,
+        status: HealthStatus,
+        failing: AtomicBool,
+        delay: Duration,
+        calls: AtomicU32,
+    }
+
+    impl MockProbe {
+        fn new(name: &'static str, status: HealthStatus) -> Self {
+            Self {
+                name,
+                status,
+                failing: AtomicBool::new(false),
+                delay: Duration::ZERO,
+                calls: AtomicU32::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl HealthProbe for MockProbe {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn probe(&self) -> Result<HealthStatus> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(LennardError::ServiceUnavailable("connection refused".to_string()));
+            }
+            Ok(self.status)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_aggregates_dependencies() {
+        let pdf = Arc::new(MockProbe::new("pdf_service", HealthStatus::Healthy));
+        let queue = Arc::new(MockProbe::new("approval_queue", HealthStatus::Degraded));
+        let slow = Arc::new(MockProbe {
+            delay: Duration::from_secs(5),
+            ..MockProbe::new("dossier", HealthStatus::Healthy)
+        });
+        let checker = HealthChecker::new()
+            .with_probe(pdf.clone())
+            .with_probe(queue.clone())
+            .with_probe_timeout(Duration::from_millis(50))
+            .with_report_ttl(ChronoDuration::zero());
+
+        let report = checker.check().await;
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert_eq!(report.dependency("approval_queue").unwrap().status, HealthStatus::Degraded);
+
+        // Timed out probes are unhealthy
+        let checker = checker.with_probe(slow);
+        let report = checker.check().await;
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        let dossier = report.dependency("dossier").unwrap();
+        assert_eq!(dossier.status, HealthStatus::Unhealthy);
+        assert!(dossier.last_error.as_deref().unwrap().contains("did not answer"));
+        assert_eq!(checker.dependency_names(), vec!["pdf_service", "approval_queue", "dossier"]);
+    }
+
+    #[tokio::test]
+    async fn test_last_error_survives_recovery_and_reports_are_reused() {
+        let pdf = Arc::new(MockProbe::new("pdf_service", HealthStatus::Healthy));
+        pdf.failing.store(true, Ordering::SeqCst);
+        let checker = HealthChecker::new()
+            .with_probe(pdf.clone())
+            .with_report_ttl(ChronoDuration::zero());
+
+        let failed = checker.check().await;
+        let dependency = failed.dependency("pdf_service").unwrap();
+        assert_eq!(dependency.status, HealthStatus::Unhealthy);
+        assert!(dependency.last_error.as_deref().unwrap().contains("connection refused"));
+
+        pdf.failing.store(false, Ordering::SeqCst);
+        let recovered = checker.check().await;
+        let dependency = recovered.dependency("pdf_service").unwrap();
+        assert_eq!(dependency.status, HealthStatus::Healthy);
+        assert!(dependency.last_error.is_some());
+        assert_eq!(dependency.last_error_at, failed.dependency("pdf_service").unwrap().last_error_at);
+        assert_eq!(pdf.calls.load(Ordering::SeqCst), 2);
+
+        // Within the TTL the last report is returned without probing
+        let checker = checker.with_report_ttl(ChronoDuration::minutes(1));
+        checker.check().await;
+        assert_eq!(pdf.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,10 +1,14 @@
 //! Service modules for business logic
 
 pub mod address_extractor;
+pub mod health;
 pub mod letter_generator;
+pub mod service_limits;
 pub mod workflow_processor;
 
 // Re-export service types
 pub use address_extractor::AddressExtractor;
+pub use health::{DependencyHealth, HealthChecker, HealthProbe, HealthReport};
 pub use letter_generator::LetterGenerator;
+pub use service_limits::{DownstreamService, ServiceLimits};
 pub use workflow_processor::WorkflowProcessor;
\ No newline at end of file
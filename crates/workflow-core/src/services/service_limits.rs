@@ -0,0 +1,98 @@
+//! Concurrency limits for downstream services
+//! One semaphore per service, shared by every task that is processed in parallel
+
+use crate::config::ServiceLimitsConfig;
+use crate::error::{LennardError, Result};
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Downstream services with a concurrency limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownstreamService {
+    Dossier,
+    LetterService,
+    PdfService,
+}
+
+impl fmt::Display for DownstreamService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DownstreamService::Dossier => "dossier",
+            DownstreamService::LetterService => "letter_service",
+            DownstreamService::PdfService => "pdf_service",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Cheap to clone - clones share the same semaphores
+#[derive(Clone)]
+pub struct ServiceLimits {
+    dossier: Arc<Semaphore>,
+    letter_service: Arc<Semaphore>,
+    pdf_service: Arc<Semaphore>,
+}
+
+impl ServiceLimits {
+    pub fn new(config: &ServiceLimitsConfig) -> Self {
+        Self {
+            dossier: Arc::new(Semaphore::new(config.dossier.max(1))),
+            letter_service: Arc::new(Semaphore::new(config.letter_service.max(1))),
+            pdf_service: Arc::new(Semaphore::new(config.pdf_service.max(1))),
+        }
+    }
+
+    fn semaphore(&self, service: DownstreamService) -> &Arc<Semaphore> {
+        match service {
+            DownstreamService::Dossier => &self.dossier,
+            DownstreamService::LetterService => &self.letter_service,
+            DownstreamService::PdfService => &self.pdf_service,
+        }
+    }
+
+    /// Wait for a free slot - the slot is released when the permit is dropped
+    pub async fn acquire(&self, service: DownstreamService) -> Result<OwnedSemaphorePermit> {
+        let semaphore = self.semaphore(service).clone();
+
+        if semaphore.available_permits() == 0 {
+            log::debug!("Waiting for a free {} slot", service);
+        }
+
+        semaphore.acquire_owned().await
+            .map_err(|_| LennardError::ServiceUnavailable(format!("{} limiter was closed", service)))
+    }
+
+    /// Free slots of a service
+    pub fn available(&self, service: DownstreamService) -> usize {
+        self.semaphore(service).available_permits()
+    }
+}
+
+impl Default for ServiceLimits {
+    fn default() -> Self {
+        Self::new(&ServiceLimitsConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_permits_are_shared_between_clones() {
+        let limits = ServiceLimits::new(&ServiceLimitsConfig {
+            dossier: 1,
+            letter_service: 2,
+            pdf_service: 2,
+        });
+        let clone = limits.clone();
+
+        let permit = limits.acquire(DownstreamService::Dossier).await.unwrap();
+        assert_eq!(clone.available(DownstreamService::Dossier), 0);
+        assert_eq!(clone.available(DownstreamService::LetterService), 2);
+
+        drop(permit);
+        assert_eq!(clone.available(DownstreamService::Dossier), 1);
+    }
+}
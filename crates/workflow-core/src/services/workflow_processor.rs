@@ -1,20 +1,29 @@
 //! Workflow processing service
 
 use crate::error::{LennardError, Result};
-use crate::types::{ZohoContact, LinkedInProfile, MailingAddress, PDFTemplateData};
+use crate::instrumentation;
+use crate::types::{ZohoContact, LinkedInProfile, MailingAddress, PDFTemplateData, RenderedLetter};
 use crate::workflow::approval_types::{LetterContent, ApprovalId};
 use crate::clients::{ZohoClient, BaserowClient, DossierClient, DossierResult, LetterExpressClient, LetterServiceClient, PDFService, TelegramClientTrait};
 use crate::clients::zoho::Authenticated;  // Import the authenticated state
 use crate::services::AddressExtractor;
+use crate::services::service_limits::{DownstreamService, ServiceLimits};
 use crate::workflow::{WorkflowSteps, approval_types::ApprovalState, ApprovalQueue};
 use std::sync::Arc;
+use tokio::sync::OwnedSemaphorePermit;
 use async_trait::async_trait;
+use crate::config::{TaskSelectionConfig, TaskSortOrder};
 use zoho_generated_types::TasksResponse;
 
-// Constants for Zoho task filters
-const TASK_SUBJECT_FILTER: &str = "Connect on LinkedIn";
-const TASK_STATUS_FILTER: &str = "Not started";  // English status in Zoho
-const TASK_OWNER_ID: &str = "1294764000001730350";   // Lennard's Zoho user ID
+/// Sort selected tasks into processing order
+fn sort_tasks(tasks: &mut [TasksResponse], order: TaskSortOrder) {
+    match order {
+        TaskSortOrder::OldestFirst => tasks.sort_by(|a, b| a.created_time.as_deref().unwrap_or("").cmp(b.created_time.as_deref().unwrap_or(""))),
+        TaskSortOrder::NewestFirst => tasks.sort_by(|a, b| b.created_time.as_deref().unwrap_or("").cmp(a.created_time.as_deref().unwrap_or(""))),
+        // Tasks without a due date come last
+        TaskSortOrder::DueDateFirst => tasks.sort_by_key(|task| (task.due_date.is_none(), task.due_date.clone())),
+    }
+}
 
 pub struct WorkflowProcessor {
     zoho_client: Arc<ZohoClient<Authenticated>>,  // Type-safe authenticated client
@@ -26,9 +35,12 @@ pub struct WorkflowProcessor {
     letter_service: Arc<LetterServiceClient>,
     telegram_client: Arc<dyn TelegramClientTrait>,
     approval_queue: Arc<ApprovalQueue>,
+    service_limits: ServiceLimits,  // Shared by all tasks running in parallel
+    task_selection: TaskSelectionConfig,  // Used by triggers without their own selection
 }
 
 impl WorkflowProcessor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         zoho_client: Arc<ZohoClient<Authenticated>>,
         baserow_client: Arc<BaserowClient>,
@@ -39,6 +51,8 @@ impl WorkflowProcessor {
         letter_service: Arc<LetterServiceClient>,
         telegram_client: Arc<dyn TelegramClientTrait>,
         approval_queue: Arc<ApprovalQueue>,
+        service_limits: ServiceLimits,
+        task_selection: TaskSelectionConfig,
     ) -> Self {
         Self {
             zoho_client,
@@ -50,6 +64,8 @@ impl WorkflowProcessor {
             letter_service,
             telegram_client,
             approval_queue,
+            service_limits,
+            task_selection,
         }
     }
     
@@ -125,34 +141,36 @@ impl WorkflowProcessor {
         
         Ok(())
     }
+    
+    /// Keep a sent PDF in the blob store for backup and debugging - returns its hash for the logs
+    fn keep_pdf(&self, pdf_data: &[u8]) -> String {
+        match self.approval_queue.blobs().put(pdf_data) {
+            Ok(blob) => {
+                log::info!("PDF stored as blob {}", blob.sha256);
+                blob.sha256
+            }
+            Err(e) => {
+                log::warn!("Failed to store PDF: {}", e);
+                "(not stored)".to_string()
+            }
+        }
+    }
 }
 
 /// Implementation of WorkflowSteps trait for the existing WorkflowProcessor
 /// This connects the new strongly-typed trait system to the existing business logic
 #[async_trait]
 impl WorkflowSteps for WorkflowProcessor {
-    async fn load_available_tasks(&self, max_count: u32) -> Result<Vec<TasksResponse>> {
-        // Define task selection criteria for workflow processing
-        // Note: These filters need to match actual Zoho task data
-        // You may need to adjust based on your Zoho CRM configuration
-        let filters = &[
-            ("Subject", TASK_SUBJECT_FILTER),
-            ("Status", TASK_STATUS_FILTER),
-            ("Owner", TASK_OWNER_ID),
-        ];
+    async fn load_available_tasks(&self, max_count: u32, selection: Option<&TaskSelectionConfig>) -> Result<Vec<TasksResponse>> {
+        let selection = selection.unwrap_or(&self.task_selection);
         
-        log::info!("Loading up to {} available tasks with filters: {:?}", max_count, filters);
+        log::info!("Loading up to {} available tasks with selection: {:?}", max_count, selection);
         
-        let mut tasks = self.zoho_client.get_tasks(filters).await?;
+        let mut tasks = self.zoho_client.get_tasks(selection).await?;
         
-        log::info!("Search API returned {} tasks (all should belong to Lennard)", tasks.len());
+        log::info!("Search API returned {} tasks", tasks.len());
         
-        // Sort by creation time (oldest first) - using optional created_time field
-        tasks.sort_by(|a, b| {
-            let a_time = a.created_time.as_deref().unwrap_or("");
-            let b_time = b.created_time.as_deref().unwrap_or("");
-            a_time.cmp(b_time)
-        });
+        sort_tasks(&mut tasks, selection.sort);
         
         // Limit to max_count
         tasks.truncate(max_count as usize);
@@ -194,6 +212,17 @@ impl WorkflowSteps for WorkflowProcessor {
             .ok_or_else(|| LennardError::Workflow(format!("LinkedIn profile {} not found", linkedin_id)))
     }
     
+    async fn reserve_capacity(&self, method: &str) -> Result<Option<OwnedSemaphorePermit>> {
+        let service = match method {
+            "generate_dossiers" => DownstreamService::Dossier,
+            "generate_letter" => DownstreamService::LetterService,
+            "render_letter_pdf" => DownstreamService::PdfService,
+            _ => return Ok(None),
+        };
+        self.service_limits.acquire(service).await.map(Some)
+    }
+    
+    // Runs with the dossier permit reserved by the orchestrator
     async fn generate_dossiers(&self, profile: &LinkedInProfile, contact_id: &str) -> Result<crate::clients::DossierResult> {
         self.dossier_client.generate_and_get_dossiers(&serde_json::to_value(profile)?, contact_id).await
     }
@@ -214,14 +243,7 @@ impl WorkflowSteps for WorkflowProcessor {
         let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
         let note_title = format!("Letter Sent to {} ({})", company_name, timestamp);
 
-        // Format the letter content from its structured fields
-        let formatted_letter = format!(
-            "Subject: {}\n\n{}\n\n{}\n\nBest regards,\n{}",
-            letter.subject,
-            letter.greeting,
-            letter.body,
-            letter.sender_name
-        );
+        let formatted_letter = letter.to_plain_text();
 
         let note_content = format!(
             "Letter Content:\n\n{}\n\n---\n\nTracking ID: {}\nSent at: {}",
@@ -235,16 +257,11 @@ impl WorkflowSteps for WorkflowProcessor {
 
     async fn generate_letter(&self, contact: &ZohoContact, profile: &LinkedInProfile, dossier: &DossierResult) -> Result<LetterContent> {
         // Use the letter service which returns the correct LetterContent type
+        // Runs with the letter service permit reserved by the orchestrator
         self.letter_service.generate_letter(contact, profile, dossier).await
     }
     
-    async fn approval_start(&self, task_id: &str, contact: &ZohoContact, profile: &LinkedInProfile, letter: &LetterContent, dossier: &DossierResult) -> Result<ApprovalId> {
-        use crate::workflow::approval_types::{TaskId, ContactId, UserId};
-        use crate::types::PDFTemplateData;
-        use base64::{Engine as _, engine::general_purpose};
-
-        log::info!("Starting approval for task {} and contact {}", task_id, contact.full_name);
-
+    async fn render_letter_pdf(&self, contact: &ZohoContact, profile: &LinkedInProfile, dossier: &DossierResult, letter: &LetterContent) -> Result<RenderedLetter> {
         // Get mailing address - REQUIRED for later PDF sending
         let mailing_address = contact.mailing_address.as_ref()
             .ok_or_else(|| LennardError::Workflow(
@@ -260,7 +277,7 @@ impl WorkflowSteps for WorkflowProcessor {
         }
 
         // Generate PDF with retry logic for page limit violations
-        log::info!("Generating PDF for approval (with retry logic for page limits)");
+        log::info!("Generating PDF for letter to {} (with retry logic for page limits)", contact.full_name);
         let max_retries = crate::constants::PDF_PAGE_LIMIT_MAX_RETRIES;
         let mut current_letter = letter.clone();
         let mut pdf_data: Option<Vec<u8>> = None;
@@ -271,7 +288,10 @@ impl WorkflowSteps for WorkflowProcessor {
 
             let pdf_template_data = PDFTemplateData::from_letter_and_address(&current_letter, mailing_address);
 
-            match self.pdf_service.generate_pdf_typed("letter_template.odt", &pdf_template_data).await {
+            // Runs with the PDF service permit reserved by the orchestrator
+            let pdf_result = self.pdf_service.generate_pdf_typed("letter_template.odt", &pdf_template_data).await;
+
+            match pdf_result {
                 Ok(data) => {
                     log::info!("PDF generated successfully on attempt {}, {} bytes", attempt, data.len());
                     pdf_data = Some(data);
@@ -307,8 +327,10 @@ impl WorkflowSteps for WorkflowProcessor {
                     );
 
                     log::info!("Regenerating letter with feedback (attempt {}/{}): {}", attempt + 1, max_retries, feedback);
+                    instrumentation::record_pdf_page_limit_retry();
 
                     // Regenerate the letter with feedback
+                    let _permit = self.service_limits.acquire(DownstreamService::LetterService).await?;
                     match self.letter_service.regenerate_letter_with_feedback(
                         contact,
                         profile,
@@ -343,6 +365,23 @@ impl WorkflowSteps for WorkflowProcessor {
         })?;
 
         log::info!("Final PDF generated successfully, {} bytes", pdf_data.len());
+
+        Ok(RenderedLetter {
+            letter: current_letter,
+            pdf: pdf_data,
+        })
+    }
+
+    async fn approval_start(&self, task_id: &str, contact: &ZohoContact, rendered: &RenderedLetter, dossier: &DossierResult) -> Result<ApprovalId> {
+        use crate::workflow::approval_types::{TaskId, ContactId, UserId};
+
+        log::info!("Starting approval for task {} and contact {}", task_id, contact.full_name);
+
+        // Mailing address was validated when the PDF was rendered - still REQUIRED for later PDF sending
+        let mailing_address = contact.mailing_address.as_ref()
+            .ok_or_else(|| LennardError::Workflow(
+                format!("Contact {} has no mailing address", contact.full_name)
+            ))?;
         
         // Create the required types
         let task_id = TaskId::new(task_id.to_string());
@@ -397,12 +436,12 @@ impl WorkflowSteps for WorkflowProcessor {
             recipient_email,
             recipient_title,
             company_name,
-            letter.clone(),
+            rendered.letter.clone(),
             user_id,
             Some(mailing_address.clone()),
-            Some(general_purpose::STANDARD.encode(&pdf_data)),
-            Some(dossier.person_dossier_content.clone()),
-            Some(dossier.company_dossier_content.clone()),
+            Some(&rendered.pdf),
+            Some(&dossier.person_dossier_content),
+            Some(&dossier.company_dossier_content),
             industry,
             website,
         )?;
@@ -412,8 +451,6 @@ impl WorkflowSteps for WorkflowProcessor {
     }
     
     async fn request_approval(&self, approval_id: &ApprovalId, letter: &LetterContent, contact: &ZohoContact) -> Result<ApprovalState> {
-        use base64::{Engine as _, engine::general_purpose};
-        
         // Use the existing approval ID that was already persisted
         let approval_id_str = approval_id.to_string();
         
@@ -425,13 +462,8 @@ impl WorkflowSteps for WorkflowProcessor {
                 format!("Approval {} not found", approval_id_str)
             ))?;
         
-        // Decode the PDF from base64
-        let pdf_data = approval_data.pdf_base64
-            .ok_or_else(|| LennardError::Workflow("Approval has no PDF data".to_string()))
-            .and_then(|base64| {
-                general_purpose::STANDARD.decode(&base64)
-                    .map_err(|e| LennardError::Workflow(format!("Failed to decode PDF: {}", e)))
-            })?;
+        let pdf_data = approval_data.pdf_bytes(self.approval_queue.blobs())?
+            .ok_or_else(|| LennardError::Workflow("Approval has no PDF data".to_string()))?;
         
         log::info!("Retrieved PDF from approval, {} bytes", pdf_data.len());
         
@@ -452,32 +484,20 @@ impl WorkflowSteps for WorkflowProcessor {
         Ok(ApprovalState::AwaitingUserResponse)
     }
     
-    async fn send_pdf_binary(&self, pdf_data: Vec<u8>, recipient_address: &MailingAddress) -> Result<String> {
+    async fn send_pdf_binary(&self, pdf_data: Vec<u8>, recipient_address: &MailingAddress, idempotency_key: &str) -> Result<String> {
         use crate::types::{LetterExpressRequest, MailingAddress, PrintColor, PrintMode, ShippingType};
-        use crate::paths::{pdfs_dir, letterexpress_logs_dir};
+        use crate::paths::letterexpress_logs_dir;
         use std::fs;
 
         // Validate the address contains actual data
         if !recipient_address.is_valid() {
-            return Err(LennardError::Workflow(format!(
-                "Invalid mailing address with empty fields. Cannot send PDF without valid recipient address."
-            )));
+            return Err(LennardError::Workflow(
+                "Invalid mailing address with empty fields. Cannot send PDF without valid recipient address.".to_string()
+            ));
         }
 
-        // Save PDF locally first (for backup and debugging)
-        let pdf_dir = pdfs_dir();
-        fs::create_dir_all(&pdf_dir).ok();
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let pdf_filename = format!("letter_approved_{}.pdf", timestamp);
-        let pdf_path = pdf_dir.join(&pdf_filename);
-        if let Err(e) = fs::write(&pdf_path, &pdf_data) {
-            log::warn!("Failed to save PDF locally: {}", e);
-        } else {
-            log::info!("PDF saved locally at: {:?}", pdf_path);
-        }
+        // The approved PDF is usually in the blob store already - this only renews it
+        let pdf_blob = self.keep_pdf(&pdf_data);
 
         // Create sender address (placeholder)
         let sender_address = MailingAddress {
@@ -496,10 +516,11 @@ impl WorkflowSteps for WorkflowProcessor {
             color: PrintColor::BlackWhite,
             mode: PrintMode::Simplex,
             shipping: ShippingType::Standard,
+            reference: Some(idempotency_key.to_string()),
         };
 
         // Try to send via LetterExpress
-        log::info!("Attempting to send approved PDF via LetterExpress");
+        log::info!("Attempting to send approved PDF via LetterExpress (idempotency key: {})", idempotency_key);
 
         match self.letterexpress_client.send_letter(&request).await {
             Ok(tracking_id) => {
@@ -524,7 +545,7 @@ impl WorkflowSteps for WorkflowProcessor {
                     Timestamp: {}\n\
                     Recipient Address:\n  {}\n  {}, {} {}\n  {}\n\
                     Error: {}\n\
-                    PDF saved at: {:?}\n",
+                    PDF blob: {}\n",
                     error_timestamp,
                     recipient_address.street,
                     recipient_address.city,
@@ -532,18 +553,23 @@ impl WorkflowSteps for WorkflowProcessor {
                     recipient_address.postal_code,
                     recipient_address.country,
                     e,
-                    pdf_path
+                    pdf_blob
                 );
                 fs::write(error_log_file, error_details).ok();
 
-                Err(LennardError::Workflow(format!("LetterExpress failed to send approved PDF: {}", e)))
+                // Passed on unchanged - the caller tells rejections from failures the letter may have survived
+                Err(e)
             }
         }
     }
 
+    async fn find_sent_letter(&self, idempotency_key: &str) -> Result<Option<String>> {
+        self.letterexpress_client.find_job_by_reference(idempotency_key).await
+    }
+
     async fn send_pdf(&self, letter: &LetterContent, contact: &ZohoContact) -> Result<String> {
         use crate::types::{LetterExpressRequest, MailingAddress, PrintColor, PrintMode, ShippingType, PDFTemplateData};
-        use crate::paths::{pdfs_dir, letterexpress_logs_dir};
+        use crate::paths::letterexpress_logs_dir;
         use std::fs;
 
         // Get mailing address from contact - REQUIRED
@@ -564,25 +590,13 @@ impl WorkflowSteps for WorkflowProcessor {
         let pdf_template_data = PDFTemplateData::from_letter_and_address(letter, recipient_address);
 
         // Generate PDF using template with strongly typed data
-        let pdf_data = self.pdf_service.generate_pdf_typed("letter_template.odt", &pdf_template_data).await?;
-        
-        // Save PDF locally first (for backup and debugging)
-        let pdf_dir = pdfs_dir();
-        fs::create_dir_all(&pdf_dir).ok();
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let pdf_filename = format!("letter_{}_{}.pdf", 
-            contact.id.replace(":", "_"),
-            timestamp
-        );
-        let pdf_path = pdf_dir.join(&pdf_filename);
-        if let Err(e) = fs::write(&pdf_path, &pdf_data) {
-            log::warn!("Failed to save PDF locally: {}", e);
-        } else {
-            log::info!("PDF saved locally at: {:?}", pdf_path);
-        }
+        let pdf_data = {
+            let _permit = self.service_limits.acquire(DownstreamService::PdfService).await?;
+            self.pdf_service.generate_pdf_typed("letter_template.odt", &pdf_template_data).await?
+        };
+        
+        // Keep the PDF first (for backup and debugging)
+        let pdf_blob = self.keep_pdf(&pdf_data);
         
         // Create sender address (placeholder)
         let sender_address = MailingAddress {
@@ -601,6 +615,7 @@ impl WorkflowSteps for WorkflowProcessor {
             color: PrintColor::BlackWhite,
             mode: PrintMode::Simplex,
             shipping: ShippingType::Standard,
+            reference: None,
         };
         
         // Try to send via LetterExpress with detailed error handling
@@ -636,7 +651,7 @@ impl WorkflowSteps for WorkflowProcessor {
                     Company: {}\n\
                     Recipient Address:\n  {}\n  {}, {} {}\n  {}\n\
                     Error: {}\n\
-                    PDF saved at: {:?}\n",
+                    PDF blob: {}\n",
                     error_timestamp,
                     contact.full_name,
                     contact.id,
@@ -647,7 +662,7 @@ impl WorkflowSteps for WorkflowProcessor {
                     recipient_address.postal_code,
                     recipient_address.country,
                     e,
-                    pdf_path
+                    pdf_blob
                 );
                 fs::write(&error_log_file, &error_details).ok();
                 log::info!("LetterExpress error details saved to: {:?}", error_log_file);
@@ -657,8 +672,8 @@ impl WorkflowSteps for WorkflowProcessor {
                 
                 // Return error with helpful context
                 Err(LennardError::ServiceUnavailable(format!(
-                    "LetterExpress service failed: {}. PDF was generated and saved locally at {}. Please check the LetterExpress credentials or send manually.",
-                    e, pdf_filename
+                    "LetterExpress service failed: {}. PDF was generated and stored as blob {}. Please check the LetterExpress credentials or send manually.",
+                    e, pdf_blob
                 )))
             }
         }
@@ -709,13 +724,40 @@ impl WorkflowSteps for WorkflowProcessor {
             .await
     }
 
+    async fn reset_task_status(
+        &self,
+        task_id: &str
+    ) -> Result<()> {
+        self.zoho_client
+            .reset_task_to_not_started(task_id)
+            .await
+    }
+
+    async fn cancel_approval(
+        &self,
+        approval_id: &ApprovalId
+    ) -> Result<()> {
+        // Failed approvals are ignored by the watchers, so the letter can never be sent
+        if !self.approval_queue.mark_failed(approval_id)? {
+            log::warn!("Approval {} of cancelled task not found", approval_id);
+        }
+        Ok(())
+    }
+
     async fn create_follow_up_task(
         &self,
         contact_id: &str,
-        _original_task_id: &str
+        original_task_id: &str
     ) -> Result<String> {
         use chrono::Utc;
 
+        // The follow-up goes to whoever owned the original task
+        let owner_id = match self.zoho_client.get_task_by_id(original_task_id).await? {
+            Some(TasksResponse { owner: Some(owner), .. }) => owner.id,
+            _ => self.task_selection.owners.first().cloned()
+                .ok_or_else(|| LennardError::Workflow(format!("No owner for the follow-up of task {}", original_task_id)))?,
+        };
+
         // Calculate due date: 3 working days from now
         let now = Utc::now();
         let due_date = crate::clients::zoho::add_working_days(now, 3);
@@ -730,7 +772,7 @@ impl WorkflowSteps for WorkflowProcessor {
         let task_id = self.zoho_client
             .create_task(
                 "n8n.campaign.continue",
-                TASK_OWNER_ID,  // Lennard's user ID
+                &owner_id,
                 contact_id,
                 due_date
             )
@@ -761,8 +803,10 @@ impl WorkflowSteps for WorkflowProcessor {
                   approval_data.recipient_name, approval_data.company_name);
         
         // Use the letter service to generate an improved version with full context
+        let _permit = self.service_limits.acquire(DownstreamService::LetterService).await?;
+        let (person_dossier, company_dossier) = approval_data.dossiers(self.approval_queue.blobs())?;
         let improved_letter = self.letter_service
-            .generate_improved_letter_with_approval(approval_data, feedback)
+            .generate_improved_letter_with_approval(approval_data, &person_dossier, &company_dossier, feedback)
             .await?;
             
         Ok(improved_letter)
@@ -775,6 +819,7 @@ impl WorkflowSteps for WorkflowProcessor {
         let pdf_template_data = PDFTemplateData::from_letter_and_address(letter, address);
         
         // Generate PDF using existing service method
+        let _permit = self.service_limits.acquire(DownstreamService::PdfService).await?;
         let pdf_bytes = self.pdf_service
             .generate_pdf_typed("letter_template.odt", &pdf_template_data)
             .await?;
@@ -803,19 +848,12 @@ impl WorkflowSteps for WorkflowProcessor {
         &self,
         approval_data: &super::super::workflow::approval_types::ApprovalData
     ) -> Result<()> {
-        use base64::{Engine as _, engine::general_purpose};
-        
         log::info!("Sending improved letter to Telegram for approval {}", approval_data.approval_id);
         
-        // Decode the PDF from base64
-        let pdf_data = approval_data.pdf_base64.as_ref()
-            .ok_or_else(|| LennardError::Workflow("Approval has no PDF data".to_string()))
-            .and_then(|base64| {
-                general_purpose::STANDARD.decode(base64)
-                    .map_err(|e| LennardError::Workflow(format!("Failed to decode PDF: {}", e)))
-            })?;
+        let pdf_data = approval_data.pdf_bytes(self.approval_queue.blobs())?
+            .ok_or_else(|| LennardError::Workflow("Approval has no PDF data".to_string()))?;
         
-        log::info!("Decoded PDF from approval, {} bytes", pdf_data.len());
+        log::info!("Read PDF of approval, {} bytes", pdf_data.len());
         
         // Create a minimal ZohoContact from approval data for the Telegram API
         let contact = ZohoContact {
@@ -844,62 +882,85 @@ impl WorkflowSteps for WorkflowProcessor {
         
         Ok(())
     }
+    
+    async fn send_approval_reminder(
+        &self,
+        approval_data: &super::super::workflow::approval_types::ApprovalData
+    ) -> Result<()> {
+        let waiting_hours = (chrono::Utc::now() - approval_data.awaiting_since()).num_hours();
+        self.telegram_client
+            .send_approval_reminder(
+                &approval_data.approval_id.to_string(),
+                &approval_data.recipient_name,
+                &approval_data.company_name,
+                waiting_hours
+            )
+            .await
+    }
+    
+    async fn send_approval_expired(
+        &self,
+        approval_data: &super::super::workflow::approval_types::ApprovalData
+    ) -> Result<()> {
+        let waiting_hours = (chrono::Utc::now() - approval_data.awaiting_since()).num_hours();
+        self.telegram_client
+            .send_approval_expired(
+                &approval_data.approval_id.to_string(),
+                &approval_data.recipient_name,
+                &approval_data.company_name,
+                waiting_hours
+            )
+            .await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn task(id: &str, created_time: Option<&str>, due_date: Option<&str>) -> TasksResponse {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "Subject": "Connect on LinkedIn",
+            "Created_Time": created_time,
+            "Due_Date": due_date,
+        })).unwrap()
+    }
+
     #[test]
-    fn test_zoho_filter_constants() {
-        // Verify that the filter constants have the correct values
-        assert_eq!(TASK_SUBJECT_FILTER, "Connect on LinkedIn",
+    fn test_default_task_selection() {
+        // The defaults are the filters that used to be hard-coded
+        let selection = TaskSelectionConfig::default();
+
+        assert_eq!(selection.subject, Some(crate::config::SubjectMatch::Equals("Connect on LinkedIn".to_string())),
             "Subject filter must be 'Connect on LinkedIn' with capital I");
 
-        assert_eq!(TASK_STATUS_FILTER, "Not started",
+        assert_eq!(selection.statuses, vec!["Not started"],
             "Status filter must be in English: 'Not started'");
 
-        assert_eq!(TASK_OWNER_ID, "1294764000001730350",
+        assert_eq!(selection.owners, vec!["1294764000001730350"],
             "Owner ID must be Lennard's Zoho user ID");
+
+        assert_eq!(selection.sort, TaskSortOrder::OldestFirst);
     }
 
     #[test]
-    fn test_filter_values_match_expected() {
-        // Create the filters array as used in load_available_tasks
-        let filters = &[
-            ("Subject", TASK_SUBJECT_FILTER),
-            ("Status", TASK_STATUS_FILTER),
-            ("Owner", TASK_OWNER_ID),
+    fn test_sort_tasks() {
+        let mut tasks = vec![
+            task("b", Some("2025-01-02T10:00:00+01:00"), None),
+            task("a", Some("2025-01-01T10:00:00+01:00"), Some("2025-02-01")),
+            task("c", Some("2025-01-03T10:00:00+01:00"), Some("2025-01-15")),
         ];
+        let ids = |tasks: &[TasksResponse]| tasks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
 
-        // Verify the filter structure and values
-        assert_eq!(filters.len(), 3, "Must have exactly 3 filters");
-
-        assert_eq!(filters[0], ("Subject", "Connect on LinkedIn"),
-            "First filter must be Subject='Connect on LinkedIn'");
-
-        assert_eq!(filters[1], ("Status", "Not started"),
-            "Second filter must be Status='Not started' (English)");
-        
-        assert_eq!(filters[2], ("Owner", "1294764000001730350"),
-            "Third filter must be Owner with Lennard's ID");
-    }
+        sort_tasks(&mut tasks, TaskSortOrder::OldestFirst);
+        assert_eq!(ids(&tasks), vec!["a", "b", "c"]);
 
-    #[test]
-    fn test_english_status_in_filters() {
-        // Ensure we're using English status (Zoho CRM uses English status values)
-        assert_eq!(TASK_STATUS_FILTER, "Not started",
-            "Status filter must be in English as per Zoho CRM API");
-    }
+        sort_tasks(&mut tasks, TaskSortOrder::NewestFirst);
+        assert_eq!(ids(&tasks), vec!["c", "b", "a"]);
 
-    #[test]
-    fn test_linkedin_capitalization() {
-        // Ensure LinkedIn has correct capitalization
-        assert!(TASK_SUBJECT_FILTER.contains("LinkedIn"), 
-            "Subject must contain 'LinkedIn' with capital I");
-        
-        assert!(!TASK_SUBJECT_FILTER.contains("Linkedin"), 
-            "Subject must NOT contain 'Linkedin' with lowercase i");
+        sort_tasks(&mut tasks, TaskSortOrder::DueDateFirst);
+        assert_eq!(ids(&tasks), vec!["c", "a", "b"]);
     }
     
 }
\ No newline at end of file
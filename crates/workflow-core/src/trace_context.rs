@@ -0,0 +1,102 @@
+//! Trace context propagation into outbound HTTP and gRPC requests
+//!
+//! Injects the span context with the globally installed propagator (W3C trace context in the
+//! server) so the downstream services continue the trace. Nothing is injected while no tracer
+//! is installed.
+
+use opentelemetry::propagation::Injector;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Span of one request to an external service, named after the operation in the exported trace
+pub fn client_span(client: &'static str, operation: &str) -> Span {
+    tracing::info_span!(
+        "client_request",
+        otel.name = operation,
+        otel.kind = "client",
+        client,
+        operation,
+    )
+}
+
+struct HeaderInjector<'a>(&'a mut reqwest::header::HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+            reqwest::header::HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+struct MetadataInjector<'a>(&'a mut tonic::metadata::MetadataMap);
+
+impl Injector for MetadataInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(key), Ok(value)) = (
+            tonic::metadata::MetadataKey::from_bytes(key.as_bytes()),
+            tonic::metadata::MetadataValue::try_from(value.as_str()),
+        ) {
+            self.0.insert(key, value);
+        }
+    }
+}
+
+/// Add the trace context of `span` to HTTP headers
+pub fn inject_http(span: &Span, headers: &mut reqwest::header::HeaderMap) {
+    let context = span.context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers))
+    });
+}
+
+/// Wrap a gRPC message in a request carrying the trace context of `span`
+pub fn traced_request<T>(span: &Span, message: T) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    let context = span.context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut MetadataInjector(request.metadata_mut()))
+    });
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::propagation::TextMapPropagator;
+    use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+    use opentelemetry::Context;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+
+    #[test]
+    fn test_injectors_write_traceparent() {
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let context = Context::new().with_remote_span_context(span_context);
+        let propagator = TraceContextPropagator::new();
+        let expected = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers));
+        assert_eq!(headers.get("traceparent").unwrap(), expected);
+
+        let mut request = tonic::Request::new(());
+        propagator.inject_context(&context, &mut MetadataInjector(request.metadata_mut()));
+        assert_eq!(request.metadata().get("traceparent").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_nothing_is_injected_without_a_tracer() {
+        let span = client_span("dossier", "GenerateBothDossiers");
+        let request = traced_request(&span, ());
+        assert!(request.metadata().is_empty());
+    }
+}
@@ -62,12 +62,86 @@ pub struct LetterContent {
 }
 
 impl LetterContent {
-    /// Unescape literal `\n` characters to actual newlines
-    /// This is needed when loading letter content from JSON where newlines were escaped
-    pub fn unescape_newlines(&mut self) {
-        self.body = self.body.replace("\\n", "\n");
-        self.subject = self.subject.replace("\\n", "\n");
-        self.greeting = self.greeting.replace("\\n", "\n");
+    /// The letter as plain text: subject, greeting, body and signature
+    pub fn to_plain_text(&self) -> String {
+        format!(
+            "{} {}\n\n{}\n\n{}\n\n{}\n{}",
+            LETTER_SUBJECT_PREFIX,
+            self.subject,
+            self.greeting,
+            self.body,
+            LETTER_CLOSING,
+            self.sender_name
+        )
+    }
+    
+    /// Parse a letter edited in the `to_plain_text` format
+    ///
+    /// Subject line and signature are optional, as is the greeting when the text is a single
+    /// paragraph - missing parts are kept from `self`. Recipient and company never change.
+    pub fn with_plain_text(&self, text: &str) -> crate::error::Result<Self> {
+        let mut letter = self.clone();
+        let mut text = text.replace("\r\n", "\n");
+        
+        if let Some(rest) = text.trim_start().strip_prefix(LETTER_SUBJECT_PREFIX) {
+            let (subject, rest) = rest.split_once('\n').unwrap_or((rest, ""));
+            letter.subject = subject.trim().to_string();
+            text = rest.to_string();
+        }
+        
+        let mut paragraphs: Vec<&str> = text.split("\n\n")
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect();
+        
+        if let Some(sender) = paragraphs.last().and_then(|last| last.strip_prefix(LETTER_CLOSING)) {
+            let sender = sender.trim();
+            if !sender.is_empty() {
+                letter.sender_name = sender.to_string();
+            }
+            paragraphs.pop();
+        }
+        
+        match paragraphs.as_slice() {
+            [] => return Err(crate::error::LennardError::Validation("Revised letter has no body".to_string())),
+            [body] => letter.body = body.to_string(),
+            [greeting, body @ ..] => {
+                letter.greeting = greeting.to_string();
+                letter.body = body.join("\n\n");
+            }
+        }
+        
+        if letter.subject.is_empty() {
+            return Err(crate::error::LennardError::Validation("Revised letter has no subject".to_string()));
+        }
+        Ok(letter)
+    }
+}
+
+const LETTER_SUBJECT_PREFIX: &str = "Subject:";
+const LETTER_CLOSING: &str = "Best regards,";
+
+/// Letter together with the PDF rendered from it
+/// The letter may differ from the generated one if it had to be shortened to fit the page limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedLetter {
+    pub letter: LetterContent,
+    #[serde(with = "base64_bytes")]
+    pub pdf: Vec<u8>,
+}
+
+/// Serialize binary data as a base64 string
+mod base64_bytes {
+    use base64::{Engine as _, engine::general_purpose};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&general_purpose::STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        general_purpose::STANDARD.decode(encoded).map_err(serde::de::Error::custom)
     }
 }
 
@@ -160,6 +234,9 @@ pub struct LetterExpressRequest {
     pub color: PrintColor,
     pub mode: PrintMode,
     pub shipping: ShippingType,
+    /// Idempotency key stored as the job notice - lets us find the job again after a crash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
 }
 
 /// Print color options
@@ -185,6 +262,7 @@ pub enum ShippingType {
 }
 
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -386,11 +464,12 @@ mod tests {
             "Anrede": "Test Greeting",
             "Brieftext": "Test Body",
             "Sender-Name": "Test Sender",
-            "Reciepient": "Test Recipient",
-            "Street-1": "Test Street",
+            "Company": "Test Company",
+            "Recipient": "Test Recipient",
+            "Street 1": "Test Street",
             "Street-2": "Test State",
             "City": "Test City",
-            "PLZ": "12345",
+            "ZipCode": "12345",
             "Country": "Germany"
         }"#;
 
@@ -537,4 +616,36 @@ mod tests {
         assert_eq!(address.postal_code, "");
         assert_eq!(address.country, "");
     }
+
+    #[test]
+    fn test_letter_plain_text_round_trip() {
+        let letter = LetterContent {
+            subject: "Subject".to_string(),
+            greeting: "Dear Ms. Doe,".to_string(),
+            body: "First paragraph.\n\nSecond paragraph.".to_string(),
+            sender_name: "Sender".to_string(),
+            recipient_name: "Jane Doe".to_string(),
+            company_name: "Test Company".to_string(),
+        };
+
+        let parsed = letter.with_plain_text(&letter.to_plain_text()).unwrap();
+        assert_eq!(parsed.subject, letter.subject);
+        assert_eq!(parsed.greeting, letter.greeting);
+        assert_eq!(parsed.body, letter.body);
+        assert_eq!(parsed.sender_name, letter.sender_name);
+
+        // A single edited paragraph replaces only the body
+        let edited = letter.with_plain_text("Just one fixed sentence.\r\n").unwrap();
+        assert_eq!(edited.subject, "Subject");
+        assert_eq!(edited.greeting, "Dear Ms. Doe,");
+        assert_eq!(edited.body, "Just one fixed sentence.");
+
+        let renamed = letter.with_plain_text("Subject: New subject\n\nHello,\n\nBody\n\nBest regards,\nSomeone Else").unwrap();
+        assert_eq!(renamed.subject, "New subject");
+        assert_eq!(renamed.greeting, "Hello,");
+        assert_eq!(renamed.sender_name, "Someone Else");
+        assert_eq!(renamed.recipient_name, "Jane Doe");
+
+        assert!(letter.with_plain_text("Subject: Only a subject\n\nBest regards,\nSender").is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,179 @@
+//! Background worker for approvals waiting for a response
+//!
+//! Reminds the Telegram chat of approvals in `awaiting_response/` at the configured intervals
+//! and expires them after the deadline. Expired approvals move to `expired/` and their Zoho
+//! task is reset so it is picked up again.
+
+use crate::config::ApprovalExpiryConfig;
+use crate::workflow::approval_types::{ApprovalData, ApprovalState};
+use crate::workflow::orchestrator::WorkflowOrchestrator;
+use crate::workflow::traits::WorkflowSteps;
+use crate::workflow::ApprovalQueue;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use log::{info, error, warn, debug};
+
+/// What is due for an approval awaiting a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaleApprovalAction {
+    /// Send a reminder - `reminders_sent` is the number of reminder intervals passed
+    Remind { reminders_sent: u32 },
+    Expire,
+}
+
+/// Decide whether an approval awaiting a response is due for a reminder or expiry
+///
+/// Intervals that passed while the worker was down are covered by a single reminder.
+pub fn stale_approval_action(
+    config: &ApprovalExpiryConfig,
+    approval: &ApprovalData,
+    now: DateTime<Utc>,
+) -> Option<StaleApprovalAction> {
+    let waiting = now - approval.awaiting_since();
+
+    if config.expire_after_hours > 0 && waiting >= ChronoDuration::hours(config.expire_after_hours as i64) {
+        return Some(StaleApprovalAction::Expire);
+    }
+
+    let passed = config.reminder_after_hours.iter()
+        .filter(|hours| waiting >= ChronoDuration::hours(**hours as i64))
+        .count() as u32;
+
+    (passed > approval.reminders_sent).then_some(StaleApprovalAction::Remind { reminders_sent: passed })
+}
+
+/// Periodically reminds about and expires unanswered approvals
+pub struct ApprovalExpiryWorker<T: WorkflowSteps> {
+    approval_queue: Arc<ApprovalQueue>,
+    orchestrator: Arc<WorkflowOrchestrator<T>>,
+    config: ApprovalExpiryConfig,
+}
+
+impl<T: WorkflowSteps + Send + Sync + 'static> ApprovalExpiryWorker<T> {
+    pub fn new(
+        approval_queue: Arc<ApprovalQueue>,
+        orchestrator: Arc<WorkflowOrchestrator<T>>,
+        config: ApprovalExpiryConfig,
+    ) -> Self {
+        Self {
+            approval_queue,
+            orchestrator,
+            config,
+        }
+    }
+
+    /// Start checking approvals awaiting a response
+    pub async fn start(self: Arc<Self>) {
+        info!(
+            "Starting approval expiry worker (reminders after {:?}h, expiry after {}h)",
+            self.config.reminder_after_hours,
+            self.config.expire_after_hours
+        );
+
+        loop {
+            self.check_stale_approvals().await;
+            sleep(Duration::from_secs(self.config.check_interval_secs)).await;
+        }
+    }
+
+    async fn check_stale_approvals(&self) {
+        let approvals = match self.approval_queue.list_approvals_by_state(ApprovalState::AwaitingUserResponse) {
+            Ok(approvals) => approvals,
+            Err(e) => {
+                error!("Failed to list approvals awaiting a response: {}", e);
+                return;
+            }
+        };
+
+        let now = Utc::now();
+        for approval in approvals {
+            match stale_approval_action(&self.config, &approval, now) {
+                Some(StaleApprovalAction::Expire) => self.expire(&approval).await,
+                Some(StaleApprovalAction::Remind { reminders_sent }) => self.remind(&approval, reminders_sent).await,
+                None => {}
+            }
+        }
+    }
+
+    async fn remind(&self, approval: &ApprovalData, reminders_sent: u32) {
+        // Failed reminders are retried on the next check
+        if let Err(e) = self.orchestrator.remind_approval(approval).await {
+            warn!("Failed to send reminder for approval {}: {}", approval.approval_id, e);
+            return;
+        }
+
+        if let Err(e) = self.approval_queue.record_reminder(&approval.approval_id, reminders_sent) {
+            error!("Failed to record reminder for approval {}: {}", approval.approval_id, e);
+        }
+    }
+
+    async fn expire(&self, approval: &ApprovalData) {
+        // Reset Zoho first: while the approval still awaits a response, a failed reset is retried on the next check.
+        // An answer arriving in between still goes through - a sent letter marks the task done again.
+        match self.orchestrator.reset_expired_task(approval).await {
+            Ok(()) => {}
+            Err(e) if e.is_retryable() => {
+                warn!("Failed to reset task of expiring approval {}, retrying on the next check: {}", approval.approval_id, e);
+                return;
+            }
+            // Already reported to the chat - retrying would not help
+            Err(e) => error!("Expiring approval {} without resetting its task: {}", approval.approval_id, e),
+        }
+
+        match self.approval_queue.mark_as_expired(&approval.approval_id) {
+            Ok(Some(expired)) => self.orchestrator.notify_expired_approval(&expired).await,
+            Ok(None) => debug!("Approval {} was answered before it expired", approval.approval_id),
+            Err(e) => error!("Failed to expire approval {}: {}", approval.approval_id, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn awaiting_for(hours: i64, reminders_sent: u32) -> ApprovalData {
+        let mut approval = ApprovalData::test_fixture("task-1");
+        approval.mark_awaiting_response();
+        approval.awaiting_response_since = Some(Utc::now() - ChronoDuration::hours(hours));
+        approval.reminders_sent = reminders_sent;
+        approval
+    }
+
+    #[test]
+    fn test_stale_approval_action() {
+        let config = ApprovalExpiryConfig {
+            reminder_after_hours: vec![24, 72],
+            expire_after_hours: 168,
+            check_interval_secs: 300,
+        };
+        let now = Utc::now();
+
+        assert_eq!(stale_approval_action(&config, &awaiting_for(2, 0), now), None);
+        assert_eq!(stale_approval_action(&config, &awaiting_for(30, 0), now), Some(StaleApprovalAction::Remind { reminders_sent: 1 }));
+        assert_eq!(stale_approval_action(&config, &awaiting_for(30, 1), now), None);
+        assert_eq!(stale_approval_action(&config, &awaiting_for(100, 1), now), Some(StaleApprovalAction::Remind { reminders_sent: 2 }));
+
+        // Missed intervals are covered by one reminder
+        assert_eq!(stale_approval_action(&config, &awaiting_for(100, 0), now), Some(StaleApprovalAction::Remind { reminders_sent: 2 }));
+
+        assert_eq!(stale_approval_action(&config, &awaiting_for(170, 2), now), Some(StaleApprovalAction::Expire));
+
+        let no_expiry = ApprovalExpiryConfig { expire_after_hours: 0, ..config };
+        assert_eq!(stale_approval_action(&no_expiry, &awaiting_for(1000, 2), now), None);
+    }
+
+    #[test]
+    fn test_reminders_do_not_restart_the_expiry_clock() {
+        // Approvals from before `awaiting_response_since` existed wait since their last update
+        let mut approval = awaiting_for(0, 0);
+        approval.awaiting_response_since = None;
+        approval.updated_at = Utc::now() - ChronoDuration::hours(30);
+        let since = approval.awaiting_since();
+
+        approval.record_reminder(1);
+        assert_eq!(approval.awaiting_since(), since);
+        assert_eq!(approval.awaiting_response_since, Some(since));
+    }
+}
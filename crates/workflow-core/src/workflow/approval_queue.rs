@@ -1,10 +1,15 @@
 //! Strongly typed ApprovalQueue service implementation
-//! File-based queue with atomic operations and thread safety
+//! State transitions of approvals on top of a pluggable [`ApprovalStore`]
 
 use crate::error::{LennardError, Result};
 use super::approval_types::*;
+use super::approval_store::{write_synced, ApprovalStore, FileApprovalStore, Transition};
+use super::blob_store::BlobStore;
+use super::schema::{self, TRIGGER_SCHEMA_VERSION};
+use super::events::{EventBus, WorkflowEvent, WorkflowEventKind};
 use crate::paths;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::fs;
 use chrono::Utc;
 use serde_json;
@@ -12,26 +17,22 @@ use serde_json;
 /// Thread-safe approval queue for managing letter approval requests
 pub struct ApprovalQueue {
     root_path: PathBuf,
-    enable_file_locking: bool,
+    store: Arc<dyn ApprovalStore>,
+    blobs: BlobStore,
+    events: Arc<EventBus>,
 }
 
 impl ApprovalQueue {
-    /// Create new ApprovalQueue with specified root path
+    /// Create new ApprovalQueue with specified root path, storing approvals as files below it
     pub fn new<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        let store = Arc::new(FileApprovalStore::new(&root_path)?);
+        Self::with_store(root_path, store)
+    }
+    
+    /// Create an ApprovalQueue keeping its approvals in `store` - triggers and letter data stay below the root path
+    pub fn with_store<P: AsRef<Path>>(root_path: P, store: Arc<dyn ApprovalStore>) -> Result<Self> {
         let root_path = root_path.as_ref().to_path_buf();
         
-        // Create directory structure
-        for state in &[
-            ApprovalState::PendingApproval,
-            ApprovalState::AwaitingUserResponse,
-            ApprovalState::Approved,
-            ApprovalState::NeedsImprovement,
-            ApprovalState::Failed,
-        ] {
-            let state_dir = root_path.join(state.directory_name());
-            fs::create_dir_all(&state_dir)?;
-        }
-        
         // Create data directories
         let data_dir = root_path.join(paths::DATA_DIR_NAME);
         for subdir in &[paths::DOSSIERS_DIR_NAME, paths::LETTERS_DIR_NAME, paths::ATTACHMENTS_DIR_NAME] {
@@ -43,74 +44,58 @@ impl ApprovalQueue {
         fs::create_dir_all(root_path.join(paths::TRIGGERS_DIR_NAME).join(paths::PROCESSED_DIR_NAME))?;
         fs::create_dir_all(root_path.join(paths::TRIGGERS_DIR_NAME).join(paths::FAILED_DIR_NAME))?;
         
+        let blobs = BlobStore::new(&root_path)?;
+        
         Ok(Self {
             root_path,
-            enable_file_locking: true,
+            store,
+            blobs,
+            events: Arc::new(EventBus::new()),
         })
     }
     
-    /// Get path for approval in specific state
-    fn get_approval_path(&self, state: ApprovalState, approval_id: &ApprovalId) -> PathBuf {
-        self.root_path
-            .join(state.directory_name())
-            .join(format!("approval_{}.json", approval_id))
-    }
-    
-    /// Find approval in any state
-    fn find_approval_path(&self, approval_id: &ApprovalId) -> Option<(PathBuf, ApprovalState)> {
-        log::debug!("Searching for approval {} in all state directories", approval_id);
-        
-        for state in &[
-            ApprovalState::PendingApproval,
-            ApprovalState::AwaitingUserResponse,
-            ApprovalState::Approved,
-            ApprovalState::NeedsImprovement,
-            ApprovalState::Failed,
-        ] {
-            let path = self.get_approval_path(*state, approval_id);
-            log::debug!("Checking path: {:?} - exists: {}", path, path.exists());
-            
-            if path.exists() {
-                log::debug!("Found approval {} in state {:?}", approval_id, state);
-                return Some((path, *state));
-            }
-        }
-        
-        log::debug!("Approval {} not found in any state directory", approval_id);
-        None
+    /// Publish approval events to a shared bus
+    pub fn with_event_bus(mut self, events: Arc<EventBus>) -> Self {
+        self.events = events;
+        self
     }
     
-    /// Write approval data to file
-    fn write_approval(&self, path: &Path, approval: &ApprovalData) -> Result<()> {
-        let json = serde_json::to_string_pretty(approval)
-            .map_err(|e| LennardError::Serialization(format!("Failed to serialize approval: {}", e)))?;
-        
-        fs::write(path, json)?;
-        
-        Ok(())
+    /// Store the approvals are kept in
+    pub fn store(&self) -> &Arc<dyn ApprovalStore> {
+        &self.store
     }
     
-    /// Read approval data from file
-    fn read_approval(&self, path: &Path) -> Result<ApprovalData> {
-        let json = fs::read_to_string(path)?;
-
-        let mut approval_data: ApprovalData = serde_json::from_str(&json)
-            .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize approval: {}", e)))?;
-
-        // Unescape literal \n characters that may have been stored in JSON
-        // This fixes the issue where newlines appear as literal "\n\n" in PDFs
-        approval_data.current_letter.unescape_newlines();
-
-        Ok(approval_data)
+    /// Store the PDFs and dossiers of the approvals are kept in
+    pub fn blobs(&self) -> &BlobStore {
+        &self.blobs
     }
     
-    /// Move approval file between states
-    fn move_approval(&self, from: &Path, to: &Path) -> Result<()> {
-        fs::rename(from, to)?;
-        Ok(())
+    /// Apply a state transition and announce it - None if the approval is missing or in another state
+    fn transition(
+        &self,
+        approval_id: &ApprovalId,
+        from: &[ApprovalState],
+        to: ApprovalState,
+        mut change: impl FnMut(&mut ApprovalData),
+    ) -> Result<Option<ApprovalData>> {
+        match self.store.transition(approval_id, from, to, &mut change)? {
+            Transition::Applied(approval) => {
+                self.events.publish(WorkflowEvent::approval_state_changed(&approval));
+                Ok(Some(*approval))
+            }
+            Transition::Skipped(state) => {
+                log::debug!("Approval {} is in state {:?}, not {:?}", approval_id, state, from);
+                Ok(None)
+            }
+            Transition::NotFound => {
+                log::debug!("Approval {} not found", approval_id);
+                Ok(None)
+            }
+        }
     }
     
     /// Create new approval request
+    #[allow(clippy::too_many_arguments)]
     pub fn create_approval(
         &self,
         task_id: TaskId,
@@ -122,9 +107,9 @@ impl ApprovalQueue {
         letter: LetterContent,
         requested_by: UserId,
         mailing_address: Option<crate::types::MailingAddress>,
-        pdf_base64: Option<String>,
-        person_dossier: Option<String>,
-        company_dossier: Option<String>,
+        pdf: Option<&[u8]>,
+        person_dossier: Option<&str>,
+        company_dossier: Option<&str>,
         industry: Option<String>,
         website: Option<String>,
     ) -> Result<ApprovalId> {
@@ -141,41 +126,32 @@ impl ApprovalQueue {
         approval.recipient_email = recipient_email;
         approval.recipient_title = recipient_title;
         approval.mailing_address = mailing_address;
-        approval.pdf_base64 = pdf_base64;
-        approval.person_dossier = person_dossier;
-        approval.company_dossier = company_dossier;
+        approval.set_current_pdf(pdf.map(|pdf| self.blobs.put(pdf)).transpose()?);
+        approval.person_dossier = person_dossier.map(|dossier| self.blobs.put(dossier.as_bytes())).transpose()?;
+        approval.company_dossier = company_dossier.map(|dossier| self.blobs.put(dossier.as_bytes())).transpose()?;
         approval.industry = industry;
         approval.website = website;
         
         let approval_id = approval.approval_id.clone();
-        let path = self.get_approval_path(ApprovalState::PendingApproval, &approval_id);
-        
-        self.write_approval(&path, &approval)?;
+        self.store.insert(&approval, ApprovalState::PendingApproval)?;
+        self.events.publish(WorkflowEvent::approval(&approval, WorkflowEventKind::ApprovalCreated {
+            approval_id: approval_id.clone(),
+            task_id: approval.task_id.clone(),
+        }));
         
         log::info!("Created approval request: {}", approval_id);
         Ok(approval_id)
     }
     
-    /// Get approval request by ID
+    /// Get approval request by ID, optionally only if it is in `state`
     pub fn get_approval_request(
         &self,
         approval_id: &ApprovalId,
         state: Option<ApprovalState>,
     ) -> Result<Option<ApprovalData>> {
-        if let Some(state) = state {
-            // Look in specific state
-            let path = self.get_approval_path(state, approval_id);
-            if path.exists() {
-                return Ok(Some(self.read_approval(&path)?));
-            }
-        } else {
-            // Search all states
-            if let Some((path, _)) = self.find_approval_path(approval_id) {
-                return Ok(Some(self.read_approval(&path)?));
-            }
-        }
-        
-        Ok(None)
+        Ok(self.store.get(approval_id)?
+            .filter(|(stored_state, _)| state.is_none_or(|state| state == *stored_state))
+            .map(|(_, approval)| approval))
     }
     
     /// Get all pending approvals
@@ -183,28 +159,32 @@ impl ApprovalQueue {
         self.list_approvals_by_state(ApprovalState::PendingApproval)
     }
     
+    /// Approvals still waiting for a decision - pending or sent to Telegram - oldest first
+    pub fn list_open_approvals(&self, requested_by: Option<UserId>) -> Result<Vec<ApprovalData>> {
+        let mut approvals = self.list_approvals_by_state(ApprovalState::PendingApproval)?;
+        approvals.extend(self.list_approvals_by_state(ApprovalState::AwaitingUserResponse)?);
+        
+        approvals.retain(|approval| requested_by.is_none_or(|user| approval.requested_by == user));
+        approvals.sort_by_key(|approval| approval.requested_at);
+        Ok(approvals)
+    }
+    
     /// List approvals in specific state
     pub fn list_approvals_by_state(&self, state: ApprovalState) -> Result<Vec<ApprovalData>> {
-        let state_dir = self.root_path.join(state.directory_name());
-        
-        if !state_dir.exists() {
-            return Ok(Vec::new());
-        }
-        
-        let mut approvals = Vec::new();
-        
-        for entry in fs::read_dir(&state_dir)? 
-        {
-            let entry = entry?;
-            
-            let path = entry.path();
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Ok(approval) = self.read_approval(&path) {
-                    approvals.push(approval);
-                }
-            }
-        }
-        
+        self.store.list_by_state(state)
+    }
+    
+    /// All approvals created for a Zoho task, oldest first
+    pub fn find_approvals_by_task(&self, task_id: &TaskId) -> Result<Vec<ApprovalData>> {
+        let mut approvals = self.store.find_by_task(task_id)?;
+        approvals.sort_by_key(|approval| approval.requested_at);
+        Ok(approvals)
+    }
+    
+    /// All approvals created for a Zoho contact, oldest first
+    pub fn find_approvals_by_contact(&self, contact_id: &ContactId) -> Result<Vec<ApprovalData>> {
+        let mut approvals = self.store.find_by_contact(contact_id)?;
+        approvals.sort_by_key(|approval| approval.requested_at);
         Ok(approvals)
     }
     
@@ -215,55 +195,39 @@ impl ApprovalQueue {
         message_id: TelegramMessageId,
         chat_id: TelegramChatId,
     ) -> Result<bool> {
-        if let Some((path, current_state)) = self.find_approval_path(approval_id) {
-            if current_state != ApprovalState::PendingApproval {
-                return Ok(false);
-            }
-            
-            let mut approval = self.read_approval(&path)?;
-            approval.mark_sent_to_telegram(message_id, chat_id);
-            
-            // Write updated approval
-            self.write_approval(&path, &approval)?;
-            
-            // Move to awaiting response state
-            let new_path = self.get_approval_path(ApprovalState::AwaitingUserResponse, approval_id);
-            self.move_approval(&path, &new_path)?;
-            
+        let sent = self.transition(
+            approval_id,
+            &[ApprovalState::PendingApproval],
+            ApprovalState::AwaitingUserResponse,
+            |approval| approval.mark_sent_to_telegram(message_id, chat_id.clone()),
+        )?;
+        
+        if sent.is_some() {
             log::info!("Sent approval {} to Telegram", approval_id);
-            return Ok(true);
         }
-        
-        Ok(false)
+        Ok(sent.is_some())
     }
     
     /// Mark approval as awaiting user response (after sending to Telegram)
     pub fn mark_as_awaiting_response(&self, approval_id: &ApprovalId) -> Result<()> {
-        if let Some((path, current_state)) = self.find_approval_path(approval_id) {
-            if current_state != ApprovalState::PendingApproval {
-                return Err(LennardError::Workflow(
-                    format!("Cannot transition approval {} from {:?} to AwaitingUserResponse", 
-                        approval_id, current_state)
-                ));
+        match self.store.transition(
+            approval_id,
+            &[ApprovalState::PendingApproval],
+            ApprovalState::AwaitingUserResponse,
+            &mut |approval| approval.mark_awaiting_response(),
+        )? {
+            Transition::Applied(approval) => {
+                self.events.publish(WorkflowEvent::approval_state_changed(&approval));
+                log::info!("Transitioned approval {} to AwaitingUserResponse", approval_id);
+                Ok(())
             }
-            
-            let mut approval = self.read_approval(&path)?;
-            approval.state = ApprovalState::AwaitingUserResponse;
-            approval.updated_at = Utc::now();
-            
-            // Write updated approval
-            self.write_approval(&path, &approval)?;
-            
-            // Move to awaiting_response directory
-            let new_path = self.get_approval_path(ApprovalState::AwaitingUserResponse, approval_id);
-            self.move_approval(&path, &new_path)?;
-            
-            log::info!("Transitioned approval {} to AwaitingUserResponse", approval_id);
-            Ok(())
-        } else {
-            Err(LennardError::Workflow(
+            Transition::Skipped(current_state) => Err(LennardError::Workflow(
+                format!("Cannot transition approval {} from {:?} to AwaitingUserResponse", 
+                    approval_id, current_state)
+            )),
+            Transition::NotFound => Err(LennardError::Workflow(
                 format!("Approval {} not found", approval_id)
-            ))
+            )),
         }
     }
     
@@ -271,30 +235,17 @@ impl ApprovalQueue {
     pub fn handle_user_approval(&self, approval_id: &ApprovalId) -> Result<Option<ApprovalData>> {
         log::info!("handle_user_approval called for approval_id: {}", approval_id);
         
-        if let Some((path, current_state)) = self.find_approval_path(approval_id) {
-            log::info!("Found approval at {:?} with state {:?}", path, current_state);
-            
-            if current_state != ApprovalState::AwaitingUserResponse {
-                log::warn!("Approval {} is in state {:?}, not AwaitingUserResponse", approval_id, current_state);
-                return Ok(None);
-            }
-            
-            let mut approval = self.read_approval(&path)?;
-            approval.mark_approved();
-            
-            // Write updated approval
-            self.write_approval(&path, &approval)?;
-            
-            // Move to approved state
-            let new_path = self.get_approval_path(ApprovalState::Approved, approval_id);
-            self.move_approval(&path, &new_path)?;
-            
+        let approved = self.transition(
+            approval_id,
+            &[ApprovalState::AwaitingUserResponse],
+            ApprovalState::Approved,
+            |approval| approval.mark_approved(),
+        )?;
+        
+        if approved.is_some() {
             log::info!("Approval {} approved by user", approval_id);
-            return Ok(Some(approval));
         }
-        
-        log::warn!("Approval {} not found in any state directory", approval_id);
-        Ok(None)
+        Ok(approved)
     }
     
     /// Handle user feedback for improvement
@@ -304,26 +255,17 @@ impl ApprovalQueue {
         feedback_text: String,
         user_id: UserId,
     ) -> Result<Option<ApprovalData>> {
-        if let Some((path, current_state)) = self.find_approval_path(approval_id) {
-            if current_state != ApprovalState::AwaitingUserResponse {
-                return Ok(None);
-            }
-
-            let mut approval = self.read_approval(&path)?;
-            approval.add_feedback(feedback_text, user_id);
-
-            // Write updated approval
-            self.write_approval(&path, &approval)?;
-
-            // Move to needs improvement state
-            let new_path = self.get_approval_path(ApprovalState::NeedsImprovement, approval_id);
-            self.move_approval(&path, &new_path)?;
+        let improving = self.transition(
+            approval_id,
+            &[ApprovalState::AwaitingUserResponse],
+            ApprovalState::NeedsImprovement,
+            |approval| approval.add_feedback(feedback_text.clone(), user_id),
+        )?;
 
+        if improving.is_some() {
             log::info!("Approval {} needs improvement based on feedback", approval_id);
-            return Ok(Some(approval));
         }
-
-        Ok(None)
+        Ok(improving)
     }
 
     /// Mark approval as rejected (failed) - no automatic retry
@@ -333,26 +275,20 @@ impl ApprovalQueue {
         rejection_reason: String,
         user_id: UserId,
     ) -> Result<Option<ApprovalData>> {
-        if let Some((path, current_state)) = self.find_approval_path(approval_id) {
-            if current_state != ApprovalState::AwaitingUserResponse {
-                return Ok(None);
-            }
-
-            let mut approval = self.read_approval(&path)?;
-            approval.add_feedback(rejection_reason, user_id);
-
-            // Write updated approval
-            self.write_approval(&path, &approval)?;
-
-            // Move to failed state (no automatic retry)
-            let new_path = self.get_approval_path(ApprovalState::Failed, approval_id);
-            self.move_approval(&path, &new_path)?;
+        let rejected = self.transition(
+            approval_id,
+            &[ApprovalState::AwaitingUserResponse],
+            ApprovalState::Failed,
+            |approval| {
+                approval.add_feedback(rejection_reason.clone(), user_id);
+                approval.mark_failed();
+            },
+        )?;
 
-            log::info!("Approval {} marked as rejected and moved to failed directory", approval_id);
-            return Ok(Some(approval));
+        if rejected.is_some() {
+            log::info!("Approval {} marked as rejected and moved to failed", approval_id);
         }
-
-        Ok(None)
+        Ok(rejected)
     }
     
     /// Requeue improved letter
@@ -361,63 +297,161 @@ impl ApprovalQueue {
         approval_id: &ApprovalId,
         improved_letter: LetterContent,
     ) -> Result<bool> {
-        if let Some((path, current_state)) = self.find_approval_path(approval_id) {
-            if current_state != ApprovalState::NeedsImprovement {
-                return Ok(false);
-            }
-            
-            let mut approval = self.read_approval(&path)?;
-            approval.add_improved_letter(improved_letter);
-            
-            // Write updated approval
-            self.write_approval(&path, &approval)?;
-            
-            // Move back to pending approval
-            let new_path = self.get_approval_path(ApprovalState::PendingApproval, approval_id);
-            self.move_approval(&path, &new_path)?;
-            
+        let requeued = self.transition(
+            approval_id,
+            &[ApprovalState::NeedsImprovement],
+            ApprovalState::PendingApproval,
+            |approval| approval.add_improved_letter(improved_letter.clone()),
+        )?;
+        
+        if requeued.is_some() {
             log::info!("Approval {} requeued with improved letter", approval_id);
-            return Ok(true);
         }
-        
-        Ok(false)
+        Ok(requeued.is_some())
+    }
+    
+    /// Store an improved letter generated from feedback in the improvement's state
+    /// Returns false if the approval no longer needs improvement
+    pub fn save_improvement(&self, improved: &ApprovalData) -> Result<bool> {
+        let saved = self.transition(
+            &improved.approval_id,
+            &[ApprovalState::NeedsImprovement],
+            improved.state,
+            |approval| *approval = improved.clone(),
+        )?;
+        
+        if saved.is_some() {
+            log::info!("Approval {} improved (iteration {}, now {:?})",
+                improved.approval_id, improved.current_iteration(), improved.state);
+        }
+        Ok(saved.is_some())
+    }
+    
+    /// Store a letter revised by the reviewer in the revision's state
+    /// Returns false if the approval no longer awaits a decision
+    pub fn save_revision(&self, revised: &ApprovalData) -> Result<bool> {
+        let saved = self.transition(
+            &revised.approval_id,
+            &[ApprovalState::PendingApproval, ApprovalState::AwaitingUserResponse],
+            revised.state,
+            |approval| *approval = revised.clone(),
+        )?;
+        
+        if saved.is_some() {
+            log::info!("Approval {} revised by hand (iteration {}, now {:?})",
+                revised.approval_id, revised.current_iteration(), revised.state);
+        }
+        Ok(saved.is_some())
+    }
+    
+    /// Record a reminder about an approval that is still awaiting a response
+    pub fn record_reminder(&self, approval_id: &ApprovalId, reminders_sent: u32) -> Result<bool> {
+        // Not a state change, so nothing is published
+        match self.store.transition(
+            approval_id,
+            &[ApprovalState::AwaitingUserResponse],
+            ApprovalState::AwaitingUserResponse,
+            &mut |approval| approval.record_reminder(reminders_sent),
+        )? {
+            Transition::Applied(approval) => {
+                log::info!("Recorded reminder {} for approval {}", approval.reminders_sent, approval_id);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+    
+    /// Expire an approval that is still awaiting a response
+    /// Returns None if the user answered in the meantime
+    pub fn mark_as_expired(&self, approval_id: &ApprovalId) -> Result<Option<ApprovalData>> {
+        let expired = self.transition(
+            approval_id,
+            &[ApprovalState::AwaitingUserResponse],
+            ApprovalState::Expired,
+            |approval| approval.mark_expired(),
+        )?;
+        
+        if expired.is_some() {
+            log::info!("Approval {} expired without a response", approval_id);
+        }
+        Ok(expired)
     }
     
     /// Mark approval as failed
     pub fn mark_failed(&self, approval_id: &ApprovalId) -> Result<bool> {
-        if let Some((path, _)) = self.find_approval_path(approval_id) {
-            let mut approval = self.read_approval(&path)?;
-            approval.mark_failed();
-            
-            // Write updated approval
-            self.write_approval(&path, &approval)?;
-            
-            // Move to failed state
-            let new_path = self.get_approval_path(ApprovalState::Failed, approval_id);
-            self.move_approval(&path, &new_path)?;
-            
+        let failed = self.transition(
+            approval_id,
+            &ApprovalState::QUEUED,
+            ApprovalState::Failed,
+            |approval| approval.mark_failed(),
+        )?;
+        
+        if failed.is_some() {
             log::info!("Approval {} marked as failed", approval_id);
-            return Ok(true);
         }
-        
-        Ok(false)
+        Ok(failed.is_some())
+    }
+    
+    /// Claim an approved approval for sending - persists the Sending state and idempotency key
+    /// Returns None if it was claimed already or is no longer approved
+    pub fn claim_for_sending(&self, approval_id: &ApprovalId) -> Result<Option<ApprovalData>> {
+        self.transition(
+            approval_id,
+            &[ApprovalState::Approved],
+            ApprovalState::Sending,
+            |approval| approval.mark_sending(),
+        )
+    }
+    
+    /// Record the LetterExpress job ID of a claimed approval
+    pub fn record_sent(&self, approval_id: &ApprovalId, job_id: &str) -> Result<Option<ApprovalData>> {
+        self.transition(
+            approval_id,
+            &[ApprovalState::Sending],
+            ApprovalState::Sending,
+            |approval| approval.mark_sent(job_id.to_string()),
+        )
+    }
+    
+    /// Archive a claimed approval once its Zoho updates are queued
+    pub fn complete_sending(&self, approval_id: &ApprovalId) -> Result<bool> {
+        let completed = self.store.transition(
+            approval_id,
+            &[ApprovalState::Sending],
+            ApprovalState::Sent,
+            &mut |_| {},
+        )?;
+        Ok(matches!(completed, Transition::Applied(_)))
+    }
+    
+    /// Return a claimed approval whose letter never reached LetterExpress to the approved queue
+    pub fn requeue_sending(&self, approval_id: &ApprovalId) -> Result<bool> {
+        let requeued = self.store.transition(
+            approval_id,
+            &[ApprovalState::Sending],
+            ApprovalState::Approved,
+            &mut |_| {},
+        )?;
+        Ok(matches!(requeued, Transition::Applied(_)))
+    }
+    
+    /// Move a claimed approval to failed - its data keeps the Sending state for a manual check
+    pub fn fail_sending(&self, approval_id: &ApprovalId) -> Result<bool> {
+        let failed = self.store.transition(
+            approval_id,
+            &[ApprovalState::Sending],
+            ApprovalState::Failed,
+            &mut |_| {},
+        )?;
+        Ok(matches!(failed, Transition::Applied(_)))
     }
     
     /// Get approval counts by state
     pub fn get_approval_counts(&self) -> Result<StateCountMap> {
         let mut counts = StateCountMap::new();
         
-        for state in &[
-            ApprovalState::PendingApproval,
-            ApprovalState::AwaitingUserResponse,
-            ApprovalState::Approved,
-            ApprovalState::NeedsImprovement,
-            ApprovalState::Failed,
-        ] {
-            let approvals = self.list_approvals_by_state(*state)?;
-            for _ in approvals {
-                counts.increment(*state);
-            }
+        for state in ApprovalState::QUEUED {
+            counts.set(state, self.store.count_by_state(state)?);
         }
         
         Ok(counts)
@@ -444,7 +478,6 @@ impl ApprovalQueue {
             counts,
             total_workflows,
             root_path: self.root_path.clone(),
-            file_locking_enabled: self.enable_file_locking,
             last_check: Utc::now(),
         })
     }
@@ -460,11 +493,14 @@ impl ApprovalQueue {
         let trigger_id = uuid::Uuid::new_v4();
         
         let trigger = WorkflowTrigger {
+            schema_version: TRIGGER_SCHEMA_VERSION,
             trigger_id: trigger_id.to_string(),
             requested_by,
             requested_at: Utc::now(),
             max_tasks,
             dry_run,
+            task_selection: None,
+            task_ids: Vec::new(),
             processed: false,
             processed_at: None,
             result: None,
@@ -477,7 +513,7 @@ impl ApprovalQueue {
         let json = serde_json::to_string_pretty(&trigger)
             .map_err(|e| LennardError::Serialization(format!("Failed to serialize trigger: {}", e)))?;
         
-        fs::write(&trigger_path, json)?;
+        write_synced(&trigger_path, json.as_bytes())?;
         
         log::info!("Created workflow trigger: {}", trigger_id);
         Ok(trigger_id)
@@ -501,7 +537,7 @@ impl ApprovalQueue {
             if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
                 let json = fs::read_to_string(&path)?;
                 
-                if let Ok(trigger) = serde_json::from_str::<WorkflowTrigger>(&json) {
+                if let Ok(trigger) = schema::parse_trigger(&json) {
                     if !trigger.processed {
                         triggers.push(trigger);
                     }
@@ -524,8 +560,7 @@ impl ApprovalQueue {
         
         let json = fs::read_to_string(&trigger_path)?;
         
-        let mut trigger: WorkflowTrigger = serde_json::from_str(&json)
-            .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize trigger: {}", e)))?;
+        let mut trigger = schema::parse_trigger(&json)?;
         
         trigger.processed = true;
         trigger.processed_at = Some(Utc::now());
@@ -534,7 +569,7 @@ impl ApprovalQueue {
         let updated_json = serde_json::to_string_pretty(&trigger)
             .map_err(|e| LennardError::Serialization(format!("Failed to serialize trigger: {}", e)))?;
         
-        fs::write(&trigger_path, updated_json)?;
+        write_synced(&trigger_path, updated_json.as_bytes())?;
         
         // Move to processed directory
         let processed_path = self.root_path
@@ -552,6 +587,7 @@ impl ApprovalQueue {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::workflow::SqliteApprovalStore;
     use tempfile::TempDir;
     
     #[test]
@@ -566,6 +602,7 @@ mod tests {
             ApprovalState::Approved,
             ApprovalState::NeedsImprovement,
             ApprovalState::Failed,
+            ApprovalState::Expired,
         ] {
             let state_dir = temp_dir.path().join(state.directory_name());
             assert!(state_dir.exists(), "State directory {:?} should exist", state_dir);
@@ -668,7 +705,7 @@ mod tests {
             letter,
             user_id,
             None,  // mailing_address
-            None,  // pdf_base64
+            None,  // pdf
             None,  // person_dossier
             None,  // company_dossier
             None,  // industry
@@ -722,7 +759,7 @@ mod tests {
             letter,
             user_id,
             None,  // mailing_address
-            None,  // pdf_base64
+            None,  // pdf
             None,  // person_dossier
             None,  // company_dossier
             None,  // industry
@@ -738,6 +775,123 @@ mod tests {
         assert_eq!(retrieved_approval.current_letter.subject, "Get Test Subject");
     }
     
+    /// Create the approval of [`ApprovalData::test_fixture`] for a task
+    fn create_test_approval(queue: &ApprovalQueue, task_id: &str, requested_by: i64) -> ApprovalId {
+        let fixture = ApprovalData::test_fixture(task_id);
+        queue.create_approval(
+            fixture.task_id,
+            fixture.contact_id,
+            fixture.recipient_name,
+            None,
+            None,
+            fixture.company_name,
+            fixture.current_letter,
+            UserId::new(requested_by),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ).unwrap()
+    }
+    
+    #[test]
+    fn test_reminder_and_expiry() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = ApprovalQueue::new(temp_dir.path()).unwrap();
+        
+        let approval_id = create_test_approval(&queue, "task-expiry", 1);
+        
+        // Only approvals awaiting a response are reminded or expired
+        assert!(!queue.record_reminder(&approval_id, 1).unwrap());
+        assert!(queue.mark_as_expired(&approval_id).unwrap().is_none());
+        
+        queue.mark_as_awaiting_response(&approval_id).unwrap();
+        assert!(queue.record_reminder(&approval_id, 1).unwrap());
+        let awaiting = queue.get_approval_request(&approval_id, Some(ApprovalState::AwaitingUserResponse)).unwrap().unwrap();
+        assert_eq!(awaiting.reminders_sent, 1);
+        assert!(awaiting.awaiting_response_since.is_some());
+        
+        let expired = queue.mark_as_expired(&approval_id).unwrap().unwrap();
+        assert_eq!(expired.state, ApprovalState::Expired);
+        assert!(queue.get_approval_request(&approval_id, Some(ApprovalState::Expired)).unwrap().is_some());
+        
+        // A late approval no longer goes through
+        assert!(queue.handle_user_approval(&approval_id).unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_rejection_marks_approval_failed() {
+        let temp_dir = TempDir::new().unwrap();
+        let events = Arc::new(EventBus::new());
+        let queue = ApprovalQueue::new(temp_dir.path()).unwrap().with_event_bus(events.clone());
+        let mut updates = events.subscribe();
+        
+        let approval_id = create_test_approval(&queue, "task-rejected", 1);
+        queue.mark_as_awaiting_response(&approval_id).unwrap();
+        
+        let rejected = queue.mark_as_rejected(&approval_id, "Wrong tone".to_string(), UserId::new(1)).unwrap().unwrap();
+        assert_eq!(rejected.state, ApprovalState::Failed);
+        
+        // Stored data and the published state agree with the failed/ directory
+        let stored = queue.get_approval_request(&approval_id, Some(ApprovalState::Failed)).unwrap().unwrap();
+        assert_eq!(stored.state, ApprovalState::Failed);
+        let states: Vec<_> = std::iter::from_fn(|| updates.try_recv().ok())
+            .filter_map(|event| match event.kind {
+                WorkflowEventKind::ApprovalStateChanged { state, .. } => Some(state),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(states.last(), Some(&ApprovalState::Failed));
+    }
+    
+    #[test]
+    fn test_list_open_approvals() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = ApprovalQueue::new(temp_dir.path()).unwrap();
+        
+        let create = |task: &str, user: i64| create_test_approval(&queue, task, user);
+        let first = create("task-first", 1);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = create("task-second", 2);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let third = create("task-third", 1);
+        
+        // Sent to Telegram and still open; approved ones are no longer listed
+        queue.mark_as_awaiting_response(&first).unwrap();
+        queue.mark_as_awaiting_response(&third).unwrap();
+        queue.handle_user_approval(&third).unwrap().unwrap();
+        
+        let open: Vec<_> = queue.list_open_approvals(None).unwrap().into_iter().map(|a| a.approval_id).collect();
+        assert_eq!(open, vec![first.clone(), second]);
+        
+        let for_user: Vec<_> = queue.list_open_approvals(Some(UserId::new(1))).unwrap().into_iter().map(|a| a.approval_id).collect();
+        assert_eq!(for_user, vec![first]);
+    }
+    
+    #[test]
+    fn test_save_revision() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = ApprovalQueue::new(temp_dir.path()).unwrap();
+        
+        let approval_id = create_test_approval(&queue, "task-revision", 1);
+        queue.mark_as_awaiting_response(&approval_id).unwrap();
+        
+        // Approved right away - stored where the approval watcher picks it up
+        let mut revised = queue.get_approval_request(&approval_id, None).unwrap().unwrap();
+        let letter = revised.current_letter.clone();
+        revised.add_edited_letter(letter, UserId::new(1));
+        revised.mark_approved();
+        assert!(queue.save_revision(&revised).unwrap());
+        let stored = queue.get_approval_request(&approval_id, Some(ApprovalState::Approved)).unwrap().unwrap();
+        assert_eq!(stored.current_iteration(), 2);
+        assert!(queue.get_approval_request(&approval_id, Some(ApprovalState::AwaitingUserResponse)).unwrap().is_none());
+        
+        // Decided approvals are no longer revised
+        assert!(!queue.save_revision(&revised).unwrap());
+    }
+    
     #[test]
     fn test_get_nonexistent_approval() {
         let temp_dir = TempDir::new().unwrap();
@@ -775,7 +929,7 @@ mod tests {
             },
             user_id,
             None,  // mailing_address
-            None,  // pdf_base64
+            None,  // pdf
             None,  // person_dossier
             None,  // company_dossier
             None,  // industry
@@ -788,7 +942,6 @@ mod tests {
         assert_eq!(health_result.total_workflows, 1);
         assert_eq!(health_result.counts.get(ApprovalState::PendingApproval), 1);
         assert_eq!(health_result.counts.get(ApprovalState::Approved), 0);
-        assert!(health_result.file_locking_enabled);
         assert_eq!(health_result.root_path, temp_dir.path());
     }
     
@@ -826,6 +979,46 @@ mod tests {
         assert_eq!(parsed.result.as_ref().unwrap(), "Test processing completed");
     }
     
+    fn assert_sending_lifecycle(queue: &ApprovalQueue) {
+        let approval_id = create_test_approval(queue, "task-sending", 1);
+        
+        queue.mark_as_awaiting_response(&approval_id).unwrap();
+        queue.handle_user_approval(&approval_id).unwrap().unwrap();
+        
+        // Only one claim succeeds
+        let claimed = queue.claim_for_sending(&approval_id).unwrap().unwrap();
+        assert!(claimed.idempotency_key.is_some());
+        assert!(queue.claim_for_sending(&approval_id).unwrap().is_none());
+        assert_eq!(queue.list_approvals_by_state(ApprovalState::Sending).unwrap().len(), 1);
+        assert_eq!(queue.get_approval_counts().unwrap().get(ApprovalState::Approved), 0);
+        
+        // A letter that never went out is queued again
+        assert!(queue.requeue_sending(&approval_id).unwrap());
+        assert_eq!(queue.get_approval_counts().unwrap().get(ApprovalState::Approved), 1);
+        queue.claim_for_sending(&approval_id).unwrap().unwrap();
+        
+        let sent = queue.record_sent(&approval_id, "job-42").unwrap().unwrap();
+        assert_eq!(sent.letterexpress_job_id.as_deref(), Some("job-42"));
+        assert!(queue.complete_sending(&approval_id).unwrap());
+        assert!(!queue.fail_sending(&approval_id).unwrap());
+        
+        assert!(queue.get_approval_request(&approval_id, Some(ApprovalState::Sent)).unwrap().is_some());
+        let by_task = queue.find_approvals_by_task(&TaskId::new("task-sending".to_string())).unwrap();
+        assert_eq!(by_task.len(), 1);
+        assert_eq!(by_task[0].state, ApprovalState::Sent);
+        assert_eq!(queue.find_approvals_by_contact(&ContactId::new("contact-1".to_string())).unwrap().len(), 1);
+        assert_eq!(queue.get_approval_counts().unwrap().total(), 0);
+    }
+    
+    #[test]
+    fn test_sending_lifecycle_on_both_stores() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_sending_lifecycle(&ApprovalQueue::new(temp_dir.path().join("files")).unwrap());
+        
+        let database = SqliteApprovalStore::open(temp_dir.path().join("approvals.db"), BlobStore::new(temp_dir.path()).unwrap()).unwrap();
+        assert_sending_lifecycle(&ApprovalQueue::with_store(temp_dir.path().join("sqlite"), Arc::new(database)).unwrap());
+    }
+    
     #[test]
     fn test_approval_persists_to_disk_across_restart() {
         use tempfile::TempDir;
@@ -866,7 +1059,7 @@ mod tests {
                 letter,
                 user_id,
                 None,  // mailing_address
-                None,  // pdf_base64
+                None,  // pdf
                 None,  // person_dossier
                 None,  // company_dossier
                 None,  // industry
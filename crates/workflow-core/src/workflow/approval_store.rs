@@ -0,0 +1,502 @@
+//! Storage backends of the approval queue
+//!
+//! [`FileApprovalStore`] keeps one JSON file per approval in a directory per state - the layout
+//! operators know from the data root. [`SqliteApprovalStore`](super::SqliteApprovalStore) keeps
+//! approvals in an embedded database with transactional transitions and indexed lookups.
+
+use crate::config::{ApprovalStoreBackend, ApprovalStoreConfig};
+use crate::error::{LennardError, Result};
+use super::approval_types::*;
+use super::sqlite_approval_store::SqliteApprovalStore;
+use super::blob_store::BlobStore;
+use super::schema;
+use crate::paths;
+use fs2::FileExt;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Suffix of approvals claimed for sending - they stay next to the approved ones
+const PROCESSING_SUFFIX: &str = ".json.processing";
+
+/// Lock files the approvals are spread over - a fixed set, so lock files never pile up
+const LOCK_STRIPES: u8 = 64;
+
+/// How long an access waits for an approval lock before it fails
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest pause between two attempts to take a contended lock
+const LOCK_MAX_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Outcome of [`ApprovalStore::transition`]
+#[derive(Debug)]
+pub enum Transition {
+    /// Changed and stored in the target state
+    Applied(Box<ApprovalData>),
+    /// Stored in a state the transition does not start from - left untouched
+    Skipped(ApprovalState),
+    NotFound,
+}
+
+/// Persistence of approvals and the state they are stored in
+///
+/// The stored state decides which queue an approval is in. It usually matches
+/// `ApprovalData::state`, but a failed send e.g. keeps its `Sending` data in `Failed`.
+pub trait ApprovalStore: Send + Sync {
+    /// Where the approvals are kept, for logs and health checks
+    fn location(&self) -> &Path;
+
+    /// Store a new approval in `state`
+    fn insert(&self, approval: &ApprovalData, state: ApprovalState) -> Result<()>;
+
+    /// Approval with the state it is stored in
+    fn get(&self, approval_id: &ApprovalId) -> Result<Option<(ApprovalState, ApprovalData)>>;
+
+    /// Approvals stored in `state` - unreadable entries are skipped
+    fn list_by_state(&self, state: ApprovalState) -> Result<Vec<ApprovalData>>;
+
+    /// Approvals in any state created for a Zoho task
+    fn find_by_task(&self, task_id: &TaskId) -> Result<Vec<ApprovalData>>;
+
+    /// Approvals in any state created for a Zoho contact
+    fn find_by_contact(&self, contact_id: &ContactId) -> Result<Vec<ApprovalData>>;
+
+    /// Number of approvals stored in `state`
+    fn count_by_state(&self, state: ApprovalState) -> Result<usize>;
+
+    /// Apply `change` and move the approval to `to` in one step, if it is stored in one of `from`
+    fn transition(
+        &self,
+        approval_id: &ApprovalId,
+        from: &[ApprovalState],
+        to: ApprovalState,
+        change: &mut dyn FnMut(&mut ApprovalData),
+    ) -> Result<Transition>;
+
+    /// Delete an approval if it is still stored in `state` - false if it is not
+    fn remove(&self, approval_id: &ApprovalId, state: ApprovalState) -> Result<bool>;
+}
+
+/// Approvals as JSON files in one directory per state below the data root
+///
+/// Approvals claimed for sending are kept as `approved/approval_<id>.json.processing`,
+/// sent ones in `processed/`. Every access to an approval holds an advisory lock on one of
+/// `LOCK_STRIPES` files `locks/stripe_<n>.lock`, so transitions of other threads and processes
+/// never interleave.
+pub struct FileApprovalStore {
+    root_path: PathBuf,
+    /// Receives the PDFs and dossiers of approvals written before they were kept as blobs
+    blobs: BlobStore,
+}
+
+/// Exclusive lock on one approval - released when dropped
+struct ApprovalLock {
+    _file: File,
+}
+
+/// Flush a directory entry change, e.g. a rename, to disk
+fn sync_dir(dir: &Path) -> Result<()> {
+    // Directories cannot be opened for syncing on every platform - the rename itself is still atomic
+    if let Ok(dir) = File::open(dir) {
+        dir.sync_all()?;
+    }
+    Ok(())
+}
+
+fn is_contended(error: &std::io::Error) -> bool {
+    error.raw_os_error() == fs2::lock_contended_error().raw_os_error()
+}
+
+/// Retry a contended lock with a short backoff until it is free or `LOCK_TIMEOUT` has passed
+fn wait_for_lock(file: File, approval_id: &ApprovalId) -> Result<ApprovalLock> {
+    let started = Instant::now();
+    let mut backoff = Duration::from_millis(1);
+    loop {
+        if started.elapsed() >= LOCK_TIMEOUT {
+            return Err(LennardError::Timeout(format!(
+                "Approval {} stayed locked for {}s", approval_id, LOCK_TIMEOUT.as_secs()
+            )));
+        }
+        std::thread::sleep(backoff);
+        backoff = (backoff * 2).min(LOCK_MAX_BACKOFF);
+        match file.try_lock_exclusive() {
+            Ok(()) => return Ok(ApprovalLock { _file: file }),
+            Err(e) if is_contended(&e) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Replace `path` with `content` via a synced temporary file and rename
+pub(crate) fn write_synced(path: &Path, content: &[u8]) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut tmp = File::create(&tmp_path)?;
+    tmp.write_all(content)?;
+    tmp.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    if let Some(dir) = path.parent() {
+        sync_dir(dir)?;
+    }
+    Ok(())
+}
+
+impl FileApprovalStore {
+    pub fn new<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        let root_path = root_path.as_ref().to_path_buf();
+        for state in ApprovalState::ALL {
+            fs::create_dir_all(root_path.join(state.directory_name()))?;
+        }
+        fs::create_dir_all(root_path.join(paths::LOCKS_DIR_NAME))?;
+        let blobs = BlobStore::new(&root_path)?;
+        Ok(Self { root_path, blobs })
+    }
+
+    /// Lock file of an approval - shared with the other approvals of its stripe
+    fn lock_path(&self, approval_id: &ApprovalId) -> PathBuf {
+        let stripe = Sha256::digest(approval_id.to_string().as_bytes())[0] % LOCK_STRIPES;
+        self.root_path
+            .join(paths::LOCKS_DIR_NAME)
+            .join(format!("stripe_{:02}.lock", stripe))
+    }
+
+    /// Wait until no other thread or process accesses the approval
+    ///
+    /// Waiting for a contended lock blocks the calling thread for up to `LOCK_TIMEOUT`. On a
+    /// multi-threaded runtime the worker first hands its other tasks to the rest of the pool,
+    /// so a contended stripe never stalls unrelated tasks.
+    fn lock(&self, approval_id: &ApprovalId) -> Result<ApprovalLock> {
+        let file = OpenOptions::new().create(true).truncate(false).write(true).open(self.lock_path(approval_id))?;
+        match file.try_lock_exclusive() {
+            Ok(()) => return Ok(ApprovalLock { _file: file }),
+            Err(e) if is_contended(&e) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| wait_for_lock(file, approval_id))
+            }
+            _ => wait_for_lock(file, approval_id),
+        }
+    }
+
+    fn approval_path(&self, state: ApprovalState, approval_id: &ApprovalId) -> PathBuf {
+        let dir = self.root_path.join(state.directory_name());
+        match state {
+            ApprovalState::Sending => dir.join(format!("approval_{}{}", approval_id, PROCESSING_SUFFIX)),
+            _ => dir.join(format!("approval_{}.json", approval_id)),
+        }
+    }
+
+    /// Whether a file in the directory of `state` holds an approval stored in `state`
+    fn is_approval_file(state: ApprovalState, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if !name.starts_with("approval_") {
+            return false;
+        }
+        match state {
+            ApprovalState::Sending => name.ends_with(PROCESSING_SUFFIX),
+            _ => path.extension().and_then(|s| s.to_str()) == Some("json"),
+        }
+    }
+
+    fn find(&self, approval_id: &ApprovalId) -> Option<(ApprovalState, PathBuf)> {
+        ApprovalState::ALL.into_iter()
+            .map(|state| (state, self.approval_path(state, approval_id)))
+            .find(|(_, path)| path.exists())
+    }
+
+    fn read(&self, path: &Path) -> Result<ApprovalData> {
+        let json = fs::read_to_string(path)?;
+
+        schema::parse_approval(&json, &self.blobs)
+    }
+
+    /// Write via a synced temporary file, so a crash never leaves half an approval
+    fn write(&self, path: &Path, approval: &ApprovalData) -> Result<()> {
+        let json = serde_json::to_string_pretty(approval)
+            .map_err(|e| LennardError::Serialization(format!("Failed to serialize approval: {}", e)))?;
+
+        write_synced(path, json.as_bytes())
+    }
+
+    /// Move an approval file - a single rename, so it is never in two states at once
+    fn move_file(&self, from: &Path, to: &Path) -> Result<()> {
+        fs::rename(from, to)?;
+        if let Some(dir) = to.parent() {
+            sync_dir(dir)?;
+        }
+        if let Some(dir) = from.parent().filter(|dir| Some(*dir) != to.parent()) {
+            sync_dir(dir)?;
+        }
+        Ok(())
+    }
+
+    fn list_paths(&self, state: ApprovalState) -> Result<Vec<PathBuf>> {
+        let dir = self.root_path.join(state.directory_name());
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_file() && Self::is_approval_file(state, &path) {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    fn find_all(&self, matches: impl Fn(&ApprovalData) -> bool) -> Result<Vec<ApprovalData>> {
+        let mut approvals = Vec::new();
+        for state in ApprovalState::ALL {
+            approvals.extend(self.list_by_state(state)?.into_iter().filter(|approval| matches(approval)));
+        }
+        Ok(approvals)
+    }
+}
+
+impl ApprovalStore for FileApprovalStore {
+    fn location(&self) -> &Path {
+        &self.root_path
+    }
+
+    fn insert(&self, approval: &ApprovalData, state: ApprovalState) -> Result<()> {
+        let _lock = self.lock(&approval.approval_id)?;
+        self.write(&self.approval_path(state, &approval.approval_id), approval)
+    }
+
+    fn get(&self, approval_id: &ApprovalId) -> Result<Option<(ApprovalState, ApprovalData)>> {
+        // Locked, so a concurrent transition cannot move the file between finding and reading it
+        let _lock = self.lock(approval_id)?;
+        match self.find(approval_id) {
+            Some((state, path)) => Ok(Some((state, self.read(&path)?))),
+            None => Ok(None),
+        }
+    }
+
+    fn list_by_state(&self, state: ApprovalState) -> Result<Vec<ApprovalData>> {
+        let mut approvals = Vec::new();
+        for path in self.list_paths(state)? {
+            match self.read(&path) {
+                Ok(approval) => approvals.push(approval),
+                Err(e) => log::debug!("Skipping unreadable approval file {:?}: {}", path, e),
+            }
+        }
+        Ok(approvals)
+    }
+
+    fn find_by_task(&self, task_id: &TaskId) -> Result<Vec<ApprovalData>> {
+        self.find_all(|approval| &approval.task_id == task_id)
+    }
+
+    fn find_by_contact(&self, contact_id: &ContactId) -> Result<Vec<ApprovalData>> {
+        self.find_all(|approval| &approval.contact_id == contact_id)
+    }
+
+    fn count_by_state(&self, state: ApprovalState) -> Result<usize> {
+        Ok(self.list_paths(state)?.len())
+    }
+
+    fn transition(
+        &self,
+        approval_id: &ApprovalId,
+        from: &[ApprovalState],
+        to: ApprovalState,
+        change: &mut dyn FnMut(&mut ApprovalData),
+    ) -> Result<Transition> {
+        // Held from reading the approval until it has been moved
+        let _lock = self.lock(approval_id)?;
+        let Some((state, path)) = self.find(approval_id) else {
+            return Ok(Transition::NotFound);
+        };
+        if !from.contains(&state) {
+            return Ok(Transition::Skipped(state));
+        }
+
+        let mut approval = self.read(&path)?;
+        change(&mut approval);
+
+        // Written in place first, so the move never exposes the old content in the new state.
+        // A crash in between leaves the new content in the old state - never a second copy.
+        self.write(&path, &approval)?;
+        let new_path = self.approval_path(to, approval_id);
+        if new_path != path {
+            self.move_file(&path, &new_path)?;
+        }
+        Ok(Transition::Applied(Box::new(approval)))
+    }
+
+    fn remove(&self, approval_id: &ApprovalId, state: ApprovalState) -> Result<bool> {
+        let _lock = self.lock(approval_id)?;
+        let path = self.approval_path(state, approval_id);
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        fs::remove_file(&path)?;
+        if let Some(dir) = path.parent() {
+            sync_dir(dir)?;
+        }
+        Ok(true)
+    }
+}
+
+/// Result of [`migrate_approvals`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub imported: usize,
+    /// Already present in the target
+    pub skipped: usize,
+}
+
+/// Copy every approval from `source` to `target`, keeping its stored state
+///
+/// Approvals already in the target are left alone, so an interrupted migration can be
+/// run again. An approval found in several states is imported with its latest copy.
+pub fn migrate_approvals(source: &dyn ApprovalStore, target: &dyn ApprovalStore) -> Result<MigrationReport> {
+    let mut latest: HashMap<ApprovalId, (ApprovalState, ApprovalData)> = HashMap::new();
+    for state in ApprovalState::ALL {
+        for approval in source.list_by_state(state)? {
+            match latest.get(&approval.approval_id) {
+                Some((_, existing)) if existing.updated_at >= approval.updated_at => {}
+                _ => {
+                    latest.insert(approval.approval_id.clone(), (state, approval));
+                }
+            }
+        }
+    }
+
+    let mut report = MigrationReport::default();
+    for (state, approval) in latest.into_values() {
+        if target.get(&approval.approval_id)?.is_some() {
+            report.skipped += 1;
+        } else {
+            target.insert(&approval, state)?;
+            report.imported += 1;
+        }
+    }
+
+    log::info!("Migrated approvals from {} to {}: {} imported, {} already present",
+        source.location().display(), target.location().display(), report.imported, report.skipped);
+    Ok(report)
+}
+
+/// Open the approval store selected in the configuration
+pub fn open_approval_store<P: AsRef<Path>>(data_root: P, config: &ApprovalStoreConfig) -> Result<Arc<dyn ApprovalStore>> {
+    let data_root = data_root.as_ref();
+    Ok(match config.backend {
+        ApprovalStoreBackend::File => Arc::new(FileApprovalStore::new(data_root)?),
+        ApprovalStoreBackend::Sqlite => Arc::new(SqliteApprovalStore::open(config.sqlite_path(data_root), BlobStore::new(data_root)?)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn files_of(root: &Path, approval_id: &ApprovalId) -> Vec<PathBuf> {
+        let name = format!("approval_{}", approval_id);
+        ApprovalState::QUEUED.iter()
+            .chain([ApprovalState::Sent].iter())
+            .flat_map(|state| fs::read_dir(root.join(state.directory_name())).unwrap())
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.file_name().unwrap().to_str().unwrap().starts_with(&name))
+            .collect()
+    }
+
+    #[test]
+    fn test_concurrent_transitions_do_not_lose_updates() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(FileApprovalStore::new(temp_dir.path()).unwrap());
+        let approval = ApprovalData::test_fixture("task-1");
+        store.insert(&approval, ApprovalState::AwaitingUserResponse).unwrap();
+
+        let threads: Vec<_> = (0..8).map(|_| {
+            let store = store.clone();
+            let approval_id = approval.approval_id.clone();
+            std::thread::spawn(move || {
+                for _ in 0..25 {
+                    let outcome = store.transition(
+                        &approval_id,
+                        &[ApprovalState::AwaitingUserResponse],
+                        ApprovalState::AwaitingUserResponse,
+                        &mut |approval| approval.reminders_sent += 1,
+                    ).unwrap();
+                    assert!(matches!(outcome, Transition::Applied(_)));
+                    assert!(store.get(&approval_id).unwrap().is_some());
+                }
+            })
+        }).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let (_, stored) = store.get(&approval.approval_id).unwrap().unwrap();
+        assert_eq!(stored.reminders_sent, 200);
+    }
+
+    #[test]
+    fn test_lock_files_do_not_pile_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FileApprovalStore::new(temp_dir.path()).unwrap();
+
+        for _ in 0..200 {
+            let approval = ApprovalData::test_fixture("task-1");
+            store.insert(&approval, ApprovalState::PendingApproval).unwrap();
+            store.remove(&approval.approval_id, ApprovalState::PendingApproval).unwrap();
+        }
+
+        let lock_files = fs::read_dir(temp_dir.path().join(paths::LOCKS_DIR_NAME)).unwrap().count();
+        assert!(lock_files <= LOCK_STRIPES as usize);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_contended_lock_does_not_stall_the_runtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(FileApprovalStore::new(temp_dir.path()).unwrap());
+        let approval = ApprovalData::test_fixture("task-1");
+        store.insert(&approval, ApprovalState::PendingApproval).unwrap();
+
+        // Another process holds the stripe of the approval
+        let holder = File::open(store.lock_path(&approval.approval_id)).unwrap();
+        holder.lock_exclusive().unwrap();
+
+        let reader = {
+            let store = store.clone();
+            let approval_id = approval.approval_id.clone();
+            tokio::spawn(async move { store.get(&approval_id) })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // The only worker waits for the lock, yet other tasks still run
+        let other = tokio::time::timeout(Duration::from_secs(1), tokio::spawn(async { 42 })).await;
+        assert_eq!(other.unwrap().unwrap(), 42);
+
+        holder.unlock().unwrap();
+        assert!(reader.await.unwrap().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_transition_leaves_a_single_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FileApprovalStore::new(temp_dir.path()).unwrap();
+        let approval = ApprovalData::test_fixture("task-1");
+        store.insert(&approval, ApprovalState::Approved).unwrap();
+
+        for (from, to) in [
+            (ApprovalState::Approved, ApprovalState::Sending),
+            (ApprovalState::Sending, ApprovalState::Sent),
+        ] {
+            store.transition(&approval.approval_id, &[from], to, &mut |_| {}).unwrap();
+            let files = files_of(temp_dir.path(), &approval.approval_id);
+            assert_eq!(files, vec![store.approval_path(to, &approval.approval_id)]);
+        }
+    }
+}
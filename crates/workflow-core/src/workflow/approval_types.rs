@@ -4,6 +4,9 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use chrono::{DateTime, Utc};
+use crate::config::TaskSelectionConfig;
+use super::blob_store::{BlobRef, BlobStore};
+use super::schema::APPROVAL_SCHEMA_VERSION;
 
 /// Strongly typed ApprovalId
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -132,19 +135,70 @@ pub enum ApprovalState {
     PendingApproval,
     AwaitingUserResponse,
     Approved,
+    /// Handed to LetterExpress - the idempotency key is persisted, the job ID not yet
+    Sending,
+    /// Accepted by LetterExpress - the job ID is recorded, Zoho updates may still be pending
+    Sent,
     NeedsImprovement,
     Failed,
+    /// Not answered before the expiry deadline - the task was reset in Zoho
+    Expired,
 }
 
 impl ApprovalState {
+    /// States with their own queue directory
+    pub const QUEUED: [ApprovalState; 6] = [
+        Self::PendingApproval,
+        Self::AwaitingUserResponse,
+        Self::Approved,
+        Self::NeedsImprovement,
+        Self::Failed,
+        Self::Expired,
+    ];
+    
+    /// Every state an approval can be stored in
+    pub const ALL: [ApprovalState; 8] = [
+        Self::PendingApproval,
+        Self::AwaitingUserResponse,
+        Self::Approved,
+        Self::Sending,
+        Self::Sent,
+        Self::NeedsImprovement,
+        Self::Failed,
+        Self::Expired,
+    ];
+    
+    /// Stable snake_case name, e.g. `awaiting_response`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PendingApproval => "pending_approval",
+            Self::AwaitingUserResponse => "awaiting_response",
+            Self::Approved => "approved",
+            Self::Sending => "sending",
+            Self::Sent => "sent",
+            Self::NeedsImprovement => "needs_improvement",
+            Self::Failed => "failed",
+            Self::Expired => "expired",
+        }
+    }
+    
+    /// State of a [`name`](Self::name)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|state| state.name() == name)
+    }
+    
     /// Get directory name for file storage
     pub fn directory_name(&self) -> &'static str {
         match self {
             Self::PendingApproval => "pending_approval",
             Self::AwaitingUserResponse => "awaiting_response",
             Self::Approved => "approved",
+            // Sending approvals stay in approved/ as `.json.processing` until they are sent
+            Self::Sending => "approved",
+            Self::Sent => "processed",
             Self::NeedsImprovement => "needs_improvement",
             Self::Failed => "failed",
+            Self::Expired => "expired",
         }
     }
 }
@@ -167,11 +221,20 @@ pub struct LetterHistoryEntry {
     pub content: LetterContent,
     pub feedback: Option<Feedback>,
     pub created_at: DateTime<Utc>,
+    /// PDF the reviewer was shown for this iteration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pdf: Option<BlobRef>,
+    /// Reviewer who wrote this iteration by hand - None for generated letters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_by: Option<UserId>,
 }
 
 /// Main approval data structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApprovalData {
+    /// Version of the persisted layout - documents without one are version 0
+    #[serde(default)]
+    pub schema_version: u32,
     pub approval_id: ApprovalId,
     pub task_id: TaskId,
     pub contact_id: ContactId,
@@ -189,21 +252,39 @@ pub struct ApprovalData {
     pub updated_at: DateTime<Utc>,
     /// Mailing address for the recipient (needed for PDF generation)
     pub mailing_address: Option<crate::types::MailingAddress>,
-    /// Base64-encoded PDF data (generated before approval request)
+    /// PDF of the current letter (generated before approval request)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub pdf_base64: Option<String>,
-    /// Personal dossier content (LinkedIn profile, background, etc.)
+    pub pdf: Option<BlobRef>,
+    /// Personal dossier (LinkedIn profile, background, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub person_dossier: Option<String>,
-    /// Company dossier content (company research, industry info, etc.)
+    pub person_dossier: Option<BlobRef>,
+    /// Company dossier (company research, industry info, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub company_dossier: Option<String>,
+    pub company_dossier: Option<BlobRef>,
     /// Industry information
     #[serde(skip_serializing_if = "Option::is_none")]
     pub industry: Option<String>,
     /// Company website
     #[serde(skip_serializing_if = "Option::is_none")]
     pub website: Option<String>,
+    /// Idempotency key of the LetterExpress print job, persisted before the first send attempt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// LetterExpress job ID, recorded before any Zoho side effects run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub letterexpress_job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sent_at: Option<DateTime<Utc>>,
+    /// When the current letter was sent to Telegram - reminders and expiry count from here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub awaiting_response_since: Option<DateTime<Utc>>,
+    /// Reminders sent for the current letter
+    #[serde(default)]
+    pub reminders_sent: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_reminder_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_at: Option<DateTime<Utc>>,
 }
 
 impl ApprovalData {
@@ -224,9 +305,12 @@ impl ApprovalData {
             content: letter.clone(),
             feedback: None,
             created_at: now,
+            pdf: None,
+            edited_by: None,
         };
         
         Self {
+            schema_version: APPROVAL_SCHEMA_VERSION,
             approval_id,
             task_id,
             contact_id,
@@ -243,11 +327,26 @@ impl ApprovalData {
             telegram_chat_id: None,
             updated_at: now,
             mailing_address: None,
-            pdf_base64: None,
+            pdf: None,
             person_dossier: None,
             company_dossier: None,
             industry: None,
             website: None,
+            idempotency_key: None,
+            letterexpress_job_id: None,
+            sent_at: None,
+            awaiting_response_since: None,
+            reminders_sent: 0,
+            last_reminder_at: None,
+            approved_at: None,
+        }
+    }
+    
+    /// Add a letter edited by a reviewer as new iteration
+    pub fn add_edited_letter(&mut self, edited_letter: LetterContent, edited_by: UserId) {
+        self.add_improved_letter(edited_letter);
+        if let Some(entry) = self.letter_history.last_mut() {
+            entry.edited_by = Some(edited_by);
         }
     }
     
@@ -279,6 +378,8 @@ impl ApprovalData {
             content: improved_letter.clone(),
             feedback: None,
             created_at: Utc::now(),
+            pdf: None,
+            edited_by: None,
         };
         
         self.letter_history.push(new_entry);
@@ -295,13 +396,114 @@ impl ApprovalData {
     pub fn mark_sent_to_telegram(&mut self, message_id: TelegramMessageId, chat_id: TelegramChatId) {
         self.telegram_message_id = Some(message_id);
         self.telegram_chat_id = Some(chat_id);
+        self.mark_awaiting_response();
+    }
+    
+    /// Mark as waiting for the user - restarts the reminder and expiry clock
+    pub fn mark_awaiting_response(&mut self) {
+        let now = Utc::now();
         self.state = ApprovalState::AwaitingUserResponse;
+        self.awaiting_response_since = Some(now);
+        self.reminders_sent = 0;
+        self.last_reminder_at = None;
+        self.updated_at = now;
+    }
+    
+    /// PDF of the current letter, if one was generated
+    pub fn pdf_bytes(&self, blobs: &BlobStore) -> crate::error::Result<Option<Vec<u8>>> {
+        self.pdf.as_ref().map(|pdf| blobs.get(pdf)).transpose()
+    }
+    
+    /// Set the PDF of the current letter - also kept with its iteration in the history
+    pub fn set_current_pdf(&mut self, pdf: Option<BlobRef>) {
+        if let Some(entry) = self.letter_history.last_mut() {
+            entry.pdf = pdf.clone();
+        }
+        self.pdf = pdf;
+    }
+    
+    /// Person and company dossier - empty if the approval has none
+    pub fn dossiers(&self, blobs: &BlobStore) -> crate::error::Result<(String, String)> {
+        let text = |dossier: &Option<BlobRef>| dossier.as_ref()
+            .map(|dossier| blobs.get_text(dossier))
+            .transpose()
+            .map(Option::unwrap_or_default);
+        Ok((text(&self.person_dossier)?, text(&self.company_dossier)?))
+    }
+    
+    /// PDFs of the current letter and of every iteration
+    pub fn pdf_refs(&self) -> impl Iterator<Item = &BlobRef> {
+        self.pdf.iter().chain(self.letter_history.iter().filter_map(|entry| entry.pdf.as_ref()))
+    }
+    
+    /// Every blob the approval refers to
+    pub fn blob_refs(&self) -> impl Iterator<Item = &BlobRef> {
+        self.pdf_refs().chain([&self.person_dossier, &self.company_dossier].into_iter().flatten())
+    }
+    
+    /// History entry of an iteration - the current one if `iteration` is None
+    pub fn iteration(&self, iteration: Option<u32>) -> Option<&LetterHistoryEntry> {
+        match iteration {
+            Some(iteration) => self.letter_history.iter().find(|entry| entry.iteration == iteration),
+            None => self.letter_history.last(),
+        }
+    }
+    
+    /// PDF the reviewer saw for an iteration - the current one if `iteration` is None
+    pub fn iteration_pdf_bytes(&self, iteration: Option<u32>, blobs: &BlobStore) -> crate::error::Result<Option<Vec<u8>>> {
+        self.iteration(iteration)
+            .and_then(|entry| entry.pdf.as_ref())
+            .map(|pdf| blobs.get(pdf))
+            .transpose()
+    }
+    
+    /// Since when the approval waits for a response - approvals from before the field existed use `updated_at`
+    pub fn awaiting_since(&self) -> DateTime<Utc> {
+        self.awaiting_response_since.unwrap_or(self.updated_at)
+    }
+    
+    /// Record a reminder about the unanswered approval - `reminders_sent` counts every reminder it covers
+    pub fn record_reminder(&mut self, reminders_sent: u32) {
+        // Pin the expiry clock before `updated_at` moves on
+        self.awaiting_response_since.get_or_insert(self.updated_at);
+        let now = Utc::now();
+        self.reminders_sent = reminders_sent;
+        self.last_reminder_at = Some(now);
+        self.updated_at = now;
+    }
+    
+    /// Mark as expired without a response
+    pub fn mark_expired(&mut self) {
+        self.state = ApprovalState::Expired;
         self.updated_at = Utc::now();
     }
     
     /// Mark as approved by user
     pub fn mark_approved(&mut self) {
+        let now = Utc::now();
         self.state = ApprovalState::Approved;
+        self.approved_at = Some(now);
+        self.updated_at = now;
+    }
+    
+    /// Idempotency key of the print job - the persisted one, or the one it will get
+    pub fn send_key(&self) -> String {
+        self.idempotency_key.clone()
+            .unwrap_or_else(|| format!("lennard-{}-{}", self.approval_id, self.current_iteration()))
+    }
+    
+    /// Mark as being sent - keeps an already assigned idempotency key
+    pub fn mark_sending(&mut self) {
+        self.idempotency_key = Some(self.send_key());
+        self.state = ApprovalState::Sending;
+        self.updated_at = Utc::now();
+    }
+    
+    /// Mark as sent with the LetterExpress job ID
+    pub fn mark_sent(&mut self, job_id: String) {
+        self.letterexpress_job_id = Some(job_id);
+        self.state = ApprovalState::Sent;
+        self.sent_at = Some(Utc::now());
         self.updated_at = Utc::now();
     }
     
@@ -343,6 +545,10 @@ impl StateCountMap {
         *self.counts.entry(state).or_insert(0) += 1;
     }
     
+    pub fn set(&mut self, state: ApprovalState, count: usize) {
+        self.counts.insert(state, count);
+    }
+    
     pub fn get(&self, state: ApprovalState) -> usize {
         self.counts.get(&state).copied().unwrap_or(0)
     }
@@ -352,6 +558,29 @@ impl StateCountMap {
     }
 }
 
+#[cfg(test)]
+impl ApprovalData {
+    /// Pending approval of a short letter to John Doe at Test Company, requested by user 1
+    pub(crate) fn test_fixture(task_id: &str) -> Self {
+        let letter = LetterContent {
+            subject: "Subject".to_string(),
+            greeting: "Dear Test".to_string(),
+            body: "Body".to_string(),
+            sender_name: "Sender".to_string(),
+            recipient_name: "John Doe".to_string(),
+            company_name: "Test Company".to_string(),
+        };
+        Self::new(
+            TaskId::new(task_id.to_string()),
+            ContactId::new("contact-1".to_string()),
+            "John Doe".to_string(),
+            "Test Company".to_string(),
+            letter,
+            UserId::new(1),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,11 +589,14 @@ mod tests {
     #[test]
     fn test_workflow_trigger_serialization() {
         let trigger = WorkflowTrigger {
+            schema_version: crate::workflow::TRIGGER_SCHEMA_VERSION,
             trigger_id: "test-123".to_string(),
             requested_by: UserId::new(12345),
             requested_at: Utc::now(),
             max_tasks: 1,
             dry_run: false,
+            task_selection: None,
+            task_ids: Vec::new(),
             processed: false,
             processed_at: None,
             result: None,
@@ -442,6 +674,47 @@ mod tests {
         assert_eq!(approval.letter_history.len(), 1);
         assert!(approval.telegram_message_id.is_none());
     }
+    
+    #[test]
+    fn test_sending_keeps_idempotency_key() {
+        let mut approval = ApprovalData::test_fixture("task-123");
+        approval.mark_approved();
+        
+        approval.mark_sending();
+        let key = approval.idempotency_key.clone().unwrap();
+        assert_eq!(approval.state, ApprovalState::Sending);
+        
+        // A restarted send reuses the persisted key
+        let json = serde_json::to_string(&approval).unwrap();
+        let mut restored: ApprovalData = serde_json::from_str(&json).unwrap();
+        restored.mark_sending();
+        assert_eq!(restored.idempotency_key.as_deref(), Some(key.as_str()));
+        
+        restored.mark_sent("4711".to_string());
+        assert_eq!(restored.state, ApprovalState::Sent);
+        assert_eq!(restored.letterexpress_job_id.as_deref(), Some("4711"));
+        assert!(restored.sent_at.is_some());
+    }
+    
+    #[test]
+    fn test_iteration_pdfs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let blobs = BlobStore::new(temp_dir.path()).unwrap();
+        let mut approval = ApprovalData::test_fixture("task-1");
+        let letter = approval.current_letter.clone();
+        
+        approval.set_current_pdf(Some(blobs.put(b"first").unwrap()));
+        approval.add_feedback("Shorter".to_string(), UserId::new(1));
+        approval.add_improved_letter(letter);
+        approval.set_current_pdf(Some(blobs.put(b"second").unwrap()));
+        
+        assert_eq!(approval.iteration_pdf_bytes(Some(1), &blobs).unwrap().unwrap(), b"first");
+        assert_eq!(approval.iteration_pdf_bytes(Some(2), &blobs).unwrap().unwrap(), b"second");
+        assert_eq!(approval.iteration_pdf_bytes(None, &blobs).unwrap().unwrap(), b"second");
+        assert_eq!(approval.pdf_bytes(&blobs).unwrap().unwrap(), b"second");
+        assert!(approval.iteration_pdf_bytes(Some(3), &blobs).unwrap().is_none());
+        assert_eq!(approval.blob_refs().count(), 3);
+    }
 }
 
 /// Health check result
@@ -451,7 +724,6 @@ pub struct HealthCheckResult {
     pub counts: StateCountMap,
     pub total_workflows: usize,
     pub root_path: std::path::PathBuf,
-    pub file_locking_enabled: bool,
     pub last_check: DateTime<Utc>,
 }
 
@@ -459,11 +731,20 @@ pub struct HealthCheckResult {
 /// The workflow processor will dynamically load available tasks based on max_tasks limit
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowTrigger {
+    /// Version of the persisted layout - documents without one are version 0
+    #[serde(default)]
+    pub schema_version: u32,
     pub trigger_id: String,
     pub requested_by: UserId,
     pub requested_at: DateTime<Utc>,
     pub max_tasks: u32,
     pub dry_run: bool,
+    /// Overrides the configured task selection for this trigger
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_selection: Option<TaskSelectionConfig>,
+    /// Zoho tasks to process instead of selecting available ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub task_ids: Vec<String>,
     pub processed: bool,
     pub processed_at: Option<DateTime<Utc>>,
     pub result: Option<String>,
@@ -1,221 +1,261 @@
-//! Directory watcher for processing approved workflows
-//! 
-//! This module watches the approved directory and processes files as they appear,
-//! treating the directory like a message queue.
+//! Watcher for processing approved workflows
+//!
+//! This module polls the approved queue and processes approvals as they appear,
+//! treating the queue like a message queue.
+//!
+//! Sending is crash-safe: an approval is claimed by persisting its `Sending` state and
+//! idempotency key, and the LetterExpress job ID is recorded before any Zoho side effects
+//! run. Approvals left in `Sending` are reconciled against the LetterExpress job list instead
+//! of being sent again.
 
-use crate::error::{LennardError, Result};
-use crate::workflow::approval_types::ApprovalData;
+use crate::constants::{SEND_RECONCILE_GRACE_SECS, SEND_RECONCILE_INTERVAL_SECS};
+use crate::workflow::approval_types::{ApprovalData, ApprovalState};
 use crate::workflow::orchestrator::WorkflowOrchestrator;
 use crate::workflow::traits::WorkflowSteps;
 use crate::workflow::ApprovalQueue;
-use crate::paths;
-use std::sync::Arc;
-use std::path::{Path, PathBuf};
-use tokio::time::{sleep, Duration};
+use chrono::Utc;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration, Instant};
 use log::{info, error, warn, debug};
 
-/// Watches the approved directory and processes approved workflows
+/// Watches the approved queue and processes approved workflows
 pub struct ApprovalWatcher<T: WorkflowSteps> {
+    approval_queue: Arc<ApprovalQueue>,
     orchestrator: Arc<WorkflowOrchestrator<T>>,
-    approved_dir: PathBuf,
     processing_interval: Duration,
+    /// When the LetterExpress job list was last searched for approvals left in Sending
+    last_reconciled: Mutex<Option<Instant>>,
 }
 
 impl<T: WorkflowSteps + Send + Sync + 'static> ApprovalWatcher<T> {
     pub fn new(
-        _approval_queue: Arc<ApprovalQueue>,  // Keep for API compatibility but unused
+        approval_queue: Arc<ApprovalQueue>,
         orchestrator: Arc<WorkflowOrchestrator<T>>,
     ) -> Self {
-        let approved_dir = paths::approved_dir();
-        
         Self {
+            approval_queue,
             orchestrator,
-            approved_dir,
             processing_interval: Duration::from_secs(5), // Check every 5 seconds
+            last_reconciled: Mutex::new(None),
         }
     }
-    
-    /// Start watching the approved directory
+
+    /// Start watching the approved queue
     pub async fn start(self: Arc<Self>) {
-        info!("Starting approval watcher for directory: {:?}", self.approved_dir);
-        
-        // Process any existing approved files on startup
+        info!("Starting approval watcher for {:?}", self.approval_queue.store().location());
+
+        // Settle letters that were in flight when we stopped
+        self.reconcile_sending_approvals().await;
+
+        // Process any existing approvals on startup
         self.process_existing_approvals().await;
-        
-        // Then continuously watch for new files
+
+        // Then continuously watch for new approvals
         loop {
+            // Approvals whose send or reconciliation failed ambiguously are left in Sending
+            self.reconcile_sending_approvals().await;
             self.check_and_process_approvals().await;
             sleep(self.processing_interval).await;
         }
     }
-    
-    /// Process any existing approved files on startup
+
+    fn list(&self, state: ApprovalState) -> Vec<ApprovalData> {
+        match self.approval_queue.list_approvals_by_state(state) {
+            Ok(approvals) => approvals,
+            Err(e) => {
+                warn!("Failed to list {} approvals: {}", state.name(), e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Process any existing approved workflows on startup
     async fn process_existing_approvals(&self) {
         info!("Checking for existing approved workflows to process...");
-        
-        match std::fs::read_dir(&self.approved_dir) {
-            Ok(entries) => {
-                let mut count = 0;
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-                        if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                            if file_name.starts_with("approval_") {
-                                count += 1;
-                                info!("Found existing approved workflow: {}", file_name);
-                                self.process_approval_file(&path).await;
-                            }
-                        }
-                    }
+
+        let approvals = self.list(ApprovalState::Approved);
+        if approvals.is_empty() {
+            info!("No existing approved workflows found");
+            return;
+        }
+
+        let count = approvals.len();
+        for approval in approvals {
+            info!("Found existing approved workflow: {}", approval.approval_id);
+            self.process_approval(approval).await;
+        }
+        info!("Processed {} existing approved workflows", count);
+    }
+
+    /// Check for and process new approved workflows
+    async fn check_and_process_approvals(&self) {
+        for approval in self.list(ApprovalState::Approved) {
+            debug!("Found approved workflow to process: {}", approval.approval_id);
+            self.process_approval(approval).await;
+        }
+    }
+
+    /// Reconcile approvals left in `Sending` by a crash or an unreachable LetterExpress
+    /// Runs at most every `SEND_RECONCILE_INTERVAL_SECS` - every lookup fetches the job list
+    async fn reconcile_sending_approvals(&self) {
+        {
+            let mut last_reconciled = self.last_reconciled.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let interval = Duration::from_secs(SEND_RECONCILE_INTERVAL_SECS);
+            if last_reconciled.is_some_and(|last| last.elapsed() < interval) {
+                return;
+            }
+            *last_reconciled = Some(Instant::now());
+        }
+
+        for approval in self.list(ApprovalState::Sending) {
+            self.reconcile_sending_approval(approval).await;
+        }
+    }
+
+    /// Decide whether the letter of an approval left in `Sending` went out
+    ///
+    /// - Job ID recorded: the letter was sent, only the Zoho updates are completed
+    /// - Job found by idempotency key: the job ID is recorded, then as above
+    /// - No job found `SEND_RECONCILE_GRACE_SECS` after the claim: the approval goes back to the approved queue
+    /// - No idempotency key: claimed before sends were idempotent - moved to failed for a manual check
+    async fn reconcile_sending_approval(&self, approval_data: ApprovalData) {
+        if let Some(job_id) = &approval_data.letterexpress_job_id {
+            info!("Letter for approval {} was sent (job {}) - completing its Zoho updates",
+                  approval_data.approval_id, job_id);
+            self.complete_sent_letter(&approval_data).await;
+            return;
+        }
+
+        if approval_data.state != ApprovalState::Sending || approval_data.idempotency_key.is_none() {
+            error!(
+                "Approval {} was claimed without an idempotency key - cannot tell whether its letter was sent. \
+                 Moving it to failed, please check the LetterExpress job list manually",
+                approval_data.approval_id
+            );
+            self.move_to_failed(&approval_data);
+            return;
+        }
+
+        match self.orchestrator.find_sent_letter(&approval_data).await {
+            Ok(Some(job_id)) => {
+                info!("Found LetterExpress job {} for approval {} - recording it", job_id, approval_data.approval_id);
+                let approval_data = self.record_sent(approval_data, job_id);
+                self.complete_sent_letter(&approval_data).await;
+            }
+            Ok(None) => {
+                // The job list may lag behind a letter that was just accepted - claimed sets updated_at
+                let claimed_for = Utc::now() - approval_data.updated_at;
+                if claimed_for < chrono::Duration::seconds(SEND_RECONCILE_GRACE_SECS) {
+                    debug!("No LetterExpress job for approval {} yet, claimed {}s ago - checking again later",
+                           approval_data.approval_id, claimed_for.num_seconds());
+                    return;
                 }
-                
-                if count == 0 {
-                    info!("No existing approved workflows found");
-                } else {
-                    info!("Processed {} existing approved workflows", count);
+                info!("Letter for approval {} never reached LetterExpress - queueing it again", approval_data.approval_id);
+                if let Err(e) = self.approval_queue.requeue_sending(&approval_data.approval_id) {
+                    error!("Failed to move approval {} back to the approved queue: {}", approval_data.approval_id, e);
                 }
             }
             Err(e) => {
-                error!("Failed to read approved directory: {}", e);
+                warn!("Cannot reconcile approval {} yet, will retry: {}", approval_data.approval_id, e);
             }
         }
     }
-    
-    /// Check for and process new approved files
-    async fn check_and_process_approvals(&self) {
-        match std::fs::read_dir(&self.approved_dir) {
-            Ok(entries) => {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-                        if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                            if file_name.starts_with("approval_") && !file_name.contains(".processing") {
-                                debug!("Found approved workflow to process: {}", file_name);
-                                self.process_approval_file(&path).await;
-                            }
-                        }
-                    }
-                }
+
+    /// Process a single approved workflow
+    async fn process_approval(&self, approval: ApprovalData) {
+        info!(
+            "Processing approval {} for task {} (recipient: {})",
+            approval.approval_id,
+            approval.task_id,
+            approval.recipient_name
+        );
+
+        // Persist the Sending state and idempotency key BEFORE sending,
+        // so every claimed approval can be reconciled after a crash
+        let approval_data = match self.approval_queue.claim_for_sending(&approval.approval_id) {
+            Ok(Some(approval_data)) => approval_data,
+            Ok(None) => {
+                debug!("Approval {} was claimed elsewhere", approval.approval_id);
+                return;
+            }
+            Err(e) => {
+                error!("Failed to persist sending state of approval {}: {}", approval.approval_id, e);
+                return;
+            }
+        };
+
+        match self.orchestrator.send_approved_letter(&approval_data).await {
+            Ok(job_id) => {
+                let approval_data = self.record_sent(approval_data, job_id);
+                self.complete_sent_letter(&approval_data).await;
+            }
+            Err(e) if e.is_ambiguous() => {
+                // The letter may have gone out anyway - reconciliation checks the job list
+                warn!(
+                    "Sending letter for approval {} failed, leaving it to reconciliation: {}",
+                    approval_data.approval_id,
+                    e
+                );
             }
             Err(e) => {
-                warn!("Failed to read approved directory: {}", e);
+                error!(
+                    "Failed to send letter for approval {}: {}",
+                    approval_data.approval_id,
+                    e
+                );
+                self.move_to_failed(&approval_data);
             }
         }
     }
-    
-    /// Process a single approval file
-    async fn process_approval_file(&self, path: &Path) {
-        let file_name = path.file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown");
-            
-        info!("Processing approved workflow: {}", file_name);
-        
-        // Mark file as being processed by renaming it
-        let processing_path = path.with_extension("json.processing");
-        if let Err(e) = std::fs::rename(path, &processing_path) {
-            error!("Failed to mark file as processing: {}", e);
-            return;
+
+    /// Record the LetterExpress job ID before any Zoho side effects run
+    fn record_sent(&self, mut approval_data: ApprovalData, job_id: String) -> ApprovalData {
+        match self.approval_queue.record_sent(&approval_data.approval_id, &job_id) {
+            Ok(Some(recorded)) => recorded,
+            result => {
+                // Not fatal: the outbox entry written next also records that the letter went out
+                error!(
+                    "Letter for approval {} was sent (job {}) but the job ID could not be recorded: {}",
+                    approval_data.approval_id,
+                    job_id,
+                    result.err().map(|e| e.to_string()).unwrap_or_else(|| "no longer sending".to_string())
+                );
+                approval_data.mark_sent(job_id);
+                approval_data
+            }
         }
-        
-        // Read and parse the approval data
-        match self.read_approval_data(&processing_path) {
-            Ok(approval_data) => {
+    }
+
+    /// Queue the Zoho updates of a sent letter and archive the approval
+    async fn complete_sent_letter(&self, approval_data: &ApprovalData) {
+        // Continue the workflow - the recorded job ID keeps the letter from being sent again
+        match self.orchestrator.continue_after_approval(approval_data).await {
+            Ok(tracking_id) => {
                 info!(
-                    "Processing approval {} for task {} (recipient: {})",
+                    "Successfully sent letter for approval {} - tracking ID: {}",
                     approval_data.approval_id,
-                    approval_data.task_id,
-                    approval_data.recipient_name
+                    tracking_id
                 );
-                
-                // Continue the workflow
-                match self.orchestrator.continue_after_approval(&approval_data).await {
-                    Ok(tracking_id) => {
-                        info!(
-                            "Successfully sent letter for approval {} - tracking ID: {}",
-                            approval_data.approval_id,
-                            tracking_id
-                        );
-                        
-                        // Move to processed directory
-                        let processed_dir = paths::processed_dir();
-                            
-                        // Ensure processed directory exists
-                        if let Err(e) = std::fs::create_dir_all(&processed_dir) {
-                            error!("Failed to create processed directory: {}", e);
-                        }
-                        
-                        let processed_path = processed_dir.join(format!(
-                            "approval_{}_processed_{}.json",
-                            approval_data.approval_id,
-                            chrono::Utc::now().format("%Y%m%d_%H%M%S")
-                        ));
-                        
-                        if let Err(e) = std::fs::rename(&processing_path, processed_path) {
-                            error!("Failed to move file to processed: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        error!(
-                            "Failed to continue workflow for approval {}: {}",
-                            approval_data.approval_id,
-                            e
-                        );
-                        
-                        // Move to failed directory
-                        let failed_dir = paths::failed_dir();
-                            
-                        // Ensure failed directory exists
-                        if let Err(e) = std::fs::create_dir_all(&failed_dir) {
-                            error!("Failed to create failed directory: {}", e);
-                        }
-                        
-                        let failed_path = failed_dir.join(format!(
-                            "approval_{}_failed_{}.json",
-                            approval_data.approval_id,
-                            chrono::Utc::now().format("%Y%m%d_%H%M%S")
-                        ));
-                        
-                        if let Err(e) = std::fs::rename(&processing_path, failed_path) {
-                            error!("Failed to move file to failed: {}", e);
-                        }
-                    }
+
+                if let Err(e) = self.approval_queue.complete_sending(&approval_data.approval_id) {
+                    error!("Failed to archive approval {} as sent: {}", approval_data.approval_id, e);
                 }
             }
             Err(e) => {
-                error!("Failed to read approval data from {}: {}", file_name, e);
-                
-                // Move to error directory (use failed dir for errors too)
-                let error_dir = paths::failed_dir();
-                    
-                if let Err(e) = std::fs::create_dir_all(&error_dir) {
-                    error!("Failed to create error directory: {}", e);
-                }
-                
-                let error_path = error_dir.join(format!(
-                    "{}_error_{}.json",
-                    file_name,
-                    chrono::Utc::now().format("%Y%m%d_%H%M%S")
-                ));
-                
-                if let Err(e) = std::fs::rename(&processing_path, error_path) {
-                    error!("Failed to move file to error: {}", e);
-                }
+                error!(
+                    "Failed to continue workflow for approval {}: {}",
+                    approval_data.approval_id,
+                    e
+                );
+                self.move_to_failed(approval_data);
             }
         }
     }
-    
-    /// Read and parse approval data from file
-    fn read_approval_data(&self, path: &Path) -> Result<ApprovalData> {
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| LennardError::IoError(format!("Failed to read approval file: {}", e)))?;
-
-        let mut approval_data: ApprovalData = serde_json::from_str(&content)
-            .map_err(|e| LennardError::Serialization(format!("Failed to parse approval data: {}", e)))?;
 
-        // Unescape literal \n characters that may have been stored in JSON
-        approval_data.current_letter.unescape_newlines();
-
-        Ok(approval_data)
+    /// Move a claimed approval to the failed queue
+    fn move_to_failed(&self, approval_data: &ApprovalData) {
+        if let Err(e) = self.approval_queue.fail_sending(&approval_data.approval_id) {
+            error!("Failed to move approval {} to failed: {}", approval_data.approval_id, e);
+        }
     }
-}
\ No newline at end of file
+}
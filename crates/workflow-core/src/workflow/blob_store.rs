@@ -0,0 +1,234 @@
+//! Content-addressed store for PDFs, dossiers and attachments
+//!
+//! Every blob is written once to `data/blobs/<first two hex digits>/<sha256>`, however many
+//! approvals and outbox entries refer to it. Reads verify the content against its hash.
+//! [`BlobStore::collect_garbage`] removes blobs nothing refers to any more.
+
+use crate::error::{LennardError, Result};
+use super::approval_store::ApprovalStore;
+use super::approval_types::ApprovalState;
+use super::outbox::OutboxStore;
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Reference to a blob - the SHA-256 of its content and its size
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlobRef {
+    pub sha256: String,
+    pub size: u64,
+}
+
+impl BlobRef {
+    /// Reference of `content`
+    pub fn of(content: &[u8]) -> Self {
+        Self {
+            sha256: format!("{:x}", Sha256::digest(content)),
+            size: content.len() as u64,
+        }
+    }
+}
+
+fn is_sha256(name: &str) -> bool {
+    name.len() == 64 && name.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Outcome of a garbage collection
+#[derive(Debug, Default)]
+pub struct BlobGcReport {
+    pub removed: usize,
+    pub removed_bytes: u64,
+    pub kept: usize,
+}
+
+/// Blobs below `data/blobs/` of a data root
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    blobs_dir: PathBuf,
+    dry_run: bool,
+}
+
+impl BlobStore {
+    /// Create the blob store below the given data root
+    pub fn new<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        let blobs_dir = root_path.as_ref().join(paths::DATA_DIR_NAME).join(paths::BLOBS_DIR_NAME);
+        fs::create_dir_all(&blobs_dir)?;
+        Ok(Self { blobs_dir, dry_run: false })
+    }
+
+    /// A store that only hashes what is put into it - for dry runs of migrations
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    fn path(&self, blob: &BlobRef) -> Result<PathBuf> {
+        // References come from JSON files - never let one point outside the store
+        if !is_sha256(&blob.sha256) {
+            return Err(LennardError::Integrity(format!("Invalid blob hash '{}'", blob.sha256)));
+        }
+        Ok(self.blobs_dir.join(&blob.sha256[..2]).join(&blob.sha256))
+    }
+
+    /// Store `content` unless a blob with the same hash exists
+    pub fn put(&self, content: &[u8]) -> Result<BlobRef> {
+        let blob = BlobRef::of(content);
+        if self.dry_run {
+            return Ok(blob);
+        }
+
+        let path = self.path(&blob)?;
+        // Renew the age of an existing blob so a running garbage collection does not take it
+        // away - one that collection removed in the meantime is written again
+        match File::options().append(true).open(&path) {
+            Ok(file) => {
+                file.set_modified(SystemTime::now())?;
+                return Ok(blob);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let dir = path.parent().expect("blob paths have a fan-out directory");
+        fs::create_dir_all(dir)?;
+        // Concurrent writers of the same blob each use their own temporary file
+        let tmp_path = dir.join(format!("{}.{}.tmp", blob.sha256, uuid::Uuid::new_v4()));
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(content)?;
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(blob)
+    }
+
+    /// Content of a blob, verified against its hash and size
+    pub fn get(&self, blob: &BlobRef) -> Result<Vec<u8>> {
+        let path = self.path(blob)?;
+        let content = fs::read(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => LennardError::NotFound(format!("Blob {}", blob.sha256)),
+            _ => e.into(),
+        })?;
+
+        if BlobRef::of(&content) != *blob {
+            return Err(LennardError::Integrity(format!(
+                "Blob {} is corrupt ({} bytes on disk, {} expected)", blob.sha256, content.len(), blob.size
+            )));
+        }
+        Ok(content)
+    }
+
+    /// Content of a text blob such as a dossier
+    pub fn get_text(&self, blob: &BlobRef) -> Result<String> {
+        String::from_utf8(self.get(blob)?)
+            .map_err(|e| LennardError::Integrity(format!("Blob {} is not UTF-8: {}", blob.sha256, e)))
+    }
+
+    /// Remove blobs not in `referenced` that are older than `min_age`, and leftover temporary files
+    pub fn collect_garbage(&self, referenced: &HashSet<String>, min_age: Duration, dry_run: bool) -> Result<BlobGcReport> {
+        let mut report = BlobGcReport::default();
+        let now = SystemTime::now();
+
+        for fan_out in fs::read_dir(&self.blobs_dir)? {
+            let fan_out = fan_out?.path();
+            if !fan_out.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&fan_out)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let metadata = entry.metadata()?;
+                let age = now.duration_since(metadata.modified()?).unwrap_or_default();
+
+                if referenced.contains(&name) || age < min_age {
+                    report.kept += 1;
+                    continue;
+                }
+                if !dry_run {
+                    fs::remove_file(entry.path())?;
+                }
+                log::debug!("Removed unreferenced blob {}", name);
+                report.removed += 1;
+                report.removed_bytes += metadata.len();
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Hashes of the blobs approvals in any state and unfinished outbox entries refer to
+pub fn referenced_blobs(approvals: &dyn ApprovalStore, outbox: &OutboxStore) -> Result<HashSet<String>> {
+    let mut referenced = HashSet::new();
+    for state in ApprovalState::ALL {
+        for approval in approvals.list_by_state(state)? {
+            referenced.extend(approval.blob_refs().map(|blob| blob.sha256.clone()));
+        }
+    }
+    for entry in outbox.list_pending()?.into_iter().chain(outbox.list_failed()?) {
+        referenced.insert(entry.pdf.sha256);
+    }
+    Ok(referenced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_blobs_are_stored_once_and_verified() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = BlobStore::new(temp_dir.path()).unwrap();
+
+        let blob = store.put(b"%PDF-1.4 letter").unwrap();
+        assert_eq!(store.put(b"%PDF-1.4 letter").unwrap(), blob);
+        assert_eq!(blob.size, 15);
+        assert_eq!(store.get(&blob).unwrap(), b"%PDF-1.4 letter");
+
+        let path = temp_dir.path().join("data").join("blobs").join(&blob.sha256[..2]).join(&blob.sha256);
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+
+        // A blob collected between two puts is written again
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(store.put(b"%PDF-1.4 letter").unwrap(), blob);
+        assert_eq!(store.get(&blob).unwrap(), b"%PDF-1.4 letter");
+
+        fs::write(&path, b"%PDF-1.4 tampered").unwrap();
+        assert!(matches!(store.get(&blob), Err(LennardError::Integrity(_))));
+
+        let outside = BlobRef { sha256: "../../secrets".to_string(), size: 0 };
+        assert!(matches!(store.get(&outside), Err(LennardError::Integrity(_))));
+        assert!(matches!(store.get(&BlobRef::of(b"missing")), Err(LennardError::NotFound(_))));
+
+        // Dry runs only hash
+        let hashed = store.clone().dry_run().put(b"not written").unwrap();
+        assert!(store.get(&hashed).is_err());
+    }
+
+    #[test]
+    fn test_garbage_collection_keeps_referenced_and_recent_blobs() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = BlobStore::new(temp_dir.path()).unwrap();
+        let kept = store.put(b"referenced").unwrap();
+        let unreferenced = store.put(b"unreferenced").unwrap();
+        let referenced = HashSet::from([kept.sha256.clone()]);
+
+        let report = store.collect_garbage(&referenced, Duration::from_secs(3600), false).unwrap();
+        assert_eq!(report.removed, 0);
+        assert_eq!(report.kept, 2);
+
+        let report = store.collect_garbage(&referenced, Duration::ZERO, true).unwrap();
+        assert_eq!(report.removed, 1);
+        assert!(store.get(&unreferenced).is_ok());
+
+        let report = store.collect_garbage(&referenced, Duration::ZERO, false).unwrap();
+        assert_eq!(report.removed, 1);
+        assert_eq!(report.removed_bytes, 12);
+        assert!(store.get(&kept).is_ok());
+        assert!(store.get(&unreferenced).is_err());
+    }
+}
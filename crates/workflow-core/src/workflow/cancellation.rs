@@ -0,0 +1,116 @@
+//! Cooperative cancellation of running workflow runs
+//! Every running trigger registers a token - the orchestrator checks it between tasks and steps
+
+use crate::error::{LennardError, Result};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Cancellation handle of a single run - clones share the same state
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    reason: Arc<Mutex<Option<String>>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation - the first reason wins
+    pub fn cancel(&self, reason: &str) {
+        let mut current = self.reason.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if current.is_none() {
+            *current = Some(reason.to_string());
+        }
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Reason given when the run was cancelled
+    pub fn reason(&self) -> Option<String> {
+        self.reason.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Fail with `LennardError::Cancelled` once cancellation was requested
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(LennardError::Cancelled(
+                self.reason().unwrap_or_else(|| "Workflow cancelled".to_string())
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Tokens of all runs currently being processed, by workflow id
+#[derive(Debug, Default)]
+pub struct CancellationRegistry {
+    tokens: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl CancellationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn tokens(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancellationToken>> {
+        self.tokens.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Register a running workflow and return its token
+    pub fn register(&self, workflow_id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.tokens().insert(workflow_id.to_string(), token.clone());
+        token
+    }
+
+    /// Remove a workflow once it finished
+    pub fn unregister(&self, workflow_id: &str) {
+        self.tokens().remove(workflow_id);
+    }
+
+    /// Request cancellation of a running workflow
+    /// Returns false if no workflow with this id is running
+    pub fn cancel(&self, workflow_id: &str, reason: &str) -> bool {
+        match self.tokens().get(workflow_id) {
+            Some(token) => {
+                token.cancel(reason);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_running(&self, workflow_id: &str) -> bool {
+        self.tokens().contains_key(workflow_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_registered_workflow() {
+        let registry = CancellationRegistry::new();
+        let token = registry.register("run-1");
+        assert!(token.check().is_ok());
+
+        assert!(!registry.cancel("unknown", "stop"));
+        assert!(registry.cancel("run-1", "stop"));
+        assert!(registry.cancel("run-1", "second reason"));
+
+        assert!(token.is_cancelled());
+        assert_eq!(token.reason().as_deref(), Some("stop"));
+        assert!(matches!(token.check(), Err(LennardError::Cancelled(reason)) if reason == "stop"));
+
+        registry.unregister("run-1");
+        assert!(!registry.is_running("run-1"));
+        assert!(!registry.cancel("run-1", "stop"));
+    }
+}
@@ -0,0 +1,259 @@
+//! Per-task step checkpoints
+//! Each completed step of a task is written to `checkpoints/<task_id>/<step>.json`, so a retry
+//! resumes after the last completed step instead of re-running dossier and LLM calls
+
+use crate::error::{LennardError, Result};
+use super::approval_types::TaskId;
+use super::run_store::sanitize_id;
+use crate::paths;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::fs;
+
+/// Checkpointed steps of a task, in execution order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowStep {
+    /// Step 1: Zoho contact (including an address extracted in step 3.5)
+    Contact,
+    /// Step 2: LinkedIn profile
+    Profile,
+    /// Step 3: Person and company dossiers
+    Dossiers,
+    /// Step 4: Generated letter
+    Letter,
+    /// Step 4.5: Final letter and its PDF
+    Pdf,
+    /// Step 5a: Created approval
+    Approval,
+}
+
+impl WorkflowStep {
+    /// All steps in execution order
+    pub const ALL: [WorkflowStep; 6] = [
+        WorkflowStep::Contact,
+        WorkflowStep::Profile,
+        WorkflowStep::Dossiers,
+        WorkflowStep::Letter,
+        WorkflowStep::Pdf,
+        WorkflowStep::Approval,
+    ];
+
+    /// `WorkflowSteps` method that produces the output of this step
+    pub fn method(&self) -> &'static str {
+        match self {
+            WorkflowStep::Contact => "load_contact",
+            WorkflowStep::Profile => "load_profile",
+            WorkflowStep::Dossiers => "generate_dossiers",
+            WorkflowStep::Letter => "generate_letter",
+            WorkflowStep::Pdf => "render_letter_pdf",
+            WorkflowStep::Approval => "approval_start",
+        }
+    }
+
+    /// Name used in file names, the API and the CLI
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkflowStep::Contact => "contact",
+            WorkflowStep::Profile => "profile",
+            WorkflowStep::Dossiers => "dossiers",
+            WorkflowStep::Letter => "letter",
+            WorkflowStep::Pdf => "pdf",
+            WorkflowStep::Approval => "approval",
+        }
+    }
+}
+
+impl fmt::Display for WorkflowStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for WorkflowStep {
+    type Err = LennardError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        WorkflowStep::ALL
+            .into_iter()
+            .find(|step| step.as_str() == s.trim().to_lowercase())
+            .ok_or_else(|| LennardError::Validation(format!(
+                "Unknown workflow step '{}', expected one of: {}",
+                s,
+                WorkflowStep::ALL.map(|step| step.as_str()).join(", ")
+            )))
+    }
+}
+
+/// File-based store for step checkpoints
+pub struct TaskCheckpointStore {
+    checkpoints_dir: PathBuf,
+}
+
+impl TaskCheckpointStore {
+    /// Create new TaskCheckpointStore below the given data root
+    pub fn new<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        let checkpoints_dir = root_path.as_ref().join(paths::CHECKPOINTS_DIR_NAME);
+        fs::create_dir_all(&checkpoints_dir)?;
+
+        Ok(Self { checkpoints_dir })
+    }
+
+    /// Directory holding the checkpoints of a task - the id is sanitized so it can never escape the store
+    fn task_dir(&self, task_id: &TaskId) -> PathBuf {
+        self.checkpoints_dir.join(sanitize_id(task_id.as_str()))
+    }
+
+    fn step_path(&self, task_id: &TaskId, step: WorkflowStep) -> PathBuf {
+        self.task_dir(task_id).join(format!("{}.json", step.as_str()))
+    }
+
+    /// Write the output of a completed step
+    pub fn save<T: Serialize>(&self, task_id: &TaskId, step: WorkflowStep, value: &T) -> Result<()> {
+        let dir = self.task_dir(task_id);
+        fs::create_dir_all(&dir)?;
+
+        let json = serde_json::to_string_pretty(value)
+            .map_err(|e| LennardError::Serialization(format!("Failed to serialize {} checkpoint: {}", step, e)))?;
+
+        let path = self.step_path(task_id, step);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, &path)?;
+
+        log::debug!("Saved {} checkpoint for task {}", step, task_id);
+        Ok(())
+    }
+
+    /// Read the output of a completed step
+    /// An unreadable checkpoint is logged and treated as missing so the step simply runs again
+    pub fn load<T: DeserializeOwned>(&self, task_id: &TaskId, step: WorkflowStep) -> Result<Option<T>> {
+        let path = self.step_path(task_id, step);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = fs::read_to_string(&path)?;
+        match serde_json::from_str(&json) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => {
+                log::warn!("Ignoring unreadable {} checkpoint for task {}: {}", step, task_id, e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Steps with a checkpoint for this task, in execution order
+    pub fn completed_steps(&self, task_id: &TaskId) -> Vec<WorkflowStep> {
+        WorkflowStep::ALL
+            .into_iter()
+            .filter(|step| self.step_path(task_id, *step).exists())
+            .collect()
+    }
+
+    /// Remove the checkpoints of `step` and every later step so they are re-run
+    pub fn clear_from(&self, task_id: &TaskId, step: WorkflowStep) -> Result<()> {
+        for later in WorkflowStep::ALL.into_iter().filter(|s| *s >= step) {
+            let path = self.step_path(task_id, later);
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+
+        log::info!("Cleared checkpoints of task {} from step '{}'", task_id, step);
+        Ok(())
+    }
+
+    /// Remove all checkpoints of a task
+    pub fn clear(&self, task_id: &TaskId) -> Result<()> {
+        let dir = self.task_dir(task_id);
+
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+            log::debug!("Cleared all checkpoints of task {}", task_id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{LetterContent, RenderedLetter};
+    use tempfile::TempDir;
+
+    fn task_id() -> TaskId {
+        TaskId::new("task-1".to_string())
+    }
+
+    #[test]
+    fn test_step_names_round_trip() {
+        for step in WorkflowStep::ALL {
+            assert_eq!(step.as_str().parse::<WorkflowStep>().unwrap(), step);
+        }
+        assert_eq!(" Dossiers ".parse::<WorkflowStep>().unwrap(), WorkflowStep::Dossiers);
+        assert!("send".parse::<WorkflowStep>().is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_checkpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TaskCheckpointStore::new(temp_dir.path()).unwrap();
+
+        let rendered = RenderedLetter {
+            letter: LetterContent {
+                subject: "Betreff".to_string(),
+                greeting: "Hallo".to_string(),
+                body: "Text".to_string(),
+                sender_name: "Lennard".to_string(),
+                recipient_name: "Jane".to_string(),
+                company_name: "ACME".to_string(),
+            },
+            pdf: b"%PDF-1.4".to_vec(),
+        };
+        store.save(&task_id(), WorkflowStep::Pdf, &rendered).unwrap();
+
+        assert!(temp_dir.path().join("checkpoints").join("task-1").join("pdf.json").exists());
+        let loaded: RenderedLetter = store.load(&task_id(), WorkflowStep::Pdf).unwrap().unwrap();
+        assert_eq!(loaded.pdf, rendered.pdf);
+        assert_eq!(loaded.letter.subject, "Betreff");
+
+        let missing: Option<RenderedLetter> = store.load(&task_id(), WorkflowStep::Letter).unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn test_unreadable_checkpoint_is_treated_as_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TaskCheckpointStore::new(temp_dir.path()).unwrap();
+
+        store.save(&task_id(), WorkflowStep::Letter, &"not a letter").unwrap();
+        let loaded: Option<RenderedLetter> = store.load(&task_id(), WorkflowStep::Letter).unwrap();
+        assert!(loaded.is_none());
+    }
+
+    #[test]
+    fn test_clear_from_step() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TaskCheckpointStore::new(temp_dir.path()).unwrap();
+
+        for step in WorkflowStep::ALL {
+            store.save(&task_id(), step, &step.as_str()).unwrap();
+        }
+        assert_eq!(store.completed_steps(&task_id()).len(), 6);
+
+        store.clear_from(&task_id(), WorkflowStep::Letter).unwrap();
+        assert_eq!(
+            store.completed_steps(&task_id()),
+            vec![WorkflowStep::Contact, WorkflowStep::Profile, WorkflowStep::Dossiers]
+        );
+
+        store.clear(&task_id()).unwrap();
+        assert!(store.completed_steps(&task_id()).is_empty());
+        assert!(!temp_dir.path().join("checkpoints").join("task-1").exists());
+    }
+}
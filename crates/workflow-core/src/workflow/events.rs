@@ -0,0 +1,154 @@
+//! Domain events of workflow runs and approvals
+//!
+//! The orchestrator, the approval queue and the watchers publish to a shared `EventBus`;
+//! subscribers such as the gRPC streams receive every event published after they subscribed.
+
+use super::approval_types::{ApprovalData, ApprovalId, ApprovalState, TaskId, UserId};
+use super::run_types::{TaskResult, WorkflowStatus};
+use crate::constants::EVENT_BUS_CAPACITY;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// What happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkflowEventKind {
+    RunStarted,
+    TaskStarted { task_id: TaskId },
+    StepCompleted { task_id: TaskId, step: String },
+    StepFailed { task_id: TaskId, step: String, error: String },
+    TaskFinished { result: TaskResult },
+    RunFinished { status: WorkflowStatus, message: Option<String> },
+    ApprovalCreated { approval_id: ApprovalId, task_id: TaskId },
+    ApprovalStateChanged { approval_id: ApprovalId, task_id: TaskId, state: ApprovalState },
+    LetterSent { approval_id: ApprovalId, task_id: TaskId, job_id: String },
+}
+
+impl WorkflowEventKind {
+    /// Stable snake_case name, e.g. `step_completed`
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorkflowEventKind::RunStarted => "run_started",
+            WorkflowEventKind::TaskStarted { .. } => "task_started",
+            WorkflowEventKind::StepCompleted { .. } => "step_completed",
+            WorkflowEventKind::StepFailed { .. } => "step_failed",
+            WorkflowEventKind::TaskFinished { .. } => "task_finished",
+            WorkflowEventKind::RunFinished { .. } => "run_finished",
+            WorkflowEventKind::ApprovalCreated { .. } => "approval_created",
+            WorkflowEventKind::ApprovalStateChanged { .. } => "approval_state_changed",
+            WorkflowEventKind::LetterSent { .. } => "letter_sent",
+        }
+    }
+}
+
+/// A published event with the run or user it belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowEvent {
+    /// Set for events of a workflow run
+    pub workflow_id: Option<String>,
+    /// Requester of the run or the approval
+    pub user_id: UserId,
+    pub timestamp: DateTime<Utc>,
+    pub kind: WorkflowEventKind,
+}
+
+impl WorkflowEvent {
+    /// Event of a workflow run
+    pub fn run(workflow_id: &str, user_id: UserId, kind: WorkflowEventKind) -> Self {
+        Self {
+            workflow_id: Some(workflow_id.to_string()),
+            user_id,
+            timestamp: Utc::now(),
+            kind,
+        }
+    }
+
+    /// Event of an approval, outside of any run
+    pub fn approval(approval: &ApprovalData, kind: WorkflowEventKind) -> Self {
+        Self {
+            workflow_id: None,
+            user_id: approval.requested_by,
+            timestamp: Utc::now(),
+            kind,
+        }
+    }
+
+    /// The approval's current state as an event
+    pub fn approval_state_changed(approval: &ApprovalData) -> Self {
+        Self::approval(approval, WorkflowEventKind::ApprovalStateChanged {
+            approval_id: approval.approval_id.clone(),
+            task_id: approval.task_id.clone(),
+            state: approval.state,
+        })
+    }
+
+    /// Whether this is an event of an approval rather than of a run
+    pub fn is_approval_event(&self) -> bool {
+        matches!(
+            self.kind,
+            WorkflowEventKind::ApprovalCreated { .. }
+                | WorkflowEventKind::ApprovalStateChanged { .. }
+                | WorkflowEventKind::LetterSent { .. }
+        )
+    }
+}
+
+/// In-process fan-out of workflow events
+///
+/// Publishing never blocks or fails - without subscribers events are dropped.
+pub struct EventBus {
+    sender: broadcast::Sender<WorkflowEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: WorkflowEvent) {
+        log::debug!("Event {} (workflow: {:?})", event.kind.name(), event.workflow_id);
+        // Only fails without subscribers
+        let _ = self.sender.send(event);
+    }
+
+    /// Receive all events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<WorkflowEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_events() {
+        let bus = EventBus::new();
+
+        // Nobody listens yet - dropped without error
+        bus.publish(WorkflowEvent::run("run-1", UserId::new(1), WorkflowEventKind::RunStarted));
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        let started = WorkflowEvent::run("run-2", UserId::new(1), WorkflowEventKind::TaskStarted {
+            task_id: TaskId::new("task-1".to_string()),
+        });
+        bus.publish(started);
+
+        for receiver in [&mut first, &mut second] {
+            let event = receiver.recv().await.unwrap();
+            assert_eq!(event.workflow_id.as_deref(), Some("run-2"));
+            assert_eq!(event.kind.name(), "task_started");
+            assert!(!event.is_approval_event());
+        }
+        assert!(first.try_recv().is_err());
+    }
+}
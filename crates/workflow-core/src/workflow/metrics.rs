@@ -0,0 +1,309 @@
+//! Workflow metrics aggregated from persisted data
+//!
+//! Computed on demand from the run records under `runs/` and the approvals in every state of
+//! the approval store - sent letters, rejected approvals and failed sends included. Dry runs
+//! are not counted.
+
+use crate::error::Result;
+use super::approval_store::ApprovalStore;
+use super::approval_types::{ApprovalData, ApprovalId, ApprovalState};
+use super::run_store::WorkflowRunStore;
+use super::run_types::{TaskState, WorkflowRun, WorkflowStatus};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Bucket for failed tasks that did not fail in a workflow step, e.g. tasks that could not be loaded
+pub const UNKNOWN_STEP: &str = "unknown";
+
+/// Count, mean and 95th percentile of a series of samples
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SampleStats {
+    pub count: u32,
+    pub mean: f64,
+    pub p95: f64,
+}
+
+impl SampleStats {
+    /// Nearest-rank percentile - all zero without samples
+    pub fn from_samples(mut samples: Vec<f64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let count = samples.len();
+        let rank = ((count as f64) * 0.95).ceil() as usize;
+
+        Self {
+            count: count as u32,
+            mean: samples.iter().sum::<f64>() / count as f64,
+            p95: samples[rank.clamp(1, count) - 1],
+        }
+    }
+}
+
+/// Aggregates over the runs started and approvals requested in a date range
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowMetricsReport {
+    pub total_workflows: u32,
+    pub completed_workflows: u32,
+    pub failed_workflows: u32,
+    pub active_workflows: u32,
+    /// Seconds from start to finish of finished runs
+    pub run_duration: SampleStats,
+    /// Tasks that finished processing, successfully or not
+    pub tasks_processed: u32,
+    pub tasks_failed: u32,
+    /// Failed tasks by the workflow step they failed at
+    pub failures_by_step: BTreeMap<String, u32>,
+    pub letters_sent: u32,
+    /// Letters whose send was attempted but did not go through
+    pub letters_failed: u32,
+    /// Approvals by `ApprovalState::name`
+    pub approvals_by_state: BTreeMap<String, u32>,
+    /// Seconds from the start of a task until its letter was approved
+    pub task_start_to_approval: SampleStats,
+    /// Seconds from approval until the letter was sent
+    pub approval_to_send: SampleStats,
+    /// Letter iterations per approval - 1 means approved without improvements
+    pub iterations_per_letter: SampleStats,
+}
+
+impl WorkflowMetricsReport {
+    /// Aggregate runs and approvals within `[from, to]` - either bound may be open
+    pub fn compute(
+        runs: &[WorkflowRun],
+        approvals: &[ApprovalData],
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Self {
+        let in_range = |at: DateTime<Utc>| from.is_none_or(|from| at >= from) && to.is_none_or(|to| at <= to);
+        let mut report = Self::default();
+
+        let runs: Vec<&WorkflowRun> = runs.iter()
+            .filter(|run| !run.dry_run && in_range(run.started_at))
+            .collect();
+        let approvals: Vec<&ApprovalData> = approvals.iter()
+            .filter(|approval| in_range(approval.requested_at))
+            .collect();
+
+        let mut run_durations = Vec::new();
+        let mut task_started: HashMap<&ApprovalId, DateTime<Utc>> = HashMap::new();
+        for run in &runs {
+            report.total_workflows += 1;
+            match run.status {
+                WorkflowStatus::Completed => report.completed_workflows += 1,
+                WorkflowStatus::Failed => report.failed_workflows += 1,
+                status if status.is_active() => report.active_workflows += 1,
+                _ => {}
+            }
+            if let Some(finished_at) = run.finished_at {
+                run_durations.push(seconds_between(run.started_at, finished_at));
+            }
+
+            for task in &run.task_results {
+                match task.state {
+                    TaskState::Succeeded => report.tasks_processed += 1,
+                    TaskState::Failed => {
+                        report.tasks_processed += 1;
+                        report.tasks_failed += 1;
+                        let step = task.failed_step.as_deref().unwrap_or(UNKNOWN_STEP);
+                        *report.failures_by_step.entry(step.to_string()).or_default() += 1;
+                    }
+                    _ => {}
+                }
+                if let Some(approval_id) = &task.approval_id {
+                    task_started.insert(approval_id, task.started_at);
+                }
+            }
+        }
+
+        let mut to_approval = Vec::new();
+        let mut to_send = Vec::new();
+        let mut iterations = Vec::new();
+        for approval in &approvals {
+            *report.approvals_by_state.entry(approval.state.name().to_string()).or_default() += 1;
+            iterations.push(approval.letter_history.len() as f64);
+
+            if approval.sent_at.is_some() {
+                report.letters_sent += 1;
+            } else if approval.state == ApprovalState::Failed && approval.idempotency_key.is_some() {
+                report.letters_failed += 1;
+            }
+
+            if let Some(approved_at) = approval.approved_at {
+                // The task start is known as long as its run is in range as well
+                if let Some(started_at) = task_started.get(&approval.approval_id) {
+                    to_approval.push(seconds_between(*started_at, approved_at));
+                }
+                if let Some(sent_at) = approval.sent_at {
+                    to_send.push(seconds_between(approved_at, sent_at));
+                }
+            }
+        }
+
+        report.run_duration = SampleStats::from_samples(run_durations);
+        report.task_start_to_approval = SampleStats::from_samples(to_approval);
+        report.approval_to_send = SampleStats::from_samples(to_send);
+        report.iterations_per_letter = SampleStats::from_samples(iterations);
+        report
+    }
+}
+
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds().max(0) as f64 / 1000.0
+}
+
+/// Reads run records below the data root and approvals from the approval store
+pub struct WorkflowMetricsCollector {
+    approvals: Arc<dyn ApprovalStore>,
+    run_store: WorkflowRunStore,
+}
+
+impl WorkflowMetricsCollector {
+    pub fn new<P: AsRef<Path>>(root_path: P, approvals: Arc<dyn ApprovalStore>) -> Result<Self> {
+        Ok(Self {
+            approvals,
+            run_store: WorkflowRunStore::new(&root_path)?,
+        })
+    }
+
+    /// Aggregate everything started or requested within `[from, to]`
+    pub fn collect(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Result<WorkflowMetricsReport> {
+        let runs = self.run_store.all_runs()?;
+        let approvals = self.all_approvals()?;
+        Ok(WorkflowMetricsReport::compute(&runs, &approvals, from, to))
+    }
+
+    /// When the most recent letter was accepted by LetterExpress
+    pub fn last_letter_sent_at(&self) -> Result<Option<DateTime<Utc>>> {
+        Ok(self.all_approvals()?.iter().filter_map(|approval| approval.sent_at).max())
+    }
+
+    /// Every approval, including sent and failed ones - the latest copy per approval
+    fn all_approvals(&self) -> Result<Vec<ApprovalData>> {
+        let mut approvals: HashMap<ApprovalId, ApprovalData> = HashMap::new();
+        for state in ApprovalState::ALL {
+            for mut approval in self.approvals.list_by_state(state)? {
+                // A failed send leaves the approval in Sending inside the failed queue
+                if state == ApprovalState::Failed && approval.state == ApprovalState::Sending {
+                    approval.mark_failed();
+                }
+
+                match approvals.get(&approval.approval_id) {
+                    Some(existing) if existing.updated_at >= approval.updated_at => {}
+                    _ => {
+                        approvals.insert(approval.approval_id.clone(), approval);
+                    }
+                }
+            }
+        }
+
+        Ok(approvals.into_values().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths;
+    use crate::workflow::approval_store::FileApprovalStore;
+    use crate::workflow::approval_types::{TaskId, UserId, WorkflowTrigger};
+    use crate::workflow::run_types::TaskResult;
+    use chrono::Duration;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, approval: &ApprovalData) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(name), serde_json::to_string(approval).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_sample_stats() {
+        assert_eq!(SampleStats::from_samples(Vec::new()), SampleStats::default());
+
+        let stats = SampleStats::from_samples((1..=20).map(f64::from).collect());
+        assert_eq!(stats.count, 20);
+        assert_eq!(stats.mean, 10.5);
+        assert_eq!(stats.p95, 19.0);
+    }
+
+    #[test]
+    fn test_collect_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let started = Utc::now() - Duration::hours(3);
+
+        // Sent letter that needed one improvement
+        let mut sent = ApprovalData::test_fixture("task-sent");
+        sent.add_feedback("Shorter".to_string(), UserId::new(1));
+        sent.add_improved_letter(sent.current_letter.clone());
+        sent.mark_approved();
+        sent.approved_at = Some(started + Duration::hours(1));
+        sent.mark_sending();
+        sent.mark_sent("4711".to_string());
+        sent.sent_at = Some(started + Duration::minutes(90));
+        write(&root.join(paths::PROCESSED_DIR_NAME), "approval_sent_processed.json", &sent);
+
+        // Send failed, rejected and still pending approvals
+        let mut send_failed = ApprovalData::test_fixture("task-send-failed");
+        send_failed.mark_approved();
+        send_failed.mark_sending();
+        write(&root.join(paths::FAILED_DIR_NAME), "approval_send_failed.json", &send_failed);
+        let mut rejected = ApprovalData::test_fixture("task-rejected");
+        rejected.mark_failed();
+        write(&root.join(paths::FAILED_DIR_NAME), "approval_rejected.json", &rejected);
+        write(&root.join(paths::PENDING_APPROVAL_DIR_NAME), "approval_pending.json", &ApprovalData::test_fixture("task-pending"));
+        fs::write(root.join(paths::FAILED_DIR_NAME).join("trigger_error.json"), "{}").unwrap();
+
+        let trigger = WorkflowTrigger {
+            schema_version: crate::workflow::TRIGGER_SCHEMA_VERSION,
+            trigger_id: "run-1".to_string(),
+            requested_by: UserId::new(1),
+            requested_at: started,
+            max_tasks: 2,
+            dry_run: false,
+            task_selection: None,
+            task_ids: Vec::new(),
+            processed: false,
+            processed_at: None,
+            result: None,
+        };
+        let mut run = WorkflowRun::start(&trigger);
+        run.started_at = started;
+        let mut succeeded = TaskResult::new(TaskId::new("task-sent".to_string()));
+        succeeded.started_at = started;
+        succeeded.approval_id = Some(sent.approval_id.clone());
+        succeeded.succeed();
+        let mut failed = TaskResult::new(TaskId::new("task-failed".to_string()));
+        failed.failed_step = Some("load_contact".to_string());
+        failed.fail("Contact not found".to_string());
+        run.task_results = vec![succeeded, failed];
+        run.finish(WorkflowStatus::Completed, None);
+
+        let approvals = Arc::new(FileApprovalStore::new(root).unwrap());
+        let collector = WorkflowMetricsCollector::new(root, approvals).unwrap();
+        collector.run_store.save_run(&run).unwrap();
+        let report = collector.collect(None, None).unwrap();
+
+        assert_eq!((report.total_workflows, report.completed_workflows), (1, 1));
+        assert_eq!((report.tasks_processed, report.tasks_failed), (2, 1));
+        assert_eq!(report.failures_by_step.get("load_contact"), Some(&1));
+        assert_eq!((report.letters_sent, report.letters_failed), (1, 1));
+        assert_eq!(report.approvals_by_state.get("failed"), Some(&2));
+        assert_eq!(report.approvals_by_state.get("sent"), Some(&1));
+        assert_eq!(report.approvals_by_state.get("pending_approval"), Some(&1));
+        assert_eq!(report.task_start_to_approval.mean, 3600.0);
+        assert_eq!(report.approval_to_send.mean, 1800.0);
+        assert_eq!(report.iterations_per_letter.count, 4);
+        assert_eq!(report.iterations_per_letter.p95, 2.0);
+
+        // Nothing started after now
+        let empty = collector.collect(Some(Utc::now() + Duration::minutes(1)), None).unwrap();
+        assert_eq!(empty.total_workflows, 0);
+        assert!(empty.approvals_by_state.is_empty());
+    }
+}
@@ -2,6 +2,23 @@
 
 pub mod approval_types;
 pub mod approval_queue;
+pub mod approval_store;
+pub mod blob_store;
+pub mod sqlite_approval_store;
+pub mod schema;
+pub mod run_types;
+pub mod run_store;
+pub mod checkpoint_store;
+pub mod cancellation;
+pub mod retry;
+pub mod events;
+pub mod metrics;
+pub mod outbox;
+pub mod outbox_worker;
+pub mod approval_expiry_worker;
+pub mod retention;
+pub mod retention_worker;
+pub mod scheduler;
 pub mod approval_watcher;
 pub mod needs_improvement_watcher;
 pub mod traits;
@@ -9,6 +26,23 @@ pub mod orchestrator;
 
 pub use approval_types::*;
 pub use approval_queue::ApprovalQueue;
+pub use approval_store::{ApprovalStore, FileApprovalStore, Transition, MigrationReport, migrate_approvals, open_approval_store};
+pub use blob_store::{BlobRef, BlobStore, BlobGcReport, referenced_blobs};
+pub use sqlite_approval_store::SqliteApprovalStore;
+pub use schema::{APPROVAL_SCHEMA_VERSION, TRIGGER_SCHEMA_VERSION, SchemaMigrationReport, migrate_data_dir};
+pub use run_types::{WorkflowRun, WorkflowRunFilter, WorkflowRunPage, WorkflowStatus, TaskResult, TaskState, StepRetry, DryRunReport, DryRunTaskReport};
+pub use run_store::WorkflowRunStore;
+pub use checkpoint_store::{TaskCheckpointStore, WorkflowStep};
+pub use cancellation::{CancellationRegistry, CancellationToken};
+pub use retry::{RetryPolicy, RetryPolicies};
+pub use events::{EventBus, WorkflowEvent, WorkflowEventKind};
+pub use metrics::{WorkflowMetricsCollector, WorkflowMetricsReport, SampleStats};
+pub use outbox::{OutboxStore, OutboxEntry, OutboxItem, OutboxItemStatus, SideEffect};
+pub use outbox_worker::OutboxWorker;
+pub use approval_expiry_worker::ApprovalExpiryWorker;
+pub use retention::{Retention, RetentionReport};
+pub use retention_worker::RetentionWorker;
+pub use scheduler::{WorkflowScheduler, Schedule, ScheduleStatus};
 pub use approval_watcher::ApprovalWatcher;
 pub use needs_improvement_watcher::NeedsImprovementWatcher;
 pub use traits::WorkflowSteps;
@@ -1,30 +1,371 @@
 //! Workflow orchestrator with strongly-typed steps
 
 use super::traits::WorkflowSteps;
-use super::approval_types::WorkflowTrigger;
+use super::approval_types::{WorkflowTrigger, TaskId, ContactId, UserId};
+use super::schema::TRIGGER_SCHEMA_VERSION;
+use super::run_types::{WorkflowRun, WorkflowStatus, TaskResult, DryRunReport, DryRunTaskReport};
+use super::run_store::{WorkflowRunStore, DRY_RUN_REPORT_FILE};
+use super::checkpoint_store::{TaskCheckpointStore, WorkflowStep};
+use super::cancellation::{CancellationRegistry, CancellationToken};
+use super::retry::{self, RetryPolicies};
+use super::outbox::{OutboxEntry, OutboxItemStatus, OutboxStore, SideEffect};
+use super::blob_store::BlobStore;
+use super::events::{EventBus, WorkflowEvent, WorkflowEventKind};
+use crate::clients::DossierResult;
 use crate::error::{LennardError, Result};
+use crate::instrumentation;
+use crate::types::{MailingAddress, RenderedLetter, ZohoContact};
 use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use std::future::Future;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use zoho_generated_types::TasksResponse;
+
+/// Outcome of one task within a run
+struct TaskOutcome {
+    success: bool,
+    /// Line for the trigger result message
+    summary: String,
+    dry_run_report: Option<DryRunTaskReport>,
+}
+
+/// Result of the read-only steps of a task, ready for approval
+struct PreparedLetter {
+    contact: ZohoContact,
+    dossier: DossierResult,
+    rendered: RenderedLetter,
+    /// Address extracted from the dossiers because the contact had none
+    address_update: Option<MailingAddress>,
+}
 
 /// Single orchestration component with hard-coded workflow steps
 pub struct WorkflowOrchestrator<T: WorkflowSteps> {
     steps: T,
+    run_store: Arc<WorkflowRunStore>,
+    checkpoints: Arc<TaskCheckpointStore>,
+    outbox: Arc<OutboxStore>,
+    /// PDFs of approvals and outbox entries
+    blobs: Arc<BlobStore>,
+    /// Serializes outbox delivery so a side effect is never applied twice concurrently
+    outbox_lock: tokio::sync::Mutex<()>,
+    max_concurrent_tasks: usize,
+    /// Cancellation tokens of the runs currently being processed
+    cancellations: CancellationRegistry,
+    retry_policies: RetryPolicies,
+    events: Arc<EventBus>,
+}
+
+/// The run a task is processed in - identifies the task's published events
+#[derive(Clone, Copy)]
+struct RunScope<'a> {
+    workflow_id: &'a str,
+    requested_by: UserId,
+}
+
+/// Wrap a step error with the step name - cancellations pass through so they are not reported as failures
+fn step_error(step: &str, error: LennardError) -> LennardError {
+    match error {
+        LennardError::Cancelled(_) => error,
+        e => LennardError::Workflow(format!("{} failed: {}", step, e)),
+    }
+}
+
+/// Check that an explicitly requested task may be processed
+/// 
+/// Tasks already in progress or completed would otherwise get a second letter
+fn validate_requested_task(task: &TasksResponse) -> Result<()> {
+    if task.who_id.is_none() {
+        return Err(LennardError::Validation(format!("Task {} has no associated contact", task.id)));
+    }
+    match task.status.as_deref() {
+        Some(status @ ("In Progress" | "Completed")) => Err(LennardError::Validation(
+            format!("Task {} is already '{}'", task.id, status)
+        )),
+        _ => Ok(()),
+    }
 }
 
 impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
-    pub fn new(steps: T) -> Self {
-        Self { steps }
+    /// Create an orchestrator that processes one task at a time
+    pub fn new(
+        steps: T,
+        run_store: Arc<WorkflowRunStore>,
+        checkpoints: Arc<TaskCheckpointStore>,
+        outbox: Arc<OutboxStore>,
+        blobs: Arc<BlobStore>,
+    ) -> Self {
+        Self {
+            steps,
+            run_store,
+            checkpoints,
+            outbox,
+            blobs,
+            outbox_lock: tokio::sync::Mutex::new(()),
+            max_concurrent_tasks: 1,
+            cancellations: CancellationRegistry::new(),
+            retry_policies: RetryPolicies::none(),
+            events: Arc::new(EventBus::new()),
+        }
+    }
+    
+    /// Process up to `max_concurrent_tasks` tasks of a trigger in parallel
+    pub fn with_max_concurrent_tasks(mut self, max_concurrent_tasks: usize) -> Self {
+        self.max_concurrent_tasks = max_concurrent_tasks.max(1);
+        self
+    }
+    
+    /// Retry transient step failures - without this every step gets a single attempt
+    pub fn with_retry_policies(mut self, retry_policies: RetryPolicies) -> Self {
+        self.retry_policies = retry_policies;
+        self
+    }
+    
+    /// Publish run and task events to a shared bus - without this they go to a bus of their own
+    pub fn with_event_bus(mut self, events: Arc<EventBus>) -> Self {
+        self.events = events;
+        self
+    }
+    
+    /// Bus the run, task and letter events are published to
+    pub fn events(&self) -> &Arc<EventBus> {
+        &self.events
+    }
+    
+    /// Announce the terminal state of a run
+    fn publish_run_finished(&self, run: &WorkflowRun) {
+        self.events.publish(WorkflowEvent::run(&run.workflow_id, run.requested_by, WorkflowEventKind::RunFinished {
+            status: run.status,
+            message: run.error_message.clone(),
+        }));
+    }
+    
+    /// Persist the current state of a run - failures are logged but never abort processing
+    fn save_run(&self, run: &WorkflowRun) {
+        if let Err(e) = self.run_store.save_run(run) {
+            log::error!("Failed to persist workflow run {}: {}", run.workflow_id, e);
+        }
+    }
+    
+    /// Persist the record of a new run - a workflow id that already has one is rejected
+    fn create_run(&self, run: &WorkflowRun) -> Result<()> {
+        match self.run_store.create_run(run) {
+            Err(e @ LennardError::Validation(_)) => {
+                log::error!("Refusing to start workflow {}: {}", run.workflow_id, e);
+                Err(e)
+            }
+            // Like any other run update, a failed write never aborts processing
+            Err(e) => {
+                log::error!("Failed to persist workflow run {}: {}", run.workflow_id, e);
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+    
+    /// Finish a run that failed before any task was processed
+    fn fail_run(&self, run: &mut WorkflowRun, message: String) {
+        run.finish(WorkflowStatus::Failed, Some(message));
+        self.save_run(run);
+        self.cancellations.unregister(&run.workflow_id);
+        self.publish_run_finished(run);
+    }
+    
+    /// Request cooperative cancellation of a running workflow
+    /// 
+    /// Tasks stop at their next step boundary, tasks not yet picked up are skipped.
+    /// Returns false if no workflow with this id is currently being processed.
+    pub fn cancel_workflow(&self, workflow_id: &str, reason: &str) -> bool {
+        let cancelled = self.cancellations.cancel(workflow_id, reason);
+        if cancelled {
+            log::info!("Cancellation of workflow {} requested: {}", workflow_id, reason);
+        }
+        cancelled
+    }
+    
+    /// Whether a workflow with this id is currently being processed by this orchestrator
+    pub fn is_running(&self, workflow_id: &str) -> bool {
+        self.cancellations.is_running(workflow_id)
     }
     
     /// Process a workflow trigger through dynamic task loading and all 7 steps
-    pub async fn process_workflow(&self, trigger: WorkflowTrigger) -> Result<WorkflowTrigger> {
-        log::info!("Processing workflow trigger {} for up to {} tasks", 
-                   trigger.trigger_id, trigger.max_tasks);
+    /// 
+    /// A dry run executes the read-only steps for real but never marks tasks, writes to Zoho,
+    /// sends to Telegram or creates approvals - it produces a report and the PDFs instead
+    #[tracing::instrument(name = "workflow_run", skip_all, fields(workflow_id = %trigger.trigger_id, dry_run = trigger.dry_run))]
+    pub async fn process_workflow(&self, mut trigger: WorkflowTrigger) -> Result<WorkflowTrigger> {
+        // Explicitly requested tasks are all processed, whatever max_tasks says - each id once
+        if !trigger.task_ids.is_empty() {
+            let mut seen = HashSet::new();
+            trigger.task_ids = trigger.task_ids.iter()
+                .map(|id| id.trim().to_string())
+                .filter(|id| seen.insert(id.clone()))
+                .collect();
+            trigger.max_tasks = trigger.task_ids.iter().filter(|id| !id.is_empty()).count() as u32;
+        }
+        
+        log::info!("Processing workflow trigger {} for up to {} tasks{}", 
+                   trigger.trigger_id, trigger.max_tasks,
+                   if trigger.dry_run { " (dry run)" } else { "" });
+        
+        // Every trigger becomes a persisted run record and can be cancelled from now on
+        let mut run = WorkflowRun::start(&trigger);
+        self.create_run(&run)?;
+        let cancel = self.cancellations.register(&run.workflow_id);
+        self.events.publish(WorkflowEvent::run(&run.workflow_id, run.requested_by, WorkflowEventKind::RunStarted));
+        
+        // Trigger files bring their own selection unchecked
+        if let Some(Err(e)) = trigger.task_selection.as_ref().map(|selection| selection.validate()) {
+            self.fail_run(&mut run, format!("Invalid task selection: {}", e));
+            return Err(e);
+        }
+        
+        if !trigger.task_ids.is_empty() {
+            let (tasks, rejected) = self.load_requested_tasks(&trigger.task_ids).await;
+            return self.run_tasks(trigger, run, tasks, rejected, cancel).await;
+        }
         
         // Load available tasks from Zoho CRM dynamically
-        let available_tasks = self.steps.load_available_tasks(trigger.max_tasks).await?;
+        let available_tasks = match retry::retry(
+            "load_available_tasks",
+            self.retry_policies.for_method("load_available_tasks"),
+            || self.steps.load_available_tasks(trigger.max_tasks, trigger.task_selection.as_ref()),
+            |_| {},
+        ).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                self.fail_run(&mut run, format!("Failed to load tasks: {}", e));
+                return Err(e);
+            }
+        };
+        
+        self.run_tasks(trigger, run, available_tasks, Vec::new(), cancel).await
+    }
+    
+    /// Load the tasks a trigger names explicitly, by their trimmed and distinct ids
+    /// 
+    /// Tasks that can't be loaded or must not be processed are returned as failed results instead
+    async fn load_requested_tasks(&self, task_ids: &[String]) -> (Vec<TasksResponse>, Vec<TaskResult>) {
+        let mut tasks = Vec::new();
+        let mut rejected = Vec::new();
+        
+        for task_id in task_ids {
+            let loaded = if task_id.is_empty() {
+                Err(LennardError::Validation("Task ID must not be empty".to_string()))
+            } else {
+                retry::retry(
+                    "load_task",
+                    self.retry_policies.for_method("load_task"),
+                    || self.steps.load_task(task_id),
+                    |_| {},
+                ).await.and_then(|task| validate_requested_task(&task).map(|_| task))
+            };
+            
+            match loaded {
+                Ok(task) => tasks.push(task),
+                Err(e) => {
+                    log::warn!("Rejected requested task {}: {}", task_id, e);
+                    let mut task_result = TaskResult::new(TaskId::new(task_id.to_string()));
+                    task_result.fail(e.to_string());
+                    rejected.push(task_result);
+                }
+            }
+        }
+        
+        (tasks, rejected)
+    }
+    
+    /// Resume a single task from its checkpoints, recorded as a new workflow run
+    /// 
+    /// With `from_step` the checkpoints of that step and all later steps are discarded first,
+    /// so the task restarts at that step
+    #[tracing::instrument(skip_all, fields(task_id = task_id))]
+    pub async fn resume_task(&self, task_id: &str, from_step: Option<WorkflowStep>, requested_by: UserId) -> Result<WorkflowTrigger> {
+        let task_id = TaskId::new(task_id.to_string());
+        let trigger = WorkflowTrigger {
+            schema_version: TRIGGER_SCHEMA_VERSION,
+            trigger_id: uuid::Uuid::new_v4().to_string(),
+            requested_by,
+            requested_at: Utc::now(),
+            max_tasks: 1,
+            dry_run: false,
+            task_selection: None,
+            task_ids: Vec::new(),
+            processed: false,
+            processed_at: None,
+            result: None,
+        };
+        
+        log::info!("Resuming task {} from {} (run {}), completed steps: {:?}",
+                   task_id,
+                   from_step.map(|s| s.to_string()).unwrap_or_else(|| "last checkpoint".to_string()),
+                   trigger.trigger_id,
+                   self.checkpoints.completed_steps(&task_id));
+        
+        let mut run = WorkflowRun::start(&trigger);
+        self.create_run(&run)?;
+        let cancel = self.cancellations.register(&run.workflow_id);
+        self.events.publish(WorkflowEvent::run(&run.workflow_id, run.requested_by, WorkflowEventKind::RunStarted));
+        
+        if let Some(step) = from_step {
+            if let Err(e) = self.checkpoints.clear_from(&task_id, step) {
+                self.fail_run(&mut run, format!("Failed to clear checkpoints: {}", e));
+                return Err(e);
+            }
+        }
+        
+        let task = match retry::retry(
+            "load_task",
+            self.retry_policies.for_method("load_task"),
+            || self.steps.load_task(task_id.as_str()),
+            |_| {},
+        ).await {
+            Ok(task) => task,
+            Err(e) => {
+                self.fail_run(&mut run, format!("Failed to load task: {}", e));
+                return Err(e);
+            }
+        };
+        
+        self.run_tasks(trigger, run, vec![task], Vec::new(), cancel).await
+    }
+    
+    /// Process loaded tasks and finish the run
+    /// 
+    /// Up to `max_concurrent_tasks` tasks run in parallel. Results are collected in task order and
+    /// a failing task never cancels the others - only a cancellation of the whole run does.
+    /// `rejected` are requested tasks that failed before processing - they count as failed tasks of the run.
+    async fn run_tasks(&self, trigger: WorkflowTrigger, mut run: WorkflowRun, mut tasks: Vec<TasksResponse>, rejected: Vec<TaskResult>, cancel: CancellationToken) -> Result<WorkflowTrigger> {
+        // Never process more tasks than requested, whatever the loader returned
+        tasks.truncate(trigger.max_tasks as usize);
+        
+        let mut dry_run_report = trigger.dry_run.then(|| DryRunReport::new(run.workflow_id.clone()));
+        if trigger.dry_run {
+            run.dry_run_output = Some(self.run_store.dry_run_dir(&run.workflow_id));
+        }
+        run.total_tasks = (tasks.len() + rejected.len()) as u32;
+        let mut results: Vec<String> = rejected.iter()
+            .map(|r| format!("❌ Task {}: {}", r.task_id, r.error_message.as_deref().unwrap_or_default()))
+            .collect();
+        for task_result in rejected {
+            run.record_task(task_result);
+        }
+        // Record every selected task up front so a cancelled run shows what never started
+        for task in &tasks {
+            run.record_task(TaskResult::new(TaskId::new(task.id.clone())));
+        }
+        self.save_run(&run);
         
-        if available_tasks.is_empty() {
+        if tasks.is_empty() && results.is_empty() {
             log::info!("No available tasks found for processing");
+            if let Some(report) = &dry_run_report {
+                self.save_dry_run_report(report);
+            }
+            run.finish(WorkflowStatus::Completed, None);
+            self.save_run(&run);
+            self.cancellations.unregister(&run.workflow_id);
+            self.publish_run_finished(&run);
             return Ok(WorkflowTrigger {
                 result: Some("No tasks available for processing".to_string()),
                 processed: true,
@@ -33,41 +374,56 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
             });
         }
         
-        let mut processed_count = 0;
-        let mut results = Vec::new();
+        log::info!("Processing {} tasks with up to {} in parallel", tasks.len(), self.max_concurrent_tasks);
         
-        // Process each task through the 7-step workflow
-        for task in available_tasks {
-            log::info!("Processing task: {} - {}", task.id, task.subject);
-            
-            match self.process_single_task(&task).await {
-                Ok(result) => {
-                    processed_count += 1;
-                    results.push(format!("✅ Task {}: {}", task.id, result));
-                    log::info!("Successfully processed task {}: {}", task.id, result);
-                }
-                Err(e) => {
-                    results.push(format!("❌ Task {}: {}", task.id, e));
-                    log::error!("Failed to process task {}: {}", task.id, e);
-                    
-                    // Send error notification and update task status (no company name available at this level)
-                    if let Err(notification_err) = self.handle_task_error(&task, &e, None).await {
-                        log::error!("Failed to send error notification: {}", notification_err);
-                    }
-                }
+        let mut processed_count = 0;
+        let workflow_id = run.workflow_id.clone();
+        let scope = RunScope { workflow_id: &workflow_id, requested_by: trigger.requested_by };
+        let run = Mutex::new(run);
+        
+        // Process each task through the 7-step workflow - `buffered` yields outcomes in task order
+        let task_futures: Vec<_> = tasks.iter()
+            .map(|task| self.run_task(scope, trigger.dry_run, &run, task, &cancel))
+            .collect();
+        let mut outcomes = stream::iter(task_futures).buffered(self.max_concurrent_tasks);
+        
+        while let Some(outcome) = outcomes.next().await {
+            if outcome.success {
+                processed_count += 1;
             }
+            results.push(outcome.summary);
             
-            // Respect max_tasks limit (defensive check)
-            if processed_count >= trigger.max_tasks as usize {
-                break;
+            if let (Some(report), Some(task_report)) = (dry_run_report.as_mut(), outcome.dry_run_report) {
+                report.tasks.push(task_report);
+                self.save_dry_run_report(report);
             }
         }
+        drop(outcomes);
+        let mut run = run.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
         
-        let final_result = if processed_count > 0 {
+        let mut final_result = if processed_count > 0 {
             format!("Processed {} tasks:\n{}", processed_count, results.join("\n"))
         } else {
-            "No tasks were successfully processed".to_string()
+            // Keep the reasons - e.g. every requested task may have been rejected before it started
+            format!("No tasks were successfully processed:\n{}", results.join("\n"))
         };
+        if let Some(output) = &run.dry_run_output {
+            final_result = format!("Dry run - nothing was sent. Report: {}\n{}", output.join(DRY_RUN_REPORT_FILE).display(), final_result);
+        }
+        
+        if cancel.is_cancelled() {
+            let reason = format!("Cancelled: {}", cancel.reason().unwrap_or_default());
+            log::info!("Workflow {} {}", run.workflow_id, reason.to_lowercase());
+            final_result = format!("{}\n{}", reason, final_result);
+            run.finish(WorkflowStatus::Cancelled, Some(reason));
+        } else if processed_count > 0 {
+            run.finish(WorkflowStatus::Completed, None);
+        } else {
+            run.finish(WorkflowStatus::Failed, Some(final_result.clone()));
+        }
+        self.save_run(&run);
+        self.cancellations.unregister(&run.workflow_id);
+        self.publish_run_finished(&run);
         
         Ok(WorkflowTrigger {
             result: Some(final_result),
@@ -77,8 +433,126 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
         })
     }
     
+    /// Process one task of a run and record its result
+    #[tracing::instrument(name = "task", skip_all, fields(task_id = %task.id, workflow_id = scope.workflow_id, dry_run = dry_run))]
+    async fn run_task(&self, scope: RunScope<'_>, dry_run: bool, run: &Mutex<WorkflowRun>, task: &TasksResponse, cancel: &CancellationToken) -> TaskOutcome {
+        let mut task_result = TaskResult::new(TaskId::new(task.id.clone()));
+        
+        // Checked between tasks - a cancelled run never picks up another task
+        if let Err(e) = cancel.check() {
+            log::info!("Skipping task {}: {}", task.id, e);
+            task_result.skip(e.to_string());
+            self.record_task(run, &task_result);
+            self.publish_task_finished(scope, &task_result);
+            return TaskOutcome {
+                success: false,
+                summary: format!("⏭️ Task {}: not started", task.id),
+                dry_run_report: None,
+            };
+        }
+        
+        log::info!("Processing task: {} - {}", task.id, task.subject);
+        task_result.start();
+        self.record_task(run, &task_result);
+        self.events.publish(WorkflowEvent::run(scope.workflow_id, scope.requested_by, WorkflowEventKind::TaskStarted {
+            task_id: task_result.task_id.clone(),
+        }));
+        
+        let mut dry_run_report = dry_run.then(|| DryRunTaskReport::new(task_result.task_id.clone()));
+        let outcome = match dry_run_report.as_mut() {
+            Some(report) => self.dry_run_single_task(scope, task, &mut task_result, report, cancel).await,
+            None => self.process_single_task(scope, task, &mut task_result, cancel).await,
+        };
+        
+        let summary = match &outcome {
+            Ok(result) => {
+                log::info!("Successfully processed task {}: {}", task.id, result);
+                task_result.succeed();
+                format!("✅ Task {}: {}", task.id, result)
+            }
+            Err(e @ LennardError::Cancelled(_)) => {
+                log::info!("Aborted task {}: {}", task.id, e);
+                // A dry run changed nothing that needs to be undone
+                if !dry_run {
+                    self.clean_up_aborted_task(&task_result).await;
+                }
+                task_result.abort(e.to_string());
+                format!("🛑 Task {}: aborted", task.id)
+            }
+            Err(e) => {
+                log::error!("Failed to process task {}: {}", task.id, e);
+                task_result.fail(e.to_string());
+                
+                // Send error notification and update task status - a dry run leaves both untouched
+                if !dry_run {
+                    if let Err(notification_err) = self.handle_task_error(task, e, task_result.company_name.as_deref()).await {
+                        log::error!("Failed to send error notification: {}", notification_err);
+                    }
+                }
+                format!("❌ Task {}: {}", task.id, e)
+            }
+        };
+        
+        if let Some(report) = dry_run_report.as_mut() {
+            report.success = outcome.is_ok();
+            report.error_message = outcome.as_ref().err().map(|e| e.to_string());
+        }
+        self.record_task(run, &task_result);
+        self.publish_task_finished(scope, &task_result);
+        
+        TaskOutcome {
+            success: outcome.is_ok(),
+            summary,
+            dry_run_report,
+        }
+    }
+    
+    /// Leave an aborted task as if it had never been picked up
+    /// Withdraws its approval and resets the Zoho status of a claimed task - failures are logged
+    async fn clean_up_aborted_task(&self, task_result: &TaskResult) {
+        let task_id = &task_result.task_id;
+        
+        if let Some(approval_id) = &task_result.approval_id {
+            if let Err(e) = self.steps.cancel_approval(approval_id).await {
+                log::error!("Failed to withdraw approval {} of aborted task {}: {}", approval_id, task_id, e);
+            }
+            // A later resume must create a fresh approval instead of reusing the withdrawn one
+            if let Err(e) = self.checkpoints.clear_from(task_id, WorkflowStep::Approval) {
+                log::warn!("Failed to clear approval checkpoint of task {}: {}", task_id, e);
+            }
+        }
+        
+        // A task aborted before it was marked "In Progress" keeps whatever status it had
+        if !task_result.claimed {
+            return;
+        }
+        if let Err(e) = self.steps.reset_task_status(task_id.as_str()).await {
+            log::error!("Failed to reset Zoho status of aborted task {}: {}", task_id, e);
+        }
+    }
+    
+    fn publish_task_finished(&self, scope: RunScope<'_>, task_result: &TaskResult) {
+        self.events.publish(WorkflowEvent::run(scope.workflow_id, scope.requested_by, WorkflowEventKind::TaskFinished {
+            result: task_result.clone(),
+        }));
+    }
+    
+    /// Record a task result on the shared run and persist it
+    fn record_task(&self, run: &Mutex<WorkflowRun>, task_result: &TaskResult) {
+        let mut run = run.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        run.record_task(task_result.clone());
+        self.save_run(&run);
+    }
+    
+    /// Persist the dry run report - failures are logged but never abort processing
+    fn save_dry_run_report(&self, report: &DryRunReport) {
+        if let Err(e) = self.run_store.save_dry_run_report(report) {
+            log::error!("Failed to write dry run report for {}: {}", report.workflow_id, e);
+        }
+    }
+    
     /// Handle task error by sending notifications and updating status
-    async fn handle_task_error(&self, task: &zoho_generated_types::TasksResponse, error: &LennardError, company_name: Option<&str>) -> Result<()> {
+    async fn handle_task_error(&self, task: &TasksResponse, error: &LennardError, company_name: Option<&str>) -> Result<()> {
         // Extract contact name from task or use default
         let contact_name = task.who_id.as_ref()
             .and_then(|who| who.name.as_deref())
@@ -108,56 +582,233 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
     }
     
     /// Process a single task through the complete 7-step workflow
-    async fn process_single_task(&self, task: &zoho_generated_types::TasksResponse) -> Result<String> {
+    /// Errors are reported to Telegram and Zoho by the caller
+    async fn process_single_task(&self, scope: RunScope<'_>, task: &TasksResponse, task_result: &mut TaskResult, cancel: &CancellationToken) -> Result<String> {
         log::info!("Starting 7-step workflow for task: {}", task.id);
 
         // CRITICAL: Mark task as "In Progress" IMMEDIATELY to prevent duplicate execution
         // This must happen before any long-running operations (dossier, letter, PDF generation)
-        self.steps.mark_task_in_progress(&task.id).await
-            .map_err(|e| LennardError::Workflow(format!("Failed to mark task as in progress: {}", e)))?;
+        self.call_step(task_result, "mark_task_in_progress", cancel, || self.steps.mark_task_in_progress(&task.id)).await
+            .map_err(|e| step_error("Marking task as in progress", e))?;
+        task_result.claimed = true;
 
-        // Step 1: Load contact - requires task, guaranteed to return contact
-        let mut contact = match self.steps.load_contact(task).await {
-            Ok(c) => c,
+        // Steps 1-4.5: contact, profile, dossiers, address, letter and PDF
+        let prepared = self.prepare_letter(scope, task, task_result, false, cancel).await?;
+        let task_id = task_result.task_id.clone();
+        
+        // Step 5a: Start approval - creates and persists the approval request
+        // Checkpointed so a retry after a failed step 5b does not create a second approval
+        let approval_id = self.run_step(scope, task_result, WorkflowStep::Approval, true, cancel, || {
+            self.steps.approval_start(&task.id, &prepared.contact, &prepared.rendered, &prepared.dossier)
+        }).await
+            .map_err(|e| step_error("Step 5a (approval start)", e))?;
+        
+        log::info!("Step 5a: Created approval with ID: {}", approval_id);
+        task_result.approval_id = Some(approval_id.clone());
+        
+        // Step 5b: Request approval - sends the notification for the persisted approval
+        // Last chance to cancel before the letter goes out for review - the approval is then withdrawn by the caller
+        let approval_state = self.call_step(task_result, "request_approval", cancel, || {
+            self.steps.request_approval(&approval_id, &prepared.rendered.letter, &prepared.contact)
+        }).await
+            .map_err(|e| step_error("Step 5b (request approval)", e))?;
+        
+        log::info!("Step 5b: Approval status: {:?}", approval_state);
+        
+        // The approval now holds everything needed to continue - checkpoints are no longer required
+        if let Err(e) = self.checkpoints.clear(&task_id) {
+            log::warn!("Failed to clear checkpoints of task {}: {}", task_id, e);
+        }
+        
+        // IMPORTANT: Workflow STOPS here and waits for user approval
+        // The workflow will be continued via continue_after_approval() when the user approves
+        // We should NEVER immediately proceed to Step 6 here
+        
+        Ok("Awaiting user response via Telegram".to_string())
+    }
+    
+    /// Dry run a single task - runs steps 1-4.5 and records what a real run would have done
+    async fn dry_run_single_task(
+        &self,
+        scope: RunScope<'_>,
+        task: &TasksResponse,
+        task_result: &mut TaskResult,
+        report: &mut DryRunTaskReport,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        log::info!("Starting dry run for task: {}", task.id);
+        report.skipped_actions.push("Mark task as 'In Progress' in Zoho".to_string());
+        
+        let prepared = self.prepare_letter(scope, task, task_result, true, cancel).await;
+        report.contact_name = task_result.contact_name.clone();
+        report.company_name = task_result.company_name.clone();
+        let prepared = prepared?;
+        
+        if let Some(address) = &prepared.address_update {
+            report.skipped_actions.push(format!(
+                "Write extracted mailing address to Zoho contact {}", prepared.contact.id
+            ));
+            report.address_update = Some(address.clone());
+        }
+        
+        let pdf_file = self.run_store.save_dry_run_pdf(scope.workflow_id, &task_result.task_id, &prepared.rendered.pdf)?;
+        report.pdf_size_bytes = Some(prepared.rendered.pdf.len());
+        report.letter = Some(prepared.rendered.letter);
+        report.skipped_actions.push("Create approval and send it to Telegram for review".to_string());
+        
+        log::info!("Dry run: wrote {} for task {}", pdf_file, task.id);
+        let result = format!("Dry run - letter and PDF generated ({})", pdf_file);
+        report.pdf_file = Some(pdf_file);
+        
+        Ok(result)
+    }
+    
+    /// Call a `WorkflowSteps` method under its retry policy, recording retries on the task result
+    /// Fails with `LennardError::Cancelled` before any attempt if the run was cancelled
+    #[tracing::instrument(name = "workflow_step", skip_all, fields(
+        step = method,
+        task_id = %task_result.task_id,
+        contact_id = task_result.contact_id.as_ref().map(|id| id.as_str()),
+        approval_id = task_result.approval_id.as_ref().map(|id| id.as_str()),
+    ))]
+    async fn call_step<V, F, Fut>(&self, task_result: &mut TaskResult, method: &str, cancel: &CancellationToken, mut call: F) -> Result<V>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        let retries = &mut task_result.retries;
+        retry::retry_reserved(
+            method,
+            self.retry_policies.for_method(method),
+            || self.steps.reserve_capacity(method),
+            || {
+                let cancelled = cancel.check();
+                let attempt = call();
+                async move {
+                    cancelled?;
+                    attempt.await
+                }
+            },
+            |failed| retries.push(failed),
+        ).await
+    }
+    
+    /// Run a workflow step, or restore its output from the task's checkpoint if the step already completed
+    async fn run_step<V, F, Fut>(&self, scope: RunScope<'_>, task_result: &mut TaskResult, step: WorkflowStep, use_checkpoints: bool, cancel: &CancellationToken, run: F) -> Result<V>
+    where
+        V: Serialize + DeserializeOwned,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        cancel.check()?;
+        let task_id = task_result.task_id.clone();
+        
+        if use_checkpoints {
+            match self.checkpoints.load(&task_id, step) {
+                Ok(Some(value)) => {
+                    log::info!("Task {}: restored step '{}' from checkpoint", task_id, step);
+                    return Ok(value);
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to read '{}' checkpoint of task {}: {}", step, task_id, e),
+            }
+        }
+        
+        let started = std::time::Instant::now();
+        let value = match self.call_step(task_result, step.method(), cancel, run).await {
+            Ok(value) => {
+                instrumentation::observe_step(step.as_str(), started.elapsed(), true);
+                value
+            }
             Err(e) => {
-                let error = LennardError::Workflow(format!("Step 1 (load contact) failed: {}", e));
-                // Send error notification with task-level info (no company name available yet)
-                self.handle_task_error(task, &error, None).await?;
-                return Err(error);
+                if !matches!(e, LennardError::Cancelled(_)) {
+                    instrumentation::observe_step(step.as_str(), started.elapsed(), false);
+                    task_result.failed_step = Some(step.to_string());
+                    self.events.publish(WorkflowEvent::run(scope.workflow_id, scope.requested_by, WorkflowEventKind::StepFailed {
+                        task_id,
+                        step: step.to_string(),
+                        error: e.to_string(),
+                    }));
+                }
+                return Err(e);
             }
         };
+        self.events.publish(WorkflowEvent::run(scope.workflow_id, scope.requested_by, WorkflowEventKind::StepCompleted {
+            task_id: task_id.clone(),
+            step: step.to_string(),
+        }));
+        
+        if use_checkpoints {
+            self.save_checkpoint(&task_id, step, &value);
+        }
+        Ok(value)
+    }
+    
+    /// Persist a step checkpoint - failures are logged but never abort processing
+    fn save_checkpoint<V: Serialize>(&self, task_id: &TaskId, step: WorkflowStep, value: &V) {
+        if let Err(e) = self.checkpoints.save(task_id, step, value) {
+            log::error!("Failed to save '{}' checkpoint of task {}: {}", step, task_id, e);
+        }
+    }
+    
+    /// Steps 1-4.5: everything up to the rendered PDF
+    /// Only writes the extracted address back to Zoho, and only when not in a dry run.
+    /// Completed steps are restored from checkpoints - dry runs neither read nor write them.
+    async fn prepare_letter(
+        &self,
+        scope: RunScope<'_>,
+        task: &TasksResponse,
+        task_result: &mut TaskResult,
+        dry_run: bool,
+        cancel: &CancellationToken,
+    ) -> Result<PreparedLetter> {
+        let task_id = task_result.task_id.clone();
+        let use_checkpoints = !dry_run;
+        
+        // Step 1: Load contact - requires task, guaranteed to return contact
+        let mut contact = self.run_step(scope, task_result, WorkflowStep::Contact, use_checkpoints, cancel, || self.steps.load_contact(task)).await
+            .map_err(|e| step_error("Step 1 (load contact)", e))?;
         
         log::info!("Step 1: Loaded contact '{}'", contact.full_name);
+        task_result.contact_id = Some(ContactId::new(contact.id.clone()));
+        task_result.contact_name = Some(contact.full_name.clone());
         
         // Step 2: Load profile - requires contact, guaranteed to return profile
-        let profile = self.steps.load_profile(&contact).await
-            .map_err(|e| LennardError::Workflow(format!("Step 2 (load profile) failed: {}", e)))?;
+        let profile = self.run_step(scope, task_result, WorkflowStep::Profile, use_checkpoints, cancel, || self.steps.load_profile(&contact)).await
+            .map_err(|e| step_error("Step 2 (load profile)", e))?;
         
         log::info!("Step 2: Loaded LinkedIn profile for '{}'", profile.full_name);
         
         // Step 3: Generate dossiers - requires profile and contact, returns extracted data
-        let dossier_result = self.steps.generate_dossiers(&profile, &contact.id).await
-            .map_err(|e| LennardError::Workflow(format!("Step 3 (generate dossiers) failed: {}", e)))?;
+        let dossier = self.run_step(scope, task_result, WorkflowStep::Dossiers, use_checkpoints, cancel, || self.steps.generate_dossiers(&profile, &contact.id)).await
+            .map_err(|e| step_error("Step 3 (generate dossiers)", e))?;
         
         // Use extracted company name (no fallback to task.what_id)
-        let company_name = if !dossier_result.company_name.is_empty() {
-            dossier_result.company_name.clone()
+        let company_name = if !dossier.company_name.is_empty() {
+            dossier.company_name.clone()
         } else {
             "Unknown Company".to_string()
         };
         
         log::info!("Step 3: Generated dossiers with company: {}", company_name);
+        task_result.company_name = Some(company_name);
         
         // Step 3.5: Update contact with extracted address if missing
+        let mut address_update = None;
         if contact.mailing_address.is_none() {
-            if let Some(address) = dossier_result.mailing_address.clone() {
+            if let Some(address) = dossier.mailing_address.clone() {
                 // Double-check the address is valid before using it
                 if address.is_valid() {
                     log::info!("Step 3.5: Extracted valid mailing address for {}", contact.full_name);
                     // Update the local contact object with the extracted address
                     contact.mailing_address = Some(address.clone());
                     // Also update Zoho contact with address for persistence
-                    self.steps.update_contact_address(&contact.id, &address).await?;
+                    if !dry_run {
+                        self.call_step(task_result, "update_contact_address", cancel, || self.steps.update_contact_address(&contact.id, &address)).await?;
+                        // A resumed task must not write the address again
+                        self.save_checkpoint(&task_id, WorkflowStep::Contact, &contact);
+                    }
+                    address_update = Some(address);
                 } else {
                     return Err(LennardError::Workflow(format!(
                         "Extracted address for {} is invalid (empty fields). Cannot proceed without valid recipient address.",
@@ -183,71 +834,28 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
         }
         
         // Step 4: Generate letter - requires contact, profile and dossier, guaranteed letter
-        let letter = self.steps.generate_letter(&contact, &profile, &dossier_result).await
-            .map_err(|e| LennardError::Workflow(format!("Step 4 (generate letter) failed: {}", e)))?;
+        let letter = self.run_step(scope, task_result, WorkflowStep::Letter, use_checkpoints, cancel, || self.steps.generate_letter(&contact, &profile, &dossier)).await
+            .map_err(|e| step_error("Step 4 (generate letter)", e))?;
         
         log::info!("Step 4: Generated letter with subject '{}'", letter.subject);
         
-        // Step 5a: Start approval - creates and persists the approval request
-        let approval_id = match self.steps.approval_start(&task.id, &contact, &profile, &letter, &dossier_result).await {
-            Ok(id) => id,
-            Err(e) => {
-                let error = LennardError::Workflow(format!("Step 5a (approval start) failed: {}", e));
-                // Send error notification with full context using extracted company name
-                if let Err(notify_err) = self.steps.send_error_notification(
-                    &task.id,
-                    &contact.full_name,
-                    &company_name,
-                    &error.to_string()
-                ).await {
-                    log::error!("Failed to send error notification: {}", notify_err);
-                }
-                
-                // Update task status
-                if let Err(status_err) = self.steps.update_task_error_status(&task.id, &error.to_string()).await {
-                    log::error!("Failed to update task status: {}", status_err);
-                }
-                
-                return Err(error);
-            }
-        };
-        
-        log::info!("Step 5a: Created approval with ID: {}", approval_id);
-        
-        // Step 5b: Request approval - sends the notification for the persisted approval
-        let approval_state = match self.steps.request_approval(&approval_id, &letter, &contact).await {
-            Ok(a) => a,
-            Err(e) => {
-                let error = LennardError::Workflow(format!("Step 5b (request approval) failed: {}", e));
-                // Send error notification with full context using extracted company name
-                if let Err(notify_err) = self.steps.send_error_notification(
-                    &task.id,
-                    &contact.full_name,
-                    &company_name,
-                    &error.to_string()
-                ).await {
-                    log::error!("Failed to send error notification: {}", notify_err);
-                }
-                
-                // Update task status
-                if let Err(status_err) = self.steps.update_task_error_status(&task.id, &error.to_string()).await {
-                    log::error!("Failed to update task status: {}", status_err);
-                }
-                
-                return Err(error);
-            }
-        };
-        
-        log::info!("Step 5b: Approval status: {:?}", approval_state);
+        // Step 4.5: Render PDF - the letter is shortened automatically if it exceeds the page limit
+        let rendered = self.run_step(scope, task_result, WorkflowStep::Pdf, use_checkpoints, cancel, || self.steps.render_letter_pdf(&contact, &profile, &dossier, &letter)).await
+            .map_err(|e| step_error("Step 4.5 (render PDF)", e))?;
         
-        // IMPORTANT: Workflow STOPS here and waits for user approval
-        // The workflow will be continued via continue_after_approval() when the user approves
-        // We should NEVER immediately proceed to Step 6 here
+        log::info!("Step 4.5: Rendered PDF, {} bytes", rendered.pdf.len());
         
-        Ok("Awaiting user response via Telegram".to_string())
+        Ok(PreparedLetter {
+            contact,
+            dossier,
+            rendered,
+            address_update,
+        })
     }
     
     /// Process improvement request - generate an improved letter based on feedback
+    /// The improved approval is returned pending - the caller persists it before `request_review`
+    #[tracing::instrument(skip_all, fields(approval_id = %approval_data.approval_id, task_id = %approval_data.task_id, contact_id = %approval_data.contact_id))]
     pub async fn process_improvement_request(
         &self, 
         approval_data: &super::approval_types::ApprovalData,
@@ -255,7 +863,6 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
     ) -> Result<super::approval_types::ApprovalData> {
         use crate::workflow::approval_types::{ApprovalState, LetterHistoryEntry};
         use chrono::Utc;
-        use base64::Engine;
         
         log::info!("Processing improvement request for approval {}", approval_data.approval_id);
         log::info!("Feedback: {}", feedback);
@@ -271,17 +878,7 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
         
         log::info!("Generated improved letter for approval {}", approval_data.approval_id);
         
-        // Add current letter to history before updating
-        let history_entry = LetterHistoryEntry {
-            iteration: improved_approval.letter_history.len() as u32 + 1,
-            content: approval_data.current_letter.clone(),
-            feedback: approval_data.letter_history.last()
-                .and_then(|e| e.feedback.clone()),
-            created_at: Utc::now(),
-        };
-        improved_approval.letter_history.push(history_entry);
-        
-        // Update with improved letter
+        // Update with improved letter - the feedback is already recorded on the current iteration
         improved_approval.current_letter = improved_letter;
         improved_approval.state = ApprovalState::PendingApproval;
         improved_approval.updated_at = Utc::now();
@@ -338,6 +935,7 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
 
                     log::info!("Regenerating improved letter with automatic feedback (attempt {}/{}): {}",
                               attempt + 1, max_retries, auto_feedback);
+                    instrumentation::record_pdf_page_limit_retry();
 
                     // Regenerate the improved letter with combined feedback (user + automatic length reduction)
                     match self.steps.generate_improved_letter(
@@ -372,27 +970,68 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
 
         log::info!("Final improved PDF generated successfully, {} bytes", pdf_bytes.len());
 
-        // Update the current letter with the final version (may have been regenerated for length)
+        // Record the final version (may have been regenerated for length) as a new iteration
+        improved_approval.letter_history.push(LetterHistoryEntry {
+            iteration: improved_approval.current_iteration() + 1,
+            content: current_letter.clone(),
+            feedback: None,
+            created_at: Utc::now(),
+            pdf: None,
+            edited_by: None,
+        });
         improved_approval.current_letter = current_letter;
-        improved_approval.pdf_base64 = Some(base64::engine::general_purpose::STANDARD.encode(&pdf_bytes));
-        
-        // Send the improved letter to Telegram for re-approval
-        log::info!("Sending improved letter to Telegram for approval {}", improved_approval.approval_id);
-        self.steps.send_improved_approval_to_telegram(&improved_approval).await?;
-        
-        // Set state to awaiting response since we sent to Telegram
-        improved_approval.state = ApprovalState::AwaitingUserResponse;
+        improved_approval.set_current_pdf(Some(self.blobs.put(&pdf_bytes)?));
+
+        Ok(improved_approval)
+    }
+
+    /// Send a persisted, pending new iteration of an approval to Telegram for re-approval
+    /// The caller marks it as awaiting a response once this succeeded
+    #[tracing::instrument(skip_all, fields(approval_id = %approval_data.approval_id, task_id = %approval_data.task_id, contact_id = %approval_data.contact_id))]
+    pub async fn request_review(&self, approval_data: &super::approval_types::ApprovalData) -> Result<()> {
+        log::info!("Sending iteration {} to Telegram for approval {}", approval_data.current_iteration(), approval_data.approval_id);
+        self.steps.send_improved_approval_to_telegram(approval_data).await?;
         
         // Log that the approval update is ready
         self.steps.request_approval_update(
-            &improved_approval.approval_id.to_string(),
-            improved_approval.letter_history.len()
-        ).await?;
+            &approval_data.approval_id.to_string(),
+            approval_data.letter_history.len()
+        ).await
+    }
 
-        Ok(improved_approval)
+    /// Apply a letter edited by the reviewer - rendered as a new iteration without the LLM
+    ///
+    /// The edited letter is returned pending - the caller persists it, then sends it to Telegram
+    /// with `request_review` - or approved right away with `approve`. Text that exceeds the page
+    /// limit is rejected rather than shortened.
+    #[tracing::instrument(skip_all, fields(approval_id = %approval_data.approval_id, task_id = %approval_data.task_id, contact_id = %approval_data.contact_id))]
+    pub async fn revise_letter(
+        &self,
+        approval_data: &super::approval_types::ApprovalData,
+        revised_text: &str,
+        revised_by: UserId,
+        approve: bool,
+    ) -> Result<super::approval_types::ApprovalData> {
+        log::info!("Applying revision by user {} to approval {}", revised_by.value(), approval_data.approval_id);
+        
+        let letter = approval_data.current_letter.with_plain_text(revised_text)?;
+        let mailing_address = approval_data.mailing_address.as_ref()
+            .ok_or_else(|| LennardError::Workflow("Missing mailing address in approval data".to_string()))?;
+        let pdf_bytes = self.steps.generate_pdf_with_address(&letter, mailing_address).await?;
+        
+        let mut revised = approval_data.clone();
+        revised.add_edited_letter(letter, revised_by);
+        revised.set_current_pdf(Some(self.blobs.put(&pdf_bytes)?));
+        
+        if approve {
+            revised.mark_approved();
+        }
+        
+        Ok(revised)
     }
 
     /// Handle workflow rejection - update Zoho task and send notification
+    #[tracing::instrument(skip_all, fields(task_id = task_id))]
     pub async fn handle_rejection(
         &self,
         task_id: &str,
@@ -427,12 +1066,96 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
         Ok(())
     }
 
+    /// Remind the chat of an approval that is still waiting for a response
+    #[tracing::instrument(skip_all, fields(approval_id = %approval_data.approval_id, task_id = %approval_data.task_id, contact_id = %approval_data.contact_id))]
+    pub async fn remind_approval(&self, approval_data: &super::approval_types::ApprovalData) -> Result<()> {
+        log::info!("Reminding about approval {} for task {}", approval_data.approval_id, approval_data.task_id);
+        self.steps.send_approval_reminder(approval_data).await
+    }
+    
+    /// Reset the Zoho task of an expiring approval so it is picked up again
+    /// A permanent failure is reported to the chat - a transient one is left to the caller to retry
+    #[tracing::instrument(skip_all, fields(approval_id = %approval_data.approval_id, task_id = %approval_data.task_id, contact_id = %approval_data.contact_id))]
+    pub async fn reset_expired_task(&self, approval_data: &super::approval_types::ApprovalData) -> Result<()> {
+        log::info!("Resetting task {} of expiring approval {}", approval_data.task_id, approval_data.approval_id);
+        
+        if let Err(e) = self.steps.reset_task_status(approval_data.task_id.as_str()).await {
+            if e.is_retryable() {
+                return Err(e);
+            }
+            log::error!("Approval {} expired but task {} could not be reset: {}", approval_data.approval_id, approval_data.task_id, e);
+            
+            if let Err(notify_error) = self.steps.send_error_notification(
+                approval_data.task_id.as_str(),
+                &approval_data.recipient_name,
+                &approval_data.company_name,
+                &format!("Approval expired, but the Zoho task could not be reset to 'Not started': {}", e)
+            ).await {
+                log::error!("Failed to send error notification to Telegram: {}", notify_error);
+            }
+            return Err(e);
+        }
+        
+        Ok(())
+    }
+    
+    /// Tell the chat that an approval expired - failures are logged
+    pub async fn notify_expired_approval(&self, approval_data: &super::approval_types::ApprovalData) {
+        if let Err(e) = self.steps.send_approval_expired(approval_data).await {
+            log::error!("Failed to send expiry notification to Telegram: {}", e);
+        }
+    }
+    
     /// Continue workflow after approval - complete Step 6 (send PDF via LetterExpress)
+    /// 
+    /// If the approval already carries a LetterExpress job ID the letter is not sent again.
+    /// The Zoho updates that follow the sent letter go through the outbox: they are attempted
+    /// right away and retried by the OutboxWorker until they succeed.
+    #[tracing::instrument(skip_all, fields(approval_id = %approval_data.approval_id, task_id = %approval_data.task_id, contact_id = %approval_data.contact_id))]
     pub async fn continue_after_approval(&self, approval_data: &super::approval_types::ApprovalData) -> Result<String> {
-        use base64::{Engine as _, engine::general_purpose};
-        
         log::info!("Continuing workflow after approval for task: {}", approval_data.task_id);
         
+        // An outbox entry is the durable record that the letter of this approval went out
+        if let Some(entry) = self.outbox.get(&approval_data.approval_id)? {
+            log::warn!("Letter for approval {} was already sent (tracking: {}) - not sending it again",
+                       approval_data.approval_id, entry.tracking_id);
+            return Ok(format!("Letter already sent, tracking: {}", entry.tracking_id));
+        }
+        
+        let tracking_id = match &approval_data.letterexpress_job_id {
+            Some(job_id) => {
+                log::info!("Letter for approval {} was already accepted by LetterExpress (job {})",
+                           approval_data.approval_id, job_id);
+                job_id.clone()
+            }
+            None => self.send_approved_letter(approval_data).await?,
+        };
+        
+        let pdf = approval_data.pdf.clone()
+            .ok_or_else(|| LennardError::Workflow("Approval missing PDF data".to_string()))?;
+
+        // Record the side effects before attempting any of them: note, PDF attachment,
+        // task status "Done" and the follow-up task
+        let entry = OutboxEntry::for_sent_letter(approval_data, &tracking_id, pdf);
+        if let Err(e) = self.outbox.save(&entry) {
+            log::error!("Letter for approval {} was sent (tracking: {}) but its Zoho updates could not be queued: {}",
+                        approval_data.approval_id, tracking_id, e);
+            return Err(LennardError::Workflow(format!(
+                "Letter was sent (tracking: {}) but the outbox entry could not be written: {}", tracking_id, e
+            )));
+        }
+        
+        self.deliver_outbox_entry(entry).await;
+
+        Ok(format!("Letter sent successfully after approval, tracking: {}", tracking_id))
+    }
+    
+    /// Step 6: Send the approved PDF via LetterExpress - returns the job ID
+    /// 
+    /// The print job carries the approval's idempotency key, so `find_sent_letter` can tell
+    /// whether it went out if we crash before the job ID is recorded.
+    #[tracing::instrument(skip_all, fields(approval_id = %approval_data.approval_id, task_id = %approval_data.task_id, contact_id = %approval_data.contact_id))]
+    pub async fn send_approved_letter(&self, approval_data: &super::approval_types::ApprovalData) -> Result<String> {
         // The approval contains everything we need:
         // - The approved letter content
         // - The mailing address 
@@ -442,411 +1165,1009 @@ impl<T: WorkflowSteps> WorkflowOrchestrator<T> {
         let mailing_address = approval_data.mailing_address.as_ref()
             .ok_or_else(|| LennardError::Workflow("Approval missing mailing address".to_string()))?;
         
-        let pdf_base64 = approval_data.pdf_base64.as_ref()
+        // Read the approved PDF - we will use THIS PDF (not regenerate it)
+        let pdf_data = approval_data.pdf_bytes(&self.blobs)?
             .ok_or_else(|| LennardError::Workflow("Approval missing PDF data".to_string()))?;
-        
-        // Decode the approved PDF - we will use THIS PDF (not regenerate it)
-        let pdf_data = general_purpose::STANDARD.decode(pdf_base64)
-            .map_err(|e| LennardError::Workflow(format!("Failed to decode PDF: {}", e)))?;
 
-        // Step 6: Send approved PDF via LetterExpress using the binary method
         // IMPORTANT: We use the EXACT PDF that was approved, not a regenerated one
         // This prevents page limit violations if the regenerated PDF differs from approved
         log::info!("Sending approved PDF via LetterExpress (NOT regenerating)");
 
-        let tracking_id = self.steps.send_pdf_binary(pdf_data, mailing_address).await
-            .map_err(|e| LennardError::Workflow(format!("Step 6 (send approved PDF) failed: {}", e)))?;
+        // Errors keep their kind - an ambiguous failure must not be taken for a rejection
+        let tracking_id = self.steps.send_pdf_binary(pdf_data, mailing_address, &approval_data.send_key()).await
+            .inspect_err(|e| log::error!("Step 6 (send approved PDF) failed: {}", e))?;
 
         log::info!("Step 6: Letter sent successfully after approval, tracking: {}", tracking_id);
-
-        // Store letter content in Zoho Notes for audit trail
-        if let Err(e) = self.steps.store_letter_content(
-            &approval_data.contact_id.to_string(),
-            &approval_data.company_name,
-            &approval_data.current_letter,
-            &tracking_id
-        ).await {
-            log::error!("Failed to store letter content in Zoho: {}", e);
-            // Don't fail the whole workflow if note creation fails
-        } else {
-            log::info!("Letter content stored in Zoho contact notes");
+        instrumentation::record_letter_sent(Utc::now());
+        self.events.publish(WorkflowEvent::approval(approval_data, WorkflowEventKind::LetterSent {
+            approval_id: approval_data.approval_id.clone(),
+            task_id: approval_data.task_id.clone(),
+            job_id: tracking_id.clone(),
+        }));
+        Ok(tracking_id)
+    }
+    
+    /// Job ID of the letter LetterExpress accepted for this approval, if any
+    pub async fn find_sent_letter(&self, approval_data: &super::approval_types::ApprovalData) -> Result<Option<String>> {
+        let idempotency_key = approval_data.idempotency_key.as_ref()
+            .ok_or_else(|| LennardError::Workflow(format!(
+                "Approval {} has no idempotency key - cannot look up its print job", approval_data.approval_id
+            )))?;
+        self.steps.find_sent_letter(idempotency_key).await
+    }
+    
+    /// Deliver the due side effects of all pending outbox entries
+    /// Returns the number of entries that were attempted
+    pub async fn process_outbox(&self) -> Result<usize> {
+        let now = Utc::now();
+        let due: Vec<OutboxEntry> = self.outbox.list_pending()?
+            .into_iter()
+            .filter(|entry| entry.is_due(now))
+            .collect();
+        
+        let count = due.len();
+        for entry in due {
+            self.deliver_outbox_entry(entry).await;
         }
-
-        // Update task status in Zoho to mark as completed and attach the letter
-        let task_id = approval_data.task_id.to_string();
-        let success_message = format!("Brief erfolgreich versendet. Tracking: {}", tracking_id);
         
-        // First, attach the PDF to the task
-        let pdf_data = general_purpose::STANDARD.decode(pdf_base64)
-            .map_err(|e| LennardError::Workflow(format!("Failed to decode PDF for attachment: {}", e)))?;
+        Ok(count)
+    }
+    
+    /// Outbox entries with side effects that were given up
+    pub fn failed_outbox_entries(&self) -> Result<Vec<OutboxEntry>> {
+        self.outbox.list_failed()
+    }
+    
+    /// Attempt the due side effects of an outbox entry and persist the outcome
+    /// Once a side effect is given up, the entry is reported via Telegram
+    #[tracing::instrument(skip_all, fields(approval_id = %entry.approval_id, task_id = %entry.task_id))]
+    async fn deliver_outbox_entry(&self, entry: OutboxEntry) {
+        let _guard = self.outbox_lock.lock().await;
+        
+        // Another delivery may have finished the entry in the meantime
+        let mut entry = match self.outbox.get(&entry.approval_id) {
+            Ok(Some(current)) if current.is_pending() => current,
+            Ok(_) => return,
+            Err(e) => {
+                log::error!("Failed to read outbox entry of approval {}: {}", entry.approval_id, e);
+                return;
+            }
+        };
         
-        let pdf_filename = format!("Brief_{}.pdf", approval_data.contact_id);
-        if let Err(e) = self.steps.attach_file_to_task(&task_id, pdf_data, &pdf_filename).await {
-            log::error!("Failed to attach PDF to Zoho task: {}", e);
-            // Don't fail the whole workflow if attachment fails
-        } else {
-            log::info!("Attached PDF letter to Zoho task {}", task_id);
+        let now = Utc::now();
+        for index in 0..entry.items.len() {
+            if !entry.items[index].is_due(now) {
+                continue;
+            }
+            
+            let effect = entry.items[index].effect.clone();
+            match self.apply_side_effect(&entry, &effect).await {
+                Ok(()) => {
+                    log::info!("Outbox: {} done for approval {}", effect.name(), entry.approval_id);
+                    entry.items[index].succeed();
+                }
+                Err(e) => {
+                    let item = &mut entry.items[index];
+                    item.fail(&e);
+                    if item.status == OutboxItemStatus::Failed {
+                        log::error!("Outbox: giving up {} for approval {} after {} attempts: {}",
+                                    effect.name(), entry.approval_id, item.attempts, e);
+                    } else {
+                        log::warn!("Outbox: {} failed for approval {} (attempt {}), retrying at {}: {}",
+                                   effect.name(), entry.approval_id, item.attempts, item.next_attempt_at, e);
+                    }
+                }
+            }
         }
+        entry.updated_at = Utc::now();
         
-        // Then update the status to completed
-        if let Err(e) = self.steps.update_task_completed_status(&task_id, &success_message).await {
-            log::error!("Failed to update Zoho task status to completed: {}", e);
-            // Don't fail the whole workflow if status update fails
-        } else {
-            log::info!("Updated Zoho task {} status to 'Done'", task_id);
+        if let Err(e) = self.outbox.save(&entry) {
+            log::error!("Failed to save outbox entry of approval {}: {}", entry.approval_id, e);
+            return;
         }
-
-        // Create follow-up task for the contact
-        match self.steps.create_follow_up_task(approval_data.contact_id.as_str(), &task_id).await {
-            Ok(follow_up_task_id) => {
-                log::info!("Created follow-up task {} for contact {}", follow_up_task_id, approval_data.contact_id);
+        
+        if !entry.is_pending() && entry.has_failed() {
+            self.report_failed_outbox_entry(&entry).await;
+        }
+    }
+    
+    /// Execute a single side effect
+    async fn apply_side_effect(&self, entry: &OutboxEntry, effect: &SideEffect) -> Result<()> {
+        match effect {
+            SideEffect::StoreLetterContent { contact_id, company_name, letter } => {
+                self.steps.store_letter_content(contact_id.as_str(), company_name, letter, &entry.tracking_id).await
             }
-            Err(e) => {
-                log::error!("Failed to create follow-up task for contact {}: {}", approval_data.contact_id, e);
-                // Don't fail the whole workflow if follow-up task creation fails
+            SideEffect::AttachPdf { task_id, filename } => {
+                let pdf_data = self.blobs.get(&entry.pdf)?;
+                self.steps.attach_file_to_task(task_id.as_str(), pdf_data, filename).await
+            }
+            SideEffect::MarkTaskCompleted { task_id, message } => {
+                self.steps.update_task_completed_status(task_id.as_str(), message).await
+            }
+            SideEffect::CreateFollowUpTask { contact_id, task_id } => {
+                let follow_up_task_id = self.steps.create_follow_up_task(contact_id.as_str(), task_id.as_str()).await?;
+                log::info!("Created follow-up task {} for contact {}", follow_up_task_id, contact_id);
+                Ok(())
             }
         }
-
-        Ok(format!("Letter sent successfully after approval, tracking: {}", tracking_id))
+    }
+    
+    /// Tell the user that Zoho is out of sync with a letter that was sent
+    async fn report_failed_outbox_entry(&self, entry: &OutboxEntry) {
+        let failed: Vec<String> = entry.items.iter()
+            .filter(|item| item.status == OutboxItemStatus::Failed)
+            .map(|item| format!("{} ({})", item.effect.name(), item.last_error.as_deref().unwrap_or("unknown error")))
+            .collect();
+        
+        let message = format!(
+            "Letter was sent (tracking: {}) but Zoho could not be updated: {}",
+            entry.tracking_id,
+            failed.join(", ")
+        );
+        
+        if let Err(e) = self.steps.send_error_notification(
+            entry.task_id.as_str(),
+            &entry.recipient_name,
+            &entry.company_name,
+            &message
+        ).await {
+            log::error!("Failed to send outbox failure notification: {}", e);
+        }
     }
 }
 
-// Tests temporarily disabled while migrating from ZohoTask to TasksResponse
-// TODO: Update tests to use generated TasksResponse type
-/*
 #[cfg(test)]
 mod tests {
     use super::*;
     use async_trait::async_trait;
-    use crate::types::*;
-    use crate::error::LennardError;
-    use chrono::Utc;
-    
+    use crate::types::LinkedInProfile;
+    use crate::workflow::approval_types::{ApprovalData, ApprovalId, ApprovalState, LetterContent, UserId};
+    use crate::workflow::run_types::TaskState;
+    use crate::workflow::outbox::OutboxItemStatus;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+    use tokio::sync::{Barrier, Notify};
+
+    /// Records every side effect so tests can assert what was (not) touched
     struct MockWorkflowSteps {
-        tasks_to_return: Vec<ZohoTask>,
-        should_fail_at_step: Option<&'static str>,
-        approval_state: ApprovalState,
+        tasks: Vec<TasksResponse>,
+        fail_at_step: Mutex<Option<&'static str>>,
+        /// Number of failures at `fail_at_step` before it succeeds - unlimited if unset
+        failures_left: Mutex<Option<usize>>,
+        /// Only this task fails (at load_contact and update_task_completed_status)
+        failing_task: Option<&'static str>,
+        calls: Mutex<Vec<String>>,
+        dossiers_in_flight: AtomicUsize,
+        max_dossiers_in_flight: AtomicUsize,
+        /// The first `n` dossier calls wait for each other - they only get through if they run at once
+        dossier_barrier: Option<(usize, Barrier)>,
+        dossier_calls: AtomicUsize,
+        /// This step signals `paused` and waits for `resume` before returning
+        pause_at: Option<&'static str>,
+        paused: Notify,
+        resume: Notify,
+        /// Idempotency keys of the letters "LetterExpress" accepted
+        sent_keys: Mutex<Vec<String>>,
     }
-    
+
     impl MockWorkflowSteps {
-        fn new_with_tasks(tasks: Vec<ZohoTask>) -> Self {
+        fn new(task_ids: &[&str]) -> Self {
+            let tasks = task_ids.iter()
+                .map(|id| serde_json::from_value(serde_json::json!({
+                    "id": id,
+                    "Subject": "Connect on LinkedIn",
+                    "Who_Id": { "id": format!("contact-{}", id), "name": "Mock Contact" }
+                })).unwrap())
+                .collect();
+
             Self {
-                tasks_to_return: tasks,
-                should_fail_at_step: None,
-                approval_state: ApprovalState::Approved,
+                tasks,
+                fail_at_step: Mutex::new(None),
+                failures_left: Mutex::new(None),
+                failing_task: None,
+                calls: Mutex::new(Vec::new()),
+                dossiers_in_flight: AtomicUsize::new(0),
+                max_dossiers_in_flight: AtomicUsize::new(0),
+                dossier_barrier: None,
+                dossier_calls: AtomicUsize::new(0),
+                pause_at: None,
+                paused: Notify::new(),
+                resume: Notify::new(),
+                sent_keys: Mutex::new(Vec::new()),
             }
         }
-        
-        fn new_empty() -> Self {
-            Self {
-                tasks_to_return: vec![],
-                should_fail_at_step: None,
-                approval_state: ApprovalState::Approved,
+
+        fn with_pause_at(mut self, step: &'static str) -> Self {
+            self.pause_at = Some(step);
+            self
+        }
+
+        async fn pause(&self, call: &str) {
+            if self.pause_at == Some(call) {
+                self.paused.notify_one();
+                self.resume.notified().await;
             }
         }
-        
-        fn with_failure_at(mut self, step: &'static str) -> Self {
-            self.should_fail_at_step = Some(step);
+
+        fn with_failing_task(mut self, task_id: &'static str) -> Self {
+            self.failing_task = Some(task_id);
             self
         }
-        
-        fn with_approval_state(mut self, state: ApprovalState) -> Self {
-            self.approval_state = state;
+
+        fn with_dossier_barrier(mut self, parallel: usize) -> Self {
+            self.dossier_barrier = Some((parallel, Barrier::new(parallel)));
+            self
+        }
+
+        fn with_failure_at(self, step: &'static str) -> Self {
+            *self.fail_at_step.lock().unwrap() = Some(step);
             self
         }
+
+        fn with_failures_at(self, step: &'static str, times: usize) -> Self {
+            *self.failures_left.lock().unwrap() = Some(times);
+            self.with_failure_at(step)
+        }
+
+        fn stop_failing(&self) {
+            *self.fail_at_step.lock().unwrap() = None;
+        }
+
+        fn record(&self, call: &str) -> Result<()> {
+            self.calls.lock().unwrap().push(call.to_string());
+            if *self.fail_at_step.lock().unwrap() == Some(call) {
+                let mut failures_left = self.failures_left.lock().unwrap();
+                match failures_left.as_mut() {
+                    Some(0) => {}
+                    Some(left) => {
+                        *left -= 1;
+                        return Err(LennardError::ServiceUnavailable(format!("{} failed", call)));
+                    }
+                    None => return Err(LennardError::ServiceUnavailable(format!("{} failed", call))),
+                }
+            }
+            Ok(())
+        }
+
+        fn calls(&self, name: &str) -> usize {
+            self.calls.lock().unwrap().iter().filter(|c| *c == name).count()
+        }
+
+        fn letter() -> LetterContent {
+            LetterContent {
+                subject: "Mock Letter Subject".to_string(),
+                greeting: "Dear Mock Contact".to_string(),
+                body: "This is a mock letter body.".to_string(),
+                sender_name: "Mock Sender".to_string(),
+                recipient_name: "Mock Contact".to_string(),
+                company_name: "Mock Company".to_string(),
+            }
+        }
     }
-    
+
     #[async_trait]
-    impl WorkflowSteps for MockWorkflowSteps {
-        async fn load_available_tasks(&self, _max_count: u32) -> Result<Vec<ZohoTask>> {
-            if self.should_fail_at_step == Some("load_available_tasks") {
-                return Err(LennardError::ServiceUnavailable("Test error".to_string()));
-            }
-            Ok(self.tasks_to_return.clone())
+    impl WorkflowSteps for Arc<MockWorkflowSteps> {
+        async fn load_available_tasks(&self, max_count: u32, _selection: Option<&crate::config::TaskSelectionConfig>) -> Result<Vec<TasksResponse>> {
+            self.record("load_available_tasks")?;
+            Ok(self.tasks.iter().take(max_count as usize).cloned().collect())
         }
-        
-        async fn load_task(&self, task_id: &str) -> Result<ZohoTask> {
-            self.tasks_to_return
-                .iter()
-                .find(|t| t.id == task_id)
-                .cloned()
-                .ok_or_else(|| LennardError::NotFound("Task not found".to_string()))
+
+        async fn load_task(&self, task_id: &str) -> Result<TasksResponse> {
+            self.record("load_task")?;
+            self.tasks.iter().find(|t| t.id == task_id).cloned()
+                .ok_or_else(|| LennardError::NotFound(format!("Task {} not found", task_id)))
         }
-        
-        async fn load_contact(&self, task: &ZohoTask) -> Result<ZohoContact> {
-            if self.should_fail_at_step == Some("load_contact") {
-                return Err(LennardError::ServiceUnavailable("Contact load failed".to_string()));
+
+        async fn load_contact(&self, task: &TasksResponse) -> Result<ZohoContact> {
+            self.record("load_contact")?;
+            if self.failing_task == Some(task.id.as_str()) {
+                return Err(LennardError::NotFound(format!("Contact of {} not found", task.id)));
             }
-            
             Ok(ZohoContact {
-                id: task.contact_id.clone().unwrap_or_else(|| "mock-contact-123".to_string()),
+                id: task.who_id.as_ref().unwrap().id.clone(),
                 full_name: "Mock Contact".to_string(),
-                email: Some("mock@example.com".to_string()),
+                email: None,
                 phone: None,
-                company: Some("Mock Company".to_string()),
-                linkedin_id: Some("mock-linkedin-123".to_string()),
+                company: None,
+                linkedin_id: Some("mock-linkedin".to_string()),
                 mailing_address: None,
             })
         }
-        
+
         async fn load_profile(&self, _contact: &ZohoContact) -> Result<LinkedInProfile> {
-            if self.should_fail_at_step == Some("load_profile") {
-                return Err(LennardError::ServiceUnavailable("Profile load failed".to_string()));
-            }
-            
+            self.record("load_profile")?;
             Ok(LinkedInProfile {
-                profile_id: "mock-profile-123".to_string(),
+                profile_id: "mock-profile".to_string(),
                 profile_url: "https://linkedin.com/in/mock".to_string(),
-                full_name: "Mock LinkedIn Profile".to_string(),
-                headline: Some("Mock Professional".to_string()),
-                location: Some("Test City".to_string()),
-                company: Some("Mock Company".to_string()),
+                full_name: "Mock Contact".to_string(),
+                headline: None,
+                location: None,
+                company: None,
                 raw_data: std::collections::HashMap::new(),
             })
         }
-        
-        async fn generate_dossiers(&self, _profile: &LinkedInProfile, _contact_id: &str) -> Result<crate::clients::DossierResult> {
-            if self.should_fail_at_step == Some("generate_dossiers") {
-                return Err(LennardError::ServiceUnavailable("Dossier generation failed".to_string()));
+
+        async fn generate_dossiers(&self, _profile: &LinkedInProfile, _contact_id: &str) -> Result<DossierResult> {
+            self.record("generate_dossiers")?;
+            let in_flight = self.dossiers_in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_dossiers_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            if let Some((parallel, barrier)) = &self.dossier_barrier {
+                if self.dossier_calls.fetch_add(1, Ordering::SeqCst) < *parallel {
+                    barrier.wait().await;
+                }
             }
-            Ok(crate::clients::DossierResult {
-                person_dossier_content: "Mock person dossier content".to_string(),
-                company_dossier_content: "Mock company dossier content".to_string(),
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            self.dossiers_in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(DossierResult {
+                person_dossier_content: "Mock person dossier".to_string(),
+                company_dossier_content: "Mock company dossier".to_string(),
                 company_name: "Mock Company".to_string(),
-                mailing_address: Some(crate::types::MailingAddress {
-                    street: "123 Mock Street".to_string(),
-                    city: "Mock City".to_string(),
-                    state: Some("Mock State".to_string()),
-                    postal_code: "12345".to_string(),
+                mailing_address: Some(MailingAddress {
+                    street: "Mockstraße 1".to_string(),
+                    city: "Berlin".to_string(),
+                    state: None,
+                    postal_code: "10115".to_string(),
                     country: "Germany".to_string(),
                 }),
             })
         }
-        
-        async fn generate_letter(&self, _contact: &ZohoContact, _profile: &LinkedInProfile) -> Result<LetterContent> {
-            if self.should_fail_at_step == Some("generate_letter") {
-                return Err(LennardError::ServiceUnavailable("Letter generation failed".to_string()));
-            }
-            
-            Ok(LetterContent {
-                subject: "Mock Letter Subject".to_string(),
-                greeting: "Dear Mock Contact".to_string(),
-                body: "This is a mock letter body.".to_string(),
-                sender_name: "Mock Sender".to_string(),
-                recipient_name: "Mock Contact".to_string(),
-                company_name: "Mock Company".to_string(),
+
+        async fn update_contact_address(&self, _contact_id: &str, _address: &MailingAddress) -> Result<()> {
+            self.record("update_contact_address")
+        }
+
+        async fn store_letter_content(&self, _contact_id: &str, _company_name: &str, _letter: &LetterContent, _tracking_id: &str) -> Result<()> {
+            self.record("store_letter_content")
+        }
+
+        async fn generate_letter(&self, _contact: &ZohoContact, _profile: &LinkedInProfile, _dossier: &DossierResult) -> Result<LetterContent> {
+            self.record("generate_letter")?;
+            Ok(MockWorkflowSteps::letter())
+        }
+
+        async fn render_letter_pdf(&self, _contact: &ZohoContact, _profile: &LinkedInProfile, _dossier: &DossierResult, letter: &LetterContent) -> Result<RenderedLetter> {
+            self.record("render_letter_pdf")?;
+            Ok(RenderedLetter {
+                letter: letter.clone(),
+                pdf: b"%PDF-1.4 mock".to_vec(),
             })
         }
-        
-        async fn approval_start(&self, _task_id: &str, _contact: &ZohoContact, _profile: &LinkedInProfile, _letter: &LetterContent, _dossier: &DossierResult) -> Result<ApprovalId> {
-            if self.should_fail_at_step == Some("approval_start") {
-                return Err(LennardError::ServiceUnavailable("Approval start failed".to_string()));
-            }
-            // Return a mock approval ID
+
+        async fn approval_start(&self, _task_id: &str, _contact: &ZohoContact, _rendered: &RenderedLetter, _dossier: &DossierResult) -> Result<ApprovalId> {
+            self.record("approval_start")?;
+            self.pause("approval_start").await;
             Ok(ApprovalId::new())
         }
-        
+
         async fn request_approval(&self, _approval_id: &ApprovalId, _letter: &LetterContent, _contact: &ZohoContact) -> Result<ApprovalState> {
-            if self.should_fail_at_step == Some("request_approval") {
-                return Err(LennardError::ServiceUnavailable("Approval request failed".to_string()));
-            }
-            Ok(self.approval_state)
+            self.record("request_approval")?;
+            Ok(ApprovalState::AwaitingUserResponse)
         }
-        
+
         async fn send_pdf(&self, _letter: &LetterContent, _contact: &ZohoContact) -> Result<String> {
-            if self.should_fail_at_step == Some("send_pdf") {
-                return Err(LennardError::ServiceUnavailable("PDF sending failed".to_string()));
-            }
-            Ok("mock-tracking-123".to_string())
+            self.record("send_pdf")?;
+            Ok("mock-tracking".to_string())
         }
-        
-        async fn send_error_notification(
-            &self,
-            _task_id: &str,
-            _contact_name: &str,
-            _company_name: &str,
-            _error_message: &str
-        ) -> Result<()> {
-            Ok(())
+
+        async fn send_pdf_binary(&self, _pdf_data: Vec<u8>, _recipient_address: &MailingAddress, idempotency_key: &str) -> Result<String> {
+            self.record("send_pdf_binary")?;
+            self.sent_keys.lock().unwrap().push(idempotency_key.to_string());
+            Ok("mock-tracking".to_string())
         }
-        
-        async fn update_task_error_status(
-            &self,
-            _task_id: &str,
-            _error_message: &str
-        ) -> Result<()> {
+
+        async fn find_sent_letter(&self, idempotency_key: &str) -> Result<Option<String>> {
+            self.record("find_sent_letter")?;
+            let sent = self.sent_keys.lock().unwrap().iter().any(|key| key == idempotency_key);
+            Ok(sent.then(|| "mock-tracking".to_string()))
+        }
+
+        async fn send_error_notification(&self, _task_id: &str, _contact_name: &str, _company_name: &str, _error_message: &str) -> Result<()> {
+            self.record("send_error_notification")
+        }
+
+        async fn update_task_error_status(&self, _task_id: &str, _error_message: &str) -> Result<()> {
+            self.record("update_task_error_status")
+        }
+
+        async fn update_task_completed_status(&self, task_id: &str, _success_message: &str) -> Result<()> {
+            self.record("update_task_completed_status")?;
+            if self.failing_task == Some(task_id) {
+                return Err(LennardError::NotFound(format!("Task {} not found", task_id)));
+            }
             Ok(())
         }
+
+        async fn mark_task_in_progress(&self, _task_id: &str) -> Result<()> {
+            self.pause("mark_task_in_progress").await;
+            self.record("mark_task_in_progress")
+        }
+
+        async fn reset_task_status(&self, _task_id: &str) -> Result<()> {
+            self.record("reset_task_status")
+        }
+
+        async fn cancel_approval(&self, _approval_id: &ApprovalId) -> Result<()> {
+            self.record("cancel_approval")
+        }
+
+        async fn create_follow_up_task(&self, _contact_id: &str, _original_task_id: &str) -> Result<String> {
+            self.record("create_follow_up_task")?;
+            Ok("follow-up".to_string())
+        }
+
+        async fn attach_file_to_task(&self, _task_id: &str, _file_data: Vec<u8>, _filename: &str) -> Result<()> {
+            self.record("attach_file_to_task")
+        }
+
+        async fn generate_improved_letter(&self, _approval_data: &ApprovalData, _feedback: &str) -> Result<LetterContent> {
+            self.record("generate_improved_letter")?;
+            Ok(MockWorkflowSteps::letter())
+        }
+
+        async fn generate_pdf_with_address(&self, _letter: &LetterContent, _address: &MailingAddress) -> Result<Vec<u8>> {
+            self.record("generate_pdf_with_address")?;
+            Ok(b"%PDF-1.4 mock".to_vec())
+        }
+
+        async fn request_approval_update(&self, _approval_id: &str, _iteration_count: usize) -> Result<()> {
+            self.record("request_approval_update")
+        }
+
+        async fn send_improved_approval_to_telegram(&self, _approval_data: &ApprovalData) -> Result<()> {
+            self.record("send_improved_approval_to_telegram")
+        }
+
+        async fn send_approval_reminder(&self, _approval_data: &ApprovalData) -> Result<()> {
+            self.record("send_approval_reminder")
+        }
+
+        async fn send_approval_expired(&self, _approval_data: &ApprovalData) -> Result<()> {
+            self.record("send_approval_expired")
+        }
     }
-    
-    #[tokio::test]
-    async fn test_workflow_orchestrator_no_tasks() {
-        let mock_steps = MockWorkflowSteps::new_empty();
-        let orchestrator = WorkflowOrchestrator::new(mock_steps);
-        
-        let trigger = WorkflowTrigger {
-            trigger_id: "test-123".to_string(),
+
+    fn setup(steps: MockWorkflowSteps) -> (TempDir, Arc<MockWorkflowSteps>, Arc<WorkflowRunStore>, WorkflowOrchestrator<Arc<MockWorkflowSteps>>) {
+        let temp_dir = TempDir::new().unwrap();
+        let steps = Arc::new(steps);
+        let run_store = Arc::new(WorkflowRunStore::new(temp_dir.path()).unwrap());
+        let checkpoints = Arc::new(TaskCheckpointStore::new(temp_dir.path()).unwrap());
+        let outbox = Arc::new(OutboxStore::new(temp_dir.path()).unwrap());
+        let blobs = Arc::new(BlobStore::new(temp_dir.path()).unwrap());
+        let orchestrator = WorkflowOrchestrator::new(steps.clone(), run_store.clone(), checkpoints, outbox, blobs);
+        (temp_dir, steps, run_store, orchestrator)
+    }
+
+    fn checkpoint_store(dir: &TempDir) -> TaskCheckpointStore {
+        TaskCheckpointStore::new(dir.path()).unwrap()
+    }
+
+    fn trigger(trigger_id: &str, max_tasks: u32, dry_run: bool) -> WorkflowTrigger {
+        WorkflowTrigger {
+            schema_version: TRIGGER_SCHEMA_VERSION,
+            trigger_id: trigger_id.to_string(),
             requested_by: UserId::new(12345),
             requested_at: Utc::now(),
-            max_tasks: 1,
-            dry_run: false,
+            max_tasks,
+            dry_run,
+            task_selection: None,
+            task_ids: Vec::new(),
             processed: false,
             processed_at: None,
             result: None,
-        };
-        
-        let result = orchestrator.process_workflow(trigger).await.unwrap();
-        
+        }
+    }
+
+    #[tokio::test]
+    async fn test_workflow_orchestrator_no_tasks() {
+        let (_dir, _steps, run_store, orchestrator) = setup(MockWorkflowSteps::new(&[]));
+
+        let result = orchestrator.process_workflow(trigger("test-empty", 1, false)).await.unwrap();
+
         assert!(result.processed);
         assert!(result.result.as_ref().unwrap().contains("No tasks available"));
+        assert_eq!(run_store.get_run("test-empty").unwrap().unwrap().status, WorkflowStatus::Completed);
     }
-    
+
     #[tokio::test]
-    async fn test_workflow_orchestrator_with_approved_task() {
-        let test_task = ZohoTask {
-            id: "task-123".to_string(),
-            subject: "Test Task".to_string(),
-            description: Some("Test task description".to_string()),
-            status: "Not Started".to_string(),
-            contact_id: Some("contact-456".to_string()),
-            created_time: Utc::now().to_rfc3339(),
-        };
-        
-        let mock_steps = MockWorkflowSteps::new_with_tasks(vec![test_task])
-            .with_approval_state(ApprovalState::Approved);
-        let orchestrator = WorkflowOrchestrator::new(mock_steps);
-        
-        let trigger = WorkflowTrigger {
-            trigger_id: "test-123".to_string(),
-            requested_by: UserId::new(12345),
-            requested_at: Utc::now(),
-            max_tasks: 1,
-            dry_run: false,
-            processed: false,
-            processed_at: None,
-            result: None,
-        };
-        
-        let result = orchestrator.process_workflow(trigger).await.unwrap();
-        
-        assert!(result.processed);
+    async fn test_workflow_orchestrator_awaits_approval() {
+        let (_dir, steps, run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001", "task-002"]));
+
+        let result = orchestrator.process_workflow(trigger("test-multi", 2, false)).await.unwrap();
+
         let result_msg = result.result.unwrap();
-        assert!(result_msg.contains("Processed 1 tasks"));
-        assert!(result_msg.contains("Letter sent successfully"));
-        assert!(result_msg.contains("mock-tracking-123"));
+        assert!(result_msg.contains("Processed 2 tasks"));
+        assert!(result_msg.contains("Awaiting user response"));
+        assert_eq!(steps.calls("mark_task_in_progress"), 2);
+        assert_eq!(steps.calls("update_contact_address"), 2);
+        assert_eq!(steps.calls("request_approval"), 2);
+
+        let run = run_store.get_run("test-multi").unwrap().unwrap();
+        assert_eq!(run.status, WorkflowStatus::Completed);
+        assert_eq!(run.completed_tasks(), 2);
+        assert!(run.task_results.iter().all(|r| r.approval_id.is_some()));
+        assert!(run.dry_run_output.is_none());
     }
-    
+
     #[tokio::test]
-    async fn test_workflow_orchestrator_with_pending_approval() {
-        let test_task = ZohoTask {
-            id: "task-456".to_string(),
-            subject: "Test Task Pending".to_string(),
-            description: Some("Test pending task description".to_string()),
-            status: "Not Started".to_string(),
-            contact_id: Some("contact-789".to_string()),
-            created_time: Utc::now().to_rfc3339(),
-        };
-        
-        let mock_steps = MockWorkflowSteps::new_with_tasks(vec![test_task])
-            .with_approval_state(ApprovalState::PendingApproval);
-        let orchestrator = WorkflowOrchestrator::new(mock_steps);
-        
-        let trigger = WorkflowTrigger {
-            trigger_id: "test-456".to_string(),
-            requested_by: UserId::new(67890),
-            requested_at: Utc::now(),
-            max_tasks: 1,
-            dry_run: false,
-            processed: false,
-            processed_at: None,
-            result: None,
-        };
-        
-        let result = orchestrator.process_workflow(trigger).await.unwrap();
-        
-        assert!(result.processed);
+    async fn test_workflow_orchestrator_step_failure_notifies_once() {
+        let (_dir, steps, run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-789"]).with_failure_at("load_contact"));
+
+        let result = orchestrator.process_workflow(trigger("test-fail", 1, false)).await.unwrap();
+
+        assert!(result.result.unwrap().contains("No tasks were successfully processed"));
+        assert_eq!(steps.calls("send_error_notification"), 1);
+        assert_eq!(steps.calls("update_task_error_status"), 1);
+        assert_eq!(run_store.get_run("test-fail").unwrap().unwrap().status, WorkflowStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_run_publishes_events() {
+        let (_dir, _steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-789"]).with_failure_at("load_contact"));
+        let mut events = orchestrator.events().subscribe();
+
+        orchestrator.process_workflow(trigger("test-events", 1, false)).await.unwrap();
+
+        let mut published = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            assert_eq!(event.workflow_id.as_deref(), Some("test-events"));
+            published.push(event);
+        }
+        let names: Vec<_> = published.iter().map(|e| e.kind.name()).collect();
+        assert_eq!(names.first(), Some(&"run_started"));
+        assert_eq!(names[1], "task_started");
+        assert_eq!(names[names.len() - 2..], ["task_finished", "run_finished"]);
+
+        let failed = published.iter().find_map(|e| match &e.kind {
+            WorkflowEventKind::StepFailed { task_id, error, .. } => Some((task_id.as_str(), error.as_str())),
+            _ => None,
+        });
+        assert_eq!(failed.map(|(task_id, _)| task_id), Some("task-789"));
+        assert!(matches!(
+            published.last().unwrap().kind,
+            WorkflowEventKind::RunFinished { status: WorkflowStatus::Failed, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_requested_tasks_are_loaded_by_id() {
+        let (_dir, steps, run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001", "task-002", "task-003"]));
+
+        let mut explicit = trigger("explicit", 1, false);
+        explicit.task_ids = ["task-003", "missing", "task-003", " task-001 ", ""].map(String::from).to_vec();
+        let result = orchestrator.process_workflow(explicit).await.unwrap();
+
+        // Only the named tasks - max_tasks does not cut them off and duplicates run once
+        assert_eq!(steps.calls("load_available_tasks"), 0);
+        assert_eq!(steps.calls("load_task"), 3);
+        assert_eq!(steps.calls("request_approval"), 2);
+        assert!(result.result.unwrap().contains("Processed 2 tasks"));
+
+        // The task that could not be loaded is reported as failed, without notifications
+        let run = run_store.get_run("explicit").unwrap().unwrap();
+        assert_eq!(run.max_tasks, 3);
+        assert_eq!(run.total_tasks, 4);
+        assert_eq!(run.completed_tasks(), 2);
+        let missing = run.task_results.iter().find(|r| r.task_id.as_str() == "missing").unwrap();
+        assert_eq!(missing.state, TaskState::Failed);
+        assert!(missing.error_message.as_ref().unwrap().contains("not found"));
+        assert_eq!(steps.calls("send_error_notification"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_requested_tasks_are_reported() {
+        let (_dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&[]));
+
+        let mut explicit = trigger("all-rejected", 1, false);
+        explicit.task_ids = vec!["missing".to_string()];
+        let result = orchestrator.process_workflow(explicit).await.unwrap().result.unwrap();
+
+        assert!(result.starts_with("No tasks were successfully processed"));
+        assert!(result.contains("❌ Task missing: Not found: Task missing not found"));
+        assert_eq!(steps.calls("mark_task_in_progress"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_has_no_side_effects() {
+        let (_dir, steps, run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001", "task-002"]));
+
+        let result = orchestrator.process_workflow(trigger("test-dry", 2, true)).await.unwrap();
+
+        assert!(result.result.unwrap().starts_with("Dry run"));
+        for side_effect in ["mark_task_in_progress", "update_contact_address", "approval_start",
+                            "request_approval", "send_error_notification", "update_task_error_status"] {
+            assert_eq!(steps.calls(side_effect), 0, "dry run must not call {}", side_effect);
+        }
+        assert_eq!(steps.calls("render_letter_pdf"), 2);
+
+        let run = run_store.get_run("test-dry").unwrap().unwrap();
+        assert!(run.dry_run);
+        assert_eq!(run.completed_tasks(), 2);
+        let output = run.dry_run_output.unwrap();
+
+        let report = run_store.get_dry_run_report("test-dry").unwrap().unwrap();
+        assert_eq!(report.tasks.len(), 2);
+        let task = &report.tasks[0];
+        assert!(task.success);
+        assert_eq!(task.company_name.as_deref(), Some("Mock Company"));
+        assert!(task.address_update.is_some());
+        assert_eq!(task.letter.as_ref().unwrap().subject, "Mock Letter Subject");
+        assert_eq!(task.skipped_actions.len(), 3);
+        assert!(output.join(task.pdf_file.as_ref().unwrap()).exists());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_failure_is_reported_not_notified() {
+        let (_dir, steps, run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001"]).with_failure_at("render_letter_pdf"));
+
+        orchestrator.process_workflow(trigger("test-dry-fail", 1, true)).await.unwrap();
+
+        assert_eq!(steps.calls("send_error_notification"), 0);
+        assert_eq!(steps.calls("update_task_error_status"), 0);
+
+        let report = run_store.get_dry_run_report("test-dry-fail").unwrap().unwrap();
+        assert!(!report.tasks[0].success);
+        assert!(report.tasks[0].error_message.as_ref().unwrap().contains("render PDF"));
+        assert!(report.tasks[0].pdf_file.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_retry_resumes_from_last_checkpoint() {
+        let (dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001"]).with_failure_at("generate_letter"));
+        let task_id = TaskId::new("task-001".to_string());
+
+        orchestrator.process_workflow(trigger("first", 1, false)).await.unwrap();
+        assert_eq!(
+            checkpoint_store(&dir).completed_steps(&task_id),
+            vec![WorkflowStep::Contact, WorkflowStep::Profile, WorkflowStep::Dossiers]
+        );
+
+        steps.stop_failing();
+        let result = orchestrator.resume_task("task-001", None, UserId::new(1)).await.unwrap();
+
+        assert!(result.result.unwrap().contains("Processed 1 tasks"));
+        assert_eq!(steps.calls("generate_dossiers"), 1);
+        assert_eq!(steps.calls("load_profile"), 1);
+        // The extracted address was written before the failure and is not written again
+        assert_eq!(steps.calls("update_contact_address"), 1);
+        assert_eq!(steps.calls("generate_letter"), 2);
+        // Checkpoints are dropped once the approval request is out
+        assert!(checkpoint_store(&dir).completed_steps(&task_id).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_approval_request_does_not_create_second_approval() {
+        let (dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001"]).with_failure_at("request_approval"));
+        let task_id = TaskId::new("task-001".to_string());
+
+        orchestrator.process_workflow(trigger("first", 1, false)).await.unwrap();
+        assert!(checkpoint_store(&dir).completed_steps(&task_id).contains(&WorkflowStep::Approval));
+
+        steps.stop_failing();
+        orchestrator.resume_task("task-001", None, UserId::new(1)).await.unwrap();
+
+        assert_eq!(steps.calls("approval_start"), 1);
+        assert_eq!(steps.calls("request_approval"), 2);
+        assert_eq!(steps.calls("render_letter_pdf"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_step_reruns_that_step() {
+        let (dir, steps, run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001"]).with_failure_at("render_letter_pdf"));
+        let task_id = TaskId::new("task-001".to_string());
+
+        orchestrator.process_workflow(trigger("first", 1, false)).await.unwrap();
+        assert!(checkpoint_store(&dir).completed_steps(&task_id).contains(&WorkflowStep::Letter));
+
+        steps.stop_failing();
+        let result = orchestrator.resume_task("task-001", Some(WorkflowStep::Letter), UserId::new(7)).await.unwrap();
+
+        assert_eq!(steps.calls("generate_dossiers"), 1);
+        assert_eq!(steps.calls("generate_letter"), 2);
+
+        // The resume is recorded as its own run
+        let run = run_store.get_run(&result.trigger_id).unwrap().unwrap();
+        assert_eq!(run.requested_by, UserId::new(7));
+        assert_eq!(run.status, WorkflowStatus::Completed);
+        assert_eq!(run.task_results[0].task_id, task_id);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_ignores_checkpoints() {
+        let (dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001"]));
+        let task_id = TaskId::new("task-001".to_string());
+        checkpoint_store(&dir).save(&task_id, WorkflowStep::Letter, &MockWorkflowSteps::letter()).unwrap();
+
+        orchestrator.process_workflow(trigger("dry", 1, true)).await.unwrap();
+
+        assert_eq!(steps.calls("generate_letter"), 1);
+        assert_eq!(checkpoint_store(&dir).completed_steps(&task_id), vec![WorkflowStep::Letter]);
+    }
+
+    #[tokio::test]
+    async fn test_tasks_run_in_parallel_with_ordered_results() {
+        let (_dir, steps, run_store, orchestrator) = setup(
+            MockWorkflowSteps::new(&["task-001", "task-002", "task-003", "task-004", "task-005"])
+                .with_failing_task("task-003")
+                .with_dossier_barrier(2)
+        );
+        let orchestrator = orchestrator.with_max_concurrent_tasks(2);
+
+        // The barrier only opens once task-001 and task-002 are generating dossiers at the same time
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            orchestrator.process_workflow(trigger("parallel", 4, false)),
+        ).await.expect("tasks never ran in parallel").unwrap();
+
+        // Never more than the configured number of tasks at once
+        assert_eq!(steps.max_dossiers_in_flight.load(Ordering::SeqCst), 2);
+
+        // max_tasks is honored and results keep the task order
         let result_msg = result.result.unwrap();
-        assert!(result_msg.contains("Processed 1 tasks"));
-        assert!(result_msg.contains("Approval request created"));
+        assert!(result_msg.starts_with("Processed 3 tasks"));
+        let lines: Vec<&str> = result_msg.lines().skip(1).collect();
+        assert_eq!(lines.len(), 4);
+        for (line, task_id) in lines.iter().zip(["task-001", "task-002", "task-003", "task-004"]) {
+            assert!(line.contains(task_id), "{} should report {}", line, task_id);
+        }
+
+        // The failing task did not cancel the others
+        let run = run_store.get_run("parallel").unwrap().unwrap();
+        assert_eq!(run.total_tasks, 4);
+        assert_eq!(run.completed_tasks(), 3);
+        let ids: Vec<&str> = run.task_results.iter().map(|r| r.task_id.as_str()).collect();
+        assert_eq!(ids, vec!["task-001", "task-002", "task-003", "task-004"]);
+        assert!(!run.task_results[2].success);
+        assert_eq!(steps.calls("send_error_notification"), 1);
     }
-    
+
     #[tokio::test]
-    async fn test_workflow_orchestrator_step_failure() {
-        let test_task = ZohoTask {
-            id: "task-789".to_string(),
-            subject: "Test Task Fail".to_string(),
-            description: Some("Test failing task description".to_string()),
-            status: "Not Started".to_string(),
-            contact_id: Some("contact-101".to_string()),
-            created_time: Utc::now().to_rfc3339(),
-        };
-        
-        let mock_steps = MockWorkflowSteps::new_with_tasks(vec![test_task])
-            .with_failure_at("load_contact");
-        let orchestrator = WorkflowOrchestrator::new(mock_steps);
-        
-        let trigger = WorkflowTrigger {
-            trigger_id: "test-789".to_string(),
-            requested_by: UserId::new(11111),
-            requested_at: Utc::now(),
-            max_tasks: 1,
-            dry_run: false,
-            processed: false,
-            processed_at: None,
-            result: None,
+    async fn test_cancel_workflow_cleans_up_and_reports_task_states() {
+        let (dir, steps, run_store, orchestrator) = setup(
+            MockWorkflowSteps::new(&["task-001", "task-002", "task-003"]).with_pause_at("approval_start")
+        );
+        assert!(!orchestrator.cancel_workflow("cancel-me", "too early"));
+
+        // Cancel while the first task is creating its approval
+        let cancel = async {
+            steps.paused.notified().await;
+            assert!(orchestrator.is_running("cancel-me"));
+            assert!(orchestrator.cancel_workflow("cancel-me", "Stopped by operator"));
+            steps.resume.notify_one();
         };
-        
-        let result = orchestrator.process_workflow(trigger).await.unwrap();
-        
-        assert!(result.processed);
-        let result_msg = result.result.unwrap();
-        assert!(result_msg.contains("No tasks were successfully processed"));
-        // Error details should be in the logs, not in the final result message
+        let (result, _) = tokio::join!(orchestrator.process_workflow(trigger("cancel-me", 3, false)), cancel);
+
+        assert!(result.unwrap().result.unwrap().starts_with("Cancelled: Stopped by operator"));
+        assert!(!orchestrator.is_running("cancel-me"));
+
+        // The approval never reached Telegram and was withdrawn, the Zoho status was reset
+        assert_eq!(steps.calls("request_approval"), 0);
+        assert_eq!(steps.calls("cancel_approval"), 1);
+        assert_eq!(steps.calls("reset_task_status"), 1);
+        assert_eq!(steps.calls("mark_task_in_progress"), 1);
+        assert_eq!(steps.calls("send_error_notification"), 0);
+
+        // A later resume reuses the letter but creates a new approval
+        let completed = checkpoint_store(&dir).completed_steps(&TaskId::new("task-001".to_string()));
+        assert!(completed.contains(&WorkflowStep::Pdf));
+        assert!(!completed.contains(&WorkflowStep::Approval));
+
+        let run = run_store.get_run("cancel-me").unwrap().unwrap();
+        assert_eq!(run.status, WorkflowStatus::Cancelled);
+        let states: Vec<TaskState> = run.task_results.iter().map(|r| r.state).collect();
+        assert_eq!(states, vec![TaskState::Aborted, TaskState::NotStarted, TaskState::NotStarted]);
     }
-    
+
+    fn fast_retries(max_attempts: u32) -> RetryPolicies {
+        let mut config = crate::config::RetryConfig::default();
+        config.default.max_attempts = max_attempts;
+        config.default.initial_backoff_ms = 1;
+        config.default.max_backoff_ms = 1;
+        RetryPolicies::from_config(&config)
+    }
+
     #[tokio::test]
-    async fn test_workflow_orchestrator_multiple_tasks() {
-        let test_tasks = vec![
-            ZohoTask {
-                id: "task-001".to_string(),
-                subject: "Test Task 1".to_string(),
-                description: Some("Test task 1 description".to_string()),
-                status: "Not Started".to_string(),
-                contact_id: Some("contact-001".to_string()),
-                created_time: Utc::now().to_rfc3339(),
-            },
-            ZohoTask {
-                id: "task-002".to_string(),
-                subject: "Test Task 2".to_string(),
-                description: Some("Test task 2 description".to_string()),
-                status: "Not Started".to_string(),
-                contact_id: Some("contact-002".to_string()),
-                created_time: Utc::now().to_rfc3339(),
-            },
-        ];
-        
-        let mock_steps = MockWorkflowSteps::new_with_tasks(test_tasks)
-            .with_approval_state(ApprovalState::Approved);
-        let orchestrator = WorkflowOrchestrator::new(mock_steps);
-        
-        let trigger = WorkflowTrigger {
-            trigger_id: "test-multi".to_string(),
-            requested_by: UserId::new(22222),
-            requested_at: Utc::now(),
-            max_tasks: 2,
-            dry_run: false,
-            processed: false,
-            processed_at: None,
-            result: None,
+    async fn test_transient_step_failure_is_retried_and_recorded() {
+        let (_dir, steps, run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001"]).with_failures_at("generate_dossiers", 2));
+        let orchestrator = orchestrator.with_retry_policies(fast_retries(3));
+
+        let result = orchestrator.process_workflow(trigger("retry", 1, false)).await.unwrap();
+
+        assert!(result.result.unwrap().contains("Processed 1 tasks"));
+        assert_eq!(steps.calls("generate_dossiers"), 3);
+        assert_eq!(steps.calls("send_error_notification"), 0);
+
+        let run = run_store.get_run("retry").unwrap().unwrap();
+        let retries = &run.task_results[0].retries;
+        assert_eq!(retries.len(), 2);
+        assert!(retries.iter().all(|r| r.method == "generate_dossiers"));
+        assert_eq!(retries[1].attempt, 2);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_fail_the_task() {
+        let (_dir, steps, run_store, orchestrator) = setup(MockWorkflowSteps::new(&["task-001"]).with_failure_at("load_profile"));
+        let orchestrator = orchestrator.with_retry_policies(fast_retries(2));
+
+        orchestrator.process_workflow(trigger("exhausted", 1, false)).await.unwrap();
+
+        assert_eq!(steps.calls("load_profile"), 2);
+        assert_eq!(steps.calls("send_error_notification"), 1);
+        let run = run_store.get_run("exhausted").unwrap().unwrap();
+        assert_eq!(run.task_results[0].state, TaskState::Failed);
+        assert_eq!(run.task_results[0].retries.len(), 1);
+    }
+
+    fn approved(orchestrator: &WorkflowOrchestrator<Arc<MockWorkflowSteps>>, task_id: &str) -> ApprovalData {
+        let mut approval = ApprovalData::test_fixture(task_id);
+        approval.state = ApprovalState::Approved;
+        approval.mailing_address = Some(MailingAddress {
+            street: "Mockstraße 1".to_string(),
+            city: "Berlin".to_string(),
+            state: None,
+            postal_code: "10115".to_string(),
+            country: "Germany".to_string(),
+        });
+        approval.set_current_pdf(Some(orchestrator.blobs.put(b"%PDF-1.4 mock").unwrap()));
+        approval
+    }
+
+    #[tokio::test]
+    async fn test_improvement_adds_one_iteration_with_its_pdf() {
+        let (_dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&[]));
+        let blobs = &orchestrator.blobs;
+        let mut approval = approved(&orchestrator, "task-001");
+        approval.set_current_pdf(Some(blobs.put(b"%PDF-1.4 first").unwrap()));
+        approval.add_feedback("Shorter please".to_string(), UserId::new(1));
+
+        let improved = orchestrator.process_improvement_request(&approval, "Shorter please").await.unwrap();
+
+        // The reviewed letter keeps its feedback and PDF, the improved one follows as iteration 2
+        assert_eq!(improved.letter_history.len(), 2);
+        assert_eq!(improved.letter_history[0].feedback.as_ref().unwrap().text, "Shorter please");
+        assert_eq!(improved.letter_history[1].iteration, 2);
+        assert_eq!(improved.iteration_pdf_bytes(Some(1), blobs).unwrap().unwrap(), b"%PDF-1.4 first");
+        assert_eq!(improved.iteration_pdf_bytes(None, blobs).unwrap().unwrap(), b"%PDF-1.4 mock");
+
+        // Nothing reaches Telegram before the caller persisted the pending iteration
+        assert_eq!(improved.state, ApprovalState::PendingApproval);
+        assert_eq!(steps.calls("send_improved_approval_to_telegram"), 0);
+        orchestrator.request_review(&improved).await.unwrap();
+        assert_eq!(steps.calls("send_improved_approval_to_telegram"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_revised_letter_becomes_new_iteration() {
+        let (_dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&[]));
+        let mut approval = approved(&orchestrator, "task-001");
+        approval.mark_awaiting_response();
+
+        let revised = orchestrator.revise_letter(&approval, "One fixed sentence.", UserId::new(7), false).await.unwrap();
+
+        // Rendered without the LLM, pending until the caller stored it and sent it for approval
+        assert_eq!(steps.calls("generate_improved_letter"), 0);
+        assert_eq!(steps.calls("generate_pdf_with_address"), 1);
+        assert_eq!(steps.calls("send_improved_approval_to_telegram"), 0);
+        assert_eq!(revised.state, ApprovalState::PendingApproval);
+        assert_eq!(revised.current_letter.body, "One fixed sentence.");
+        assert_eq!(revised.current_letter.subject, approval.current_letter.subject);
+        let entry = revised.letter_history.last().unwrap();
+        assert_eq!((entry.iteration, entry.edited_by), (2, Some(UserId::new(7))));
+        assert!(entry.pdf.is_some());
+
+        let approved_revision = orchestrator.revise_letter(&approval, "Another sentence.", UserId::new(7), true).await.unwrap();
+        assert_eq!(approved_revision.state, ApprovalState::Approved);
+        assert_eq!(steps.calls("send_improved_approval_to_telegram"), 0);
+
+        assert!(orchestrator.revise_letter(&approval, "  ", UserId::new(7), false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_side_effects_are_retried_from_outbox() {
+        let (dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&[]).with_failure_at("attach_file_to_task"));
+        let approval = approved(&orchestrator, "task-001");
+
+        let result = orchestrator.continue_after_approval(&approval).await.unwrap();
+        assert!(result.contains("mock-tracking"));
+        assert_eq!(steps.calls("send_pdf_binary"), 1);
+        assert_eq!(steps.calls("update_task_completed_status"), 1);
+
+        // Only the failed attachment is left, scheduled for a later attempt
+        let outbox = OutboxStore::new(dir.path()).unwrap();
+        let mut entry = outbox.list_pending().unwrap().pop().unwrap();
+        let pending: Vec<&str> = entry.items.iter()
+            .filter(|item| item.status == OutboxItemStatus::Pending)
+            .map(|item| item.effect.name())
+            .collect();
+        assert_eq!(pending, vec!["attach_pdf"]);
+        assert_eq!(orchestrator.process_outbox().await.unwrap(), 0);
+
+        steps.stop_failing();
+        entry.items[1].next_attempt_at = Utc::now();
+        outbox.save(&entry).unwrap();
+        assert_eq!(orchestrator.process_outbox().await.unwrap(), 1);
+
+        assert_eq!(steps.calls("attach_file_to_task"), 2);
+        assert_eq!(steps.calls("create_follow_up_task"), 1);
+        assert!(outbox.list_pending().unwrap().is_empty());
+
+        // Processing the approval again never sends the letter twice
+        orchestrator.continue_after_approval(&approval).await.unwrap();
+        assert_eq!(steps.calls("send_pdf_binary"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_permanently_failed_side_effect_is_reported() {
+        let (_dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&[]).with_failing_task("task-001"));
+
+        orchestrator.continue_after_approval(&approved(&orchestrator, "task-001")).await.unwrap();
+
+        let failed = orchestrator.failed_outbox_entries().unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].items[2].status, OutboxItemStatus::Failed);
+        assert_eq!(steps.calls("send_error_notification"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_before_claim_leaves_zoho_status_alone() {
+        let (_dir, steps, run_store, orchestrator) = setup(
+            MockWorkflowSteps::new(&["task-001"])
+                .with_pause_at("mark_task_in_progress")
+                .with_failure_at("mark_task_in_progress")
+        );
+        let orchestrator = orchestrator.with_retry_policies(fast_retries(3));
+
+        // Cancel while marking the task "In Progress" fails - the retry is never attempted
+        let cancel = async {
+            steps.paused.notified().await;
+            assert!(orchestrator.cancel_workflow("unclaimed", "Stopped by operator"));
+            steps.resume.notify_one();
         };
-        
-        let result = orchestrator.process_workflow(trigger).await.unwrap();
-        
-        assert!(result.processed);
-        let result_msg = result.result.unwrap();
-        assert!(result_msg.contains("Processed 2 tasks"));
-        assert!(result_msg.contains("task-001"));
-        assert!(result_msg.contains("task-002"));
+        let (result, _) = tokio::join!(orchestrator.process_workflow(trigger("unclaimed", 1, false)), cancel);
+
+        assert!(result.unwrap().result.unwrap().starts_with("Cancelled"));
+        assert_eq!(steps.calls("mark_task_in_progress"), 1);
+        assert_eq!(steps.calls("reset_task_status"), 0);
+
+        let run = run_store.get_run("unclaimed").unwrap().unwrap();
+        assert_eq!(run.task_results[0].state, TaskState::Aborted);
+        assert!(!run.task_results[0].claimed);
+    }
+
+    #[tokio::test]
+    async fn test_sent_letter_is_found_by_idempotency_key() {
+        let (_dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&[]));
+        let mut approval = approved(&orchestrator, "task-001");
+        approval.mark_sending();
+
+        let job_id = orchestrator.send_approved_letter(&approval).await.unwrap();
+
+        // After a crash before the job ID was recorded, the job is found by its key
+        assert_eq!(orchestrator.find_sent_letter(&approval).await.unwrap(), Some(job_id.clone()));
+        let mut unsent = approved(&orchestrator, "task-002");
+        unsent.mark_sending();
+        assert_eq!(orchestrator.find_sent_letter(&unsent).await.unwrap(), None);
+
+        // With the job ID recorded, only the Zoho updates run
+        approval.mark_sent(job_id);
+        let result = orchestrator.continue_after_approval(&approval).await.unwrap();
+        assert!(result.contains("mock-tracking"));
+        assert_eq!(steps.calls("send_pdf_binary"), 1);
+        assert_eq!(steps.calls("update_task_completed_status"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_approval_resets_task() {
+        let (_dir, steps, _run_store, orchestrator) = setup(MockWorkflowSteps::new(&[]).with_failure_at("reset_task_status"));
+        let approval = approved(&orchestrator, "task-001");
+
+        // A transient failure is left to the caller, without bothering the chat
+        assert!(orchestrator.reset_expired_task(&approval).await.unwrap_err().is_retryable());
+        assert_eq!(steps.calls("send_error_notification"), 0);
+
+        steps.stop_failing();
+        orchestrator.reset_expired_task(&approval).await.unwrap();
+        assert_eq!(steps.calls("reset_task_status"), 2);
+        assert_eq!(steps.calls("send_pdf_binary"), 0);
     }
 }
-*/
\ No newline at end of file
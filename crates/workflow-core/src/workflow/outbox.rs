@@ -0,0 +1,381 @@
+//! Durable outbox for the side effects of a sent letter
+//! Once LetterExpress accepted a letter, the Zoho updates that belong to it are written to
+//! `outbox/approval_<id>.json` and retried by the OutboxWorker until they succeed.
+//! Finished entries move to `outbox/processed/`, entries with a permanently failed side effect
+//! to `outbox/failed/`.
+
+use crate::constants::{OUTBOX_INITIAL_BACKOFF_SECS, OUTBOX_MAX_ATTEMPTS, OUTBOX_MAX_BACKOFF_SECS};
+use crate::error::{LennardError, Result};
+use super::approval_types::{ApprovalData, ApprovalId, ContactId, LetterContent, TaskId};
+use super::blob_store::{BlobRef, BlobStore};
+use base64::Engine as _;
+use crate::paths;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::fs;
+
+/// A Zoho update that has to follow a sent letter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SideEffect {
+    /// Store the letter content as a note on the contact
+    StoreLetterContent {
+        contact_id: ContactId,
+        company_name: String,
+        letter: LetterContent,
+    },
+    /// Attach the sent PDF to the task - the PDF is read from the entry
+    AttachPdf {
+        task_id: TaskId,
+        filename: String,
+    },
+    /// Set the task status to "Done"
+    MarkTaskCompleted {
+        task_id: TaskId,
+        message: String,
+    },
+    /// Create the follow-up task for the contact
+    CreateFollowUpTask {
+        contact_id: ContactId,
+        task_id: TaskId,
+    },
+}
+
+impl SideEffect {
+    /// Short name for logs and notifications
+    pub fn name(&self) -> &'static str {
+        match self {
+            SideEffect::StoreLetterContent { .. } => "store_letter_content",
+            SideEffect::AttachPdf { .. } => "attach_pdf",
+            SideEffect::MarkTaskCompleted { .. } => "mark_task_completed",
+            SideEffect::CreateFollowUpTask { .. } => "create_follow_up_task",
+        }
+    }
+}
+
+/// Delivery state of a side effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxItemStatus {
+    Pending,
+    Done,
+    /// Given up - needs manual attention
+    Failed,
+}
+
+/// A side effect and its delivery attempts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxItem {
+    pub effect: SideEffect,
+    pub status: OutboxItemStatus,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Earliest time of the next attempt
+    pub next_attempt_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl OutboxItem {
+    fn new(effect: SideEffect) -> Self {
+        Self {
+            effect,
+            status: OutboxItemStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: Utc::now(),
+            completed_at: None,
+        }
+    }
+
+    /// Whether the item should be attempted now
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.status == OutboxItemStatus::Pending && self.next_attempt_at <= now
+    }
+
+    pub fn succeed(&mut self) {
+        self.attempts += 1;
+        self.status = OutboxItemStatus::Done;
+        self.last_error = None;
+        self.completed_at = Some(Utc::now());
+    }
+
+    /// Record a failed attempt - the item is given up once the error is permanent
+    /// or all attempts are used, otherwise it is scheduled again with exponential backoff
+    pub fn fail(&mut self, error: &LennardError) {
+        self.attempts += 1;
+        self.last_error = Some(error.to_string());
+
+        if !error.is_retryable() || self.attempts >= OUTBOX_MAX_ATTEMPTS {
+            self.status = OutboxItemStatus::Failed;
+            return;
+        }
+
+        let exponent = (self.attempts - 1).min(16);
+        let delay = (OUTBOX_INITIAL_BACKOFF_SECS << exponent).min(OUTBOX_MAX_BACKOFF_SECS);
+        self.next_attempt_at = Utc::now() + Duration::seconds(delay);
+    }
+}
+
+/// Side effects of one sent letter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub approval_id: ApprovalId,
+    pub task_id: TaskId,
+    pub recipient_name: String,
+    pub company_name: String,
+    /// LetterExpress tracking id of the sent letter
+    pub tracking_id: String,
+    /// The PDF that was sent
+    pub pdf: BlobRef,
+    pub items: Vec<OutboxItem>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OutboxEntry {
+    /// All side effects of a letter that was just sent for this approval
+    pub fn for_sent_letter(approval: &ApprovalData, tracking_id: &str, pdf: BlobRef) -> Self {
+        let now = Utc::now();
+        let effects = vec![
+            SideEffect::StoreLetterContent {
+                contact_id: approval.contact_id.clone(),
+                company_name: approval.company_name.clone(),
+                letter: approval.current_letter.clone(),
+            },
+            SideEffect::AttachPdf {
+                task_id: approval.task_id.clone(),
+                filename: format!("Brief_{}.pdf", approval.contact_id),
+            },
+            SideEffect::MarkTaskCompleted {
+                task_id: approval.task_id.clone(),
+                message: format!("Brief erfolgreich versendet. Tracking: {}", tracking_id),
+            },
+            SideEffect::CreateFollowUpTask {
+                contact_id: approval.contact_id.clone(),
+                task_id: approval.task_id.clone(),
+            },
+        ];
+
+        Self {
+            approval_id: approval.approval_id.clone(),
+            task_id: approval.task_id.clone(),
+            recipient_name: approval.recipient_name.clone(),
+            company_name: approval.company_name.clone(),
+            tracking_id: tracking_id.to_string(),
+            pdf,
+            items: effects.into_iter().map(OutboxItem::new).collect(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Whether any side effect is still to be delivered
+    pub fn is_pending(&self) -> bool {
+        self.items.iter().any(|item| item.status == OutboxItemStatus::Pending)
+    }
+
+    /// Whether any side effect was given up
+    pub fn has_failed(&self) -> bool {
+        self.items.iter().any(|item| item.status == OutboxItemStatus::Failed)
+    }
+
+    /// Whether any side effect is due for an attempt
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.items.iter().any(|item| item.is_due(now))
+    }
+}
+
+/// File-based outbox
+pub struct OutboxStore {
+    outbox_dir: PathBuf,
+    processed_dir: PathBuf,
+    failed_dir: PathBuf,
+    blobs: BlobStore,
+}
+
+impl OutboxStore {
+    /// Create new OutboxStore below the given data root
+    pub fn new<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        let outbox_dir = root_path.as_ref().join(paths::OUTBOX_DIR_NAME);
+        let processed_dir = outbox_dir.join(paths::PROCESSED_DIR_NAME);
+        let failed_dir = outbox_dir.join(paths::FAILED_DIR_NAME);
+        for dir in [&outbox_dir, &processed_dir, &failed_dir] {
+            fs::create_dir_all(dir)?;
+        }
+
+        let blobs = BlobStore::new(root_path)?;
+        Ok(Self { outbox_dir, processed_dir, failed_dir, blobs })
+    }
+
+    fn file_name(approval_id: &ApprovalId) -> String {
+        format!("approval_{}.json", approval_id)
+    }
+
+    fn read_entry(&self, path: &Path) -> Result<OutboxEntry> {
+        let json = fs::read_to_string(path)?;
+        let mut entry: serde_json::Value = serde_json::from_str(&json)
+            .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize outbox entry: {}", e)))?;
+
+        // Entries from before the blob store carry the PDF inline
+        if let Some(serde_json::Value::String(pdf_base64)) = entry.get("pdf_base64") {
+            let pdf = base64::engine::general_purpose::STANDARD.decode(pdf_base64)
+                .map_err(|e| LennardError::Deserialization(format!("Failed to decode PDF of outbox entry: {}", e)))?;
+            entry["pdf"] = serde_json::to_value(self.blobs.put(&pdf)?)?;
+        }
+
+        serde_json::from_value(entry)
+            .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize outbox entry: {}", e)))
+    }
+
+    /// Write an entry - pending entries stay in the outbox, finished ones are moved out
+    pub fn save(&self, entry: &OutboxEntry) -> Result<()> {
+        let json = serde_json::to_string_pretty(entry)
+            .map_err(|e| LennardError::Serialization(format!("Failed to serialize outbox entry: {}", e)))?;
+
+        let file_name = Self::file_name(&entry.approval_id);
+        let dir = if entry.is_pending() {
+            &self.outbox_dir
+        } else if entry.has_failed() {
+            &self.failed_dir
+        } else {
+            &self.processed_dir
+        };
+
+        // Write to a temp file first so the worker never observes a half-written entry
+        let path = dir.join(&file_name);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, &path)?;
+
+        if !entry.is_pending() {
+            let pending_path = self.outbox_dir.join(&file_name);
+            if pending_path.exists() {
+                fs::remove_file(pending_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Entry of an approval in any state - its existence means the letter was sent
+    pub fn get(&self, approval_id: &ApprovalId) -> Result<Option<OutboxEntry>> {
+        let file_name = Self::file_name(approval_id);
+
+        for dir in [&self.outbox_dir, &self.processed_dir, &self.failed_dir] {
+            let path = dir.join(&file_name);
+            if path.exists() {
+                return Ok(Some(self.read_entry(&path)?));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<OutboxEntry>> {
+        let mut entries = Vec::new();
+
+        for file in fs::read_dir(dir)?.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            match self.read_entry(&path) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => log::warn!("Skipping unreadable outbox entry {:?}: {}", path, e),
+            }
+        }
+
+        entries.sort_by_key(|entry| entry.created_at);
+        Ok(entries)
+    }
+
+    /// Entries with side effects still to be delivered, oldest first
+    pub fn list_pending(&self) -> Result<Vec<OutboxEntry>> {
+        self.list(&self.outbox_dir)
+    }
+
+    /// Entries with a side effect that was given up, oldest first
+    pub fn list_failed(&self) -> Result<Vec<OutboxEntry>> {
+        self.list(&self.failed_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry() -> OutboxEntry {
+        let approval = ApprovalData::test_fixture("task-1");
+        OutboxEntry::for_sent_letter(&approval, "tracking-1", BlobRef::of(b"%PDF-"))
+    }
+
+    #[test]
+    fn test_entry_moves_out_of_outbox_when_finished() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = OutboxStore::new(temp_dir.path()).unwrap();
+        let mut entry = entry();
+        assert_eq!(entry.items.len(), 4);
+
+        store.save(&entry).unwrap();
+        assert_eq!(store.list_pending().unwrap().len(), 1);
+
+        for item in entry.items.iter_mut() {
+            item.succeed();
+        }
+        store.save(&entry).unwrap();
+
+        assert!(store.list_pending().unwrap().is_empty());
+        assert!(temp_dir.path().join("outbox").join("processed")
+            .join(format!("approval_{}.json", entry.approval_id)).exists());
+        assert_eq!(store.get(&entry.approval_id).unwrap().unwrap().tracking_id, "tracking-1");
+    }
+
+    #[test]
+    fn test_inline_pdf_of_old_entries_moves_to_blob_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = OutboxStore::new(temp_dir.path()).unwrap();
+        let entry = entry();
+        store.save(&entry).unwrap();
+
+        let path = temp_dir.path().join("outbox").join(format!("approval_{}.json", entry.approval_id));
+        let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        json.as_object_mut().unwrap().remove("pdf");
+        json["pdf_base64"] = "JVBERi0=".into();
+        fs::write(&path, json.to_string()).unwrap();
+
+        let restored = store.get(&entry.approval_id).unwrap().unwrap();
+        assert_eq!(restored.pdf, entry.pdf);
+        assert_eq!(BlobStore::new(temp_dir.path()).unwrap().get(&restored.pdf).unwrap(), b"%PDF-");
+    }
+
+    #[test]
+    fn test_failed_attempts_back_off_and_give_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = OutboxStore::new(temp_dir.path()).unwrap();
+        let mut entry = entry();
+
+        let transient = LennardError::ServiceUnavailable("Zoho 503".to_string());
+        entry.items[0].fail(&transient);
+        assert_eq!(entry.items[0].status, OutboxItemStatus::Pending);
+        assert!(!entry.items[0].is_due(Utc::now()));
+
+        entry.items[1].fail(&LennardError::NotFound("task deleted".to_string()));
+        assert_eq!(entry.items[1].status, OutboxItemStatus::Failed);
+
+        while entry.items[0].status == OutboxItemStatus::Pending {
+            entry.items[0].fail(&transient);
+        }
+        assert_eq!(entry.items[0].attempts, OUTBOX_MAX_ATTEMPTS);
+        for item in entry.items.iter_mut().skip(2) {
+            item.succeed();
+        }
+
+        store.save(&entry).unwrap();
+        assert!(store.list_pending().unwrap().is_empty());
+        assert_eq!(store.list_failed().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,47 @@
+//! Background worker delivering the outbox
+//!
+//! Retries the Zoho side effects of sent letters until they succeed. Side effects that fail
+//! permanently are moved to `outbox/failed/` and reported via Telegram.
+
+use crate::workflow::orchestrator::WorkflowOrchestrator;
+use crate::workflow::traits::WorkflowSteps;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use log::{info, error, warn};
+
+/// Periodically delivers due outbox entries
+pub struct OutboxWorker<T: WorkflowSteps> {
+    orchestrator: Arc<WorkflowOrchestrator<T>>,
+    processing_interval: Duration,
+}
+
+impl<T: WorkflowSteps + Send + Sync + 'static> OutboxWorker<T> {
+    pub fn new(orchestrator: Arc<WorkflowOrchestrator<T>>) -> Self {
+        Self {
+            orchestrator,
+            processing_interval: Duration::from_secs(30), // Check every 30 seconds
+        }
+    }
+
+    /// Start delivering the outbox
+    pub async fn start(self: Arc<Self>) {
+        info!("Starting outbox worker");
+
+        match self.orchestrator.failed_outbox_entries() {
+            Ok(failed) if !failed.is_empty() => {
+                warn!("{} sent letters have Zoho updates that need manual attention (see outbox/failed)", failed.len());
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to read failed outbox entries: {}", e),
+        }
+
+        loop {
+            match self.orchestrator.process_outbox().await {
+                Ok(0) => {}
+                Ok(count) => info!("Delivered outbox side effects of {} sent letters", count),
+                Err(e) => error!("Failed to process outbox: {}", e),
+            }
+            sleep(self.processing_interval).await;
+        }
+    }
+}
@@ -0,0 +1,480 @@
+//! Retention of workflow data and logs
+//!
+//! [`Retention::run`] moves sent, failed and expired approvals into a dated
+//! `archive/approvals_<date>.tar.gz` together with their PDFs, deletes old PDFs, processed
+//! triggers and outbox entries, run records and dry-run output, checkpoints of unfinished tasks,
+//! logs and archives, and finally removes the blobs nothing refers to any more. Dossiers are not
+//! archived - they are research material, not part of the letter.
+//!
+//! Logs are not rotated: the dossier and LetterExpress logs are one file per request and only
+//! expire, and the server's own log goes to stdout for the container runtime to rotate.
+
+use crate::config::RetentionConfig;
+use crate::constants::BLOB_GC_MIN_AGE_SECS;
+use crate::error::{LennardError, Result};
+use super::approval_store::ApprovalStore;
+use super::approval_types::{ApprovalData, ApprovalId, ApprovalState};
+use super::blob_store::{referenced_blobs, BlobGcReport, BlobStore};
+use super::outbox::OutboxStore;
+use crate::paths;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use flate2::{write::GzEncoder, Compression};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Approval states that never change again
+const FINISHED_STATES: [ApprovalState; 3] = [ApprovalState::Sent, ApprovalState::Failed, ApprovalState::Expired];
+
+/// What a retention run archived and removed
+#[derive(Debug, Default)]
+pub struct RetentionReport {
+    /// Approvals moved into `archive`
+    pub archived: Vec<ApprovalId>,
+    pub archive: Option<PathBuf>,
+    pub removed_archives: Vec<PathBuf>,
+    pub removed_pdfs: Vec<PathBuf>,
+    /// Processed and failed triggers and processed outbox entries
+    pub removed_processed: Vec<PathBuf>,
+    /// Run records and dry-run output directories
+    pub removed_runs: Vec<PathBuf>,
+    /// Checkpoint directories of tasks
+    pub removed_checkpoints: Vec<PathBuf>,
+    /// Per-approval lock files from before locks were striped
+    pub removed_locks: Vec<PathBuf>,
+    pub removed_logs: Vec<PathBuf>,
+    pub blobs: BlobGcReport,
+}
+
+impl RetentionReport {
+    /// Every deleted file and directory except blobs
+    pub fn removed_files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.removed_archives.iter()
+            .chain(&self.removed_pdfs)
+            .chain(&self.removed_processed)
+            .chain(&self.removed_runs)
+            .chain(&self.removed_checkpoints)
+            .chain(&self.removed_locks)
+            .chain(&self.removed_logs)
+    }
+}
+
+impl fmt::Display for RetentionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.archive {
+            Some(archive) => write!(f, "{} approvals archived into {}", self.archived.len(), archive.display())?,
+            None => write!(f, "no approvals archived")?,
+        }
+        write!(
+            f,
+            ", removed {} archives, {} PDFs, {} processed files, {} runs, {} checkpoints, {} legacy locks, {} logs and {} blobs ({} bytes)",
+            self.removed_archives.len(),
+            self.removed_pdfs.len(),
+            self.removed_processed.len(),
+            self.removed_runs.len(),
+            self.removed_checkpoints.len(),
+            self.removed_locks.len(),
+            self.removed_logs.len(),
+            self.blobs.removed,
+            self.blobs.removed_bytes,
+        )
+    }
+}
+
+/// Applies a [`RetentionConfig`] to a data root and a logs root
+pub struct Retention {
+    config: RetentionConfig,
+    approvals: Arc<dyn ApprovalStore>,
+    outbox: OutboxStore,
+    blobs: Arc<BlobStore>,
+    data_root: PathBuf,
+    logs_root: PathBuf,
+}
+
+impl Retention {
+    pub fn new<D: AsRef<Path>, L: AsRef<Path>>(
+        config: RetentionConfig,
+        approvals: Arc<dyn ApprovalStore>,
+        blobs: Arc<BlobStore>,
+        data_root: D,
+        logs_root: L,
+    ) -> Result<Self> {
+        Ok(Self {
+            config,
+            approvals,
+            outbox: OutboxStore::new(&data_root)?,
+            blobs,
+            data_root: data_root.as_ref().to_path_buf(),
+            logs_root: logs_root.as_ref().to_path_buf(),
+        })
+    }
+
+    pub fn config(&self) -> &RetentionConfig {
+        &self.config
+    }
+
+    /// Archive and remove everything past its retention - a dry run only reports it
+    ///
+    /// A dry run still counts the blobs of approvals it would archive as referenced.
+    pub fn run(&self, now: DateTime<Utc>, dry_run: bool) -> Result<RetentionReport> {
+        let mut report = RetentionReport::default();
+        let archive_dir = self.data_root.join(paths::ARCHIVE_DIR_NAME);
+
+        self.archive_approvals(&archive_dir, now, dry_run, &mut report)?;
+        report.removed_archives = remove_older_than(&archive_dir, self.config.archive_retention_days, now, dry_run)?;
+        report.removed_pdfs = remove_older_than(
+            &self.data_root.join(paths::DATA_DIR_NAME).join(paths::PDFS_DIR_NAME),
+            self.config.pdf_retention_days, now, dry_run,
+        )?;
+
+        let triggers_dir = self.data_root.join(paths::TRIGGERS_DIR_NAME);
+        for dir in [
+            triggers_dir.join(paths::PROCESSED_DIR_NAME),
+            triggers_dir.join(paths::FAILED_DIR_NAME),
+            self.data_root.join(paths::OUTBOX_DIR_NAME).join(paths::PROCESSED_DIR_NAME),
+        ] {
+            report.removed_processed.extend(remove_older_than(&dir, self.config.processed_retention_days, now, dry_run)?);
+        }
+
+        report.removed_runs = remove_older_than(&self.data_root.join(paths::RUNS_DIR_NAME), self.config.run_retention_days, now, dry_run)?;
+        report.removed_runs.extend(remove_dirs_older_than(
+            &self.data_root.join(paths::DRY_RUNS_DIR_NAME),
+            self.config.run_retention_days, now, dry_run,
+        )?);
+        // A task being processed keeps writing its checkpoints, so only abandoned ones are this old
+        report.removed_checkpoints = remove_dirs_older_than(
+            &self.data_root.join(paths::CHECKPOINTS_DIR_NAME),
+            self.config.checkpoint_retention_days, now, dry_run,
+        )?;
+        report.removed_locks = remove_legacy_locks(&self.data_root.join(paths::LOCKS_DIR_NAME), dry_run)?;
+
+        for dir in [
+            self.logs_root.join(paths::GRPC_LOGS_DIR_NAME).join(paths::DOSSIER_LOGS_DIR_NAME),
+            self.logs_root.join(paths::LETTEREXPRESS_LOGS_DIR_NAME),
+        ] {
+            report.removed_logs.extend(remove_older_than(&dir, self.config.log_retention_days, now, dry_run)?);
+        }
+
+        // Last, so the PDFs of the approvals archived above go as well
+        let referenced = referenced_blobs(self.approvals.as_ref(), &self.outbox)?;
+        report.blobs = self.blobs.collect_garbage(&referenced, Duration::from_secs(BLOB_GC_MIN_AGE_SECS), dry_run)?;
+
+        Ok(report)
+    }
+
+    fn archive_approvals(&self, archive_dir: &Path, now: DateTime<Utc>, dry_run: bool, report: &mut RetentionReport) -> Result<()> {
+        let Some(cutoff) = cutoff(self.config.archive_approvals_after_days, now) else {
+            return Ok(());
+        };
+
+        let mut finished = Vec::new();
+        for state in FINISHED_STATES {
+            finished.extend(self.approvals.list_by_state(state)?.into_iter()
+                .filter(|approval| approval.updated_at < cutoff)
+                .map(|approval| (state, approval)));
+        }
+        if finished.is_empty() {
+            return Ok(());
+        }
+
+        let archive = archive_path(archive_dir, now);
+        if dry_run {
+            report.archived = finished.into_iter().map(|(_, approval)| approval.approval_id).collect();
+            report.archive = Some(archive);
+            return Ok(());
+        }
+
+        fs::create_dir_all(archive_dir)?;
+        self.write_archive(&archive, &finished)?;
+        log::info!("Archived {} approvals into {}", finished.len(), archive.display());
+
+        // Only removed once the archive is on disk - a crash in between leaves them in both
+        for (state, approval) in finished {
+            if self.approvals.remove(&approval.approval_id, state)? {
+                report.archived.push(approval.approval_id);
+            } else {
+                log::debug!("Approval {} left {:?} while it was archived", approval.approval_id, state);
+            }
+        }
+        report.archive = Some(archive);
+        Ok(())
+    }
+
+    /// Approvals as `<state directory>/approval_<id>.json` and their PDFs as `pdfs/<sha256>.pdf`
+    fn write_archive(&self, archive: &Path, approvals: &[(ApprovalState, ApprovalData)]) -> Result<()> {
+        let tmp_path = archive.with_extension("tmp");
+        let mut builder = tar::Builder::new(GzEncoder::new(File::create(&tmp_path)?, Compression::default()));
+        let mut pdfs = HashSet::new();
+
+        for (state, approval) in approvals {
+            let json = serde_json::to_vec_pretty(approval)
+                .map_err(|e| LennardError::Serialization(format!("Failed to serialize approval: {}", e)))?;
+            let name = format!("{}/approval_{}.json", state.directory_name(), approval.approval_id);
+            append(&mut builder, &name, &json, approval.updated_at)?;
+
+            for pdf in approval.pdf_refs().filter(|pdf| pdfs.insert(pdf.sha256.clone())) {
+                // A lost PDF must not keep the approval from being archived
+                match self.blobs.get(pdf) {
+                    Ok(content) => append(&mut builder, &format!("pdfs/{}.pdf", pdf.sha256), &content, approval.updated_at)?,
+                    Err(e) => log::warn!("Archiving approval {} without PDF {}: {}", approval.approval_id, pdf.sha256, e),
+                }
+            }
+        }
+
+        let file = builder.into_inner()?.finish()?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, archive)?;
+        Ok(())
+    }
+}
+
+fn append<W: std::io::Write>(builder: &mut tar::Builder<W>, name: &str, content: &[u8], modified: DateTime<Utc>) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(modified.timestamp().max(0) as u64);
+    builder.append_data(&mut header, name, content)?;
+    Ok(())
+}
+
+/// `approvals_<date>.tar.gz`, numbered if the date already has an archive
+fn archive_path(archive_dir: &Path, now: DateTime<Utc>) -> PathBuf {
+    let date = now.format("%Y-%m-%d");
+    let mut path = archive_dir.join(format!("approvals_{}.tar.gz", date));
+    let mut number = 1;
+    while path.exists() {
+        number += 1;
+        path = archive_dir.join(format!("approvals_{}_{}.tar.gz", date, number));
+    }
+    path
+}
+
+/// Point in time before which an artifact kept for `days` is due - None if kept forever
+fn cutoff(days: u64, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    (days > 0).then(|| now - ChronoDuration::days(days as i64))
+}
+
+/// Delete the files directly in `dir` last modified before the cutoff of `days`
+fn remove_older_than(dir: &Path, days: u64, now: DateTime<Utc>, dry_run: bool) -> Result<Vec<PathBuf>> {
+    let Some(cutoff) = cutoff(days, now) else {
+        return Ok(Vec::new());
+    };
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let cutoff = SystemTime::from(cutoff);
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() || metadata.modified()? >= cutoff {
+            continue;
+        }
+        if !dry_run {
+            fs::remove_file(entry.path())?;
+        }
+        removed.push(entry.path());
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+/// Delete the directories directly in `dir` with nothing in them modified since the cutoff of `days`
+fn remove_dirs_older_than(dir: &Path, days: u64, now: DateTime<Utc>, dry_run: bool) -> Result<Vec<PathBuf>> {
+    let Some(cutoff) = cutoff(days, now) else {
+        return Ok(Vec::new());
+    };
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let cutoff = SystemTime::from(cutoff);
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() || last_modified(&entry.path())? >= cutoff {
+            continue;
+        }
+        if !dry_run {
+            fs::remove_dir_all(entry.path())?;
+        }
+        removed.push(entry.path());
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+/// Latest modification of a directory and the entries directly in it
+fn last_modified(dir: &Path) -> Result<SystemTime> {
+    let mut latest = fs::metadata(dir)?.modified()?;
+    for entry in fs::read_dir(dir)? {
+        latest = latest.max(entry?.metadata()?.modified()?);
+    }
+    Ok(latest)
+}
+
+/// Delete the `approval_<id>.lock` files left from before approval locks were striped
+fn remove_legacy_locks(dir: &Path, dry_run: bool) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if !name.starts_with("approval_") || !name.ends_with(".lock") {
+            continue;
+        }
+        if !dry_run {
+            fs::remove_file(entry.path())?;
+        }
+        removed.push(entry.path());
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow::approval_store::FileApprovalStore;
+    use flate2::read::GzDecoder;
+    use tempfile::TempDir;
+
+    fn approval(updated_days_ago: i64, blobs: &BlobStore) -> ApprovalData {
+        let mut approval = ApprovalData::test_fixture("task-1");
+        approval.set_current_pdf(Some(blobs.put(format!("%PDF-1.4 {}", approval.approval_id).as_bytes()).unwrap()));
+        approval.person_dossier = Some(blobs.put(b"About John Doe").unwrap());
+        approval.updated_at = Utc::now() - ChronoDuration::days(updated_days_ago);
+        approval
+    }
+
+    fn write_aged(path: &Path, days: u64) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "{}").unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(days * 24 * 3600);
+        File::options().append(true).open(path).unwrap().set_modified(modified).unwrap();
+    }
+
+    fn age_dir(path: &Path, days: u64) {
+        let modified = SystemTime::now() - Duration::from_secs(days * 24 * 3600);
+        File::open(path).unwrap().set_modified(modified).unwrap();
+    }
+
+    #[test]
+    fn test_retention_removes_runs_checkpoints_and_legacy_locks() {
+        let data_dir = TempDir::new().unwrap();
+        let logs_dir = TempDir::new().unwrap();
+        let store = Arc::new(FileApprovalStore::new(data_dir.path()).unwrap());
+        let blobs = Arc::new(BlobStore::new(data_dir.path()).unwrap());
+
+        let old_run = data_dir.path().join("runs/run_1.json");
+        let recent_run = data_dir.path().join("runs/run_2.json");
+        let old_dry_run = data_dir.path().join("dry_runs/dry-1");
+        let abandoned_checkpoint = data_dir.path().join("checkpoints/task-1");
+        let active_checkpoint = data_dir.path().join("checkpoints/task-2");
+        write_aged(&old_run, 100);
+        write_aged(&recent_run, 1);
+        write_aged(&old_dry_run.join("report.json"), 100);
+        age_dir(&old_dry_run, 100);
+        write_aged(&abandoned_checkpoint.join("dossiers.json"), 40);
+        age_dir(&abandoned_checkpoint, 40);
+        // A task still being processed wrote its last checkpoint recently
+        write_aged(&active_checkpoint.join("dossiers.json"), 40);
+        write_aged(&active_checkpoint.join("letter.json"), 0);
+        age_dir(&active_checkpoint, 40);
+
+        let legacy_lock = data_dir.path().join("locks/approval_5f0c6d3e.lock");
+        let stripe_lock = data_dir.path().join("locks/stripe_00.lock");
+        write_aged(&legacy_lock, 0);
+        write_aged(&stripe_lock, 0);
+
+        let retention = Retention::new(RetentionConfig::default(), store, blobs, data_dir.path(), logs_dir.path()).unwrap();
+        let report = retention.run(Utc::now(), false).unwrap();
+
+        assert_eq!(report.removed_runs, vec![old_run.clone(), old_dry_run.clone()]);
+        assert_eq!(report.removed_checkpoints, vec![abandoned_checkpoint.clone()]);
+        assert_eq!(report.removed_locks, vec![legacy_lock.clone()]);
+        assert!(!old_run.exists() && !old_dry_run.exists() && !abandoned_checkpoint.exists() && !legacy_lock.exists());
+        assert!(recent_run.exists() && active_checkpoint.exists() && stripe_lock.exists());
+    }
+
+    #[test]
+    fn test_retention_archives_and_removes_old_artifacts() {
+        let data_dir = TempDir::new().unwrap();
+        let logs_dir = TempDir::new().unwrap();
+        let store = Arc::new(FileApprovalStore::new(data_dir.path()).unwrap());
+        let blobs = Arc::new(BlobStore::new(data_dir.path()).unwrap());
+
+        let old_sent = approval(40, &blobs);
+        let recent_sent = approval(2, &blobs);
+        let old_pending = approval(40, &blobs);
+        store.insert(&old_sent, ApprovalState::Sent).unwrap();
+        store.insert(&recent_sent, ApprovalState::Sent).unwrap();
+        store.insert(&old_pending, ApprovalState::PendingApproval).unwrap();
+
+        let old_pdf = data_dir.path().join("data/pdfs/letter_1.pdf");
+        let old_trigger = data_dir.path().join("triggers/processed/trigger_1.json");
+        let recent_trigger = data_dir.path().join("triggers/processed/trigger_2.json");
+        let old_log = logs_dir.path().join("grpc/dossier/20250101_000000_contact_request.json");
+        write_aged(&old_pdf, 200);
+        write_aged(&old_trigger, 40);
+        write_aged(&recent_trigger, 1);
+        write_aged(&old_log, 20);
+
+        let retention = Retention::new(RetentionConfig::default(), store.clone(), blobs.clone(), data_dir.path(), logs_dir.path()).unwrap();
+
+        let report = retention.run(Utc::now(), true).unwrap();
+        assert_eq!(report.archived, vec![old_sent.approval_id.clone()]);
+        assert_eq!(report.removed_files().count(), 3);
+        assert!(!report.archive.as_ref().unwrap().exists());
+        assert!(old_pdf.exists() && old_trigger.exists() && old_log.exists());
+
+        let report = retention.run(Utc::now(), false).unwrap();
+        assert_eq!(report.archived, vec![old_sent.approval_id.clone()]);
+        assert_eq!(report.removed_pdfs, vec![old_pdf.clone()]);
+        assert_eq!(report.removed_processed, vec![old_trigger.clone()]);
+        assert_eq!(report.removed_logs, vec![old_log.clone()]);
+        assert!(!old_pdf.exists() && !old_trigger.exists() && !old_log.exists());
+        assert!(recent_trigger.exists());
+
+        assert!(store.get(&old_sent.approval_id).unwrap().is_none());
+        assert!(store.get(&recent_sent.approval_id).unwrap().is_some());
+        assert!(store.get(&old_pending.approval_id).unwrap().is_some());
+
+        // The archive holds the approval and its PDF, but not the dossier
+        let archive = report.archive.unwrap();
+        assert!(archive.file_name().unwrap().to_string_lossy().starts_with("approvals_"));
+        let mut names: Vec<String> = tar::Archive::new(GzDecoder::new(File::open(&archive).unwrap()))
+            .entries().unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![
+            format!("pdfs/{}.pdf", old_sent.pdf.as_ref().unwrap().sha256),
+            format!("processed/approval_{}.json", old_sent.approval_id),
+        ]);
+
+        // Nothing left to archive - a second run of the day must not overwrite the archive
+        let report = retention.run(Utc::now(), false).unwrap();
+        assert!(report.archive.is_none());
+        assert_ne!(archive_path(&data_dir.path().join("archive"), Utc::now()), archive);
+    }
+
+    #[test]
+    fn test_zero_days_keep_artifacts_forever() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_log = temp_dir.path().join("old.json");
+        write_aged(&old_log, 1000);
+
+        assert!(remove_older_than(temp_dir.path(), 0, Utc::now(), false).unwrap().is_empty());
+        assert_eq!(remove_older_than(temp_dir.path(), 30, Utc::now(), true).unwrap(), vec![old_log.clone()]);
+        assert!(old_log.exists());
+    }
+}
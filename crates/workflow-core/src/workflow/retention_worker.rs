@@ -0,0 +1,46 @@
+//! Background worker applying the retention policy
+//!
+//! Runs [`Retention::run`] at start-up and every `workflow.retention.interval_hours` after.
+
+use crate::workflow::retention::Retention;
+use chrono::Utc;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use log::{info, error};
+
+/// Periodically archives and removes workflow data and logs past their retention
+pub struct RetentionWorker {
+    retention: Arc<Retention>,
+}
+
+impl RetentionWorker {
+    pub fn new(retention: Arc<Retention>) -> Self {
+        Self { retention }
+    }
+
+    /// Start applying the retention policy
+    pub async fn start(self: Arc<Self>) {
+        let interval = Duration::from_secs(self.retention.config().interval_hours.max(1) * 3600);
+        info!("Starting retention worker (every {}h)", interval.as_secs() / 3600);
+
+        loop {
+            self.apply().await;
+            sleep(interval).await;
+        }
+    }
+
+    async fn apply(&self) {
+        // Compressing archives and walking directories blocks - keep it off the runtime threads
+        let retention = self.retention.clone();
+        match tokio::task::spawn_blocking(move || retention.run(Utc::now(), false)).await {
+            Ok(Ok(report)) => {
+                for path in report.removed_files() {
+                    log::debug!("Removed {}", path.display());
+                }
+                info!("Retention: {}", report);
+            }
+            Ok(Err(e)) => error!("Retention run failed: {}", e),
+            Err(e) => error!("Retention run panicked: {}", e),
+        }
+    }
+}
@@ -0,0 +1,327 @@
+//! Retry policy for workflow steps
+//! Transient failures (see `LennardError::is_retryable`) are retried with exponential backoff
+//! and jitter, every attempt is bounded by a timeout
+
+use crate::config::{RetryConfig, RetryPolicyConfig};
+use crate::error::{LennardError, Result};
+use super::run_types::StepRetry;
+use chrono::Utc;
+use rand::Rng;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+/// `WorkflowSteps` methods the orchestrator runs with a retry policy
+/// Every other method gets a single attempt - a repeated approval would reach Telegram twice
+pub const RETRIED_METHODS: &[&str] = &[
+    "load_available_tasks",
+    "load_task",
+    "mark_task_in_progress",
+    "load_contact",
+    "load_profile",
+    "generate_dossiers",
+    "update_contact_address",
+    "generate_letter",
+    "render_letter_pdf",
+];
+
+/// Retry policy of a single step
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts including the first one
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub backoff_multiplier: f64,
+    /// Random deviation of each backoff, as a fraction of it
+    pub jitter: f64,
+    /// Timeout of a single attempt
+    pub timeout: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// A single attempt without timeout
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            backoff_multiplier: 1.0,
+            jitter: 0.0,
+            timeout: None,
+        }
+    }
+
+    pub fn from_config(config: &RetryPolicyConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms.max(config.initial_backoff_ms)),
+            backoff_multiplier: config.backoff_multiplier.max(1.0),
+            jitter: config.jitter.clamp(0.0, 1.0),
+            timeout: (config.timeout_secs > 0).then(|| Duration::from_secs(config.timeout_secs)),
+        }
+    }
+
+    /// Delay before the attempt following `attempt` (1-based), capped at `max_backoff` before jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(32) as i32;
+        let base = (self.initial_backoff.as_secs_f64() * self.backoff_multiplier.powi(exponent))
+            .min(self.max_backoff.as_secs_f64());
+
+        let factor = if self.jitter > 0.0 {
+            1.0 + rand::thread_rng().gen_range(-self.jitter..=self.jitter)
+        } else {
+            1.0
+        };
+
+        Duration::from_secs_f64((base * factor).max(0.0))
+    }
+}
+
+/// Retry policies of all step methods
+#[derive(Debug, Clone)]
+pub struct RetryPolicies {
+    default: RetryPolicy,
+    steps: HashMap<String, RetryPolicy>,
+    /// Policy of the methods missing from `RETRIED_METHODS`
+    single_attempt: RetryPolicy,
+}
+
+impl RetryPolicies {
+    /// No retries and no timeouts for any step
+    pub fn none() -> Self {
+        Self {
+            default: RetryPolicy::none(),
+            steps: HashMap::new(),
+            single_attempt: RetryPolicy::none(),
+        }
+    }
+
+    pub fn from_config(config: &RetryConfig) -> Self {
+        Self {
+            default: RetryPolicy::from_config(&config.default),
+            steps: config.steps.keys()
+                .map(|method| (method.clone(), RetryPolicy::from_config(&config.policy_for(method))))
+                .collect(),
+            single_attempt: RetryPolicy::none(),
+        }
+    }
+
+    /// Policy of a `WorkflowSteps` method
+    pub fn for_method(&self, method: &str) -> &RetryPolicy {
+        if !RETRIED_METHODS.contains(&method) {
+            return &self.single_attempt;
+        }
+        self.steps.get(method).unwrap_or(&self.default)
+    }
+}
+
+/// Run `operation` until it succeeds, fails permanently or runs out of attempts
+///
+/// Every retried failure is passed to `on_retry` before the backoff.
+pub async fn retry<T, F, Fut>(
+    method: &str,
+    policy: &RetryPolicy,
+    operation: F,
+    on_retry: impl FnMut(StepRetry),
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    retry_reserved(method, policy, || async { Ok(()) }, operation, on_retry).await
+}
+
+/// Like [`retry`], but every attempt first waits for `reserve`
+///
+/// The reservation (e.g. a service permit) is held until the attempt finished,
+/// waiting for it does not count against the attempt's timeout.
+pub async fn retry_reserved<T, G, R, RFut, F, Fut>(
+    method: &str,
+    policy: &RetryPolicy,
+    mut reserve: R,
+    mut operation: F,
+    mut on_retry: impl FnMut(StepRetry),
+) -> Result<T>
+where
+    R: FnMut() -> RFut,
+    RFut: Future<Output = Result<G>>,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+
+    loop {
+        let result = match reserve().await {
+            Ok(reservation) => {
+                let result = match policy.timeout {
+                    Some(timeout) => match tokio::time::timeout(timeout, operation()).await {
+                        Ok(result) => result,
+                        Err(_) => Err(LennardError::Timeout(format!(
+                            "{} did not finish within {}s", method, timeout.as_secs()
+                        ))),
+                    },
+                    None => operation().await,
+                };
+                drop(reservation);
+                result
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Err(e) if attempt < policy.max_attempts && e.is_retryable() => {
+                let delay = policy.backoff(attempt);
+                log::warn!("{} failed (attempt {}/{}), retrying in {}ms: {}",
+                           method, attempt, policy.max_attempts, delay.as_millis(), e);
+
+                on_retry(StepRetry {
+                    method: method.to_string(),
+                    attempt,
+                    error: e.to_string(),
+                    delay_ms: delay.as_millis() as u64,
+                    failed_at: Utc::now(),
+                });
+
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            backoff_multiplier: 2.0,
+            jitter: 0.0,
+            timeout: Some(Duration::from_millis(50)),
+        }
+    }
+
+    #[test]
+    fn test_error_classification() {
+        assert!(LennardError::ServiceUnavailable("503".to_string()).is_retryable());
+        assert!(LennardError::Timeout("slow".to_string()).is_retryable());
+        assert!(!LennardError::Validation("bad".to_string()).is_retryable());
+        assert!(!LennardError::NotFound("gone".to_string()).is_retryable());
+        assert!(!LennardError::Cancelled("stop".to_string()).is_retryable());
+        assert!(!LennardError::PageLimitExceeded { page_count: 2, limit: 1, message: String::new() }.is_retryable());
+
+        assert!(LennardError::Timeout("slow".to_string()).is_ambiguous());
+        assert!(LennardError::Json(serde_json::from_str::<u32>("{").unwrap_err()).is_ambiguous());
+        assert!(!LennardError::Validation("rejected".to_string()).is_ambiguous());
+        assert!(!LennardError::Workflow("no address".to_string()).is_ambiguous());
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            backoff_multiplier: 2.0,
+            jitter: 0.0,
+            timeout: None,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+
+        let jittered = RetryPolicy { jitter: 0.5, ..policy };
+        for _ in 0..20 {
+            let delay = jittered.backoff(1);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let calls = AtomicU32::new(0);
+        let mut retries = Vec::new();
+
+        let result = retry("generate_dossiers", &fast_policy(3), || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(LennardError::ServiceUnavailable("dossier service down".to_string())),
+                _ => Ok("dossier"),
+            }
+        }, |r| retries.push(r)).await;
+
+        assert_eq!(result.unwrap(), "dossier");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].method, "generate_dossiers");
+        assert!(retries[0].error.contains("dossier service down"));
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failures_and_exhausted_attempts() {
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = retry("load_contact", &fast_policy(3), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(LennardError::NotFound("no contact".to_string()))
+        }, |_| {}).await;
+        assert!(matches!(result, Err(LennardError::NotFound(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = retry("load_profile", &fast_policy(3), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok(())
+        }, |_| {}).await;
+        assert!(matches!(result, Err(LennardError::Timeout(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_policies_from_config() {
+        let mut config = RetryConfig::default();
+        config.steps.insert("generate_dossiers".to_string(), crate::config::RetryPolicyOverride {
+            max_attempts: Some(5),
+            ..Default::default()
+        });
+
+        let policies = RetryPolicies::from_config(&config);
+        let dossiers = policies.for_method("generate_dossiers");
+        assert_eq!(dossiers.max_attempts, 5);
+        assert_eq!(dossiers.initial_backoff, Duration::from_millis(config.default.initial_backoff_ms));
+        assert_eq!(policies.for_method("load_contact").max_attempts, config.default.max_attempts);
+        assert_eq!(RetryPolicies::none().for_method("load_contact").max_attempts, 1);
+
+        // Creating and sending approvals is never repeated
+        assert_eq!(policies.for_method("approval_start"), &RetryPolicy::none());
+        assert_eq!(policies.for_method("request_approval"), &RetryPolicy::none());
+    }
+
+    #[tokio::test]
+    async fn test_waiting_for_a_reservation_is_not_timed() {
+        let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(1));
+        let held = semaphore.clone().acquire_owned().await.unwrap();
+        let release = async {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            drop(held);
+        };
+
+        // The permit only frees up after three timeouts' worth of waiting
+        let policy = fast_policy(1);
+        let attempt = retry_reserved("generate_dossiers", &policy, || async {
+            semaphore.clone().acquire_owned().await
+                .map_err(|_| LennardError::ServiceUnavailable("closed".to_string()))
+        }, || async { Ok("dossier") }, |_| {});
+        let (result, _) = tokio::join!(attempt, release);
+
+        assert_eq!(result.unwrap(), "dossier");
+        assert_eq!(semaphore.available_permits(), 1);
+    }
+}
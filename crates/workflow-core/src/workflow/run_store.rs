@@ -0,0 +1,364 @@
+//! File-based store for workflow run records
+//! One JSON file per run under `runs/`, keyed by the trigger id
+//! Dry runs additionally get a folder under `dry_runs/` with their report and PDFs
+
+use crate::error::{LennardError, Result};
+use super::run_types::*;
+use super::approval_types::TaskId;
+use crate::paths;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::fs;
+
+/// File name of the report inside a dry run folder
+pub const DRY_RUN_REPORT_FILE: &str = "report.json";
+
+/// Persistent store for workflow runs
+pub struct WorkflowRunStore {
+    runs_dir: PathBuf,
+    dry_runs_dir: PathBuf,
+}
+
+/// Replace everything but `[A-Za-z0-9_-]` so ids can never escape the store directories
+///
+/// Ids that needed replacing get a hash of the original id appended, so two ids never
+/// share a file name.
+pub(crate) fn sanitize_id(id: &str) -> String {
+    let sanitized: String = id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if sanitized == id {
+        return sanitized;
+    }
+    format!("{}_{:.8x}", sanitized, Sha256::digest(id.as_bytes()))
+}
+
+impl WorkflowRunStore {
+    /// Create new WorkflowRunStore below the given data root
+    pub fn new<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        let runs_dir = root_path.as_ref().join(paths::RUNS_DIR_NAME);
+        let dry_runs_dir = root_path.as_ref().join(paths::DRY_RUNS_DIR_NAME);
+        fs::create_dir_all(&runs_dir)?;
+        fs::create_dir_all(&dry_runs_dir)?;
+
+        Ok(Self { runs_dir, dry_runs_dir })
+    }
+
+    /// Get path for a run - the id is sanitized so it can never escape the runs directory
+    fn get_run_path(&self, workflow_id: &str) -> PathBuf {
+        self.runs_dir.join(format!("run_{}.json", sanitize_id(workflow_id)))
+    }
+
+    /// Read run record from file
+    fn read_run(&self, path: &Path) -> Result<WorkflowRun> {
+        let json = fs::read_to_string(path)?;
+
+        serde_json::from_str(&json)
+            .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize workflow run: {}", e)))
+    }
+
+    /// Record a new run - fails if the workflow id already has a record
+    pub fn create_run(&self, run: &WorkflowRun) -> Result<()> {
+        let json = serde_json::to_string_pretty(run)
+            .map_err(|e| LennardError::Serialization(format!("Failed to serialize workflow run: {}", e)))?;
+
+        // Linking fails if the record exists, so a reused id never overwrites an earlier run
+        let path = self.get_run_path(&run.workflow_id);
+        let tmp_path = path.with_extension(format!("json.{}.tmp", uuid::Uuid::new_v4()));
+        fs::write(&tmp_path, json)?;
+        let linked = fs::hard_link(&tmp_path, &path);
+        fs::remove_file(&tmp_path)?;
+        match linked {
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Err(LennardError::Validation(format!(
+                "Workflow {} already has a run record", run.workflow_id
+            ))),
+            other => Ok(other?),
+        }
+    }
+
+    /// Create or update a run record
+    pub fn save_run(&self, run: &WorkflowRun) -> Result<()> {
+        let json = serde_json::to_string_pretty(run)
+            .map_err(|e| LennardError::Serialization(format!("Failed to serialize workflow run: {}", e)))?;
+
+        // Write to a temp file first so readers never observe a half-written record
+        let path = self.get_run_path(&run.workflow_id);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, &path)?;
+
+        log::debug!("Saved workflow run {} ({:?})", run.workflow_id, run.status);
+        Ok(())
+    }
+
+    /// Get run record by workflow id
+    pub fn get_run(&self, workflow_id: &str) -> Result<Option<WorkflowRun>> {
+        let path = self.get_run_path(workflow_id);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.read_run(&path)?))
+    }
+
+    /// Load all run records, newest first
+    pub fn all_runs(&self) -> Result<Vec<WorkflowRun>> {
+        let mut runs = Vec::new();
+
+        for entry in fs::read_dir(&self.runs_dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
+                match self.read_run(&path) {
+                    Ok(run) => runs.push(run),
+                    Err(e) => log::warn!("Skipping unreadable workflow run {:?}: {}", path, e),
+                }
+            }
+        }
+
+        runs.sort_by(|a, b| b.started_at.cmp(&a.started_at)
+            .then_with(|| a.workflow_id.cmp(&b.workflow_id)));
+
+        Ok(runs)
+    }
+
+    /// List runs matching the filter, newest first, starting at `offset`
+    pub fn list_runs(&self, filter: &WorkflowRunFilter, offset: usize, limit: usize) -> Result<WorkflowRunPage> {
+        let matching: Vec<WorkflowRun> = self.all_runs()?
+            .into_iter()
+            .filter(|run| filter.matches(run))
+            .collect();
+
+        let total_count = matching.len();
+        let runs: Vec<WorkflowRun> = matching.into_iter().skip(offset).take(limit).collect();
+
+        let next_offset = if offset + runs.len() < total_count {
+            Some(offset + runs.len())
+        } else {
+            None
+        };
+
+        Ok(WorkflowRunPage {
+            runs,
+            total_count,
+            next_offset,
+        })
+    }
+
+    /// Folder holding the output of a dry run
+    pub fn dry_run_dir(&self, workflow_id: &str) -> PathBuf {
+        self.dry_runs_dir.join(sanitize_id(workflow_id))
+    }
+
+    /// Write the PDF generated for a task during a dry run, returns the file name within the dry run folder
+    pub fn save_dry_run_pdf(&self, workflow_id: &str, task_id: &TaskId, pdf: &[u8]) -> Result<String> {
+        let dir = self.dry_run_dir(workflow_id);
+        fs::create_dir_all(&dir)?;
+
+        let file_name = format!("task_{}.pdf", sanitize_id(task_id.as_str()));
+        fs::write(dir.join(&file_name), pdf)?;
+
+        log::debug!("Saved dry run PDF {} for workflow {}", file_name, workflow_id);
+        Ok(file_name)
+    }
+
+    /// Write (or overwrite) the report of a dry run
+    pub fn save_dry_run_report(&self, report: &DryRunReport) -> Result<PathBuf> {
+        let dir = self.dry_run_dir(&report.workflow_id);
+        fs::create_dir_all(&dir)?;
+
+        let json = serde_json::to_string_pretty(report)
+            .map_err(|e| LennardError::Serialization(format!("Failed to serialize dry run report: {}", e)))?;
+
+        let path = dir.join(DRY_RUN_REPORT_FILE);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, &path)?;
+
+        Ok(path)
+    }
+
+    /// Get the report of a dry run
+    pub fn get_dry_run_report(&self, workflow_id: &str) -> Result<Option<DryRunReport>> {
+        let path = self.dry_run_dir(workflow_id).join(DRY_RUN_REPORT_FILE);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = fs::read_to_string(&path)?;
+        let report = serde_json::from_str(&json)
+            .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize dry run report: {}", e)))?;
+
+        Ok(Some(report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow::approval_types::{TaskId, UserId, WorkflowTrigger};
+    use chrono::{Duration, Utc};
+    use tempfile::TempDir;
+
+    fn test_trigger(trigger_id: &str, user: i64) -> WorkflowTrigger {
+        WorkflowTrigger {
+            schema_version: crate::workflow::TRIGGER_SCHEMA_VERSION,
+            trigger_id: trigger_id.to_string(),
+            requested_by: UserId::new(user),
+            requested_at: Utc::now(),
+            max_tasks: 2,
+            dry_run: false,
+            task_selection: None,
+            task_ids: Vec::new(),
+            processed: false,
+            processed_at: None,
+            result: None,
+        }
+    }
+
+    #[test]
+    fn test_save_and_get_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = WorkflowRunStore::new(temp_dir.path()).unwrap();
+
+        let mut run = WorkflowRun::start(&test_trigger("run-1", 42));
+        let mut task = TaskResult::new(TaskId::new("task-1".to_string()));
+        task.contact_name = Some("Jane Doe".to_string());
+        task.fail("Step 3 failed".to_string());
+        run.record_task(task);
+        run.finish(WorkflowStatus::Failed, None);
+        store.save_run(&run).unwrap();
+
+        assert!(temp_dir.path().join("runs").join("run_run-1.json").exists());
+
+        let loaded = store.get_run("run-1").unwrap().unwrap();
+        assert_eq!(loaded.status, WorkflowStatus::Failed);
+        assert_eq!(loaded.requested_by, UserId::new(42));
+        assert_eq!(loaded.task_results.len(), 1);
+        assert_eq!(loaded.task_results[0].error_message.as_deref(), Some("Step 3 failed"));
+        assert!(loaded.finished_at.is_some());
+
+        assert!(store.get_run("unknown").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_record_task_replaces_existing_result() {
+        let mut run = WorkflowRun::start(&test_trigger("run-2", 1));
+
+        run.record_task(TaskResult::new(TaskId::new("task-1".to_string())));
+        let mut done = TaskResult::new(TaskId::new("task-1".to_string()));
+        done.succeed();
+        run.record_task(done);
+
+        assert_eq!(run.task_results.len(), 1);
+        assert_eq!(run.completed_tasks(), 1);
+    }
+
+    #[test]
+    fn test_run_id_cannot_escape_runs_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = WorkflowRunStore::new(temp_dir.path()).unwrap();
+
+        let run = WorkflowRun::start(&test_trigger("../../etc/passwd", 1));
+        store.save_run(&run).unwrap();
+
+        assert!(store.get_run("../../etc/passwd").unwrap().is_some());
+        assert_eq!(std::fs::read_dir(temp_dir.path().join("runs")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_run_ids_never_share_a_record() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = WorkflowRunStore::new(temp_dir.path()).unwrap();
+
+        // Both sanitize to `run_a_b`
+        store.create_run(&WorkflowRun::start(&test_trigger("run/a.b", 1))).unwrap();
+        store.create_run(&WorkflowRun::start(&test_trigger("run:a:b", 2))).unwrap();
+        assert_eq!(store.get_run("run/a.b").unwrap().unwrap().requested_by, UserId::new(1));
+        assert_eq!(store.get_run("run:a:b").unwrap().unwrap().requested_by, UserId::new(2));
+
+        // A reused id keeps the record of the earlier run
+        let reused = store.create_run(&WorkflowRun::start(&test_trigger("run/a.b", 3)));
+        assert!(matches!(reused, Err(LennardError::Validation(_))));
+        assert_eq!(store.get_run("run/a.b").unwrap().unwrap().requested_by, UserId::new(1));
+        assert_eq!(std::fs::read_dir(temp_dir.path().join("runs")).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_list_runs_filter_and_pagination() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = WorkflowRunStore::new(temp_dir.path()).unwrap();
+        let base = Utc::now() - Duration::days(10);
+
+        for i in 0..5 {
+            let mut run = WorkflowRun::start(&test_trigger(&format!("run-{}", i), if i % 2 == 0 { 1 } else { 2 }));
+            run.started_at = base + Duration::days(i);
+            if i < 4 {
+                run.finish(WorkflowStatus::Completed, None);
+            }
+            store.save_run(&run).unwrap();
+        }
+
+        // Newest first
+        let all = store.list_runs(&WorkflowRunFilter::default(), 0, 10).unwrap();
+        assert_eq!(all.total_count, 5);
+        assert_eq!(all.runs[0].workflow_id, "run-4");
+        assert!(all.next_offset.is_none());
+
+        // Pagination
+        let page = store.list_runs(&WorkflowRunFilter::default(), 0, 2).unwrap();
+        assert_eq!(page.runs.len(), 2);
+        assert_eq!(page.next_offset, Some(2));
+        let last = store.list_runs(&WorkflowRunFilter::default(), 4, 2).unwrap();
+        assert_eq!(last.runs.len(), 1);
+        assert!(last.next_offset.is_none());
+
+        // Requested by
+        let filter = WorkflowRunFilter { requested_by: Some(UserId::new(2)), ..Default::default() };
+        assert_eq!(store.list_runs(&filter, 0, 10).unwrap().total_count, 2);
+
+        // Status and active only
+        let filter = WorkflowRunFilter { status: Some(WorkflowStatus::Completed), ..Default::default() };
+        assert_eq!(store.list_runs(&filter, 0, 10).unwrap().total_count, 4);
+        let filter = WorkflowRunFilter { active_only: true, ..Default::default() };
+        let active = store.list_runs(&filter, 0, 10).unwrap();
+        assert_eq!(active.total_count, 1);
+        assert_eq!(active.runs[0].workflow_id, "run-4");
+
+        // Date range
+        let filter = WorkflowRunFilter {
+            started_from: Some(base + Duration::days(1)),
+            started_to: Some(base + Duration::days(3)),
+            ..Default::default()
+        };
+        assert_eq!(store.list_runs(&filter, 0, 10).unwrap().total_count, 3);
+    }
+
+    #[test]
+    fn test_dry_run_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = WorkflowRunStore::new(temp_dir.path()).unwrap();
+        let task_id = TaskId::new("task/1".to_string());
+
+        let pdf_file = store.save_dry_run_pdf("dry-1", &task_id, b"%PDF-1.4").unwrap();
+        // `task_1` plus 8 hex digits of the original id's hash
+        assert!(pdf_file.starts_with("task_task_1_") && pdf_file.ends_with(".pdf"));
+        assert_eq!(pdf_file.len(), "task_task_1_.pdf".len() + 8);
+        assert!(temp_dir.path().join("dry_runs").join("dry-1").join(&pdf_file).exists());
+
+        let mut report = DryRunReport::new("dry-1".to_string());
+        let mut task = DryRunTaskReport::new(task_id);
+        task.pdf_file = Some(pdf_file);
+        task.skipped_actions.push("Create approval".to_string());
+        task.success = true;
+        report.tasks.push(task);
+        store.save_dry_run_report(&report).unwrap();
+
+        let loaded = store.get_dry_run_report("dry-1").unwrap().unwrap();
+        assert_eq!(loaded.tasks.len(), 1);
+        assert!(loaded.tasks[0].success);
+        assert_eq!(loaded.tasks[0].skipped_actions, vec!["Create approval".to_string()]);
+        assert!(store.get_dry_run_report("unknown").unwrap().is_none());
+    }
+}
@@ -0,0 +1,333 @@
+//! Strongly typed workflow run records
+//! Every trigger processed by the orchestrator is persisted as a WorkflowRun
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use super::approval_types::{ApprovalId, ContactId, TaskId, UserId, WorkflowTrigger};
+use crate::types::{LetterContent, MailingAddress};
+use std::path::PathBuf;
+
+/// Lifecycle status of a workflow run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WorkflowStatus {
+    /// Run recorded but processing has not started yet
+    Pending,
+    /// Tasks are currently being processed
+    Running,
+    /// Processing finished and at least one task succeeded (or no tasks were available)
+    Completed,
+    /// Task loading failed or every task failed
+    Failed,
+    /// Processing was stopped by a CancelWorkflow request
+    Cancelled,
+}
+
+impl WorkflowStatus {
+    /// Whether the run is still being worked on
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Pending | Self::Running)
+    }
+}
+
+/// Processing state of a single task within a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum TaskState {
+    /// Selected for the run but not picked up yet
+    #[default]
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    /// Stopped part-way by a cancellation
+    Aborted,
+    /// The run was cancelled before the task was picked up
+    NotStarted,
+}
+
+/// A failed attempt of a step that was retried
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRetry {
+    /// `WorkflowSteps` method that failed
+    pub method: String,
+    /// Failed attempt, starting at 1
+    pub attempt: u32,
+    pub error: String,
+    /// Backoff before the next attempt
+    pub delay_ms: u64,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Result of processing a single task within a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskResult {
+    pub task_id: TaskId,
+    #[serde(default)]
+    pub state: TaskState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_id: Option<ContactId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company_name: Option<String>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    /// Approval created for this task (set once step 5a succeeded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_id: Option<ApprovalId>,
+    /// Set once the task was marked "In Progress" in Zoho
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub claimed: bool,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Transient step failures that were retried
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retries: Vec<StepRetry>,
+    /// Step the task failed at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_step: Option<String>,
+}
+
+impl TaskResult {
+    /// Start tracking a queued task
+    pub fn new(task_id: TaskId) -> Self {
+        Self {
+            task_id,
+            state: TaskState::Queued,
+            contact_id: None,
+            contact_name: None,
+            company_name: None,
+            success: false,
+            error_message: None,
+            approval_id: None,
+            claimed: false,
+            started_at: Utc::now(),
+            finished_at: None,
+            retries: Vec::new(),
+            failed_step: None,
+        }
+    }
+
+    /// Mark the task as picked up for processing
+    pub fn start(&mut self) {
+        self.state = TaskState::Running;
+        self.started_at = Utc::now();
+    }
+
+    /// Mark the task as successfully processed
+    pub fn succeed(&mut self) {
+        self.state = TaskState::Succeeded;
+        self.success = true;
+        self.error_message = None;
+        self.finished_at = Some(Utc::now());
+    }
+
+    /// Mark the task as failed with the given error
+    pub fn fail(&mut self, error_message: String) {
+        self.state = TaskState::Failed;
+        self.success = false;
+        self.error_message = Some(error_message);
+        self.finished_at = Some(Utc::now());
+    }
+
+    /// Mark the task as stopped part-way by a cancellation
+    pub fn abort(&mut self, reason: String) {
+        self.state = TaskState::Aborted;
+        self.success = false;
+        self.error_message = Some(reason);
+        self.finished_at = Some(Utc::now());
+    }
+
+    /// Mark the task as never picked up because the run was cancelled
+    pub fn skip(&mut self, reason: String) {
+        self.state = TaskState::NotStarted;
+        self.success = false;
+        self.error_message = Some(reason);
+    }
+}
+
+/// Persisted record of a single workflow trigger being processed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRun {
+    /// Same as the trigger_id of the originating WorkflowTrigger
+    pub workflow_id: String,
+    pub requested_by: UserId,
+    pub requested_at: DateTime<Utc>,
+    pub max_tasks: u32,
+    pub dry_run: bool,
+    pub status: WorkflowStatus,
+    pub task_results: Vec<TaskResult>,
+    /// Number of tasks selected for this run
+    pub total_tasks: u32,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    /// Folder holding the report and PDFs of a dry run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run_output: Option<PathBuf>,
+}
+
+impl WorkflowRun {
+    /// Create a running record for the given trigger
+    pub fn start(trigger: &WorkflowTrigger) -> Self {
+        let now = Utc::now();
+
+        Self {
+            workflow_id: trigger.trigger_id.clone(),
+            requested_by: trigger.requested_by,
+            requested_at: trigger.requested_at,
+            max_tasks: trigger.max_tasks,
+            dry_run: trigger.dry_run,
+            status: WorkflowStatus::Running,
+            task_results: Vec::new(),
+            total_tasks: 0,
+            started_at: now,
+            finished_at: None,
+            updated_at: now,
+            error_message: None,
+            dry_run_output: None,
+        }
+    }
+
+    /// Number of tasks that were processed successfully
+    pub fn completed_tasks(&self) -> u32 {
+        self.task_results.iter().filter(|r| r.success).count() as u32
+    }
+
+    /// Record the result of a task, replacing an earlier result for the same task
+    pub fn record_task(&mut self, result: TaskResult) {
+        match self.task_results.iter_mut().find(|r| r.task_id == result.task_id) {
+            Some(existing) => *existing = result,
+            None => self.task_results.push(result),
+        }
+        self.updated_at = Utc::now();
+    }
+
+    /// Finish the run with a terminal status
+    pub fn finish(&mut self, status: WorkflowStatus, error_message: Option<String>) {
+        let now = Utc::now();
+        self.status = status;
+        self.error_message = error_message;
+        self.finished_at = Some(now);
+        self.updated_at = now;
+    }
+}
+
+/// Filter for listing workflow runs - all fields are optional and combined with AND
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowRunFilter {
+    pub status: Option<WorkflowStatus>,
+    pub requested_by: Option<UserId>,
+    /// Only runs started at or after this instant
+    pub started_from: Option<DateTime<Utc>>,
+    /// Only runs started at or before this instant
+    pub started_to: Option<DateTime<Utc>>,
+    /// Only runs that are still pending or running
+    pub active_only: bool,
+}
+
+impl WorkflowRunFilter {
+    /// Check whether a run matches this filter
+    pub fn matches(&self, run: &WorkflowRun) -> bool {
+        if let Some(status) = self.status {
+            if run.status != status {
+                return false;
+            }
+        }
+        if let Some(user) = self.requested_by {
+            if run.requested_by != user {
+                return false;
+            }
+        }
+        if let Some(from) = self.started_from {
+            if run.started_at < from {
+                return false;
+            }
+        }
+        if let Some(to) = self.started_to {
+            if run.started_at > to {
+                return false;
+            }
+        }
+        if self.active_only && !run.status.is_active() {
+            return false;
+        }
+        true
+    }
+}
+
+/// One page of workflow runs
+#[derive(Debug, Clone)]
+pub struct WorkflowRunPage {
+    pub runs: Vec<WorkflowRun>,
+    /// Number of runs matching the filter across all pages
+    pub total_count: usize,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<usize>,
+}
+
+/// What a dry run produced for a single task and what a real run would have done on top
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunTaskReport {
+    pub task_id: TaskId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company_name: Option<String>,
+    /// Address extracted from the dossiers that a real run would write back to Zoho
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_update: Option<MailingAddress>,
+    /// Final letter (possibly shortened to fit the page limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub letter: Option<LetterContent>,
+    /// Generated PDF, relative to the dry run folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pdf_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pdf_size_bytes: Option<usize>,
+    /// Side effects a real run would have performed
+    pub skipped_actions: Vec<String>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+impl DryRunTaskReport {
+    /// Start an empty report for a task
+    pub fn new(task_id: TaskId) -> Self {
+        Self {
+            task_id,
+            contact_name: None,
+            company_name: None,
+            address_update: None,
+            letter: None,
+            pdf_file: None,
+            pdf_size_bytes: None,
+            skipped_actions: Vec::new(),
+            success: false,
+            error_message: None,
+        }
+    }
+}
+
+/// Report written to the dry run folder as `report.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunReport {
+    pub workflow_id: String,
+    pub generated_at: DateTime<Utc>,
+    pub tasks: Vec<DryRunTaskReport>,
+}
+
+impl DryRunReport {
+    pub fn new(workflow_id: String) -> Self {
+        Self {
+            workflow_id,
+            generated_at: Utc::now(),
+            tasks: Vec::new(),
+        }
+    }
+}
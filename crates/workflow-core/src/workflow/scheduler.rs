@@ -0,0 +1,343 @@
+//! Built-in scheduler for recurring workflow triggers
+//!
+//! Every schedule from the config fires a `WorkflowTrigger` into `triggers/`, where the workflow
+//! monitor picks it up like any other trigger file. Trigger IDs are derived from the schedule name
+//! and the fire time, so an occurrence is never triggered twice - not even across restarts.
+
+use crate::config::{MissedRunPolicy, ScheduleConfig};
+use crate::constants::{SCHEDULE_CHECK_INTERVAL_SECS, SCHEDULE_MISFIRE_GRACE_SECS};
+use crate::error::{LennardError, Result};
+use crate::paths;
+use super::approval_types::{UserId, WorkflowTrigger};
+use super::schema::TRIGGER_SCHEMA_VERSION;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+/// A parsed schedule
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub config: ScheduleConfig,
+    cron: cron::Schedule,
+    timezone: Tz,
+}
+
+impl Schedule {
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self> {
+        let valid_name = !config.name.is_empty()
+            && config.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(LennardError::Config(format!(
+                "Invalid schedule name '{}': use letters, digits, '-' and '_' only", config.name
+            )));
+        }
+
+        if config.max_tasks == 0 {
+            return Err(LennardError::Config(format!("Schedule '{}': max_tasks must be at least 1", config.name)));
+        }
+
+        let cron = cron::Schedule::from_str(&config.cron)
+            .map_err(|e| LennardError::Config(format!("Schedule '{}': invalid cron expression '{}': {}", config.name, config.cron, e)))?;
+
+        let timezone = config.timezone.parse::<Tz>()
+            .map_err(|e| LennardError::Config(format!("Schedule '{}': unknown time zone '{}': {}", config.name, config.timezone, e)))?;
+
+        Ok(Self {
+            config: config.clone(),
+            cron,
+            timezone,
+        })
+    }
+
+    /// First fire time after `after`
+    pub fn next_fire_time(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.cron.after(&after.with_timezone(&self.timezone))
+            .next()
+            .map(|time| time.with_timezone(&Utc))
+    }
+
+    /// Number and latest of the fire times after `after` up to and including `until`
+    pub fn fire_times_between(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> (usize, Option<DateTime<Utc>>) {
+        self.cron.after(&after.with_timezone(&self.timezone))
+            .map(|time| time.with_timezone(&Utc))
+            .take_while(|time| *time <= until)
+            .fold((0, None), |(count, _), time| (count + 1, Some(time)))
+    }
+
+    /// Stable trigger ID of an occurrence
+    pub fn trigger_id(&self, fire_time: DateTime<Utc>) -> String {
+        format!("schedule_{}_{}", self.config.name, fire_time.format("%Y%m%dT%H%M%SZ"))
+    }
+}
+
+/// Persisted progress of a schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleState {
+    pub name: String,
+    /// Latest occurrence that was triggered or skipped
+    pub last_fire_time: Option<DateTime<Utc>>,
+    pub last_trigger_id: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A schedule with its next and last run, for inspection
+#[derive(Debug, Clone)]
+pub struct ScheduleStatus {
+    pub config: ScheduleConfig,
+    pub next_fire_time: Option<DateTime<Utc>>,
+    pub last_fire_time: Option<DateTime<Utc>>,
+    pub last_trigger_id: Option<String>,
+}
+
+/// Fires the configured schedules as trigger files
+pub struct WorkflowScheduler {
+    schedules: Vec<Schedule>,
+    state_dir: PathBuf,
+    triggers_dir: PathBuf,
+    /// Without a persisted state, nothing before this counts as missed
+    started_at: DateTime<Utc>,
+}
+
+impl WorkflowScheduler {
+    /// Create new WorkflowScheduler below the given data root
+    pub fn new<P: AsRef<Path>>(configs: &[ScheduleConfig], root_path: P) -> Result<Self> {
+        let schedules = configs.iter()
+            .map(Schedule::from_config)
+            .collect::<Result<Vec<_>>>()?;
+
+        let state_dir = root_path.as_ref().join(paths::SCHEDULES_DIR_NAME);
+        let triggers_dir = root_path.as_ref().join(paths::TRIGGERS_DIR_NAME);
+        fs::create_dir_all(&state_dir)?;
+        fs::create_dir_all(&triggers_dir)?;
+
+        Ok(Self {
+            schedules,
+            state_dir,
+            triggers_dir,
+            started_at: Utc::now(),
+        })
+    }
+
+    /// Start firing due schedules
+    pub async fn start(self: Arc<Self>) {
+        let enabled = self.schedules.iter().filter(|s| s.config.enabled).count();
+        log::info!("Starting workflow scheduler with {} enabled schedules", enabled);
+
+        loop {
+            if let Err(e) = self.fire_due(Utc::now()) {
+                log::error!("Failed to fire workflow schedules: {}", e);
+            }
+            sleep(Duration::from_secs(SCHEDULE_CHECK_INTERVAL_SECS)).await;
+        }
+    }
+
+    /// Write trigger files for all schedules due at `now` - returns the fired triggers
+    pub fn fire_due(&self, now: DateTime<Utc>) -> Result<Vec<WorkflowTrigger>> {
+        let mut fired = Vec::new();
+
+        for schedule in self.schedules.iter().filter(|s| s.config.enabled) {
+            let name = &schedule.config.name;
+            let mut state = self.state(name)?;
+
+            let after = state.last_fire_time.unwrap_or(self.started_at);
+            let (due, latest) = schedule.fire_times_between(after, now);
+            let Some(fire_time) = latest else {
+                continue;
+            };
+
+            let on_time = now - fire_time <= ChronoDuration::seconds(SCHEDULE_MISFIRE_GRACE_SECS);
+            let fire = on_time || schedule.config.missed_runs == MissedRunPolicy::CatchUp;
+            let missed = if fire { due - 1 } else { due };
+            if missed > 0 {
+                log::warn!("Schedule {}: skipping {} missed runs (missed_runs: {})", name, missed, schedule.config.missed_runs);
+            }
+
+            if fire {
+                let trigger = WorkflowTrigger {
+                    schema_version: TRIGGER_SCHEMA_VERSION,
+                    trigger_id: schedule.trigger_id(fire_time),
+                    requested_by: UserId::new(0),  // 0 = system-initiated
+                    requested_at: now,
+                    max_tasks: schedule.config.max_tasks,
+                    dry_run: schedule.config.dry_run,
+                    task_selection: schedule.config.task_selection.clone(),
+                    task_ids: Vec::new(),
+                    processed: false,
+                    processed_at: None,
+                    result: None,
+                };
+
+                if self.write_trigger(&trigger)? {
+                    log::info!("Schedule {}: triggered {} for up to {} tasks", name, trigger.trigger_id, trigger.max_tasks);
+                    state.last_trigger_id = Some(trigger.trigger_id.clone());
+                    fired.push(trigger);
+                } else {
+                    log::info!("Schedule {}: {} was already triggered", name, trigger.trigger_id);
+                }
+            }
+
+            state.last_fire_time = Some(fire_time);
+            state.updated_at = Utc::now();
+            self.save_state(&state)?;
+        }
+
+        Ok(fired)
+    }
+
+    /// All schedules with their next and last run
+    pub fn status(&self, now: DateTime<Utc>) -> Result<Vec<ScheduleStatus>> {
+        self.schedules.iter()
+            .map(|schedule| {
+                let state = self.state(&schedule.config.name)?;
+                Ok(ScheduleStatus {
+                    config: schedule.config.clone(),
+                    next_fire_time: if schedule.config.enabled { schedule.next_fire_time(now) } else { None },
+                    last_fire_time: state.last_fire_time,
+                    last_trigger_id: state.last_trigger_id,
+                })
+            })
+            .collect()
+    }
+
+    fn state_path(&self, name: &str) -> PathBuf {
+        self.state_dir.join(format!("schedule_{}.json", name))
+    }
+
+    fn state(&self, name: &str) -> Result<ScheduleState> {
+        let path = self.state_path(name);
+        if !path.exists() {
+            return Ok(ScheduleState {
+                name: name.to_string(),
+                last_fire_time: None,
+                last_trigger_id: None,
+                updated_at: Utc::now(),
+            });
+        }
+
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json)
+            .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize schedule state: {}", e)))
+    }
+
+    fn save_state(&self, state: &ScheduleState) -> Result<()> {
+        let json = serde_json::to_string_pretty(state)
+            .map_err(|e| LennardError::Serialization(format!("Failed to serialize schedule state: {}", e)))?;
+
+        let path = self.state_path(&state.name);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Write a trigger file unless the trigger is already queued, processed or failed
+    fn write_trigger(&self, trigger: &WorkflowTrigger) -> Result<bool> {
+        let file_name = format!("trigger_{}.json", trigger.trigger_id);
+        let known = [
+            self.triggers_dir.clone(),
+            self.triggers_dir.join(paths::PROCESSED_DIR_NAME),
+            self.triggers_dir.join(paths::FAILED_DIR_NAME),
+        ];
+        if known.iter().any(|dir| dir.join(&file_name).exists()) {
+            return Ok(false);
+        }
+
+        let json = serde_json::to_string_pretty(trigger)
+            .map_err(|e| LennardError::Serialization(format!("Failed to serialize trigger: {}", e)))?;
+
+        // Write outside triggers/ first - the workflow monitor processes every file that appears there
+        let tmp_path = self.state_dir.join(format!("{}.tmp", file_name));
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, self.triggers_dir.join(&file_name))?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn weekday_mornings(missed_runs: MissedRunPolicy) -> ScheduleConfig {
+        ScheduleConfig {
+            name: "weekday-mornings".to_string(),
+            cron: "0 0 9 * * Mon-Fri".to_string(),
+            timezone: "Europe/Berlin".to_string(),
+            max_tasks: 5,
+            dry_run: false,
+            missed_runs,
+            enabled: true,
+            task_selection: None,
+        }
+    }
+
+    fn scheduler(dir: &TempDir, config: ScheduleConfig, started_at: DateTime<Utc>) -> WorkflowScheduler {
+        let mut scheduler = WorkflowScheduler::new(&[config], dir.path()).unwrap();
+        scheduler.started_at = started_at;
+        scheduler
+    }
+
+    #[test]
+    fn test_schedule_uses_its_time_zone() {
+        let schedule = Schedule::from_config(&weekday_mornings(MissedRunPolicy::Skip)).unwrap();
+
+        // Friday 2025-01-10 12:00 UTC - next run is Monday 09:00 Berlin (CET = UTC+1)
+        let friday_noon = Utc.with_ymd_and_hms(2025, 1, 10, 12, 0, 0).unwrap();
+        let next = schedule.next_fire_time(friday_noon).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 13, 8, 0, 0).unwrap());
+        assert_eq!(schedule.trigger_id(next), "schedule_weekday-mornings_20250113T080000Z");
+
+        let invalid = ScheduleConfig { cron: "every morning".to_string(), ..weekday_mornings(MissedRunPolicy::Skip) };
+        assert!(Schedule::from_config(&invalid).is_err());
+        let unknown_zone = ScheduleConfig { timezone: "Europe/Atlantis".to_string(), ..weekday_mornings(MissedRunPolicy::Skip) };
+        assert!(Schedule::from_config(&unknown_zone).is_err());
+    }
+
+    #[test]
+    fn test_due_schedule_fires_once() {
+        let dir = TempDir::new().unwrap();
+        let monday_early = Utc.with_ymd_and_hms(2025, 1, 13, 7, 0, 0).unwrap();
+        let scheduler = scheduler(&dir, weekday_mornings(MissedRunPolicy::Skip), monday_early);
+
+        assert!(scheduler.fire_due(monday_early).unwrap().is_empty());
+
+        let monday_nine = Utc.with_ymd_and_hms(2025, 1, 13, 8, 0, 30).unwrap();
+        let fired = scheduler.fire_due(monday_nine).unwrap();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].max_tasks, 5);
+        assert!(dir.path().join("triggers").join(format!("trigger_{}.json", fired[0].trigger_id)).exists());
+
+        // The same occurrence never fires twice, even after a restart
+        assert!(scheduler.fire_due(monday_nine).unwrap().is_empty());
+        let restarted = WorkflowScheduler::new(&[weekday_mornings(MissedRunPolicy::Skip)], dir.path()).unwrap();
+        assert!(restarted.fire_due(monday_nine).unwrap().is_empty());
+
+        let status = restarted.status(monday_nine).unwrap();
+        assert_eq!(status[0].last_trigger_id.as_deref(), Some(fired[0].trigger_id.as_str()));
+        assert_eq!(status[0].next_fire_time, Some(Utc.with_ymd_and_hms(2025, 1, 14, 8, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_missed_runs_follow_policy() {
+        let friday = Utc.with_ymd_and_hms(2025, 1, 10, 12, 0, 0).unwrap();
+        let wednesday = Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap();
+
+        // Down from Friday to Wednesday noon: Monday to Wednesday 09:00 were missed
+        let dir = TempDir::new().unwrap();
+        let skip = scheduler(&dir, weekday_mornings(MissedRunPolicy::Skip), friday);
+        assert!(skip.fire_due(wednesday).unwrap().is_empty());
+        assert_eq!(skip.status(wednesday).unwrap()[0].last_fire_time, Some(Utc.with_ymd_and_hms(2025, 1, 15, 8, 0, 0).unwrap()));
+
+        let dir = TempDir::new().unwrap();
+        let catch_up = scheduler(&dir, weekday_mornings(MissedRunPolicy::CatchUp), friday);
+        let fired = catch_up.fire_due(wednesday).unwrap();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].trigger_id, "schedule_weekday-mornings_20250115T080000Z");
+    }
+}
@@ -0,0 +1,425 @@
+//! Schema versions of persisted approvals and triggers
+//!
+//! Approval and trigger JSON carries a `schema_version`. Older documents are upgraded through a
+//! chain of migrations on the raw JSON whenever they are read, so the typed structs only ever
+//! see the current layout. Approval migrations may store content in the blob store.
+//! [`migrate_data_dir`] rewrites a whole data directory in place.
+
+use crate::error::{LennardError, Result};
+use crate::paths;
+use super::approval_types::{ApprovalData, ApprovalState, WorkflowTrigger};
+use super::blob_store::BlobStore;
+use base64::Engine as _;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Upgrade of a document by one version, with the context the migrations of its kind need
+type Migration<C> = fn(&mut Map<String, Value>, &C) -> Result<()>;
+
+/// `APPROVAL_MIGRATIONS[n]` upgrades an approval from version n to n + 1
+const APPROVAL_MIGRATIONS: &[Migration<BlobStore>] = &[approval_v1, approval_v2];
+
+/// `TRIGGER_MIGRATIONS[n]` upgrades a trigger from version n to n + 1
+const TRIGGER_MIGRATIONS: &[Migration<()>] = &[trigger_v1];
+
+pub const APPROVAL_SCHEMA_VERSION: u32 = APPROVAL_MIGRATIONS.len() as u32;
+pub const TRIGGER_SCHEMA_VERSION: u32 = TRIGGER_MIGRATIONS.len() as u32;
+
+const VERSION_FIELD: &str = "schema_version";
+
+/// Longest value shown in a diff line - PDFs and dossiers are cut off
+const DIFF_VALUE_CHARS: usize = 80;
+
+fn unescape_letter(letter: &mut Value) {
+    for field in ["subject", "greeting", "body"] {
+        if let Some(Value::String(text)) = letter.get_mut(field) {
+            if text.contains("\\n") {
+                *text = text.replace("\\n", "\n");
+            }
+        }
+    }
+}
+
+/// v1: letters with newlines stored as literal `\n` get real ones, and approvals from before
+/// PDFs were kept per iteration get their PDF on the current iteration
+fn approval_v1(approval: &mut Map<String, Value>, _: &BlobStore) -> Result<()> {
+    if let Some(letter) = approval.get_mut("current_letter") {
+        unescape_letter(letter);
+    }
+
+    let pdf = approval.get("pdf_base64").filter(|pdf| !pdf.is_null()).cloned();
+    if let Some(Value::Array(history)) = approval.get_mut("letter_history") {
+        for entry in history.iter_mut() {
+            if let Some(content) = entry.get_mut("content") {
+                unescape_letter(content);
+            }
+        }
+        if let (Some(pdf), Some(Value::Object(current))) = (pdf, history.last_mut()) {
+            if current.get("pdf_base64").is_none_or(Value::is_null) {
+                current.insert("pdf_base64".to_string(), pdf);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn blob_value(blobs: &BlobStore, content: &[u8]) -> Result<Value> {
+    serde_json::to_value(blobs.put(content)?)
+        .map_err(|e| LennardError::Serialization(format!("Failed to serialize blob reference: {}", e)))
+}
+
+/// Replace an inline base64 `pdf_base64` by a `pdf` blob reference
+fn externalize_pdf(object: &mut Map<String, Value>, blobs: &BlobStore) -> Result<()> {
+    if let Some(Value::String(pdf_base64)) = object.remove("pdf_base64") {
+        let pdf = base64::engine::general_purpose::STANDARD.decode(pdf_base64)
+            .map_err(|e| LennardError::Deserialization(format!("Failed to decode PDF: {}", e)))?;
+        object.insert("pdf".to_string(), blob_value(blobs, &pdf)?);
+    }
+    Ok(())
+}
+
+/// v2: PDFs and dossiers move into the blob store, the approval keeps references
+fn approval_v2(approval: &mut Map<String, Value>, blobs: &BlobStore) -> Result<()> {
+    externalize_pdf(approval, blobs)?;
+    if let Some(Value::Array(history)) = approval.get_mut("letter_history") {
+        for entry in history.iter_mut().filter_map(Value::as_object_mut) {
+            externalize_pdf(entry, blobs)?;
+        }
+    }
+
+    for field in ["person_dossier", "company_dossier"] {
+        let dossier = match approval.get(field) {
+            Some(Value::String(dossier)) => blob_value(blobs, dossier.as_bytes())?,
+            _ => continue,
+        };
+        approval.insert(field.to_string(), dossier);
+    }
+    Ok(())
+}
+
+/// v1: drops `task_id`, which predates `task_ids` and was never read
+fn trigger_v1(trigger: &mut Map<String, Value>, _: &()) -> Result<()> {
+    trigger.remove("task_id");
+    Ok(())
+}
+
+/// Run the migrations a document is missing - returns the version it had
+fn upgrade<C>(kind: &str, value: &mut Value, migrations: &[Migration<C>], context: &C) -> Result<u32> {
+    let Some(object) = value.as_object_mut() else {
+        return Err(LennardError::Deserialization(format!("The {} is not a JSON object", kind)));
+    };
+
+    let version = object.get(VERSION_FIELD).and_then(Value::as_u64).unwrap_or(0) as usize;
+    if version > migrations.len() {
+        return Err(LennardError::Deserialization(format!(
+            "The {} has schema version {}, newer than the supported {}", kind, version, migrations.len()
+        )));
+    }
+
+    for migration in &migrations[version..] {
+        migration(object, context)?;
+    }
+    object.insert(VERSION_FIELD.to_string(), Value::from(migrations.len()));
+    Ok(version as u32)
+}
+
+/// Upgrade approval JSON to [`APPROVAL_SCHEMA_VERSION`] - returns the version it had
+pub fn upgrade_approval(value: &mut Value, blobs: &BlobStore) -> Result<u32> {
+    upgrade("approval", value, APPROVAL_MIGRATIONS, blobs)
+}
+
+/// Upgrade trigger JSON to [`TRIGGER_SCHEMA_VERSION`] - returns the version it had
+pub fn upgrade_trigger(value: &mut Value) -> Result<u32> {
+    upgrade("trigger", value, TRIGGER_MIGRATIONS, &())
+}
+
+fn parse<T: DeserializeOwned, C>(kind: &str, json: &str, migrations: &[Migration<C>], context: &C) -> Result<(u32, T)> {
+    let mut value: Value = serde_json::from_str(json)
+        .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize {}: {}", kind, e)))?;
+    let version = upgrade(kind, &mut value, migrations, context)?;
+    let parsed = serde_json::from_value(value)
+        .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize {}: {}", kind, e)))?;
+    Ok((version, parsed))
+}
+
+/// Read persisted approval JSON of any schema version - content that was inline goes to `blobs`
+pub fn parse_approval(json: &str, blobs: &BlobStore) -> Result<ApprovalData> {
+    parse("approval", json, APPROVAL_MIGRATIONS, blobs).map(|(_, approval)| approval)
+}
+
+/// Read persisted trigger JSON of any schema version
+pub fn parse_trigger(json: &str) -> Result<WorkflowTrigger> {
+    parse("trigger", json, TRIGGER_MIGRATIONS, &()).map(|(_, trigger)| trigger)
+}
+
+fn show(value: Option<&Value>) -> String {
+    let Some(value) = value else {
+        return "(none)".to_string();
+    };
+    let text = value.to_string();
+    if text.chars().count() > DIFF_VALUE_CHARS {
+        format!("{}...", text.chars().take(DIFF_VALUE_CHARS).collect::<String>())
+    } else {
+        text
+    }
+}
+
+fn diff_into(path: &str, before: Option<&Value>, after: Option<&Value>, changes: &mut Vec<String>) {
+    let child = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+            for key in keys {
+                diff_into(&child(key), before.get(key), after.get(key), changes);
+            }
+        }
+        (Some(Value::Array(before)), Some(Value::Array(after))) => {
+            for index in 0..before.len().max(after.len()) {
+                diff_into(&child(&index.to_string()), before.get(index), after.get(index), changes);
+            }
+        }
+        _ if before == after => {}
+        _ => changes.push(format!("{}: {} -> {}", path, show(before), show(after))),
+    }
+}
+
+/// Changed leaves between two JSON documents, one `path: old -> new` line each
+pub fn diff(before: &Value, after: &Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    diff_into("", Some(before), Some(after), &mut changes);
+    changes
+}
+
+/// A file [`migrate_data_dir`] upgraded - or would upgrade in a dry run
+#[derive(Debug, Clone)]
+pub struct UpgradedFile {
+    pub path: PathBuf,
+    pub from_version: u32,
+    pub changes: Vec<String>,
+}
+
+/// Outcome of [`migrate_data_dir`]
+#[derive(Debug, Default)]
+pub struct SchemaMigrationReport {
+    pub upgraded: Vec<UpgradedFile>,
+    /// Files already at the current version
+    pub current: usize,
+    /// Files that could not be read, with the reason
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Upgrade one file, writing it back in the layout of the typed struct
+fn upgrade_file<T: Serialize + DeserializeOwned, C>(
+    path: &Path,
+    kind: &str,
+    migrations: &[Migration<C>],
+    context: &C,
+    dry_run: bool,
+) -> Result<Option<UpgradedFile>> {
+    let json = fs::read_to_string(path)?;
+    let before: Value = serde_json::from_str(&json)
+        .map_err(|e| LennardError::Deserialization(format!("Failed to deserialize {}: {}", kind, e)))?;
+    let (from_version, parsed) = parse::<T, C>(kind, &json, migrations, context)?;
+    if from_version as usize == migrations.len() {
+        return Ok(None);
+    }
+
+    let after = serde_json::to_value(&parsed)
+        .map_err(|e| LennardError::Serialization(format!("Failed to serialize {}: {}", kind, e)))?;
+    if !dry_run {
+        let json = serde_json::to_string_pretty(&after)
+            .map_err(|e| LennardError::Serialization(format!("Failed to serialize {}: {}", kind, e)))?;
+        let tmp_path = path.with_extension("tmp");
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(json.as_bytes())?;
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+    }
+
+    Ok(Some(UpgradedFile {
+        path: path.to_path_buf(),
+        from_version,
+        changes: diff(&before, &after),
+    }))
+}
+
+fn files_with_prefix(dir: &Path, prefix: &str, suffixes: &[&str]) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if path.is_file() && name.starts_with(prefix) && suffixes.iter().any(|suffix| name.ends_with(suffix)) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Upgrade every approval and trigger file below `root` in place - with `dry_run` nothing is written
+pub fn migrate_data_dir<P: AsRef<Path>>(root: P, dry_run: bool) -> Result<SchemaMigrationReport> {
+    let root = root.as_ref();
+    let blobs = BlobStore::new(root)?;
+    let blobs = if dry_run { blobs.dry_run() } else { blobs };
+    let approval_dirs: BTreeSet<&str> = ApprovalState::ALL.iter().map(|state| state.directory_name()).collect();
+    let triggers_dir = root.join(paths::TRIGGERS_DIR_NAME);
+
+    let mut report = SchemaMigrationReport::default();
+    let mut record = |path: PathBuf, outcome: Result<Option<UpgradedFile>>| match outcome {
+        Ok(Some(upgraded)) => report.upgraded.push(upgraded),
+        Ok(None) => report.current += 1,
+        Err(e) => report.failed.push((path, e.to_string())),
+    };
+
+    for dir in approval_dirs {
+        for path in files_with_prefix(&root.join(dir), "approval_", &[".json", ".json.processing"])? {
+            let outcome = upgrade_file::<ApprovalData, _>(&path, "approval", APPROVAL_MIGRATIONS, &blobs, dry_run);
+            record(path, outcome);
+        }
+    }
+    for dir in [triggers_dir.clone(), triggers_dir.join(paths::PROCESSED_DIR_NAME), triggers_dir.join(paths::FAILED_DIR_NAME)] {
+        for path in files_with_prefix(&dir, "trigger_", &[".json"])? {
+            let outcome = upgrade_file::<WorkflowTrigger, _>(&path, "trigger", TRIGGER_MIGRATIONS, &(), dry_run);
+            record(path, outcome);
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow::blob_store::BlobRef;
+    use tempfile::TempDir;
+
+    const LEGACY_APPROVAL: &str = r#"{
+        "approval_id": "5f0c6d3e-8a44-4c1e-9d2b-1a7f3c9e0b11",
+        "task_id": "task-1",
+        "contact_id": "contact-1",
+        "state": "PendingApproval",
+        "recipient_name": "John Doe",
+        "recipient_email": null,
+        "recipient_title": null,
+        "company_name": "Test Company",
+        "current_letter": {
+            "subject": "Subject",
+            "greeting": "Dear John",
+            "body": "First\\n\\nSecond",
+            "sender_name": "Sender",
+            "recipient_name": "John Doe",
+            "company_name": "Test Company"
+        },
+        "letter_history": [{
+            "iteration": 1,
+            "content": {
+                "subject": "Subject",
+                "greeting": "Dear John",
+                "body": "First\\n\\nSecond",
+                "sender_name": "Sender",
+                "recipient_name": "John Doe",
+                "company_name": "Test Company"
+            },
+            "feedback": null,
+            "created_at": "2025-01-01T00:00:00Z"
+        }],
+        "requested_at": "2025-01-01T00:00:00Z",
+        "requested_by": 1,
+        "telegram_message_id": null,
+        "telegram_chat_id": null,
+        "updated_at": "2025-01-01T00:00:00Z",
+        "mailing_address": null,
+        "pdf_base64": "JVBERi0=",
+        "person_dossier": "About John Doe"
+    }"#;
+
+    const LEGACY_TRIGGER: &str = r#"{
+        "trigger_id": "test-456",
+        "task_id": "never_read",
+        "requested_by": 67890,
+        "requested_at": "2025-01-01T00:00:00Z",
+        "max_tasks": 2,
+        "dry_run": false,
+        "processed": false,
+        "processed_at": null,
+        "result": null
+    }"#;
+
+    #[test]
+    fn test_legacy_approval_is_upgraded_on_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let blobs = BlobStore::new(temp_dir.path()).unwrap();
+        let approval = parse_approval(LEGACY_APPROVAL, &blobs).unwrap();
+
+        assert_eq!(approval.schema_version, APPROVAL_SCHEMA_VERSION);
+        assert_eq!(approval.current_letter.body, "First\n\nSecond");
+        assert_eq!(approval.letter_history[0].content.body, "First\n\nSecond");
+        assert_eq!(approval.pdf_bytes(&blobs).unwrap().unwrap(), b"%PDF-");
+        assert_eq!(approval.letter_history[0].pdf, approval.pdf);
+        assert_eq!(approval.dossiers(&blobs).unwrap(), ("About John Doe".to_string(), String::new()));
+
+        // Current documents pass through unchanged
+        let json = serde_json::to_string(&approval).unwrap();
+        assert!(!json.contains("JVBERi0="));
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(upgrade_approval(&mut value, &blobs).unwrap(), APPROVAL_SCHEMA_VERSION);
+        assert_eq!(serde_json::from_value::<ApprovalData>(value).unwrap().current_letter.body, "First\n\nSecond");
+    }
+
+    #[test]
+    fn test_newer_schema_is_rejected() {
+        let json = format!(r#"{{"schema_version": {}, "trigger_id": "t"}}"#, TRIGGER_SCHEMA_VERSION + 1);
+        assert!(parse_trigger(&json).is_err());
+    }
+
+    #[test]
+    fn test_migrate_data_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let approval_path = temp_dir.path().join("pending_approval").join("approval_legacy.json");
+        let trigger_path = temp_dir.path().join("triggers").join("processed").join("trigger_test-456.json");
+        fs::create_dir_all(approval_path.parent().unwrap()).unwrap();
+        fs::create_dir_all(trigger_path.parent().unwrap()).unwrap();
+        fs::write(&approval_path, LEGACY_APPROVAL).unwrap();
+        fs::write(&trigger_path, LEGACY_TRIGGER).unwrap();
+        fs::write(temp_dir.path().join("pending_approval").join("approval_broken.json"), "{").unwrap();
+
+        // A dry run reports the changes without writing them
+        let report = migrate_data_dir(temp_dir.path(), true).unwrap();
+        assert_eq!(report.upgraded.len(), 2);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(fs::read_to_string(&approval_path).unwrap(), LEGACY_APPROVAL);
+
+        let approval = report.upgraded.iter().find(|file| file.path == approval_path).unwrap();
+        assert_eq!(approval.from_version, 0);
+        assert!(approval.changes.iter().any(|change| change.starts_with("current_letter.body: ")));
+        assert!(approval.changes.contains(&r#"pdf_base64: "JVBERi0=" -> (none)"#.to_string()));
+        assert!(approval.changes.iter().any(|change| change.starts_with("letter_history.0.pdf: (none) -> ")));
+        // Dry runs store no blobs
+        assert_eq!(fs::read_dir(temp_dir.path().join("data").join("blobs")).unwrap().count(), 0);
+        let trigger = report.upgraded.iter().find(|file| file.path == trigger_path).unwrap();
+        assert!(trigger.changes.contains(&r#"task_id: "never_read" -> (none)"#.to_string()));
+
+        let report = migrate_data_dir(temp_dir.path(), false).unwrap();
+        assert_eq!(report.upgraded.len(), 2);
+        let stored: Value = serde_json::from_str(&fs::read_to_string(&approval_path).unwrap()).unwrap();
+        assert_eq!(stored["schema_version"], APPROVAL_SCHEMA_VERSION);
+        assert_eq!(stored["current_letter"]["body"], "First\n\nSecond");
+        let pdf: BlobRef = serde_json::from_value(stored["pdf"].clone()).unwrap();
+        assert_eq!(BlobStore::new(temp_dir.path()).unwrap().get(&pdf).unwrap(), b"%PDF-");
+
+        // Everything is current afterwards
+        let report = migrate_data_dir(temp_dir.path(), false).unwrap();
+        assert!(report.upgraded.is_empty());
+        assert_eq!(report.current, 2);
+    }
+}
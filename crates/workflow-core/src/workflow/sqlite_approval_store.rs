@@ -0,0 +1,305 @@
+//! Approval store in an embedded SQLite database
+//!
+//! Approvals are kept as JSON next to indexed columns for their state, task and contact.
+//! Transitions run in immediate transactions, so concurrent writers - also from other
+//! processes - never apply two transitions to the same approval.
+
+use crate::error::{LennardError, Result};
+use super::approval_store::{ApprovalStore, Transition};
+use super::approval_types::*;
+use super::blob_store::BlobStore;
+use super::schema;
+use chrono::SecondsFormat;
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS approvals (
+        approval_id TEXT PRIMARY KEY NOT NULL,
+        state TEXT NOT NULL,
+        task_id TEXT NOT NULL,
+        contact_id TEXT NOT NULL,
+        requested_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS approvals_state ON approvals (state);
+    CREATE INDEX IF NOT EXISTS approvals_task_id ON approvals (task_id);
+    CREATE INDEX IF NOT EXISTS approvals_contact_id ON approvals (contact_id);
+";
+
+/// How long a write waits for another process holding the database lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Approvals in a SQLite database file
+pub struct SqliteApprovalStore {
+    path: PathBuf,
+    connection: Mutex<Connection>,
+    /// Receives the PDFs and dossiers of approvals written before they were kept as blobs
+    blobs: BlobStore,
+}
+
+fn to_json(approval: &ApprovalData) -> Result<String> {
+    serde_json::to_string(approval)
+        .map_err(|e| LennardError::Serialization(format!("Failed to serialize approval: {}", e)))
+}
+
+fn parse_state(name: &str) -> Result<ApprovalState> {
+    ApprovalState::from_name(name)
+        .ok_or_else(|| LennardError::Deserialization(format!("Unknown approval state '{}'", name)))
+}
+
+impl SqliteApprovalStore {
+    /// Open the database at `path`, creating it and its schema if needed
+    pub fn open<P: AsRef<Path>>(path: P, blobs: BlobStore) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open(&path)?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            path,
+            connection: Mutex::new(connection),
+            blobs,
+        })
+    }
+
+    fn parse(&self, json: &str) -> Result<ApprovalData> {
+        schema::parse_approval(json, &self.blobs)
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        // A panic while holding the lock leaves no partial write behind - transactions roll back
+        self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn query(&self, sql: &str, param: &str) -> Result<Vec<ApprovalData>> {
+        let connection = self.connection();
+        let mut statement = connection.prepare_cached(sql)?;
+        let rows = statement.query_map([param], |row| row.get::<_, String>(0))?;
+
+        let mut approvals = Vec::new();
+        for json in rows {
+            let json = json?;
+            match self.parse(&json) {
+                Ok(approval) => approvals.push(approval),
+                Err(e) => log::warn!("Skipping unreadable approval in {:?}: {}", self.path, e),
+            }
+        }
+        Ok(approvals)
+    }
+}
+
+impl ApprovalStore for SqliteApprovalStore {
+    fn location(&self) -> &Path {
+        &self.path
+    }
+
+    fn insert(&self, approval: &ApprovalData, state: ApprovalState) -> Result<()> {
+        self.connection().execute(
+            "INSERT INTO approvals (approval_id, state, task_id, contact_id, requested_at, updated_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                approval.approval_id.as_str(),
+                state.name(),
+                approval.task_id.as_str(),
+                approval.contact_id.as_str(),
+                approval.requested_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+                approval.updated_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+                to_json(approval)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get(&self, approval_id: &ApprovalId) -> Result<Option<(ApprovalState, ApprovalData)>> {
+        let row = self.connection()
+            .query_row(
+                "SELECT state, data FROM approvals WHERE approval_id = ?1",
+                [approval_id.as_str()],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?;
+
+        match row {
+            Some((state, json)) => Ok(Some((parse_state(&state)?, self.parse(&json)?))),
+            None => Ok(None),
+        }
+    }
+
+    fn list_by_state(&self, state: ApprovalState) -> Result<Vec<ApprovalData>> {
+        self.query("SELECT data FROM approvals WHERE state = ?1 ORDER BY requested_at", state.name())
+    }
+
+    fn find_by_task(&self, task_id: &TaskId) -> Result<Vec<ApprovalData>> {
+        self.query("SELECT data FROM approvals WHERE task_id = ?1 ORDER BY requested_at", task_id.as_str())
+    }
+
+    fn find_by_contact(&self, contact_id: &ContactId) -> Result<Vec<ApprovalData>> {
+        self.query("SELECT data FROM approvals WHERE contact_id = ?1 ORDER BY requested_at", contact_id.as_str())
+    }
+
+    fn count_by_state(&self, state: ApprovalState) -> Result<usize> {
+        let count: i64 = self.connection().query_row(
+            "SELECT COUNT(*) FROM approvals WHERE state = ?1",
+            [state.name()],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    fn transition(
+        &self,
+        approval_id: &ApprovalId,
+        from: &[ApprovalState],
+        to: ApprovalState,
+        change: &mut dyn FnMut(&mut ApprovalData),
+    ) -> Result<Transition> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let row = transaction
+            .query_row(
+                "SELECT state, data FROM approvals WHERE approval_id = ?1",
+                [approval_id.as_str()],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?;
+        let Some((state, json)) = row else {
+            return Ok(Transition::NotFound);
+        };
+
+        let state = parse_state(&state)?;
+        if !from.contains(&state) {
+            return Ok(Transition::Skipped(state));
+        }
+
+        let mut approval = self.parse(&json)?;
+        change(&mut approval);
+
+        transaction.execute(
+            "UPDATE approvals SET state = ?2, task_id = ?3, contact_id = ?4, updated_at = ?5, data = ?6
+             WHERE approval_id = ?1",
+            params![
+                approval_id.as_str(),
+                to.name(),
+                approval.task_id.as_str(),
+                approval.contact_id.as_str(),
+                approval.updated_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+                to_json(&approval)?,
+            ],
+        )?;
+        transaction.commit()?;
+
+        Ok(Transition::Applied(Box::new(approval)))
+    }
+
+    fn remove(&self, approval_id: &ApprovalId, state: ApprovalState) -> Result<bool> {
+        let removed = self.connection().execute(
+            "DELETE FROM approvals WHERE approval_id = ?1 AND state = ?2",
+            params![approval_id.as_str(), state.name()],
+        )?;
+        Ok(removed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow::approval_store::{migrate_approvals, FileApprovalStore};
+    use tempfile::TempDir;
+
+    fn approval(task_id: &str, contact_id: &str) -> ApprovalData {
+        let mut approval = ApprovalData::test_fixture(task_id);
+        approval.contact_id = ContactId::new(contact_id.to_string());
+        approval
+    }
+
+    #[test]
+    fn test_transitions_and_lookups() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SqliteApprovalStore::open(temp_dir.path().join("approvals.db"), BlobStore::new(temp_dir.path()).unwrap()).unwrap();
+
+        let first = approval("task-1", "contact-1");
+        let second = approval("task-2", "contact-1");
+        store.insert(&first, ApprovalState::PendingApproval).unwrap();
+        store.insert(&second, ApprovalState::PendingApproval).unwrap();
+        assert!(store.insert(&first, ApprovalState::Approved).is_err());
+
+        let outcome = store.transition(
+            &first.approval_id,
+            &[ApprovalState::PendingApproval],
+            ApprovalState::AwaitingUserResponse,
+            &mut |approval| approval.mark_awaiting_response(),
+        ).unwrap();
+        assert!(matches!(outcome, Transition::Applied(ref a) if a.state == ApprovalState::AwaitingUserResponse));
+
+        // The second attempt finds the approval already moved on
+        let outcome = store.transition(
+            &first.approval_id,
+            &[ApprovalState::PendingApproval],
+            ApprovalState::AwaitingUserResponse,
+            &mut |_| panic!("must not be applied"),
+        ).unwrap();
+        assert!(matches!(outcome, Transition::Skipped(ApprovalState::AwaitingUserResponse)));
+        assert!(matches!(
+            store.transition(&ApprovalId::new(), &ApprovalState::ALL, ApprovalState::Failed, &mut |_| {}).unwrap(),
+            Transition::NotFound
+        ));
+
+        let (state, stored) = store.get(&first.approval_id).unwrap().unwrap();
+        assert_eq!(state, ApprovalState::AwaitingUserResponse);
+        assert!(stored.awaiting_response_since.is_some());
+
+        assert_eq!(store.count_by_state(ApprovalState::PendingApproval).unwrap(), 1);
+        assert_eq!(store.list_by_state(ApprovalState::AwaitingUserResponse).unwrap().len(), 1);
+        assert_eq!(store.find_by_contact(&ContactId::new("contact-1".to_string())).unwrap().len(), 2);
+        let by_task = store.find_by_task(&TaskId::new("task-2".to_string())).unwrap();
+        assert_eq!(by_task.len(), 1);
+        assert_eq!(by_task[0].approval_id, second.approval_id);
+
+        // Reopening keeps everything
+        drop(store);
+        let store = SqliteApprovalStore::open(temp_dir.path().join("approvals.db"), BlobStore::new(temp_dir.path()).unwrap()).unwrap();
+        assert_eq!(store.count_by_state(ApprovalState::AwaitingUserResponse).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_migrate_file_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = FileApprovalStore::new(temp_dir.path()).unwrap();
+
+        let pending = approval("task-1", "contact-1");
+        let mut sending = approval("task-2", "contact-2");
+        sending.mark_sending();
+        let mut sent = approval("task-3", "contact-3");
+        sent.mark_sent("job-1".to_string());
+        files.insert(&pending, ApprovalState::PendingApproval).unwrap();
+        files.insert(&sending, ApprovalState::Sending).unwrap();
+        files.insert(&sent, ApprovalState::Sent).unwrap();
+        std::fs::write(temp_dir.path().join("failed").join("not_an_approval.json"), "{}").unwrap();
+
+        let store = SqliteApprovalStore::open(temp_dir.path().join("approvals.db"), BlobStore::new(temp_dir.path()).unwrap()).unwrap();
+        let report = migrate_approvals(&files, &store).unwrap();
+        assert_eq!(report.imported, 3);
+        assert_eq!(report.skipped, 0);
+
+        assert_eq!(store.get(&pending.approval_id).unwrap().unwrap().0, ApprovalState::PendingApproval);
+        assert_eq!(store.get(&sending.approval_id).unwrap().unwrap().0, ApprovalState::Sending);
+        let (state, stored) = store.get(&sent.approval_id).unwrap().unwrap();
+        assert_eq!(state, ApprovalState::Sent);
+        assert_eq!(stored.letterexpress_job_id.as_deref(), Some("job-1"));
+
+        // Running it again imports nothing twice
+        let report = migrate_approvals(&files, &store).unwrap();
+        assert_eq!(report.imported, 0);
+        assert_eq!(report.skipped, 3);
+    }
+}
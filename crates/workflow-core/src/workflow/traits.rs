@@ -2,9 +2,11 @@
 
 use async_trait::async_trait;
 use crate::error::Result;
-use crate::types::{ZohoContact, LinkedInProfile, MailingAddress};
+use crate::config::TaskSelectionConfig;
+use crate::types::{ZohoContact, LinkedInProfile, MailingAddress, RenderedLetter};
 use crate::clients::DossierResult;
 use super::approval_types::{LetterContent, ApprovalState, ApprovalId};
+use tokio::sync::OwnedSemaphorePermit;
 use zoho_generated_types::TasksResponse;
 
 /// Trait defining the individual workflow steps with strongly-typed parameters
@@ -14,8 +16,8 @@ use zoho_generated_types::TasksResponse;
 #[async_trait]
 pub trait WorkflowSteps: Send + Sync {
     /// Load available tasks for processing - returns up to max_count tasks
-    /// This is the primary entry point for dynamic task selection
-    async fn load_available_tasks(&self, max_count: u32) -> Result<Vec<TasksResponse>>;
+    /// This is the primary entry point for dynamic task selection - without a selection the configured one is used
+    async fn load_available_tasks(&self, max_count: u32, selection: Option<&TaskSelectionConfig>) -> Result<Vec<TasksResponse>>;
     
     /// Step 1: Load task by ID - returns required TasksResponse (for single task processing)
     async fn load_task(&self, task_id: &str) -> Result<TasksResponse>;
@@ -26,6 +28,12 @@ pub trait WorkflowSteps: Send + Sync {
     /// Step 3: Load profile - requires ZohoContact, returns required LinkedInProfile
     async fn load_profile(&self, contact: &ZohoContact) -> Result<LinkedInProfile>;
     
+    /// Wait for the downstream capacity one attempt of a step method needs
+    /// The permit is held for the attempt - the orchestrator does not count the wait against the step timeout
+    async fn reserve_capacity(&self, _method: &str) -> Result<Option<OwnedSemaphorePermit>> {
+        Ok(None)
+    }
+    
     /// Step 4: Generate dossiers - requires profile and contact, returns extracted data
     async fn generate_dossiers(&self, profile: &LinkedInProfile, contact_id: &str) -> Result<DossierResult>;
     
@@ -44,8 +52,12 @@ pub trait WorkflowSteps: Send + Sync {
     /// Step 5: Generate letter - requires contact, profile and dossier, returns required LetterContent
     async fn generate_letter(&self, contact: &ZohoContact, profile: &LinkedInProfile, dossier: &DossierResult) -> Result<LetterContent>;
     
-    /// Step 6a: Start approval - creates and persists the approval request, returns approval ID
-    async fn approval_start(&self, task_id: &str, contact: &ZohoContact, profile: &LinkedInProfile, letter: &LetterContent, dossier: &DossierResult) -> Result<ApprovalId>;
+    /// Step 5.5: Render letter PDF - shortens the letter until it fits the page limit
+    /// Read-only: nothing is persisted or sent
+    async fn render_letter_pdf(&self, contact: &ZohoContact, profile: &LinkedInProfile, dossier: &DossierResult, letter: &LetterContent) -> Result<RenderedLetter>;
+    
+    /// Step 6a: Start approval - creates and persists the approval request for the rendered letter, returns approval ID
+    async fn approval_start(&self, task_id: &str, contact: &ZohoContact, rendered: &RenderedLetter, dossier: &DossierResult) -> Result<ApprovalId>;
     
     /// Step 6b: Request approval - sends the approval request notification, returns approval status
     /// Note: This sends the notification for an already-created approval
@@ -55,7 +67,11 @@ pub trait WorkflowSteps: Send + Sync {
     async fn send_pdf(&self, letter: &LetterContent, contact: &ZohoContact) -> Result<String>;
 
     /// Send pre-generated PDF binary - used for approved PDFs to avoid regeneration
-    async fn send_pdf_binary(&self, pdf_data: Vec<u8>, recipient_address: &MailingAddress) -> Result<String>;
+    /// The idempotency key is stored with the print job so it can be found again
+    async fn send_pdf_binary(&self, pdf_data: Vec<u8>, recipient_address: &MailingAddress, idempotency_key: &str) -> Result<String>;
+
+    /// Look up the print job sent with this idempotency key - returns its job ID if the letter went out
+    async fn find_sent_letter(&self, idempotency_key: &str) -> Result<Option<String>>;
 
     /// Send error notification via Telegram
     async fn send_error_notification(
@@ -86,6 +102,18 @@ pub trait WorkflowSteps: Send + Sync {
         task_id: &str
     ) -> Result<()>;
 
+    /// Reset Zoho task from "In Progress" to "Not started" (after a cancelled run or an expired approval)
+    async fn reset_task_status(
+        &self,
+        task_id: &str
+    ) -> Result<()>;
+
+    /// Withdraw an approval of an aborted task so it is never sent to Telegram or acted upon
+    async fn cancel_approval(
+        &self,
+        approval_id: &ApprovalId
+    ) -> Result<()>;
+
     /// Create a follow-up task for the contact
     async fn create_follow_up_task(
         &self,
@@ -123,4 +151,16 @@ pub trait WorkflowSteps: Send + Sync {
         &self,
         approval_data: &super::approval_types::ApprovalData
     ) -> Result<()>;
+    
+    /// Remind the Telegram chat of an approval that is still awaiting a response
+    async fn send_approval_reminder(
+        &self,
+        approval_data: &super::approval_types::ApprovalData
+    ) -> Result<()>;
+    
+    /// Tell the Telegram chat that an approval expired without a response
+    async fn send_approval_expired(
+        &self,
+        approval_data: &super::approval_types::ApprovalData
+    ) -> Result<()>;
 }
\ No newline at end of file
@@ -1,4 +1,16 @@
-use workflow_core::config::LennardConfig;
+use workflow_core::config::{ApprovalStoreBackend, LennardConfig, MissedRunPolicy, SubjectMatch, TaskSortOrder};
+
+/// Minimal valid credentials followed by `section`, e.g. `"workflow": { ... }`
+fn config_with(section: &str) -> String {
+    format!(r#"{{
+        "baserow": {{ "url": "https://api.baserow.io", "token": "token", "table_id": 123 }},
+        "nango_zoho_lennard": {{ "api_key": "key", "connection_id": "conn", "integration_id": "zoho-crm" }},
+        "letterexpress": {{ "api_key": "key", "username": "user", "api_url": "https://api.letterxpress.de" }},
+        "telegram": {{ "bot_token": "token", "chat_id": "123" }},
+        "openai": {{ "api_key": "key", "model": "gpt-4" }},
+        {}
+    }}"#, section)
+}
 
 #[test]
 fn test_parse_actual_credentials_json() {
@@ -93,6 +105,116 @@ fn test_parse_minimal_config() {
     assert_eq!(config.zoho.nango_connection_id, "conn");
     assert_eq!(config.zoho.base_url, "https://www.zohoapis.com", "Default Zoho base URL");
     assert_eq!(config.pdf_service.base_url, "http://localhost:8000", "Default PDF service URL");
+    assert_eq!(config.workflow.max_concurrent_tasks, 1, "Tasks run one at a time unless configured");
+    assert_eq!(config.workflow.service_limits.dossier, 2, "Default dossier limit");
+    assert_eq!(config.workflow.retry.default.max_attempts, 3, "Default retry attempts");
+    assert!(config.workflow.retry.steps.is_empty());
+    assert_eq!(config.workflow.approval_expiry.expire_after_hours, 168, "Approvals expire after a week by default");
+}
+
+#[test]
+fn test_parse_workflow_section() {
+    let json = config_with(r#"
+        "workflow": {
+            "max_concurrent_tasks": 8,
+            "service_limits": { "dossier": 3, "pdf_service": 1 }
+        }"#);
+    
+    let config = LennardConfig::from_json_str(&json).expect("Failed to parse workflow section");
+    
+    assert_eq!(config.workflow.max_concurrent_tasks, 8);
+    assert_eq!(config.workflow.service_limits.dossier, 3);
+    assert_eq!(config.workflow.service_limits.letter_service, 2, "Unset limits keep their default");
+    assert_eq!(config.workflow.service_limits.pdf_service, 1);
+    
+    // Zero would block processing forever
+    let zero = json.replace("\"max_concurrent_tasks\": 8", "\"max_concurrent_tasks\": 0");
+    assert!(LennardConfig::from_json_str(&zero).is_err());
+}
+
+#[test]
+fn test_parse_retry_policy() {
+    let json = config_with(r#"
+        "workflow": {
+            "retry": {
+                "default": { "max_attempts": 4, "timeout_secs": 120 },
+                "steps": { "generate_dossiers": { "timeout_secs": 900 } }
+            }
+        }"#);
+    
+    let config = LennardConfig::from_json_str(&json).expect("Failed to parse retry section");
+    let retry = &config.workflow.retry;
+    
+    assert_eq!(retry.default.max_attempts, 4);
+    assert_eq!(retry.default.initial_backoff_ms, 1000, "Unset fields keep their default");
+    
+    let dossiers = retry.policy_for("generate_dossiers");
+    assert_eq!(dossiers.timeout_secs, 900);
+    assert_eq!(dossiers.max_attempts, 4, "Step overrides inherit the default policy");
+    assert_eq!(retry.policy_for("load_contact").timeout_secs, 120);
+    
+    // Overrides must name a known step
+    let unknown = json.replace("generate_dossiers", "generate_dossier");
+    assert!(LennardConfig::from_json_str(&unknown).is_err());
+}
+
+#[test]
+fn test_parse_approval_expiry() {
+    let json = config_with(r#"
+        "workflow": {
+            "approval_expiry": { "reminder_after_hours": [4, 12], "expire_after_hours": 48 }
+        }"#);
+    
+    let config = LennardConfig::from_json_str(&json).expect("Failed to parse approval_expiry section");
+    let expiry = &config.workflow.approval_expiry;
+    
+    assert_eq!(expiry.reminder_after_hours, vec![4, 12]);
+    assert_eq!(expiry.expire_after_hours, 48);
+    assert_eq!(expiry.check_interval_secs, 300);
+    
+    // Reminders after the deadline would never be sent
+    let late_reminder = json.replace("[4, 12]", "[4, 72]");
+    assert!(LennardConfig::from_json_str(&late_reminder).is_err());
+}
+
+#[test]
+fn test_parse_approval_store() {
+    let json = config_with(r#"
+        "workflow": {
+            "approval_store": { "backend": "sqlite" }
+        }"#);
+    
+    let config = LennardConfig::from_json_str(&json).expect("Failed to parse approval_store section");
+    let store = &config.workflow.approval_store;
+    
+    assert_eq!(store.backend, ApprovalStoreBackend::Sqlite);
+    assert_eq!(store.sqlite_path("/data/workflows"), std::path::PathBuf::from("/data/workflows/approvals.db"));
+    
+    let default = LennardConfig::from_json_str(&json.replace(r#""approval_store": { "backend": "sqlite" }"#, "")).unwrap();
+    assert_eq!(default.workflow.approval_store.backend, ApprovalStoreBackend::File);
+}
+
+#[test]
+fn test_parse_retention() {
+    let json = config_with(r#"
+        "workflow": {
+            "retention": { "pdf_retention_days": 90, "log_retention_days": 0, "interval_hours": 6 }
+        }"#);
+    
+    let config = LennardConfig::from_json_str(&json).expect("Failed to parse retention section");
+    let retention = &config.workflow.retention;
+    
+    assert_eq!(retention.pdf_retention_days, 90);
+    assert_eq!(retention.log_retention_days, 0, "0 keeps logs forever");
+    assert_eq!(retention.interval_hours, 6);
+    assert_eq!(retention.archive_approvals_after_days, 30);
+    assert_eq!(retention.archive_retention_days, 365);
+    assert_eq!(retention.processed_retention_days, 30);
+    assert_eq!(retention.run_retention_days, 90);
+    assert_eq!(retention.checkpoint_retention_days, 30);
+    
+    let default = LennardConfig::from_json_str(&json.replace(r#", "interval_hours": 6"#, "")).unwrap();
+    assert_eq!(default.workflow.retention.interval_hours, 0, "The retention task is off unless enabled");
 }
 
 #[test]
@@ -127,4 +249,50 @@ fn test_validate_config() {
     let result = LennardConfig::from_json_str(json);
     assert!(result.is_err(), "Parsing should fail with empty required fields");
     assert!(result.unwrap_err().to_string().contains("required"), "Error should mention required fields");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_parse_schedules() {
+    let json = config_with(r#"
+        "schedules": [
+            { "name": "weekday-mornings", "cron": "0 0 9 * * Mon-Fri", "max_tasks": 5, "missed_runs": "catch_up" }
+        ]"#);
+    
+    let config = LennardConfig::from_json_str(&json).expect("Failed to parse schedules section");
+    assert_eq!(config.schedules.len(), 1);
+    
+    let schedule = &config.schedules[0];
+    assert_eq!(schedule.timezone, "Europe/Berlin", "Time zone defaults to Europe/Berlin");
+    assert_eq!(schedule.max_tasks, 5);
+    assert_eq!(schedule.missed_runs, MissedRunPolicy::CatchUp);
+    assert!(schedule.enabled);
+    assert!(!schedule.dry_run);
+    
+    let invalid_cron = json.replace("0 0 9 * * Mon-Fri", "every weekday");
+    assert!(LennardConfig::from_json_str(&invalid_cron).is_err());
+}
+
+#[test]
+fn test_parse_task_selection() {
+    let json = config_with(r#"
+        "workflow": {
+            "task_selection": {
+                "subject": { "starts_with": "Connect on" },
+                "owners": ["111", "222"],
+                "due_date": { "to_days": 0 },
+                "sort": "due_date_first"
+            }
+        }"#);
+    
+    let config = LennardConfig::from_json_str(&json).expect("Failed to parse task_selection section");
+    let selection = &config.workflow.task_selection;
+    
+    assert_eq!(selection.subject, Some(SubjectMatch::StartsWith("Connect on".to_string())));
+    assert_eq!(selection.owners, vec!["111", "222"]);
+    assert_eq!(selection.statuses, vec!["Not started"]);
+    assert_eq!(selection.due_date.and_then(|window| window.to_days), Some(0));
+    assert_eq!(selection.sort, TaskSortOrder::DueDateFirst);
+    
+    let empty_owner = json.replace(r#"["111", "222"]"#, r#"["111", ""]"#);
+    assert!(LennardConfig::from_json_str(&empty_owner).is_err());
+}
@@ -6,7 +6,7 @@
 //! Run with: cargo test --features integration --test test_real_services
 
 use std::path::PathBuf;
-use workflow_core::config::LennardConfig;
+use workflow_core::config::{LennardConfig, SubjectMatch, TaskSelectionConfig};
 
 /// Load real configuration from credentials.json in project root
 fn load_real_config() -> LennardConfig {
@@ -90,11 +90,13 @@ mod real_services_tests {
                 // Test basic CRM operations
                 println!("  Testing CRM operations...");
                 
-                // Test get_tasks with filters
-                let tasks_result = zoho_client.get_tasks(&[
-                    ("Subject", "Brief an"),
-                    ("Status", "Not Started")
-                ]).await;
+                // Test get_tasks with a selection
+                let tasks_result = zoho_client.get_tasks(&TaskSelectionConfig {
+                    subject: Some(SubjectMatch::Equals("Brief an".to_string())),
+                    owners: Vec::new(),
+                    statuses: vec!["Not Started".to_string()],
+                    ..TaskSelectionConfig::default()
+                }).await;
                 
                 match tasks_result {
                     Ok(tasks) => {
@@ -239,7 +241,12 @@ mod real_services_tests {
         assert!(!profiles.is_empty(), "Should have profiles to work with");
         
         println!("Step 3: Get tasks from Zoho CRM");
-        let tasks = zoho_client.get_tasks(&[("Status", "Not Started")]).await.unwrap_or_default();
+        let tasks = zoho_client.get_tasks(&TaskSelectionConfig {
+            subject: None,
+            owners: Vec::new(),
+            statuses: vec!["Not Started".to_string()],
+            ..TaskSelectionConfig::default()
+        }).await.unwrap_or_default();
         println!("  - Found {} tasks", tasks.len());
         
         println!("Step 4: Test service connectivity");
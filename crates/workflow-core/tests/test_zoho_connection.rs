@@ -3,7 +3,7 @@
 //! This test verifies that authentication works correctly
 //! It only performs read operations - no writes
 
-use workflow_core::config::LennardConfig;
+use workflow_core::config::{LennardConfig, SubjectMatch, TaskSelectionConfig};
 use workflow_core::clients::ZohoClient;
 use std::path::Path;
 
@@ -52,12 +52,14 @@ async fn test_zoho_connection_and_authentication() {
     // Search for tasks with a very specific filter that likely returns empty
     println!("Testing API access with a simple search query...");
     
-    let filters = vec![
-        ("Subject", "TEST_TASK_THAT_DOES_NOT_EXIST_123456789"),
-        ("Status", "Not Started")
-    ];
+    let selection = TaskSelectionConfig {
+        subject: Some(SubjectMatch::Equals("TEST_TASK_THAT_DOES_NOT_EXIST_123456789".to_string())),
+        owners: Vec::new(),
+        statuses: vec!["Not Started".to_string()],
+        ..TaskSelectionConfig::default()
+    };
     
-    match authenticated_client.get_tasks(&filters).await {
+    match authenticated_client.get_tasks(&selection).await {
         Ok(tasks) => {
             println!("✓ API call successful! Found {} tasks", tasks.len());
             // We expect 0 tasks with this specific subject
@@ -122,9 +124,14 @@ async fn test_zoho_token_refresh() {
     
     // Verify the refreshed token still works
     println!("Verifying refreshed token works...");
-    let filters = vec![("Subject", "TEST_NONEXISTENT")];
+    let selection = TaskSelectionConfig {
+        subject: Some(SubjectMatch::Equals("TEST_NONEXISTENT".to_string())),
+        owners: Vec::new(),
+        statuses: Vec::new(),
+        ..TaskSelectionConfig::default()
+    };
     
-    match authenticated_client.get_tasks(&filters).await {
+    match authenticated_client.get_tasks(&selection).await {
         Ok(_) => {
             println!("✓ API call with refreshed token successful");
         }
@@ -2,7 +2,7 @@
 //! 
 //! This test demonstrates the full workflow from fetching tasks to processing them
 
-use workflow_core::config::LennardConfig;
+use workflow_core::config::{LennardConfig, TaskSelectionConfig};
 use workflow_core::clients::ZohoClient;
 use std::path::Path;
 
@@ -42,25 +42,21 @@ async fn test_complete_zoho_workflow() {
     // Step 2: Search for tasks with specific criteria
     println!("Step 2: Searching for tasks...");
 
-    // Use the same filters as the actual workflow
-    let filters = vec![
-        ("Subject", "Connect on LinkedIn"),
-        ("Status", "Not started"),
-        ("Owner", "1294764000001730350")
-    ];
+    // Use the same selection as the actual workflow
+    let selection = TaskSelectionConfig::default();
 
-    let tasks = match authenticated_client.get_tasks(&filters).await {
+    let tasks = match authenticated_client.get_tasks(&selection).await {
         Ok(tasks) => {
             println!("✓ Found {} tasks matching criteria", tasks.len());
             if tasks.is_empty() {
                 println!("  (No tasks found - trying broader search...)\n");
 
                 // Try without status filter
-                let broader_filters = vec![
-                    ("Subject", "Connect on LinkedIn"),
-                    ("Owner", "1294764000001730350")
-                ];
-                match authenticated_client.get_tasks(&broader_filters).await {
+                let broader_selection = TaskSelectionConfig {
+                    statuses: Vec::new(),
+                    ..TaskSelectionConfig::default()
+                };
+                match authenticated_client.get_tasks(&broader_selection).await {
                     Ok(t) => {
                         println!("  Found {} tasks with just subject filter", t.len());
                         t
@@ -189,8 +185,13 @@ async fn test_get_single_task_by_id() {
     };
     
     // First, find any task to get a valid ID
-    let filters = vec![("Status", "Not Started")];
-    let tasks = match authenticated_client.get_tasks(&filters).await {
+    let selection = TaskSelectionConfig {
+        subject: None,
+        owners: Vec::new(),
+        statuses: vec!["Not Started".to_string()],
+        ..TaskSelectionConfig::default()
+    };
+    let tasks = match authenticated_client.get_tasks(&selection).await {
         Ok(t) if !t.is_empty() => t,
         _ => {
             println!("No tasks found to test with");
@@ -259,7 +260,12 @@ async fn test_check_authenticated_user() {
 
     // Try to get tasks by Owner only (Lennard's ID from config)
     println!("\nTrying to get tasks for Owner ID: 1294764000001730350 (Lennard)...");
-    match authenticated_client.get_tasks(&[("Owner", "1294764000001730350")]).await {
+    let by_owner = TaskSelectionConfig {
+        subject: None,
+        statuses: Vec::new(),
+        ..TaskSelectionConfig::default()
+    };
+    match authenticated_client.get_tasks(&by_owner).await {
         Ok(tasks) => {
             println!("✓ Found {} tasks owned by Lennard", tasks.len());
             if !tasks.is_empty() {
@@ -284,7 +290,12 @@ async fn test_check_authenticated_user() {
 
     // Try "Not started" status (English)
     println!("\nTrying to get tasks with Status='Not started'...");
-    match authenticated_client.get_tasks(&[("Status", "Not started")]).await {
+    let by_status = TaskSelectionConfig {
+        subject: None,
+        owners: Vec::new(),
+        ..TaskSelectionConfig::default()
+    };
+    match authenticated_client.get_tasks(&by_status).await {
         Ok(tasks) => {
             println!("✓ Found {} tasks with this status", tasks.len());
             if !tasks.is_empty() {
@@ -306,11 +317,7 @@ async fn test_check_authenticated_user() {
 
     // Try combining filters
     println!("\nTrying Owner + Status + Subject (all three filters)...");
-    match authenticated_client.get_tasks(&[
-        ("Subject", "Connect on LinkedIn"),
-        ("Status", "Not started"),
-        ("Owner", "1294764000001730350")
-    ]).await {
+    match authenticated_client.get_tasks(&TaskSelectionConfig::default()).await {
         Ok(tasks) => {
             println!("✓ Found {} tasks with all three filters", tasks.len());
             if !tasks.is_empty() {
@@ -12,54 +12,473 @@ use workflow_grpc::{
     ApprovalState as ProtoApprovalState,
     GetWorkflowStateRequest, ListWorkflowsRequest, ListWorkflowsResponse,
     StreamWorkflowRequest, WorkflowUpdate, CancelWorkflowRequest,
-    GetMetricsRequest, WorkflowMetrics,
+    GetMetricsRequest, WorkflowMetrics, ResumeTaskRequest,
+    ListSchedulesRequest, ListSchedulesResponse, WorkflowSchedule,
     GetPendingApprovalsRequest, GetPendingApprovalsResponse,
     GetApprovalStateRequest, StreamApprovalRequest, ApprovalUpdate,
     DownloadPdfRequest, PdfDocument, RegeneratePdfRequest,
-    HealthCheckRequest, HealthCheckResponse,
+    HealthCheckRequest, HealthCheckResponse, DependencyHealth as ProtoDependencyHealth,
+    TaskResult as ProtoTaskResult, PaginationResponse,
+    TaskSelection as ProtoTaskSelection,
+    ApprovalRequest as ProtoApprovalRequest,
+    ApprovalIteration as ProtoApprovalIteration,
+    SampleStats as ProtoSampleStats,
 };
 use workflow_core::{
-    workflow::{WorkflowOrchestrator, approval_types},
-    services::WorkflowProcessor,
+    config::{TaskSelectionConfig, SubjectMatch, DueDateWindow, TaskSortOrder},
+    error::LennardError,
+    workflow::{WorkflowOrchestrator, WorkflowRunStore, WorkflowScheduler, ScheduleStatus, WorkflowRun, WorkflowRunFilter, WorkflowStatus, WorkflowStep, TaskResult, TaskState, WorkflowEvent, WorkflowEventKind, WorkflowMetricsCollector, SampleStats, TRIGGER_SCHEMA_VERSION, BlobStore, approval_types},
+    services::{WorkflowProcessor, HealthChecker, HealthReport, DependencyHealth},
 };
 use futures::Stream;
+use tokio::sync::{broadcast, mpsc};
 use tokio_stream::wrappers::ReceiverStream;
 use std::pin::Pin;
 
+/// Updates buffered per streaming client before the forwarding task waits for it
+const STREAM_BUFFER_SIZE: usize = 64;
+
+/// How often `Health.Watch` re-checks the dependencies
+const HEALTH_WATCH_INTERVAL_SECS: u64 = 5;
+
+/// `Health.Check` service that only tells whether the server answers - no dependency is probed
+pub const LIVENESS_SERVICE: &str = "liveness";
+
 /// Wrapper struct for gRPC services
 #[derive(Clone)]
 pub struct GrpcServiceWrapper {
     orchestrator: Arc<WorkflowOrchestrator<WorkflowProcessor>>,
     // Approval states are managed by ApprovalQueue (disk-based storage)
     approval_queue: Arc<workflow_core::workflow::ApprovalQueue>,
+    // Every processed trigger is recorded as a workflow run
+    run_store: Arc<WorkflowRunStore>,
+    scheduler: Arc<WorkflowScheduler>,
+    metrics: Arc<WorkflowMetricsCollector>,
+    health: Arc<HealthChecker>,
 }
 
 impl GrpcServiceWrapper {
     pub fn new(
         orchestrator: Arc<WorkflowOrchestrator<WorkflowProcessor>>,
         approval_queue: Arc<workflow_core::workflow::ApprovalQueue>,
+        run_store: Arc<WorkflowRunStore>,
+        scheduler: Arc<WorkflowScheduler>,
+        metrics: Arc<WorkflowMetricsCollector>,
+        health: Arc<HealthChecker>,
     ) -> Self {
         Self {
             orchestrator,
             approval_queue,
+            run_store,
+            scheduler,
+            metrics,
+            health,
         }
     }
+    
+    /// Apply a reviewer's edited letter text to an approval that awaits a decision
+    async fn revise_approval(
+        &self,
+        approval_id: &str,
+        revised_text: &str,
+        revised_by: approval_types::UserId,
+        approve: bool,
+    ) -> workflow_core::error::Result<approval_types::ApprovalData> {
+        let approval = self.approval_queue.get_approval_request(&approval_types::ApprovalId::from(approval_id.to_string()), None)?
+            .ok_or_else(|| LennardError::NotFound(format!("Approval {} not found", approval_id)))?;
+        if !matches!(approval.state, approval_types::ApprovalState::PendingApproval | approval_types::ApprovalState::AwaitingUserResponse) {
+            return Err(LennardError::Workflow(format!(
+                "Approval {} is {:?} and can no longer be revised", approval_id, approval.state
+            )));
+        }
+        
+        // Persisted before it reaches Telegram - pending until the reviewer got it
+        let mut revised = self.orchestrator.revise_letter(&approval, revised_text, revised_by, approve).await?;
+        if !self.approval_queue.save_revision(&revised)? {
+            return Err(LennardError::Workflow(format!(
+                "Approval {} was decided while the revision was rendered", approval_id
+            )));
+        }
+        
+        if !approve {
+            self.orchestrator.request_review(&revised).await?;
+            self.approval_queue.mark_as_awaiting_response(&revised.approval_id)?;
+            revised.mark_awaiting_response();
+        }
+        Ok(revised)
+    }
 }
 
+/// Default and maximum page size for ListWorkflows
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+/// Default and maximum page size for GetPendingApprovals - each approval carries its PDF, so a
+/// page has to stay well below tonic's 4 MB message limit
+const DEFAULT_APPROVALS_PAGE_SIZE: usize = 10;
+const MAX_APPROVALS_PAGE_SIZE: usize = 25;
+
 // Convert between proto and core types
-fn proto_to_core_trigger(proto: ProtoWorkflowTrigger) -> approval_types::WorkflowTrigger {
-    approval_types::WorkflowTrigger {
-        trigger_id: proto.trigger_id,
+fn proto_to_core_trigger(proto: ProtoWorkflowTrigger) -> workflow_core::error::Result<approval_types::WorkflowTrigger> {
+    // The trigger id doubles as the workflow id, so it must never be empty
+    let trigger_id = if proto.trigger_id.trim().is_empty() {
+        uuid::Uuid::new_v4().to_string()
+    } else {
+        proto.trigger_id
+    };
+    
+    let requested_at = proto.requested_at
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts.seconds, ts.nanos.max(0) as u32))
+        .unwrap_or_else(chrono::Utc::now);
+    
+    let task_selection = proto.task_selection.map(proto_to_task_selection).transpose()?;
+    
+    if proto.task_ids.iter().any(|task_id| task_id.trim().is_empty()) {
+        return Err(LennardError::Validation("Task IDs must not be empty".to_string()));
+    }
+    
+    Ok(approval_types::WorkflowTrigger {
+        schema_version: TRIGGER_SCHEMA_VERSION,
+        trigger_id,
         requested_by: approval_types::UserId::new(proto.requested_by),
-        requested_at: chrono::Utc::now(), // Convert from proto timestamp
+        requested_at,
         max_tasks: proto.max_tasks,
         dry_run: proto.dry_run,
+        task_selection,
+        task_ids: proto.task_ids,
         processed: false,
         processed_at: None,
         result: None,
+    })
+}
+
+fn proto_to_task_selection(proto: ProtoTaskSelection) -> workflow_core::error::Result<TaskSelectionConfig> {
+    use workflow_grpc::task_selection::Subject;
+    
+    let sort = match proto.sort.as_str() {
+        "" | "oldest_first" => TaskSortOrder::OldestFirst,
+        "newest_first" => TaskSortOrder::NewestFirst,
+        "due_date_first" => TaskSortOrder::DueDateFirst,
+        other => return Err(LennardError::Validation(format!("Unknown task sort order '{}'", other))),
+    };
+    let due_date = (proto.due_from_days.is_some() || proto.due_to_days.is_some()).then_some(DueDateWindow {
+        from_days: proto.due_from_days,
+        to_days: proto.due_to_days,
+    });
+    
+    let selection = TaskSelectionConfig {
+        subject: proto.subject.map(|subject| match subject {
+            Subject::SubjectEquals(value) => SubjectMatch::Equals(value),
+            Subject::SubjectStartsWith(value) => SubjectMatch::StartsWith(value),
+        }),
+        owners: proto.owners,
+        // An empty list would match every status, including tasks already in progress or done
+        statuses: if proto.statuses.is_empty() { TaskSelectionConfig::default().statuses } else { proto.statuses },
+        due_date,
+        tags: proto.tags,
+        sort,
+    };
+    selection.validate()?;
+    Ok(selection)
+}
+
+fn to_timestamp(dt: chrono::DateTime<chrono::Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: dt.timestamp(),
+        nanos: dt.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn core_to_proto_status(status: WorkflowStatus) -> workflow_grpc::workflow_state::Status {
+    use workflow_grpc::workflow_state::Status;
+    match status {
+        WorkflowStatus::Pending => Status::Pending,
+        WorkflowStatus::Running => Status::Running,
+        WorkflowStatus::Completed => Status::Completed,
+        WorkflowStatus::Failed => Status::Failed,
+        WorkflowStatus::Cancelled => Status::Cancelled,
+    }
+}
+
+fn proto_to_core_status(status: workflow_grpc::workflow_state::Status) -> Result<Option<WorkflowStatus>, String> {
+    use workflow_grpc::workflow_state::Status;
+    Ok(match status {
+        Status::Unspecified => None,
+        Status::Pending => Some(WorkflowStatus::Pending),
+        Status::Running => Some(WorkflowStatus::Running),
+        // A run ends once its letters are handed to the approval flow - approvals are tracked on their own
+        Status::WaitingApproval => return Err("Runs never wait for approval - use GetPendingApprovals".to_string()),
+        Status::Completed => Some(WorkflowStatus::Completed),
+        Status::Failed => Some(WorkflowStatus::Failed),
+        Status::Cancelled => Some(WorkflowStatus::Cancelled),
+    })
+}
+
+fn core_to_proto_task_status(state: TaskState) -> workflow_grpc::task_result::TaskStatus {
+    use workflow_grpc::task_result::TaskStatus;
+    match state {
+        TaskState::Queued => TaskStatus::Queued,
+        TaskState::Running => TaskStatus::Running,
+        TaskState::Succeeded => TaskStatus::Succeeded,
+        TaskState::Failed => TaskStatus::Failed,
+        TaskState::Aborted => TaskStatus::Aborted,
+        TaskState::NotStarted => TaskStatus::NotStarted,
+    }
+}
+
+fn task_result_to_proto(result: &TaskResult) -> ProtoTaskResult {
+    ProtoTaskResult {
+        task_id: result.task_id.to_string(),
+        contact_name: result.contact_name.clone().unwrap_or_default(),
+        company_name: result.company_name.clone().unwrap_or_default(),
+        success: result.success,
+        tracking_id: None, // Letters are only sent after approval
+        error_message: result.error_message.clone(),
+        processed_at: Some(to_timestamp(result.finished_at.unwrap_or(result.started_at))),
+        approval_id: result.approval_id.as_ref().map(|id| id.to_string()),
+        status: core_to_proto_task_status(result.state) as i32,
+        retry_count: result.retries.len() as u32,
+    }
+}
+
+fn run_to_proto(run: &WorkflowRun) -> ProtoWorkflowState {
+    ProtoWorkflowState {
+        workflow_id: run.workflow_id.clone(),
+        status: core_to_proto_status(run.status) as i32,
+        started_at: Some(to_timestamp(run.started_at)),
+        updated_at: Some(to_timestamp(run.updated_at)),
+        task_results: run.task_results.iter().map(task_result_to_proto).collect(),
+        error_message: run.error_message.clone(),
+        total_tasks: run.total_tasks,
+        completed_tasks: run.completed_tasks(),
+    }
+}
+
+fn run_to_update(run: &WorkflowRun) -> WorkflowUpdate {
+    WorkflowUpdate {
+        workflow_id: run.workflow_id.clone(),
+        status: core_to_proto_status(run.status) as i32,
+        latest_task: run.task_results.last().map(task_result_to_proto),
+        timestamp: Some(to_timestamp(run.updated_at)),
+        message: run.error_message.clone()
+            .unwrap_or_else(|| format!("{}/{} tasks completed", run.completed_tasks(), run.total_tasks)),
+        event_type: "snapshot".to_string(),
+    }
+}
+
+/// Convert a run event - None for approval events
+fn event_to_workflow_update(event: &WorkflowEvent) -> Option<WorkflowUpdate> {
+    let workflow_id = event.workflow_id.clone()?;
+    let mut latest_task = None;
+    let mut status = WorkflowStatus::Running;
+    let message = match &event.kind {
+        WorkflowEventKind::RunStarted => "Workflow run started".to_string(),
+        WorkflowEventKind::TaskStarted { task_id } => format!("Task {} started", task_id),
+        WorkflowEventKind::StepCompleted { task_id, step } => format!("Task {}: step '{}' completed", task_id, step),
+        WorkflowEventKind::StepFailed { task_id, step, error } => format!("Task {}: step '{}' failed: {}", task_id, step, error),
+        WorkflowEventKind::TaskFinished { result } => {
+            latest_task = Some(task_result_to_proto(result));
+            format!("Task {} finished ({:?})", result.task_id, result.state)
+        }
+        WorkflowEventKind::RunFinished { status: final_status, message } => {
+            status = *final_status;
+            message.clone().unwrap_or_else(|| format!("Workflow run finished ({:?})", final_status))
+        }
+        WorkflowEventKind::ApprovalCreated { .. }
+        | WorkflowEventKind::ApprovalStateChanged { .. }
+        | WorkflowEventKind::LetterSent { .. } => return None,
+    };
+    
+    Some(WorkflowUpdate {
+        workflow_id,
+        status: core_to_proto_status(status) as i32,
+        latest_task,
+        timestamp: Some(to_timestamp(event.timestamp)),
+        message,
+        event_type: event.kind.name().to_string(),
+    })
+}
+
+fn core_to_proto_approval_status(state: approval_types::ApprovalState) -> workflow_grpc::approval_state::Status {
+    use approval_types::ApprovalState;
+    use workflow_grpc::approval_state::Status;
+    match state {
+        ApprovalState::PendingApproval => Status::Pending,
+        ApprovalState::AwaitingUserResponse => Status::Pending,  // Map to Pending since there's no AwaitingResponse
+        ApprovalState::Approved => Status::Approved,
+        ApprovalState::Sending => Status::Sending,
+        ApprovalState::Sent => Status::Sent,
+        ApprovalState::NeedsImprovement => Status::InRevision,
+        ApprovalState::Failed => Status::Rejected,  // Map Failed to Rejected
+        ApprovalState::Expired => Status::Expired,
+    }
+}
+
+/// Convert an approval event - None for run events
+fn event_to_approval_update(event: &WorkflowEvent) -> Option<ApprovalUpdate> {
+    let (approval_id, task_id, state, message, job_id) = match &event.kind {
+        WorkflowEventKind::ApprovalCreated { approval_id, task_id } => {
+            (approval_id, task_id, approval_types::ApprovalState::PendingApproval, "Approval created".to_string(), None)
+        }
+        WorkflowEventKind::ApprovalStateChanged { approval_id, task_id, state } => {
+            (approval_id, task_id, *state, format!("Approval is now {:?}", state), None)
+        }
+        WorkflowEventKind::LetterSent { approval_id, task_id, job_id } => {
+            (approval_id, task_id, approval_types::ApprovalState::Sent, format!("Letter sent (job {})", job_id), Some(job_id.clone()))
+        }
+        _ => return None,
+    };
+    
+    Some(ApprovalUpdate {
+        approval_id: approval_id.to_string(),
+        status: core_to_proto_approval_status(state) as i32,
+        timestamp: Some(to_timestamp(event.timestamp)),
+        message: Some(message),
+        event_type: event.kind.name().to_string(),
+        task_id: task_id.to_string(),
+        job_id,
+    })
+}
+
+fn approval_to_proto_request(approval: &approval_types::ApprovalData, blobs: &BlobStore) -> ProtoApprovalRequest {
+    // A broken PDF must not hide the approval from the queue
+    let pdf_content = approval.pdf_bytes(blobs).unwrap_or_else(|e| {
+        log::warn!("Approval {}: {}", approval.approval_id, e);
+        None
+    });
+    
+    ProtoApprovalRequest {
+        approval_id: approval.approval_id.to_string(),
+        workflow_id: String::new(),  // Approvals outlive their workflow run and don't record it
+        contact_name: approval.recipient_name.clone(),
+        company_name: approval.company_name.clone(),
+        letter_content: approval.current_letter.to_plain_text(),
+        pdf_filename: if pdf_content.is_some() { format!("approval_{}.pdf", approval.approval_id) } else { String::new() },
+        pdf_content: pdf_content.unwrap_or_default(),
+        pdf_url: None,
+        created_at: Some(to_timestamp(approval.requested_at)),
+        task_id: approval.task_id.to_string(),
+        contact_id: approval.contact_id.to_string(),
+        tracking_info: None,
+        awaiting_response: approval.state == approval_types::ApprovalState::AwaitingUserResponse,
+        requested_by: approval.requested_by.value(),
+    }
+}
+
+fn iteration_pdf_filename(approval: &approval_types::ApprovalData, iteration: u32) -> String {
+    format!("approval_{}_v{}.pdf", approval.approval_id, iteration)
+}
+
+fn approval_iterations_to_proto(approval: &approval_types::ApprovalData, blobs: &BlobStore) -> Vec<ProtoApprovalIteration> {
+    approval.letter_history.iter().map(|entry| {
+        let pdf_content = approval.iteration_pdf_bytes(Some(entry.iteration), blobs).unwrap_or_else(|e| {
+            log::warn!("Approval {} iteration {}: {}", approval.approval_id, entry.iteration, e);
+            None
+        });
+        ProtoApprovalIteration {
+            iteration_number: entry.iteration,
+            letter_content: entry.content.to_plain_text(),
+            pdf_filename: if pdf_content.is_some() { iteration_pdf_filename(approval, entry.iteration) } else { String::new() },
+            pdf_content: pdf_content.unwrap_or_default(),
+            feedback: entry.feedback.as_ref().map(|f| f.text.clone()),
+            created_at: Some(to_timestamp(entry.created_at)),
+            feedback_by: entry.feedback.as_ref().map(|f| f.provided_by.value()),
+            edited_by: entry.edited_by.map(|user| user.value()),
+        }
+    }).collect()
+}
+
+fn approval_to_proto_state(approval: &approval_types::ApprovalData, blobs: &BlobStore) -> ProtoApprovalState {
+    // The final PDF is the one that was approved for sending
+    let final_pdf = match approval.state {
+        approval_types::ApprovalState::Approved
+        | approval_types::ApprovalState::Sending
+        | approval_types::ApprovalState::Sent => approval.pdf_bytes(blobs).unwrap_or_else(|e| {
+            log::warn!("Approval {}: {}", approval.approval_id, e);
+            None
+        }),
+        _ => None,
+    };
+    
+    ProtoApprovalState {
+        approval_id: approval.approval_id.to_string(),
+        status: core_to_proto_approval_status(approval.state) as i32,
+        iterations: approval_iterations_to_proto(approval, blobs),
+        final_pdf_filename: final_pdf.as_ref().map(|_| format!("approval_{}.pdf", approval.approval_id)),
+        final_pdf,
+    }
+}
+
+fn revision_error_to_status(error: LennardError) -> Status {
+    match error {
+        LennardError::NotFound(message) => Status::not_found(message),
+        LennardError::Validation(message) => Status::invalid_argument(message),
+        e @ (LennardError::PageLimitExceeded { .. } | LennardError::Workflow(_)) => Status::failed_precondition(e.to_string()),
+        e => Status::internal(format!("Failed to revise letter: {}", e)),
+    }
+}
+
+/// Forward matching events from the bus until the client disconnects.
+/// `convert` returns None for events that don't belong to the stream and
+/// `is_last` ends the stream after the given update.
+fn forward_events<T, C, L>(
+    mut events: broadcast::Receiver<WorkflowEvent>,
+    tx: mpsc::Sender<Result<T, Status>>,
+    convert: C,
+    is_last: L,
+) where
+    T: Send + 'static,
+    C: Fn(&WorkflowEvent) -> Option<T> + Send + 'static,
+    L: Fn(&WorkflowEvent) -> bool + Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            let event = tokio::select! {
+                _ = tx.closed() => break,
+                received = events.recv() => match received {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Update stream fell behind, skipped {} events", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+            
+            let Some(update) = convert(&event) else { continue };
+            if tx.send(Ok(update)).await.is_err() || is_last(&event) {
+                break;
+            }
+        }
+    });
+}
+
+fn sample_stats_to_proto(stats: &SampleStats) -> ProtoSampleStats {
+    ProtoSampleStats {
+        count: stats.count,
+        mean: stats.mean,
+        p95: stats.p95,
     }
 }
 
+/// Parse a date bound from a filter - accepts RFC 3339 timestamps or plain `YYYY-MM-DD` dates.
+/// Plain dates are expanded to the start of the day, or to its end when `end_of_day` is set.
+fn parse_date_bound(value: &str, end_of_day: bool) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&chrono::Utc));
+    }
+    
+    let date = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}': expected RFC 3339 or YYYY-MM-DD", value))?;
+    let time = if end_of_day {
+        chrono::NaiveTime::from_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        chrono::NaiveTime::from_hms_opt(0, 0, 0)
+    }.expect("valid time of day");
+    
+    Ok(date.and_time(time).and_utc())
+}
+
 #[tonic::async_trait]
 impl WorkflowService for GrpcServiceWrapper {
     async fn trigger_workflow(
@@ -67,41 +486,34 @@ impl WorkflowService for GrpcServiceWrapper {
         request: Request<ProtoWorkflowTrigger>,
     ) -> Result<Response<ProtoWorkflowState>, Status> {
         let proto_trigger = request.into_inner();
-        let trigger_id = proto_trigger.trigger_id.clone();
-        
-        log::info!("Processing workflow trigger {} for up to {} tasks", 
-                   trigger_id, proto_trigger.max_tasks);
         
         // Convert proto to core type
-        let core_trigger = proto_to_core_trigger(proto_trigger);
+        let core_trigger = proto_to_core_trigger(proto_trigger)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let workflow_id = core_trigger.trigger_id.clone();
         
-        // Process tasks directly - no state storage needed
-        let result = self.orchestrator
+        if core_trigger.task_ids.is_empty() {
+            log::info!("Processing workflow trigger {} for up to {} tasks", 
+                       workflow_id, core_trigger.max_tasks);
+        } else {
+            log::info!("Processing workflow trigger {} for tasks {:?}", 
+                       workflow_id, core_trigger.task_ids);
+        }
+        
+        // Process tasks - the orchestrator records the run in the run store
+        self.orchestrator
             .process_workflow(core_trigger)
             .await
             .map_err(|e| Status::internal(format!("Task processing failed: {}", e)))?;
         
-        // Return a simple ephemeral response - not stored anywhere
-        let response = ProtoWorkflowState {
-            workflow_id: trigger_id,
-            status: if result.processed {
-                workflow_grpc::workflow_state::Status::Completed as i32
-            } else {
-                workflow_grpc::workflow_state::Status::Failed as i32
-            },
-            started_at: Some(prost_types::Timestamp::from(std::time::SystemTime::now())),
-            updated_at: Some(prost_types::Timestamp::from(std::time::SystemTime::now())),
-            task_results: vec![], // Individual approvals track their own state
-            error_message: result.result.clone().filter(|_r| !result.processed),
-            total_tasks: result.max_tasks,
-            completed_tasks: if result.processed { result.max_tasks } else { 0 },
-        };
+        let run = self.run_store.get_run(&workflow_id)
+            .map_err(|e| Status::internal(format!("Failed to read workflow run: {}", e)))?
+            .ok_or_else(|| Status::internal(format!("Workflow run {} was not recorded", workflow_id)))?;
         
-        log::info!("Trigger {} processed: {} tasks", 
-                   response.workflow_id, response.completed_tasks);
+        let response = run_to_proto(&run);
         
-        // No longer storing state - workflows are ephemeral
-        // Each approval tracks its own lifecycle independently
+        log::info!("Trigger {} processed: {}/{} tasks", 
+                   response.workflow_id, response.completed_tasks, response.total_tasks);
         
         Ok(Response::new(response))
     }
@@ -112,25 +524,72 @@ impl WorkflowService for GrpcServiceWrapper {
     ) -> Result<Response<ProtoWorkflowState>, Status> {
         let workflow_id = request.into_inner().workflow_id;
         
-        // Workflows are ephemeral - they only exist during processing
-        // Each approval tracks its own state independently
-        log::info!("get_workflow_state called for {} - workflows are ephemeral", workflow_id);
+        log::info!("get_workflow_state called for {}", workflow_id);
         
-        Err(Status::not_found(
-            "Workflows are ephemeral. Check individual approval states instead."
-        ))
+        match self.run_store.get_run(&workflow_id) {
+            Ok(Some(run)) => Ok(Response::new(run_to_proto(&run))),
+            Ok(None) => Err(Status::not_found(format!("Workflow {} not found", workflow_id))),
+            Err(e) => {
+                log::error!("Failed to read workflow run {}: {}", workflow_id, e);
+                Err(Status::internal(format!("Failed to read workflow run: {}", e)))
+            }
+        }
     }
     
     async fn list_workflows(
         &self,
-        _request: Request<ListWorkflowsRequest>,
+        request: Request<ListWorkflowsRequest>,
     ) -> Result<Response<ListWorkflowsResponse>, Status> {
-        // Workflows are ephemeral - return empty list
-        log::info!("list_workflows called - returning empty (workflows are ephemeral)");
+        let req = request.into_inner();
+        
+        let mut filter = WorkflowRunFilter {
+            active_only: req.active_only.unwrap_or(false),
+            ..Default::default()
+        };
+        
+        if let Some(proto_filter) = req.filter {
+            if let Some(status) = proto_filter.status {
+                let status = workflow_grpc::workflow_state::Status::try_from(status)
+                    .map_err(|_| Status::invalid_argument(format!("Unknown workflow status {}", status)))?;
+                filter.status = proto_to_core_status(status).map_err(Status::invalid_argument)?;
+            }
+            filter.requested_by = proto_filter.requested_by.map(approval_types::UserId::new);
+            if let Some(date_from) = proto_filter.date_from.as_deref().filter(|d| !d.is_empty()) {
+                filter.started_from = Some(parse_date_bound(date_from, false).map_err(Status::invalid_argument)?);
+            }
+            if let Some(date_to) = proto_filter.date_to.as_deref().filter(|d| !d.is_empty()) {
+                filter.started_to = Some(parse_date_bound(date_to, true).map_err(Status::invalid_argument)?);
+            }
+        }
+        
+        let (page_size, offset) = match req.pagination {
+            Some(pagination) => {
+                let page_size = match pagination.page_size as usize {
+                    0 => DEFAULT_PAGE_SIZE,
+                    size => size.min(MAX_PAGE_SIZE),
+                };
+                let offset = if pagination.page_token.is_empty() {
+                    0
+                } else {
+                    pagination.page_token.parse::<usize>()
+                        .map_err(|_| Status::invalid_argument("Invalid page_token"))?
+                };
+                (page_size, offset)
+            }
+            None => (DEFAULT_PAGE_SIZE, 0),
+        };
+        
+        log::info!("list_workflows called (offset {}, page size {})", offset, page_size);
+        
+        let page = self.run_store.list_runs(&filter, offset, page_size)
+            .map_err(|e| Status::internal(format!("Failed to list workflow runs: {}", e)))?;
         
         Ok(Response::new(ListWorkflowsResponse {
-            workflows: vec![],
-            pagination: None,
+            workflows: page.runs.iter().map(run_to_proto).collect(),
+            pagination: Some(PaginationResponse {
+                next_page_token: page.next_offset.map(|o| o.to_string()).unwrap_or_default(),
+                total_count: page.total_count as u32,
+            }),
         }))
     }
     
@@ -140,15 +599,54 @@ impl WorkflowService for GrpcServiceWrapper {
         &self,
         request: Request<StreamWorkflowRequest>,
     ) -> Result<Response<Self::StreamWorkflowUpdatesStream>, Status> {
-        let workflow_id = request.into_inner().workflow_id;
+        let request = request.into_inner();
+        let workflow_id = (!request.workflow_id.is_empty()).then_some(request.workflow_id);
+        let user_id = request.user_id.map(approval_types::UserId::new);
         
-        log::info!("stream_workflow_updates called for {} - not supported", workflow_id);
+        log::info!("stream_workflow_updates called for {} (user: {:?})",
+            workflow_id.as_deref().unwrap_or("all workflows"), request.user_id);
         
-        // Workflows are ephemeral - no updates to stream
-        // Return an empty stream that immediately closes
-        let (_tx, rx) = tokio::sync::mpsc::channel(1);
-        let stream = ReceiverStream::new(rx);
-        Ok(Response::new(Box::pin(stream)))
+        // Subscribe before reading the run so no event in between is lost
+        let events = self.orchestrator.events().subscribe();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER_SIZE);
+        
+        if let Some(workflow_id) = &workflow_id {
+            let run = match self.run_store.get_run(workflow_id) {
+                Ok(run) => run,
+                Err(e) => {
+                    log::error!("Failed to read workflow run {}: {}", workflow_id, e);
+                    return Err(Status::internal(format!("Failed to read workflow run: {}", e)));
+                }
+            };
+            
+            // Start with the current state - a finished run has nothing more to stream
+            if let Some(run) = run {
+                let _ = tx.try_send(Ok(run_to_update(&run)));
+                if !run.status.is_active() {
+                    return Ok(Response::new(Box::pin(ReceiverStream::new(rx))));
+                }
+            }
+        }
+        
+        let requested_workflow = workflow_id.clone();
+        let is_requested = move |event: &WorkflowEvent| {
+            requested_workflow.is_none() || event.workflow_id == requested_workflow
+        };
+        let matches = is_requested.clone();
+        forward_events(
+            events,
+            tx,
+            move |event| {
+                let wanted = matches(event) && user_id.is_none_or(|id| event.user_id == id);
+                wanted.then(|| event_to_workflow_update(event)).flatten()
+            },
+            move |event| {
+                workflow_id.is_some() && is_requested(event)
+                    && matches!(event.kind, WorkflowEventKind::RunFinished { .. })
+            },
+        );
+        
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
     
     async fn cancel_workflow(
@@ -157,39 +655,137 @@ impl WorkflowService for GrpcServiceWrapper {
     ) -> Result<Response<ProtoWorkflowState>, Status> {
         let req = request.into_inner();
         let workflow_id = req.workflow_id;
+        let reason = if req.reason.trim().is_empty() {
+            "Cancelled via CancelWorkflow".to_string()
+        } else {
+            req.reason
+        };
+        
+        log::info!("cancel_workflow called for {}: {}", workflow_id, reason);
+        
+        // Cancellation is cooperative - the run stops at the next step boundary and its
+        // final state (finished, aborted and never started tasks) is available via GetWorkflowState
+        let cancelled = self.orchestrator.cancel_workflow(&workflow_id, &reason);
         
-        log::info!("cancel_workflow called for {} - not supported", workflow_id);
+        let run = match self.run_store.get_run(&workflow_id) {
+            Ok(Some(run)) => run,
+            Ok(None) => return Err(Status::not_found(format!("Workflow {} not found", workflow_id))),
+            Err(e) => {
+                log::error!("Failed to read workflow run {}: {}", workflow_id, e);
+                return Err(Status::internal(format!("Failed to read workflow run: {}", e)));
+            }
+        };
         
-        // Workflows are ephemeral - nothing to cancel
-        Err(Status::not_found(
-            "Cannot cancel workflow. Workflows are ephemeral and run to completion."
-        ))
+        if !cancelled {
+            return Err(Status::failed_precondition(format!(
+                "Workflow {} is not running (status: {:?})", workflow_id, run.status
+            )));
+        }
+        
+        Ok(Response::new(run_to_proto(&run)))
     }
     
     async fn get_workflow_metrics(
         &self,
-        _request: Request<GetMetricsRequest>,
+        request: Request<GetMetricsRequest>,
     ) -> Result<Response<WorkflowMetrics>, Status> {
-        // Calculate metrics from approval files instead
-        // This could scan the approval directories to get real metrics
-        log::info!("get_workflow_metrics called - returning zeros (workflows are ephemeral)");
+        let req = request.into_inner();
+        
+        log::info!("get_workflow_metrics called (from: {:?}, to: {:?})", req.from_date, req.to_date);
         
-        let total = 0;
-        let completed = 0;
-        let failed = 0;
-        let active = 0;
+        let from = req.from_date.as_deref()
+            .filter(|date| !date.is_empty())
+            .map(|date| parse_date_bound(date, false))
+            .transpose()
+            .map_err(Status::invalid_argument)?;
+        let to = req.to_date.as_deref()
+            .filter(|date| !date.is_empty())
+            .map(|date| parse_date_bound(date, true))
+            .transpose()
+            .map_err(Status::invalid_argument)?;
+        
+        let report = self.metrics.collect(from, to)
+            .map_err(|e| {
+                log::error!("Failed to collect workflow metrics: {}", e);
+                Status::internal(format!("Failed to collect workflow metrics: {}", e))
+            })?;
         
         Ok(Response::new(WorkflowMetrics {
-            total_workflows: total,
-            completed_workflows: completed,
-            failed_workflows: failed,
-            active_workflows: active,
-            average_processing_time_seconds: 30.0, // Placeholder
-            total_tasks_processed: completed * 5, // Estimate
+            total_workflows: report.total_workflows,
+            completed_workflows: report.completed_workflows,
+            failed_workflows: report.failed_workflows,
+            active_workflows: report.active_workflows,
+            average_processing_time_seconds: report.run_duration.mean,
+            total_tasks_processed: report.tasks_processed,
+            failed_tasks: report.tasks_failed,
+            failures_by_step: report.failures_by_step.into_iter().collect(),
+            letters_sent: report.letters_sent,
+            letters_failed: report.letters_failed,
+            approvals_by_state: report.approvals_by_state.into_iter().collect(),
+            task_start_to_approval_seconds: Some(sample_stats_to_proto(&report.task_start_to_approval)),
+            approval_to_send_seconds: Some(sample_stats_to_proto(&report.approval_to_send)),
+            iterations_per_letter: Some(sample_stats_to_proto(&report.iterations_per_letter)),
+        }))
+    }
+    
+    async fn resume_task(
+        &self,
+        request: Request<ResumeTaskRequest>,
+    ) -> Result<Response<ProtoWorkflowState>, Status> {
+        let req = request.into_inner();
+        
+        if req.task_id.trim().is_empty() {
+            return Err(Status::invalid_argument("task_id is required"));
+        }
+        
+        let from_step = req.from_step
+            .filter(|step| !step.trim().is_empty())
+            .map(|step| step.parse::<WorkflowStep>())
+            .transpose()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        
+        log::info!("resume_task called for {} (from step: {:?})", req.task_id, from_step);
+        
+        let result = self.orchestrator
+            .resume_task(&req.task_id, from_step, approval_types::UserId::new(req.requested_by))
+            .await
+            .map_err(|e| Status::internal(format!("Failed to resume task {}: {}", req.task_id, e)))?;
+        
+        let run = self.run_store.get_run(&result.trigger_id)
+            .map_err(|e| Status::internal(format!("Failed to read workflow run: {}", e)))?
+            .ok_or_else(|| Status::internal(format!("Workflow run {} was not recorded", result.trigger_id)))?;
+        
+        Ok(Response::new(run_to_proto(&run)))
+    }
+    
+    async fn list_schedules(
+        &self,
+        _request: Request<ListSchedulesRequest>,
+    ) -> Result<Response<ListSchedulesResponse>, Status> {
+        let schedules = self.scheduler.status(chrono::Utc::now())
+            .map_err(|e| Status::internal(format!("Failed to read schedules: {}", e)))?;
+        
+        Ok(Response::new(ListSchedulesResponse {
+            schedules: schedules.iter().map(schedule_to_proto).collect(),
         }))
     }
 }
 
+fn schedule_to_proto(status: &ScheduleStatus) -> WorkflowSchedule {
+    WorkflowSchedule {
+        name: status.config.name.clone(),
+        cron: status.config.cron.clone(),
+        timezone: status.config.timezone.clone(),
+        max_tasks: status.config.max_tasks,
+        dry_run: status.config.dry_run,
+        missed_runs: status.config.missed_runs.to_string(),
+        enabled: status.config.enabled,
+        next_run_at: status.next_fire_time.map(to_timestamp),
+        last_run_at: status.last_fire_time.map(to_timestamp),
+        last_trigger_id: status.last_trigger_id.clone(),
+    }
+}
+
 #[tonic::async_trait]
 impl ApprovalService for GrpcServiceWrapper {
     async fn submit_approval(
@@ -206,6 +802,26 @@ impl ApprovalService for GrpcServiceWrapper {
             log::info!("Feedback provided: {}", feedback);
         }
         
+        // Text edited by the reviewer replaces the letter directly instead of going through the LLM
+        if let Some(revised_text) = approval.revised_letter_content.as_deref().filter(|text| !text.trim().is_empty()) {
+            let approve = match approval.decision() {
+                workflow_grpc::approval_response::Decision::Approved => true,
+                workflow_grpc::approval_response::Decision::NeedsRevision => false,
+                _ => return Err(Status::invalid_argument(
+                    "Revised letter content requires an approved or needs-revision decision"
+                )),
+            };
+            let revised = self.revise_approval(
+                &approval_id,
+                revised_text,
+                approval_types::UserId::new(approval.decided_by),
+                approve,
+            ).await.map_err(revision_error_to_status)?;
+            
+            log::info!("Applied revised letter to approval {} (now {:?})", approval_id, revised.state);
+            return Ok(Response::new(approval_to_proto_state(&revised, self.approval_queue.blobs())));
+        }
+        
         // Process the approval through the approval queue
         let result = match approval.decision() {
             workflow_grpc::approval_response::Decision::Approved => {
@@ -322,12 +938,28 @@ impl ApprovalService for GrpcServiceWrapper {
     
     async fn get_pending_approvals(
         &self,
-        _request: Request<GetPendingApprovalsRequest>,
+        request: Request<GetPendingApprovalsRequest>,
     ) -> Result<Response<GetPendingApprovalsResponse>, Status> {
-        // Return empty list for now - in production, query actual pending approvals
+        let request = request.into_inner();
+        let for_user = request.for_user.map(approval_types::UserId::new);
+        let limit = match request.limit.unwrap_or(0) {
+            0 => DEFAULT_APPROVALS_PAGE_SIZE,
+            limit => (limit as usize).min(MAX_APPROVALS_PAGE_SIZE),
+        };
+        
+        log::info!("get_pending_approvals called (limit: {:?}, for_user: {:?})", request.limit, request.for_user);
+        
+        let approvals = self.approval_queue.list_open_approvals(for_user)
+            .map_err(|e| {
+                log::error!("Failed to list pending approvals: {}", e);
+                Status::internal(format!("Failed to list pending approvals: {}", e))
+            })?;
+        
         Ok(Response::new(GetPendingApprovalsResponse {
-            approvals: vec![],
-            total_count: 0,
+            total_count: approvals.len() as u32,
+            approvals: approvals.iter().take(limit)
+                .map(|approval| approval_to_proto_request(approval, self.approval_queue.blobs()))
+                .collect(),
         }))
     }
     
@@ -346,27 +978,7 @@ impl ApprovalService for GrpcServiceWrapper {
             Ok(Some(approval_data)) => {
                 log::info!("Found approval {} with state: {:?}", approval_id, approval_data.state);
                 
-                // Convert ApprovalData to ProtoApprovalState
-                let proto_state = ProtoApprovalState {
-                    approval_id: approval_id.clone(),
-                    status: match approval_data.state {
-                        workflow_core::workflow::approval_types::ApprovalState::PendingApproval => 
-                            workflow_grpc::approval_state::Status::Pending as i32,
-                        workflow_core::workflow::approval_types::ApprovalState::AwaitingUserResponse => 
-                            workflow_grpc::approval_state::Status::Pending as i32,  // Map to Pending since there's no AwaitingResponse
-                        workflow_core::workflow::approval_types::ApprovalState::Approved => 
-                            workflow_grpc::approval_state::Status::Approved as i32,
-                        workflow_core::workflow::approval_types::ApprovalState::NeedsImprovement => 
-                            workflow_grpc::approval_state::Status::InRevision as i32,
-                        workflow_core::workflow::approval_types::ApprovalState::Failed => 
-                            workflow_grpc::approval_state::Status::Rejected as i32,  // Map Failed to Rejected
-                    },
-                    iterations: vec![],  // TODO: Convert letter history
-                    final_pdf: None,     // TODO: Add PDF support
-                    final_pdf_filename: None,
-                };
-                
-                Ok(Response::new(proto_state))
+                Ok(Response::new(approval_to_proto_state(&approval_data, self.approval_queue.blobs())))
             }
             Ok(None) => {
                 log::warn!("Approval {} not found in ApprovalQueue", approval_id);
@@ -383,70 +995,197 @@ impl ApprovalService for GrpcServiceWrapper {
     
     async fn stream_approval_updates(
         &self,
-        _request: Request<StreamApprovalRequest>,
+        request: Request<StreamApprovalRequest>,
     ) -> Result<Response<Self::StreamApprovalUpdatesStream>, Status> {
-        // Create empty stream for now
-        let (_, rx) = tokio::sync::mpsc::channel(10);
-        let stream = ReceiverStream::new(rx);
-        Ok(Response::new(Box::pin(stream)))
+        let user_id = request.into_inner().user_id.map(approval_types::UserId::new);
+        
+        log::info!("stream_approval_updates called (user: {:?})", user_id);
+        
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER_SIZE);
+        forward_events(
+            self.orchestrator.events().subscribe(),
+            tx,
+            move |event| {
+                let wanted = user_id.is_none_or(|id| event.user_id == id);
+                wanted.then(|| event_to_approval_update(event)).flatten()
+            },
+            |_| false,
+        );
+        
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
     
     async fn download_approval_pdf(
         &self,
-        _request: Request<DownloadPdfRequest>,
+        request: Request<DownloadPdfRequest>,
     ) -> Result<Response<PdfDocument>, Status> {
-        // Return placeholder PDF for now
+        let request = request.into_inner();
+        
+        log::info!("download_approval_pdf called for {} (iteration: {:?})", request.approval_id, request.iteration_number);
+        
+        let approval_id = approval_types::ApprovalId::from(request.approval_id.clone());
+        let approval = match self.approval_queue.get_approval_request(&approval_id, None) {
+            Ok(Some(approval)) => approval,
+            Ok(None) => return Err(Status::not_found(format!("Approval {} not found", request.approval_id))),
+            Err(e) => {
+                log::error!("Error reading approval {}: {}", request.approval_id, e);
+                return Err(Status::internal(format!("Failed to read approval: {}", e)));
+            }
+        };
+        
+        // Without an iteration number the current PDF is returned
+        let entry = approval.iteration(request.iteration_number).ok_or_else(|| Status::not_found(format!(
+            "Approval {} has no iteration {:?} (current: {})",
+            request.approval_id, request.iteration_number, approval.current_iteration()
+        )))?;
+        let content = approval.iteration_pdf_bytes(Some(entry.iteration), self.approval_queue.blobs())
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found(format!(
+                "No PDF stored for iteration {} of approval {}", entry.iteration, request.approval_id
+            )))?;
+        
         Ok(Response::new(PdfDocument {
-            content: vec![],
-            filename: "approval.pdf".to_string(),
+            size_bytes: content.len() as u32,
+            content,
+            filename: iteration_pdf_filename(&approval, entry.iteration),
             mime_type: "application/pdf".to_string(),
-            size_bytes: 0,
-            generated_at: Some(prost_types::Timestamp::from(std::time::SystemTime::now())),
+            generated_at: Some(to_timestamp(entry.created_at)),
         }))
     }
     
     async fn regenerate_pdf(
         &self,
-        _request: Request<RegeneratePdfRequest>,
+        request: Request<RegeneratePdfRequest>,
     ) -> Result<Response<PdfDocument>, Status> {
-        // Return placeholder PDF for now
+        let request = request.into_inner();
+        
+        log::info!("regenerate_pdf called for {} (approve: {})", request.approval_id, request.approve);
+        
+        if request.revised_letter_content.trim().is_empty() {
+            return Err(Status::invalid_argument("Revised letter content must not be empty"));
+        }
+        
+        let revised = self.revise_approval(
+            &request.approval_id,
+            &request.revised_letter_content,
+            approval_types::UserId::new(request.requested_by),
+            request.approve,
+        ).await.map_err(revision_error_to_status)?;
+        
+        let iteration = revised.current_iteration();
+        let content = revised.pdf_bytes(self.approval_queue.blobs())
+            .map_err(|e| Status::internal(e.to_string()))?
+            .unwrap_or_default();
         Ok(Response::new(PdfDocument {
-            content: vec![],
-            filename: "regenerated.pdf".to_string(),
+            size_bytes: content.len() as u32,
+            content,
+            filename: iteration_pdf_filename(&revised, iteration),
             mime_type: "application/pdf".to_string(),
-            size_bytes: 0,
-            generated_at: Some(prost_types::Timestamp::from(std::time::SystemTime::now())),
+            generated_at: Some(to_timestamp(revised.updated_at)),
         }))
     }
 }
 
+fn core_to_proto_dependency_status(status: approval_types::HealthStatus) -> workflow_grpc::dependency_health::Status {
+    use workflow_grpc::dependency_health::Status as ProtoStatus;
+    match status {
+        approval_types::HealthStatus::Healthy => ProtoStatus::Healthy,
+        approval_types::HealthStatus::Degraded => ProtoStatus::Degraded,
+        approval_types::HealthStatus::Unhealthy => ProtoStatus::Unhealthy,
+    }
+}
+
+fn dependency_to_proto(dependency: &DependencyHealth) -> ProtoDependencyHealth {
+    ProtoDependencyHealth {
+        name: dependency.name.clone(),
+        status: core_to_proto_dependency_status(dependency.status) as i32,
+        latency_ms: dependency.latency_ms as i64,
+        last_error: dependency.last_error.clone(),
+        last_error_at: dependency.last_error_at.map(to_timestamp),
+        checked_at: Some(to_timestamp(dependency.checked_at)),
+    }
+}
+
+/// Health of the whole server (empty `service`) or of one dependency - `None` for unknown services
+///
+/// Degraded dependencies still serve; only unhealthy ones make the server not serving.
+fn health_report_to_response(report: &HealthReport, service: &str) -> Option<HealthCheckResponse> {
+    use workflow_grpc::health_check_response::ServingStatus;
+    let (status, dependencies) = if service.is_empty() {
+        (report.status, report.dependencies.iter().collect::<Vec<_>>())
+    } else {
+        let dependency = report.dependency(service)?;
+        (dependency.status, vec![dependency])
+    };
+    let serving = match status {
+        approval_types::HealthStatus::Unhealthy => ServingStatus::NotServing,
+        _ => ServingStatus::Serving,
+    };
+    Some(HealthCheckResponse {
+        status: serving as i32,
+        dependencies: dependencies.into_iter().map(dependency_to_proto).collect(),
+    })
+}
+
+/// Whether a watcher has to be told - latencies and timestamps change on every check
+fn health_changed(previous: &HealthCheckResponse, current: &HealthCheckResponse) -> bool {
+    let statuses = |response: &HealthCheckResponse| response.dependencies.iter()
+        .map(|d| (d.name.clone(), d.status))
+        .collect::<Vec<_>>();
+    previous.status != current.status || statuses(previous) != statuses(current)
+}
+
 #[tonic::async_trait]
 impl Health for GrpcServiceWrapper {
     async fn check(
         &self,
-        _request: Request<HealthCheckRequest>,
+        request: Request<HealthCheckRequest>,
     ) -> Result<Response<HealthCheckResponse>, Status> {
-        Ok(Response::new(HealthCheckResponse {
-            status: workflow_grpc::health_check_response::ServingStatus::Serving as i32,
-        }))
+        let service = request.into_inner().service;
+        if service == LIVENESS_SERVICE {
+            return Ok(Response::new(HealthCheckResponse {
+                status: workflow_grpc::health_check_response::ServingStatus::Serving as i32,
+                dependencies: Vec::new(),
+            }));
+        }
+        let report = self.health.check().await;
+        
+        health_report_to_response(&report, &service)
+            .map(Response::new)
+            .ok_or_else(|| Status::not_found(format!("Unknown service: {}", service)))
     }
     
     type WatchStream = Pin<Box<dyn Stream<Item = Result<HealthCheckResponse, Status>> + Send>>;
     
     async fn watch(
         &self,
-        _request: Request<HealthCheckRequest>,
+        request: Request<HealthCheckRequest>,
     ) -> Result<Response<Self::WatchStream>, Status> {
-        // Create a channel for health updates
-        let (tx, rx) = tokio::sync::mpsc::channel(10);
+        let service = request.into_inner().service;
+        let health = self.health.clone();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER_SIZE);
         
-        // Send periodic health updates
+        // Send the current health, then every change until the client goes away
         tokio::spawn(async move {
+            let mut last_sent: Option<HealthCheckResponse> = None;
             loop {
-                let _ = tx.send(Ok(HealthCheckResponse {
-                    status: workflow_grpc::health_check_response::ServingStatus::Serving as i32,
-                })).await;
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                let report = health.check().await;
+                let response = health_report_to_response(&report, &service).unwrap_or(HealthCheckResponse {
+                    status: workflow_grpc::health_check_response::ServingStatus::ServiceUnknown as i32,
+                    dependencies: Vec::new(),
+                });
+                
+                if last_sent.as_ref().is_none_or(|previous| health_changed(previous, &response)) {
+                    if tx.send(Ok(response.clone())).await.is_err() {
+                        break;
+                    }
+                    last_sent = Some(response);
+                }
+                
+                tokio::select! {
+                    _ = tx.closed() => break,
+                    _ = tokio::time::sleep(tokio::time::Duration::from_secs(HEALTH_WATCH_INTERVAL_SECS)) => {}
+                }
             }
         });
         
@@ -459,9 +1198,13 @@ impl Health for GrpcServiceWrapper {
 pub async fn start_grpc_server(
     orchestrator: Arc<WorkflowOrchestrator<WorkflowProcessor>>,
     approval_queue: Arc<workflow_core::workflow::ApprovalQueue>,
+    run_store: Arc<WorkflowRunStore>,
+    scheduler: Arc<WorkflowScheduler>,
+    metrics: Arc<WorkflowMetricsCollector>,
+    health: Arc<HealthChecker>,
     addr: std::net::SocketAddr,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let service_wrapper = GrpcServiceWrapper::new(orchestrator, approval_queue);
+    let service_wrapper = GrpcServiceWrapper::new(orchestrator, approval_queue, run_store, scheduler, metrics, health);
     
     let workflow_service = WorkflowServiceServer::new(service_wrapper.clone());
     let approval_service = ApprovalServiceServer::new(service_wrapper.clone());
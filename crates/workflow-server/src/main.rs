@@ -3,15 +3,20 @@
 //! This binary replaces the Python main_workflow.py
 
 mod grpc_service;
+mod metrics_server;
+mod telemetry;
 
 use clap::{Arg, Command};
 use workflow_core::{
     LennardConfig, 
-    workflow::{WorkflowOrchestrator, ApprovalWatcher, NeedsImprovementWatcher, approval_types::WorkflowTrigger}, 
+    workflow::{WorkflowOrchestrator, ApprovalWatcher, NeedsImprovementWatcher, OutboxWorker, ApprovalExpiryWorker, WorkflowScheduler, WorkflowMetricsCollector, WorkflowStep, RetryPolicies, EventBus, approval_types::WorkflowTrigger,
+               ApprovalStore, FileApprovalStore, SqliteApprovalStore, migrate_approvals, open_approval_store, schema, migrate_data_dir,
+               BlobStore, OutboxStore, referenced_blobs, Retention, RetentionWorker}, 
     services::WorkflowProcessor,
     clients::{BaserowClient, ZohoClient, DossierClient, LetterExpressClient, LetterServiceClient, PDFService, TelegramClient},
-    services::AddressExtractor,
+    services::{AddressExtractor, ServiceLimits, HealthChecker},
     paths,
+    constants::BLOB_GC_MIN_AGE_SECS,
 };
 use std::sync::Arc;
 use notify::{RecommendedWatcher, Watcher, RecursiveMode, Event, EventKind};
@@ -20,11 +25,6 @@ use std::path::Path;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Initialize logging with INFO as default if RUST_LOG not set
-    env_logger::Builder::from_env(
-        env_logger::Env::default().default_filter_or("info")
-    ).init();
-    
     let matches = Command::new("main-workflow")
         .version("1.0.0")
         .about("Lennard workflow processor")
@@ -40,7 +40,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             Arg::new("task-id")
                 .long("task-id")
                 .value_name("ID") 
-                .help("Process specific task ID")
+                .help("Process specific task IDs (repeat the flag or separate with commas)")
+                .action(clap::ArgAction::Append)
+                .value_delimiter(',')
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Generate letters and PDFs without touching Zoho, Telegram or LetterExpress (with --task-id), or list what --migrate-schema, --gc-blobs and --apply-retention would change")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("resume-task")
+                .long("resume-task")
+                .value_name("ID")
+                .help("Resume a task from its step checkpoints")
+        )
+        .arg(
+            Arg::new("from-step")
+                .long("from-step")
+                .value_name("STEP")
+                .requires("resume-task")
+                .value_parser(clap::builder::PossibleValuesParser::new(WorkflowStep::ALL.map(|step| step.as_str())))
+                .help("Restart the resumed task at this step, discarding its checkpoint and all later ones")
         )
         .arg(
             Arg::new("monitor-workflows")
@@ -54,6 +76,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .help("Start gRPC server")
                 .action(clap::ArgAction::SetTrue)
         )
+        .arg(
+            Arg::new("health-check")
+                .long("health-check")
+                .help("Ask the running gRPC server whether it answers and exit non-zero unless it does - external services are not checked")
+                .action(clap::ArgAction::SetTrue)
+        )
         .arg(
             Arg::new("grpc-port")
                 .long("grpc-port")
@@ -61,6 +89,50 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .help("gRPC server port")
                 .default_value("50051")
         )
+        .arg(
+            Arg::new("metrics-port")
+                .long("metrics-port")
+                .value_name("PORT")
+                .help("Port of the Prometheus /metrics endpoint started with the gRPC server")
+                .default_value("9090")
+        )
+        .arg(
+            Arg::new("disable-metrics")
+                .long("disable-metrics")
+                .help("Do not serve Prometheus metrics")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("otlp-endpoint")
+                .long("otlp-endpoint")
+                .value_name("URL")
+                .env("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .help("OTLP gRPC endpoint to export traces to, e.g. http://localhost:4317")
+        )
+        .arg(
+            Arg::new("migrate-approvals")
+                .long("migrate-approvals")
+                .help("Import the approval files of the data directory into the SQLite approval store and exit")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("migrate-schema")
+                .long("migrate-schema")
+                .help("Upgrade the approval and trigger files of the data directory to the current schema and exit")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("gc-blobs")
+                .long("gc-blobs")
+                .help("Remove blobs no approval or outbox entry refers to any more and exit")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("apply-retention")
+                .long("apply-retention")
+                .help("Archive and remove workflow data and logs past `workflow.retention` and exit")
+                .action(clap::ArgAction::SetTrue)
+        )
         .arg(
             Arg::new("data-dir")
                 .long("data-dir")
@@ -84,6 +156,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         )
         .get_matches();
     
+    // Probe the local server before loading any configuration - used by the container healthcheck
+    if matches.get_flag("health-check") {
+        let port = matches.get_one::<String>("grpc-port").unwrap();
+        std::process::exit(check_server_health(port).await);
+    }
+    
+    // Initialize logging with INFO as default if RUST_LOG not set, and trace export if configured
+    let otlp_endpoint = matches.get_one::<String>("otlp-endpoint").filter(|endpoint| !endpoint.is_empty());
+    telemetry::init(otlp_endpoint.map(String::as_str))?;
+    
+    // Every way out of run() passes here, so buffered spans are always flushed
+    let result = run(&matches).await;
+    telemetry::shutdown();
+    result
+}
+
+/// Everything after logging is set up - errors end the process with a non-zero exit code
+async fn run(matches: &clap::ArgMatches) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Initialize data directory
     let data_dir = matches.get_one::<String>("data-dir").unwrap();
     if let Err(e) = paths::init_data_root(data_dir.clone()) {
@@ -106,12 +196,78 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     }
     log::info!("Using templates directory: {}", templates_dir);
     
+    // Rewrites files in place - stop the server first, or pass --dry-run to only list the changes
+    if matches.get_flag("migrate-schema") {
+        let dry_run = matches.get_flag("dry-run");
+        let report = migrate_data_dir(paths::workflow_data_root(), dry_run)?;
+        for file in &report.upgraded {
+            println!("{} (schema version {})", file.path.display(), file.from_version);
+            for change in &file.changes {
+                println!("    {}", change);
+            }
+        }
+        for (path, error) in &report.failed {
+            eprintln!("Could not read {}: {}", path.display(), error);
+        }
+        println!("{} {} files, {} already current, {} unreadable",
+                 if dry_run { "Would upgrade" } else { "Upgraded" },
+                 report.upgraded.len(), report.current, report.failed.len());
+        return Ok(());
+    }
+    
     // Load configuration
     let config_path = matches.get_one::<String>("config").unwrap();
     let config = LennardConfig::from_file(config_path)?;
     
     log::info!("Loaded configuration from {}", config_path);
     
+    // One-shot import of the file layout - run before switching `workflow.approval_store.backend` to sqlite
+    if matches.get_flag("migrate-approvals") {
+        let files = FileApprovalStore::new(paths::workflow_data_root())?;
+        let database = SqliteApprovalStore::open(
+            config.workflow.approval_store.sqlite_path(paths::workflow_data_root()),
+            BlobStore::new(paths::workflow_data_root())?,
+        )?;
+        let report = migrate_approvals(&files, &database)?;
+        println!("Imported {} approvals into {} ({} already present)",
+                 report.imported, database.location().display(), report.skipped);
+        return Ok(());
+    }
+    
+    // Blobs younger than BLOB_GC_MIN_AGE_SECS survive, so this is safe next to a running server
+    if matches.get_flag("gc-blobs") {
+        let dry_run = matches.get_flag("dry-run");
+        let approvals = open_approval_store(paths::workflow_data_root(), &config.workflow.approval_store)?;
+        let outbox = OutboxStore::new(paths::workflow_data_root())?;
+        let referenced = referenced_blobs(approvals.as_ref(), &outbox)?;
+        let report = BlobStore::new(paths::workflow_data_root())?.collect_garbage(
+            &referenced,
+            std::time::Duration::from_secs(BLOB_GC_MIN_AGE_SECS),
+            dry_run,
+        )?;
+        println!("{} {} blobs ({} bytes), kept {}",
+                 if dry_run { "Would remove" } else { "Removed" },
+                 report.removed, report.removed_bytes, report.kept);
+        return Ok(());
+    }
+    
+    // Same run as the server's retention task, on demand
+    if matches.get_flag("apply-retention") {
+        let dry_run = matches.get_flag("dry-run");
+        let approvals = open_approval_store(paths::workflow_data_root(), &config.workflow.approval_store)?;
+        let blobs = Arc::new(BlobStore::new(paths::workflow_data_root())?);
+        let retention = Retention::new(config.workflow.retention.clone(), approvals, blobs, paths::workflow_data_root(), paths::logs_root())?;
+        let report = retention.run(chrono::Utc::now(), dry_run)?;
+        for approval_id in &report.archived {
+            println!("archive approval {}", approval_id);
+        }
+        for path in report.removed_files() {
+            println!("remove {}", path.display());
+        }
+        println!("{}{}", if dry_run { "Dry run: " } else { "" }, report);
+        return Ok(());
+    }
+    
     // Initialize all service clients with type-safe authentication
     let unauthenticated_zoho_client = ZohoClient::new(config.zoho.clone());
     
@@ -136,12 +292,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let letter_service = Arc::new(LetterServiceClient::new(config.letter_service.clone()));
     let telegram_client: Arc<dyn workflow_core::clients::TelegramClientTrait> = Arc::new(TelegramClient::new(config.telegram.clone()));
     
-    // Create ApprovalQueue with the workflows data directory
+    // Shared bus for run and approval events - fanned out by the gRPC update streams
+    let events = Arc::new(EventBus::new());
+    
+    // Create ApprovalQueue with the workflows data directory and the configured approval store
+    let approval_store = open_approval_store(paths::workflow_data_root(), &config.workflow.approval_store)
+        .expect("Failed to open approval store");
     let approval_queue = Arc::new(
-        workflow_core::workflow::ApprovalQueue::new(paths::workflow_data_root())
+        workflow_core::workflow::ApprovalQueue::with_store(paths::workflow_data_root(), approval_store.clone())
             .expect("Failed to initialize ApprovalQueue")
+            .with_event_bus(events.clone())
+    );
+    log::info!("Initialized ApprovalQueue at {} (approvals in {})",
+               paths::workflow_data_root().display(), approval_queue.store().location().display());
+    
+    // Deep health checks probe every downstream dependency for Health.Check and Health.Watch
+    let health = Arc::new(
+        HealthChecker::new()
+            .with_probe(zoho_client.clone())
+            .with_probe(dossier_client.clone())
+            .with_probe(letter_service.clone())
+            .with_probe(pdf_service.clone())
+            .with_probe(letterexpress_client.clone())
+            .with_probe(approval_queue.clone())
+    );
+    
+    // Create the run store so every processed trigger leaves a queryable record
+    let run_store = Arc::new(
+        workflow_core::workflow::WorkflowRunStore::new(paths::workflow_data_root())
+            .expect("Failed to initialize WorkflowRunStore")
+    );
+    
+    // Create the checkpoint store so failed tasks can resume after their last completed step
+    let checkpoint_store = Arc::new(
+        workflow_core::workflow::TaskCheckpointStore::new(paths::workflow_data_root())
+            .expect("Failed to initialize TaskCheckpointStore")
+    );
+    
+    // Create the outbox so the Zoho updates of a sent letter are retried until they succeed
+    let outbox_store = Arc::new(
+        workflow_core::workflow::OutboxStore::new(paths::workflow_data_root())
+            .expect("Failed to initialize OutboxStore")
+    );
+    
+    // PDFs and dossiers of approvals and outbox entries, shared by the orchestrator and retention
+    let blob_store = Arc::new(
+        BlobStore::new(paths::workflow_data_root())
+            .expect("Failed to initialize BlobStore")
     );
-    log::info!("Initialized ApprovalQueue at {}", paths::workflow_data_root().display());
     
     // Create workflow processor with all services
     let workflow_processor = WorkflowProcessor::new(
@@ -154,30 +352,58 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         letter_service,
         telegram_client,
         approval_queue.clone(),
+        ServiceLimits::new(&config.workflow.service_limits),
+        config.workflow.task_selection.clone(),
     );
     
     // Create orchestrator with strongly-typed workflow steps
-    let orchestrator = Arc::new(WorkflowOrchestrator::new(workflow_processor));
+    let orchestrator = Arc::new(
+        WorkflowOrchestrator::new(workflow_processor, run_store.clone(), checkpoint_store, outbox_store, blob_store.clone())
+            .with_max_concurrent_tasks(config.workflow.max_concurrent_tasks)
+            .with_retry_policies(RetryPolicies::from_config(&config.workflow.retry))
+            .with_event_bus(events)
+    );
+    log::info!("Processing up to {} tasks in parallel (limits: {:?})",
+               config.workflow.max_concurrent_tasks, config.workflow.service_limits);
     
     log::info!("Initialized all services and orchestrator");
     
-    if let Some(_task_id) = matches.get_one::<String>("task-id") {
-        log::info!("Processing single task mode (will dynamically load first available task)");
+    if let Some(task_id) = matches.get_one::<String>("resume-task") {
+        let from_step = matches.get_one::<String>("from-step")
+            .map(|step| step.parse::<WorkflowStep>())
+            .transpose()?;
+        
+        log::info!("Resuming task {} from {}", task_id,
+                   from_step.map(|s| s.to_string()).unwrap_or_else(|| "last checkpoint".to_string()));
         
-        // Create a generic WorkflowTrigger for single task processing
-        // Note: task loading is now handled dynamically by the orchestrator
+        match orchestrator.resume_task(task_id, from_step, workflow_core::workflow::approval_types::UserId::new(1)).await {
+            Ok(result) => {
+                log::info!("Task resumed in workflow run {}", result.trigger_id);
+                if let Some(result_msg) = &result.result {
+                    log::info!("Result: {}", result_msg);
+                }
+            },
+            Err(e) => log::error!("Failed to resume task {}: {}", task_id, e),
+        }
+    } else if let Some(task_ids) = matches.get_many::<String>("task-id") {
+        let task_ids: Vec<String> = task_ids.cloned().collect();
+        log::info!("Processing tasks {:?}", task_ids);
+        
+        // The orchestrator loads exactly these tasks instead of selecting available ones
         let trigger = WorkflowTrigger {
+            schema_version: schema::TRIGGER_SCHEMA_VERSION,
             trigger_id: uuid::Uuid::new_v4().to_string(),
             requested_by: workflow_core::workflow::approval_types::UserId::new(1), // Default user
             requested_at: chrono::Utc::now(),
-            max_tasks: 1, // Process exactly 1 task
-            dry_run: false,
+            max_tasks: task_ids.len() as u32,
+            dry_run: matches.get_flag("dry-run"),
+            task_selection: None,
+            task_ids,
             processed: false,
             processed_at: None,
             result: None,
         };
         
-        // Process using orchestrator with dynamic task loading
         match orchestrator.process_workflow(trigger).await {
             Ok(result) => {
                 log::info!("Workflow processed successfully");
@@ -201,14 +427,70 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         
         log::info!("Starting gRPC server on port {}", port);
         
-        // Start gRPC server, workflow monitor, approval watcher, and needs improvement watcher in parallel
+        // Create the scheduler that writes trigger files for the configured schedules
+        let scheduler = Arc::new(
+            WorkflowScheduler::new(&config.schedules, paths::workflow_data_root())
+                .expect("Failed to initialize WorkflowScheduler")
+        );
+        
+        // Metrics are aggregated on request from the run records and the approval store
+        let metrics = Arc::new(
+            WorkflowMetricsCollector::new(paths::workflow_data_root(), approval_queue.store().clone())
+                .expect("Failed to initialize WorkflowMetricsCollector")
+        );
+        
+        // Prometheus metrics are optional - a failing endpoint is logged but does not stop the server
+        if !matches.get_flag("disable-metrics") {
+            let metrics_port: u16 = matches.get_one::<String>("metrics-port")
+                .unwrap()
+                .parse()
+                .expect("Invalid metrics port number");
+            let metrics_addr = format!("0.0.0.0:{}", metrics_port).parse()?;
+            
+            // Carry the last send over restarts
+            match metrics.last_letter_sent_at() {
+                Ok(Some(sent_at)) => workflow_core::instrumentation::record_letter_sent(sent_at),
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to read the last letter send: {}", e),
+            }
+            
+            let approval_queue_metrics = approval_queue.clone();
+            tokio::spawn(async move {
+                if let Err(e) = metrics_server::start_metrics_server(approval_queue_metrics, metrics_addr).await {
+                    log::error!("Metrics endpoint failed: {}", e);
+                }
+            });
+        }
+        
+        if config.workflow.retention.interval_hours > 0 {
+            let retention = Retention::new(
+                config.workflow.retention.clone(),
+                approval_store.clone(),
+                blob_store.clone(),
+                paths::workflow_data_root(),
+                paths::logs_root(),
+            ).expect("Failed to initialize retention");
+            let retention_worker = Arc::new(RetentionWorker::new(Arc::new(retention)));
+            tokio::spawn(async move {
+                retention_worker.start().await;
+            });
+        }
+        
+        // Start gRPC server, workflow monitor, approval watcher, needs improvement watcher, outbox worker,
+        // approval expiry worker and scheduler in parallel
         let orchestrator_grpc = orchestrator.clone();
         let orchestrator_monitor = orchestrator.clone();
         let orchestrator_approval_watcher = orchestrator.clone();
         let orchestrator_improvement_watcher = orchestrator.clone();
+        let orchestrator_outbox_worker = orchestrator.clone();
+        let orchestrator_expiry_worker = orchestrator.clone();
         
         let approval_queue_grpc = approval_queue.clone();
+        let run_store_grpc = run_store.clone();
         let approval_queue_approval_watcher = approval_queue.clone();
+        let approval_queue_improvement_watcher = approval_queue.clone();
+        let approval_queue_expiry_worker = approval_queue.clone();
+        let scheduler_grpc = scheduler.clone();
         
         // Create approval watcher
         let approval_watcher = Arc::new(ApprovalWatcher::new(
@@ -218,11 +500,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         
         // Create needs improvement watcher
         let needs_improvement_watcher = Arc::new(NeedsImprovementWatcher::new(
+            approval_queue_improvement_watcher,
             orchestrator_improvement_watcher,
         ));
         
+        // Create outbox worker
+        let outbox_worker = Arc::new(OutboxWorker::new(orchestrator_outbox_worker));
+        
+        // Create approval expiry worker
+        let approval_expiry_worker = Arc::new(ApprovalExpiryWorker::new(
+            approval_queue_expiry_worker,
+            orchestrator_expiry_worker,
+            config.workflow.approval_expiry.clone(),
+        ));
+        
         let grpc_handle = tokio::spawn(async move {
-            grpc_service::start_grpc_server(orchestrator_grpc, approval_queue_grpc, addr).await
+            grpc_service::start_grpc_server(orchestrator_grpc, approval_queue_grpc, run_store_grpc, scheduler_grpc, metrics, health, addr).await
         });
         
         let monitor_handle = tokio::spawn(async move {
@@ -239,71 +532,88 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
         });
         
+        let outbox_worker_handle = tokio::spawn(async move {
+            outbox_worker.start().await;
+            Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+        });
+        
+        let approval_expiry_worker_handle = tokio::spawn(async move {
+            approval_expiry_worker.start().await;
+            Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+        });
+        
+        let scheduler_handle = tokio::spawn(async move {
+            scheduler.start().await;
+            Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+        });
+        
         // Wait for any to complete (or fail)
         tokio::select! {
-            result = grpc_handle => {
-                match result {
-                    Ok(Ok(_)) => log::info!("gRPC server exited normally"),
-                    Ok(Err(e)) => {
-                        log::error!("gRPC server failed to start: {}", e);
-                        std::process::exit(1);
-                    }
-                    Err(e) => {
-                        log::error!("gRPC server task panicked: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            }
-            result = monitor_handle => {
-                match result {
-                    Ok(Ok(_)) => log::info!("Workflow monitor exited normally"),
-                    Ok(Err(e)) => {
-                        log::error!("Workflow monitor failed: {}", e);
-                        std::process::exit(1);
-                    }
-                    Err(e) => {
-                        log::error!("Workflow monitor task panicked: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            }
-            result = approval_watcher_handle => {
-                match result {
-                    Ok(Ok(_)) => log::info!("Approval watcher exited normally"),
-                    Ok(Err(e)) => {
-                        log::error!("Approval watcher failed: {}", e);
-                        std::process::exit(1);
-                    }
-                    Err(e) => {
-                        log::error!("Approval watcher task panicked: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            }
-            result = improvement_watcher_handle => {
-                match result {
-                    Ok(Ok(_)) => log::info!("Needs improvement watcher exited normally"),
-                    Ok(Err(e)) => {
-                        log::error!("Needs improvement watcher failed: {}", e);
-                        std::process::exit(1);
-                    }
-                    Err(e) => {
-                        log::error!("Needs improvement watcher task panicked: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            }
+            result = grpc_handle => background_task_exit("gRPC server", result)?,
+            result = monitor_handle => background_task_exit("Workflow monitor", result)?,
+            result = approval_watcher_handle => background_task_exit("Approval watcher", result)?,
+            result = improvement_watcher_handle => background_task_exit("Needs improvement watcher", result)?,
+            result = outbox_worker_handle => background_task_exit("Outbox worker", result)?,
+            result = approval_expiry_worker_handle => background_task_exit("Approval expiry worker", result)?,
+            result = scheduler_handle => background_task_exit("Workflow scheduler", result)?,
         }
     } else {
         log::error!("No action specified. Use --help for options.");
-        std::process::exit(1);
+        return Err("No action specified".into());
     }
     
     Ok(())
 }
 
+/// Log how a background task that should run until the server stops ended - a failure is returned
+fn background_task_exit(
+    name: &str,
+    result: Result<Result<(), Box<dyn std::error::Error + Send + Sync>>, tokio::task::JoinError>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match result {
+        Ok(Ok(())) => {
+            log::info!("{} exited normally", name);
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            log::error!("{} failed: {}", name, e);
+            Err(e)
+        }
+        Err(e) => {
+            log::error!("{} task panicked: {}", name, e);
+            Err(e.into())
+        }
+    }
+}
+
 // Removed - now handled directly by WorkflowProcessor
 
+/// Call Health.Check for liveness on the local gRPC server - exit code 0 when serving, 1 otherwise
+///
+/// An outage of an external service must not mark the container unhealthy, so no dependency is probed.
+async fn check_server_health(port: &str) -> i32 {
+    use workflow_grpc::{HealthClient, HealthCheckRequest, health_check_response::ServingStatus};
+    
+    let client = HealthClient::connect(format!("http://127.0.0.1:{}", port)).await;
+    let mut client = match client {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to gRPC server on port {}: {}", port, e);
+            return 1;
+        }
+    };
+    
+    match client.check(HealthCheckRequest { service: grpc_service::LIVENESS_SERVICE.to_string() }).await {
+        Ok(response) => {
+            if response.into_inner().status() == ServingStatus::Serving { 0 } else { 1 }
+        }
+        Err(e) => {
+            eprintln!("Health check failed: {}", e);
+            1
+        }
+    }
+}
+
 async fn monitor_workflows(orchestrator: Arc<WorkflowOrchestrator<WorkflowProcessor>>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let triggers_path = paths::triggers_dir();
     let processed_path = paths::triggers_processed_dir();
@@ -378,9 +688,8 @@ async fn process_trigger_file(
     // Read and deserialize WorkflowTrigger
     let content = std::fs::read_to_string(trigger_path)?;
     
-    let trigger: WorkflowTrigger = 
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse WorkflowTrigger JSON: {}", e))?;
+    let trigger = schema::parse_trigger(&content)
+        .map_err(|e| format!("Failed to parse WorkflowTrigger JSON: {}", e))?;
     
     log::info!("Processing WorkflowTrigger: {} for up to {} tasks", trigger.trigger_id, trigger.max_tasks);
     
@@ -0,0 +1,51 @@
+//! HTTP endpoint serving the Prometheus metrics at `/metrics`
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use std::convert::Infallible;
+use std::sync::Arc;
+use workflow_core::{instrumentation, workflow::ApprovalQueue};
+
+/// Render the metrics, refreshing the approval queue depth first
+fn render(approval_queue: &ApprovalQueue) -> workflow_core::Result<String> {
+    instrumentation::set_approval_queue_depth(&approval_queue.get_approval_counts()?);
+    instrumentation::gather()
+}
+
+async fn handle(approval_queue: Arc<ApprovalQueue>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let response = if request.method() != Method::GET || request.uri().path() != "/metrics" {
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("Not found"))
+    } else {
+        match render(&approval_queue) {
+            Ok(text) => Response::builder()
+                .header(header::CONTENT_TYPE, instrumentation::CONTENT_TYPE)
+                .body(Body::from(text)),
+            Err(e) => {
+                log::error!("Failed to render metrics: {}", e);
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Body::from(e.to_string()))
+            }
+        }
+    };
+    Ok(response.expect("static response parts are valid"))
+}
+
+/// Start the metrics endpoint
+pub async fn start_metrics_server(
+    approval_queue: Arc<ApprovalQueue>,
+    addr: std::net::SocketAddr,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let make_service = make_service_fn(move |_| {
+        let approval_queue = approval_queue.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| handle(approval_queue.clone(), request)))
+        }
+    });
+    
+    log::info!("Serving Prometheus metrics on http://{}/metrics", addr);
+    Server::try_bind(&addr)?.serve(make_service).await?;
+    Ok(())
+}
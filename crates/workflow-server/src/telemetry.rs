@@ -0,0 +1,51 @@
+//! Logging and trace export
+//!
+//! `log` records are routed through `tracing`, so they are printed with the spans they belong
+//! to. With an OTLP endpoint the spans are also exported, e.g. to a local OpenTelemetry collector.
+
+use std::io::IsTerminal;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace, Resource};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// `service.name` of the exported spans
+const SERVICE_NAME: &str = "workflow-server";
+
+/// Install the subscriber - `RUST_LOG` filters logs and spans, INFO by default
+pub fn init(otlp_endpoint: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // W3C trace context, understood by the Python services
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    
+    let otel_layer = match otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", SERVICE_NAME),
+                ])))
+                .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+    
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_ansi(std::io::stderr().is_terminal()))
+        .with(otel_layer)
+        .try_init()?;
+    
+    if let Some(endpoint) = otlp_endpoint {
+        log::info!("Exporting traces to {}", endpoint);
+    }
+    Ok(())
+}
+
+/// Flush the spans that are still buffered
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}